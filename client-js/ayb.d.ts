@@ -1,17 +1,29 @@
 export type ServerSelectionModalOptions = {
     appName: string;
-    queryPermissionLevel: "read-only" | "read-write";
+    queryPermissionLevel: "read-only" | "read-write-data" | "read-write";
     serverUrls?: string[] | undefined;
     appId?: string | undefined;
     storageKey?: string | undefined;
 };
 export type AybOAuthOptions = {
     appName: string;
-    queryPermissionLevel: "read-only" | "read-write";
+    queryPermissionLevel: "read-only" | "read-write-data" | "read-write";
     serverUrl: string;
     appId?: string | undefined;
     storageKey?: string | undefined;
 };
+export type TableChange = {
+    table: string;
+    operation: "insert" | "update" | "delete";
+    row_count: number;
+    rowids: number[];
+};
+export type ChangeEvent = {
+    entity: string;
+    database: string;
+    changes: TableChange[];
+    committed_at: string;
+};
 export class AybClient {
     static escapeSQL(str: any): string;
     static parseDatabaseUrl(url: string): {
@@ -48,13 +60,23 @@ export class AybClient {
         rows: (string | null)[][];
     }>;
     queryObjects(sql: string): Promise<Record<string, string | null>[]>;
+    subscribeToChanges({ onChange, tables, onLagged, onError }?: {
+        onChange: (event: ChangeEvent) => void;
+        tables?: string[] | undefined;
+        onLagged?: ((missed: number) => void) | undefined;
+        onError?: ((error: Error) => void) | undefined;
+    }): {
+        ready: Promise<void>;
+        close: () => void;
+    };
+    _readServerSentEvents(body: ReadableStream<Uint8Array>, onEvent: (event: string, data: string) => void): Promise<void>;
     _fetchWithRetry(url: string, options: RequestInit, maxRetries?: number): Promise<Response>;
 }
 export class AybOAuth extends AybClient {
     constructor(options: AybOAuthOptions);
     serverUrl: string;
     appName: string;
-    queryPermissionLevel: "read-only" | "read-write";
+    queryPermissionLevel: "read-only" | "read-write-data" | "read-write";
     getConnectionInfo(): ({
         baseUrl: string;
         entity: string;
@@ -15,7 +15,7 @@
  *
  *   const permissionRequest = {
  *     appName: 'My App',
- *     queryPermissionLevel: 'read-write',  // or 'read-only'
+ *     queryPermissionLevel: 'read-write',  // or 'read-write-data' or 'read-only'
  *   };
  *
  *   const ayb = await restoreOAuth(permissionRequest);
@@ -211,6 +211,113 @@ class AybClient {
         });
     }
 
+    // ---- Live changes ----
+
+    /**
+     * @typedef {Object} TableChange
+     * @property {string} table - The changed table
+     * @property {'insert'|'update'|'delete'} operation - What happened to its rows
+     * @property {number} row_count - How many rows changed
+     * @property {number[]} rowids - The changed rows' rowids (SQLite only)
+     */
+    /**
+     * @typedef {Object} ChangeEvent
+     * @property {string} entity
+     * @property {string} database
+     * @property {TableChange[]} changes
+     * @property {string} committed_at
+     */
+    /**
+     * Subscribe to the changes write queries commit to the database, as
+     * they're committed. You only receive changes to tables you can read.
+     * If your connection falls behind and misses changes, `onLagged` is
+     * called so that you can re-query. The subscription ends (calling
+     * `onError`) if the connection drops or you lose access.
+     *
+     * @param {Object} options
+     * @param {(event: ChangeEvent) => void} options.onChange - Called with each change
+     * @param {string[]} [options.tables] - Only receive changes to these tables
+     * @param {(missed: number) => void} [options.onLagged] - Called when changes were missed
+     * @param {(error: Error) => void} [options.onError] - Called when the subscription ends unexpectedly
+     * @returns {{ready: Promise<void>, close: () => void}} `ready` resolves once
+     *   changes are being received; `close` ends the subscription.
+     *
+     * @example
+     *   const subscription = db.subscribeToChanges({
+     *     tables: ['todos'],
+     *     onChange: async () => renderTodos(await db.queryObjects('SELECT * FROM todos')),
+     *   });
+     *   await subscription.ready;
+     */
+    subscribeToChanges({ onChange, tables = [], onLagged, onError } = {}) {
+        if (!this._config) {
+            throw new Error('Not connected. Call saveConfig() or loadConfig() first.');
+        }
+
+        const { baseUrl, entity, database, token } = this._config;
+        let url = `${baseUrl}/v1/${entity}/${database}/changes`;
+        if (tables.length > 0) {
+            url += `?tables=${encodeURIComponent(tables.join(','))}`;
+        }
+        const controller = new AbortController();
+        const fail = (error) => {
+            if (!controller.signal.aborted && onError) onError(error);
+        };
+
+        const ready = fetch(url, {
+            headers: { 'Authorization': `Bearer ${token}`, 'Accept': 'text/event-stream' },
+            signal: controller.signal
+        }).then(async (response) => {
+            if (!response.ok) {
+                const text = await response.text();
+                throw new Error(`Subscription failed: ${text}`);
+            }
+            this._readServerSentEvents(response.body, (event, data) => {
+                if (event === 'change' && onChange) {
+                    onChange(JSON.parse(data));
+                } else if (event === 'lagged' && onLagged) {
+                    onLagged(JSON.parse(data).missed);
+                }
+            }).then(
+                () => fail(new Error('Subscription ended')),
+                fail
+            );
+        });
+
+        return { ready, close: () => controller.abort() };
+    }
+
+    /**
+     * Read a stream of Server-Sent Events, calling `onEvent` with each
+     * event's name and data. Comments (lines starting with `:`) are skipped.
+     *
+     * @param {ReadableStream<Uint8Array>} body
+     * @param {(event: string, data: string) => void} onEvent
+     * @returns {Promise<void>} Resolves when the stream ends.
+     */
+    async _readServerSentEvents(body, onEvent) {
+        const reader = body.getReader();
+        const decoder = new TextDecoder();
+        let buffer = '';
+        for (;;) {
+            const { done, value } = await reader.read();
+            if (done) return;
+            buffer += decoder.decode(value, { stream: true });
+            let boundary;
+            while ((boundary = buffer.indexOf('\n\n')) !== -1) {
+                const message = buffer.slice(0, boundary);
+                buffer = buffer.slice(boundary + 2);
+                let event = 'message';
+                const data = [];
+                for (const line of message.split('\n')) {
+                    if (line.startsWith('event:')) event = line.slice(6).trim();
+                    else if (line.startsWith('data:')) data.push(line.slice(5).trimStart());
+                }
+                if (data.length > 0) onEvent(event, data.join('\n'));
+            }
+        }
+    }
+
     // ---- Network ----
 
     /**
@@ -292,7 +399,7 @@ class AybClient {
  * @typedef {Object} AybOAuthOptions
  * @property {string} appName - Display name shown during authorization.
  *   Also used as the appId for config/migration scoping unless overridden.
- * @property {'read-only'|'read-write'} queryPermissionLevel - Permission level to request
+ * @property {'read-only'|'read-write-data'|'read-write'} queryPermissionLevel - Permission level to request
  * @property {string} serverUrl - The ayb server URL (e.g. 'https://thedata.zone')
  * @property {string} [appId] - Override appId (defaults to appName)
  * @property {string} [storageKey] - Override localStorage key prefix
@@ -305,8 +412,8 @@ class AybOAuth extends AybClient {
     constructor(options) {
         if (!options.appName) throw new Error('appName is required');
         if (!options.queryPermissionLevel) throw new Error('queryPermissionLevel is required');
-        if (!['read-only', 'read-write'].includes(options.queryPermissionLevel)) {
-            throw new Error('queryPermissionLevel must be "read-only" or "read-write"');
+        if (!['read-only', 'read-write-data', 'read-write'].includes(options.queryPermissionLevel)) {
+            throw new Error('queryPermissionLevel must be "read-only", "read-write-data", or "read-write"');
         }
 
         super({
@@ -514,7 +621,7 @@ async function restoreOAuth(options) {
 /**
  * @typedef {Object} ServerSelectionModalOptions
  * @property {string} appName - Display name shown during authorization
- * @property {'read-only'|'read-write'} queryPermissionLevel - Permission level to request
+ * @property {'read-only'|'read-write-data'|'read-write'} queryPermissionLevel - Permission level to request
  * @property {string[]} [serverUrls] - Server URLs for the dropdown.
  *   Defaults to ['https://thedata.zone'].
  * @property {string} [appId] - Override appId (defaults to appName)
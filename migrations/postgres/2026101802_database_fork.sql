@@ -0,0 +1,13 @@
+-- Where a forked database came from. The upstream database id is
+-- cleared if the upstream is later deleted, but the snapshot id still
+-- identifies the contents the fork started from.
+CREATE TABLE database_fork (
+    database_id INT NOT NULL,                  -- The fork
+    upstream_database_id INT,                  -- The database that was forked
+    upstream_snapshot_id VARCHAR(64),          -- Hash of the upstream's files at fork time, NULL if it had no data yet
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(upstream_database_id) REFERENCES database(id),
+    UNIQUE(database_id)
+);
@@ -0,0 +1,14 @@
+-- Limits an entity's access to a database to specific tables and
+-- views, and optionally to specific columns of them. A NULL entity_id
+-- limits public access, and a NULL column_name grants every column of
+-- the object. Entities without rows here can query the whole
+-- database at their sharing level.
+CREATE TABLE database_object_permission (
+    database_id INT NOT NULL,
+    entity_id INT,
+    object_name VARCHAR(255) NOT NULL,
+    column_name VARCHAR(255),
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(entity_id) REFERENCES entity(id)
+);
@@ -0,0 +1,12 @@
+-- Makes room for the read-write-data level, which allows writing data
+-- but not changing the schema, between read-only and read-write.
+-- EntityDatabaseSharingLevel: read-write 2 -> 3, manager 3 -> 4.
+UPDATE entity_database_permission SET sharing_level = sharing_level + 1 WHERE sharing_level >= 2;
+UPDATE team_database_permission SET sharing_level = sharing_level + 1 WHERE sharing_level >= 2;
+UPDATE entity SET member_sharing_level = member_sharing_level + 1 WHERE member_sharing_level >= 2;
+UPDATE database_transfer SET former_owner_sharing_level = former_owner_sharing_level + 1 WHERE former_owner_sharing_level >= 2;
+
+-- QueryMode: read-write 1 -> 2.
+UPDATE api_token SET query_permission_level = 2 WHERE query_permission_level = 1;
+UPDATE oauth_authorization_request SET query_permission_level = 2 WHERE query_permission_level = 1;
+UPDATE oauth_authorization_request SET requested_query_permission_level = 2 WHERE requested_query_permission_level = 1;
@@ -0,0 +1,21 @@
+-- An append-only record of administrative actions (and, if
+-- configured, queries) taken in an entity's namespace. Databases are
+-- recorded by slug rather than id so that entries outlive the
+-- databases they describe.
+CREATE TABLE audit_log (
+    id SERIAL NOT NULL,
+    entity_id INT NOT NULL,                    -- The entity whose namespace the action was taken in
+    database_slug VARCHAR(64),                 -- NULL for actions that don't involve a database
+    actor_id INT NOT NULL,                     -- The entity that took the action
+    token_short_id VARCHAR(12),                -- The API token the actor used, if any
+    action SMALLINT NOT NULL,                  -- AuditAction
+    details TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY(id),
+    FOREIGN KEY(entity_id) REFERENCES entity(id),
+    FOREIGN KEY(actor_id) REFERENCES entity(id)
+);
+
+CREATE INDEX idx_audit_log_entity_id ON audit_log(entity_id, id);
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at);
@@ -0,0 +1,20 @@
+-- Saved queries published as public, read-only HTTP endpoints. The
+-- query and its parameters are copied when the endpoint is published,
+-- so later edits to the saved query don't change what the endpoint
+-- exposes until it's published again.
+CREATE TABLE published_endpoint (
+    id SERIAL NOT NULL,
+    database_id INT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    saved_query_name VARCHAR(64) NOT NULL,
+    query_text TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    max_age_seconds INT NOT NULL,
+    publisher_id INT NOT NULL,
+    published_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY(id),
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(publisher_id) REFERENCES entity(id),
+    UNIQUE(database_id, name)
+);
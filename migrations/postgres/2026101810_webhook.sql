@@ -0,0 +1,39 @@
+-- Webhooks notify a URL when committed queries change a database's
+-- rows. `tables` and `events` are JSON arrays of the table names and
+-- change operations to notify about; an empty `tables` matches every
+-- table. `secret` signs deliveries, so it's stored rather than hashed.
+CREATE TABLE webhook (
+    id SERIAL NOT NULL,
+    database_id INT NOT NULL,
+    url TEXT NOT NULL,
+    tables TEXT NOT NULL,
+    events TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    creator_id INT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    PRIMARY KEY(id),
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(creator_id) REFERENCES entity(id)
+);
+
+-- Each change a webhook is notified about, which is retried until it's
+-- delivered or runs out of attempts, and doubles as the delivery log.
+CREATE TABLE webhook_delivery (
+    id SERIAL NOT NULL,
+    webhook_id INT NOT NULL,
+    payload TEXT NOT NULL,
+    status SMALLINT NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL,
+    response_status INT NULL,
+    error TEXT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at TIMESTAMP NULL,
+
+    PRIMARY KEY(id),
+    FOREIGN KEY(webhook_id) REFERENCES webhook(id)
+);
+
+CREATE INDEX idx_webhook_delivery_status ON webhook_delivery(status, next_attempt_at);
+CREATE INDEX idx_webhook_delivery_webhook_id ON webhook_delivery(webhook_id, id);
@@ -0,0 +1,14 @@
+-- Per-database overrides of the server's snapshot settings. A NULL
+-- setting falls back to the server's configuration. The `retain_*`
+-- columns are a grandfather-father-son retention policy: how many
+-- hours, days, and weeks back to keep the newest snapshot of each.
+CREATE TABLE snapshot_policy (
+    database_id INT NOT NULL,
+    retain_hourly INT,
+    retain_daily INT,
+    retain_weekly INT,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    UNIQUE(database_id)
+);
@@ -0,0 +1,17 @@
+-- Entries about a database are keyed by its id, so that its history
+-- follows it through renames and transfers, and a new database that
+-- reuses a deleted one's slug doesn't inherit that history. The slug
+-- is kept for display. Deleting a database sets `database_id` to NULL
+-- on its entries.
+ALTER TABLE audit_log ADD COLUMN database_id INT;
+
+UPDATE audit_log
+SET database_id = (
+    SELECT database.id
+    FROM database
+    WHERE database.entity_id = audit_log.entity_id
+        AND database.slug = audit_log.database_slug
+)
+WHERE database_slug IS NOT NULL;
+
+CREATE INDEX idx_audit_log_database_id ON audit_log(database_id, id);
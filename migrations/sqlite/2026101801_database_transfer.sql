@@ -0,0 +1,15 @@
+-- Pending transfers of database ownership. A database has at most one
+-- pending transfer, which is removed once the recipient accepts it or
+-- either side cancels it.
+CREATE TABLE database_transfer (
+    database_id INT NOT NULL,
+    from_entity_id INT NOT NULL,               -- The owner proposing the transfer
+    to_entity_id INT NOT NULL,                 -- The user or organization receiving the database
+    former_owner_sharing_level SMALLINT NOT NULL, -- EntityDatabaseSharingLevel the former owner keeps
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(from_entity_id) REFERENCES entity(id),
+    FOREIGN KEY(to_entity_id) REFERENCES entity(id),
+    UNIQUE(database_id)
+);
@@ -0,0 +1,14 @@
+-- The EntityDatabaseSharingLevel that members of an organization get
+-- on the organization's databases. Unused for users.
+ALTER TABLE entity ADD COLUMN member_sharing_level SMALLINT NOT NULL DEFAULT 0;
+
+CREATE TABLE organization_membership (
+    organization_id INT NOT NULL,
+    member_id INT NOT NULL,
+    role SMALLINT NOT NULL,                    -- OrganizationRole
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(organization_id) REFERENCES entity(id),
+    FOREIGN KEY(member_id) REFERENCES entity(id),
+    UNIQUE(organization_id, member_id)
+);
@@ -0,0 +1,30 @@
+-- Named groups of an organization's members that can be granted
+-- access to the organization's databases together.
+CREATE TABLE team (
+    id INTEGER PRIMARY KEY,
+    organization_id INT NOT NULL,
+    slug VARCHAR(64) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(organization_id) REFERENCES entity(id),
+    UNIQUE(organization_id, slug)
+);
+
+CREATE TABLE team_membership (
+    team_id INT NOT NULL,
+    member_id INT NOT NULL,
+
+    FOREIGN KEY(team_id) REFERENCES team(id),
+    FOREIGN KEY(member_id) REFERENCES entity(id),
+    UNIQUE(team_id, member_id)
+);
+
+CREATE TABLE team_database_permission (
+    team_id INT NOT NULL,
+    database_id INT NOT NULL,
+    sharing_level SMALLINT NOT NULL,           -- EntityDatabaseSharingLevel
+
+    FOREIGN KEY(team_id) REFERENCES team(id),
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    UNIQUE(team_id, database_id)
+);
@@ -0,0 +1,18 @@
+-- Named queries attached to a database so that entities with access
+-- to it don't have to retype them. `parameters` is a JSON array of
+-- the `:name` placeholders the query accepts.
+CREATE TABLE saved_query (
+    id INTEGER PRIMARY KEY,
+    database_id INT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    query_text TEXT NOT NULL,
+    description TEXT,
+    parameters TEXT NOT NULL,
+    author_id INT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(author_id) REFERENCES entity(id),
+    UNIQUE(database_id, name)
+);
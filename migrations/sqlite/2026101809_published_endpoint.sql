@@ -0,0 +1,19 @@
+-- Saved queries published as public, read-only HTTP endpoints. The
+-- query and its parameters are copied when the endpoint is published,
+-- so later edits to the saved query don't change what the endpoint
+-- exposes until it's published again.
+CREATE TABLE published_endpoint (
+    id INTEGER PRIMARY KEY,
+    database_id INT NOT NULL,
+    name VARCHAR(64) NOT NULL,
+    saved_query_name VARCHAR(64) NOT NULL,
+    query_text TEXT NOT NULL,
+    parameters TEXT NOT NULL,
+    max_age_seconds INT NOT NULL,
+    publisher_id INT NOT NULL,
+    published_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(database_id) REFERENCES database(id),
+    FOREIGN KEY(publisher_id) REFERENCES entity(id),
+    UNIQUE(database_id, name)
+);
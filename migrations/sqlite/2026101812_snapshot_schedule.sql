@@ -0,0 +1,5 @@
+-- How often a database is snapshotted automatically, in the same
+-- format as the server's `[snapshots.automation]` interval, and
+-- whether it is at all. NULL falls back to the server's configuration.
+ALTER TABLE snapshot_policy ADD COLUMN snapshot_interval VARCHAR(32);
+ALTER TABLE snapshot_policy ADD COLUMN automatic_snapshots BOOLEAN;
@@ -1,9 +1,15 @@
 use crate::ayb_db::models::{
-    APIToken, APITokenWithDatabase, AuthenticationMethod, Database, DatabasePermission, Entity,
-    EntityDatabasePermission, EntityDatabaseSharingLevel, InstantiatedAuthenticationMethod,
-    InstantiatedDatabase, InstantiatedEntity, NewOAuthAuthorizationRequest,
-    OAuthAuthorizationRequest, OAuthAuthorizationRequestWithDatabase, PartialDatabase,
-    PartialEntity,
+    APIToken, APITokenWithDatabase, AuditLogEntry, AuditLogEntryWithSlugs, AuditLogFilter,
+    AuthenticationMethod, Database, DatabaseForkWithSlugs, DatabaseObjectGrant,
+    DatabaseObjectPermission, DatabasePermission, DatabaseTransfer, DatabaseTransferWithSlugs,
+    DatabaseWithSlugs, Entity, EntityDatabasePermission, EntityDatabaseSharingLevel,
+    InstantiatedAuthenticationMethod, InstantiatedDatabase, InstantiatedEntity, InstantiatedTeam,
+    InstantiatedWebhook, InstantiatedWebhookDelivery, NewOAuthAuthorizationRequest,
+    OAuthAuthorizationRequest, OAuthAuthorizationRequestWithDatabase, OrganizationMember,
+    OrganizationMembership, OrganizationRole, PartialDatabase, PartialEntity, PublishedEndpoint,
+    PublishedEndpointWithPublisher, SavedQuery, SavedQueryWithAuthor, SnapshotPolicy,
+    SnapshotScheduleWithSlugs, Team, TeamDatabasePermission, Webhook, WebhookDelivery,
+    WebhookDeliveryStatus,
 };
 use crate::error::AybError;
 use async_trait::async_trait;
@@ -28,16 +34,62 @@ use std::str::FromStr;
 pub trait AybDb: DynClone + Send + Sync {
     fn is_duplicate_constraint_error(&self, db_error: &dyn sqlx::error::DatabaseError) -> bool;
     async fn create_api_token(&self, api_token: &APIToken) -> Result<APIToken, AybError>;
+    async fn create_audit_log_entry(&self, entry: &AuditLogEntry) -> Result<(), AybError>;
     async fn create_authentication_method(
         &self,
         method: &AuthenticationMethod,
     ) -> Result<InstantiatedAuthenticationMethod, AybError>;
+    async fn complete_database_transfer(
+        &self,
+        transfer: &DatabaseTransfer,
+    ) -> Result<InstantiatedDatabase, AybError>;
     async fn create_database(&self, database: &Database) -> Result<InstantiatedDatabase, AybError>;
+    async fn create_forked_database(
+        &self,
+        database: &Database,
+        upstream_database_id: i32,
+        upstream_snapshot_id: Option<&str>,
+    ) -> Result<InstantiatedDatabase, AybError>;
+    async fn create_or_replace_database_transfer(
+        &self,
+        transfer: &DatabaseTransfer,
+    ) -> Result<(), AybError>;
+    async fn create_team(&self, team: &Team) -> Result<InstantiatedTeam, AybError>;
+    async fn create_webhook(&self, webhook: &Webhook) -> Result<InstantiatedWebhook, AybError>;
+    async fn create_webhook_delivery(&self, delivery: &WebhookDelivery) -> Result<(), AybError>;
+    async fn delete_audit_log_entries_before(
+        &self,
+        cutoff: chrono::NaiveDateTime,
+    ) -> Result<u64, AybError>;
+    async fn delete_database(&self, database_id: i32) -> Result<(), AybError>;
+    async fn delete_database_object_permissions(
+        &self,
+        database_id: i32,
+        entity_id: Option<i32>,
+        object_name: Option<&str>,
+    ) -> Result<(), AybError>;
+    async fn delete_database_transfer(&self, database_id: i32) -> Result<(), AybError>;
     async fn delete_entity_database_permission(
         &self,
         entity_id: i32,
         database_id: i32,
     ) -> Result<(), AybError>;
+    async fn delete_organization_membership(
+        &self,
+        organization_id: i32,
+        member_id: i32,
+    ) -> Result<(), AybError>;
+    async fn delete_published_endpoint(&self, database_id: i32, name: &str)
+        -> Result<(), AybError>;
+    async fn delete_saved_query(&self, database_id: i32, name: &str) -> Result<(), AybError>;
+    async fn delete_team(&self, team_id: i32) -> Result<(), AybError>;
+    async fn delete_team_database_permission(
+        &self,
+        team_id: i32,
+        database_id: i32,
+    ) -> Result<(), AybError>;
+    async fn delete_team_membership(&self, team_id: i32, member_id: i32) -> Result<(), AybError>;
+    async fn delete_webhook(&self, webhook_id: i32) -> Result<(), AybError>;
     async fn get_or_create_entity(&self, entity: &Entity) -> Result<InstantiatedEntity, AybError>;
     async fn get_api_token(&self, short_token: &str) -> Result<APIToken, AybError>;
     async fn get_database(
@@ -45,6 +97,14 @@ pub trait AybDb: DynClone + Send + Sync {
         entity_slug: &str,
         database_slug: &str,
     ) -> Result<InstantiatedDatabase, AybError>;
+    async fn get_database_fork(
+        &self,
+        database_id: i32,
+    ) -> Result<Option<DatabaseForkWithSlugs>, AybError>;
+    async fn get_database_transfer(
+        &self,
+        database_id: i32,
+    ) -> Result<Option<DatabaseTransfer>, AybError>;
     async fn get_entity_by_slug(&self, entity_slug: &str) -> Result<InstantiatedEntity, AybError>;
     async fn get_entity_by_id(&self, entity_id: i32) -> Result<InstantiatedEntity, AybError>;
     async fn get_entity_database_permission(
@@ -52,6 +112,48 @@ pub trait AybDb: DynClone + Send + Sync {
         entity: &InstantiatedEntity,
         database: &InstantiatedDatabase,
     ) -> Result<Option<EntityDatabasePermission>, AybError>;
+    async fn get_organization_membership(
+        &self,
+        organization_id: i32,
+        member_id: i32,
+    ) -> Result<Option<OrganizationMembership>, AybError>;
+    async fn get_published_endpoint(
+        &self,
+        database_id: i32,
+        name: &str,
+    ) -> Result<PublishedEndpointWithPublisher, AybError>;
+    async fn get_saved_query(
+        &self,
+        database_id: i32,
+        name: &str,
+    ) -> Result<SavedQueryWithAuthor, AybError>;
+    async fn get_snapshot_policy(
+        &self,
+        database_id: i32,
+    ) -> Result<Option<SnapshotPolicy>, AybError>;
+    async fn get_team(
+        &self,
+        organization_id: i32,
+        team_slug: &str,
+    ) -> Result<InstantiatedTeam, AybError>;
+    async fn get_team_sharing_levels(
+        &self,
+        entity: &InstantiatedEntity,
+        database: &InstantiatedDatabase,
+    ) -> Result<Vec<i16>, AybError>;
+    async fn get_webhook(&self, webhook_id: i32) -> Result<InstantiatedWebhook, AybError>;
+    async fn rename_database(
+        &self,
+        database_id: i32,
+        database_slug: &str,
+    ) -> Result<InstantiatedDatabase, AybError>;
+    async fn replace_database_object_permission(
+        &self,
+        database_id: i32,
+        entity_id: Option<i32>,
+        object_name: &str,
+        columns: Option<&[String]>,
+    ) -> Result<(), AybError>;
     async fn update_database_by_id(
         &self,
         database_id: i32,
@@ -62,14 +164,53 @@ pub trait AybDb: DynClone + Send + Sync {
         entity_id: i32,
         entity: &PartialEntity,
     ) -> Result<InstantiatedEntity, AybError>;
+    async fn update_member_sharing_level(
+        &self,
+        organization_id: i32,
+        member_sharing_level: i16,
+    ) -> Result<(), AybError>;
     async fn update_or_create_entity_database_permission(
         &self,
         permission: &EntityDatabasePermission,
     ) -> Result<(), AybError>;
+    async fn update_or_create_organization_membership(
+        &self,
+        membership: &OrganizationMembership,
+    ) -> Result<(), AybError>;
+    async fn update_or_create_published_endpoint(
+        &self,
+        endpoint: &PublishedEndpoint,
+    ) -> Result<(), AybError>;
+    async fn update_or_create_saved_query(&self, saved_query: &SavedQuery) -> Result<(), AybError>;
+    async fn update_or_create_snapshot_policy(
+        &self,
+        policy: &SnapshotPolicy,
+    ) -> Result<(), AybError>;
+    async fn update_or_create_team_database_permission(
+        &self,
+        permission: &TeamDatabasePermission,
+    ) -> Result<(), AybError>;
+    async fn update_or_create_team_membership(
+        &self,
+        team_id: i32,
+        member_id: i32,
+    ) -> Result<(), AybError>;
+    async fn update_webhook_delivery(
+        &self,
+        delivery: &InstantiatedWebhookDelivery,
+    ) -> Result<(), AybError>;
+    async fn list_audit_log_entries(
+        &self,
+        entity_id: i32,
+        filter: AuditLogFilter<'_>,
+        before_id: Option<i32>,
+        limit: i64,
+    ) -> Result<Vec<AuditLogEntryWithSlugs>, AybError>;
     async fn list_authentication_methods(
         &self,
         entity: &InstantiatedEntity,
     ) -> Result<Vec<InstantiatedAuthenticationMethod>, AybError>;
+    async fn list_all_databases(&self) -> Result<Vec<DatabaseWithSlugs>, AybError>;
     async fn list_databases_by_entity(
         &self,
         entity: &InstantiatedEntity,
@@ -78,10 +219,52 @@ pub trait AybDb: DynClone + Send + Sync {
         &self,
         database: &InstantiatedDatabase,
     ) -> Result<Vec<DatabasePermission>, AybError>;
+    async fn list_database_object_grants(
+        &self,
+        database: &InstantiatedDatabase,
+    ) -> Result<Vec<DatabaseObjectGrant>, AybError>;
+    async fn list_database_object_permissions(
+        &self,
+        database_id: i32,
+        entity_id: Option<i32>,
+    ) -> Result<Vec<DatabaseObjectPermission>, AybError>;
+    async fn list_database_transfers(
+        &self,
+        entity: &InstantiatedEntity,
+    ) -> Result<Vec<DatabaseTransferWithSlugs>, AybError>;
     async fn list_api_tokens(
         &self,
         entity: &InstantiatedEntity,
     ) -> Result<Vec<APITokenWithDatabase>, AybError>;
+    async fn list_organization_members(
+        &self,
+        organization: &InstantiatedEntity,
+    ) -> Result<Vec<OrganizationMember>, AybError>;
+    async fn list_published_endpoints(
+        &self,
+        database_id: i32,
+    ) -> Result<Vec<PublishedEndpointWithPublisher>, AybError>;
+    async fn list_snapshot_schedules(&self) -> Result<Vec<SnapshotScheduleWithSlugs>, AybError>;
+    async fn list_saved_queries(
+        &self,
+        database_id: i32,
+    ) -> Result<Vec<SavedQueryWithAuthor>, AybError>;
+    async fn list_team_members(&self, team_id: i32) -> Result<Vec<String>, AybError>;
+    async fn list_teams(
+        &self,
+        organization: &InstantiatedEntity,
+    ) -> Result<Vec<InstantiatedTeam>, AybError>;
+    async fn list_due_webhook_deliveries(
+        &self,
+        now: chrono::NaiveDateTime,
+        limit: i64,
+    ) -> Result<Vec<InstantiatedWebhookDelivery>, AybError>;
+    async fn list_webhook_deliveries(
+        &self,
+        webhook_id: i32,
+        limit: i64,
+    ) -> Result<Vec<InstantiatedWebhookDelivery>, AybError>;
+    async fn list_webhooks(&self, database_id: i32) -> Result<Vec<InstantiatedWebhook>, AybError>;
     async fn revoke_api_token(
         &self,
         entity: &InstantiatedEntity,
@@ -138,6 +321,25 @@ RETURNING entity_id, short_token, hash, database_id, query_permission_level, app
                 Ok(returned_token)
             }
 
+            async fn create_audit_log_entry(&self, entry: &AuditLogEntry) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO audit_log ( entity_id, database_id, database_slug, actor_id, token_short_id, action, details )
+VALUES ( $1, $2, $3, $4, $5, $6, $7 )
+                    "#,
+                )
+                .bind(entry.entity_id)
+                .bind(entry.database_id)
+                .bind(&entry.database_slug)
+                .bind(entry.actor_id)
+                .bind(&entry.token_short_id)
+                .bind(entry.action)
+                .bind(&entry.details)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
             async fn create_authentication_method(
                 &self,
                 method: &AuthenticationMethod,
@@ -159,6 +361,91 @@ RETURNING entity_id, short_token, hash, database_id, query_permission_level, app
                 Ok(instantiated_method)
             }
 
+            async fn complete_database_transfer(
+                &self,
+                transfer: &DatabaseTransfer,
+            ) -> Result<InstantiatedDatabase, AybError> {
+                let mut tx = self.pool.begin().await?;
+                let db: InstantiatedDatabase = sqlx::query_as(
+                    r#"
+UPDATE database
+SET entity_id = $1
+WHERE id = $2
+RETURNING id, entity_id, slug, db_type, public_sharing_level
+                "#,
+                )
+                .bind(transfer.to_entity_id)
+                .bind(transfer.database_id)
+                .fetch_one(&mut tx)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::Database(db_error)
+                        if self.is_duplicate_constraint_error(&*db_error) =>
+                    {
+                        Err(AybError::Other {
+                            message: "Recipient already has a database with the same name".to_string(),
+                        })
+                    }
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: transfer.database_id.to_string(),
+                        record_type: "database".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                // The new owner has implicit access to the database, so any
+                // permission they held before the transfer is dropped.
+                sqlx::query(
+                    r#"
+DELETE FROM entity_database_permission
+WHERE database_id = $1 AND (entity_id = $2 OR entity_id = $3)
+                "#,
+                )
+                .bind(transfer.database_id)
+                .bind(transfer.to_entity_id)
+                .bind(transfer.from_entity_id)
+                .execute(&mut tx)
+                .await?;
+                sqlx::query(
+                    r#"
+DELETE FROM database_object_permission
+WHERE database_id = $1 AND entity_id = $2
+                "#,
+                )
+                .bind(transfer.database_id)
+                .bind(transfer.to_entity_id)
+                .execute(&mut tx)
+                .await?;
+                if transfer.former_owner_sharing_level != EntityDatabaseSharingLevel::NoAccess as i16 {
+                    sqlx::query(
+                        r#"
+INSERT INTO entity_database_permission (entity_id, database_id, sharing_level)
+VALUES ($1, $2, $3)
+                "#,
+                    )
+                    .bind(transfer.from_entity_id)
+                    .bind(transfer.database_id)
+                    .bind(transfer.former_owner_sharing_level)
+                    .execute(&mut tx)
+                    .await?;
+                }
+                // Teams belong to the former owner, so their grants don't
+                // survive the transfer. Other entities' object grants, and
+                // those on public access, keep limiting them as before.
+                for statement in [
+                    "DELETE FROM team_database_permission WHERE database_id = $1",
+                    "DELETE FROM database_transfer WHERE database_id = $1",
+                ] {
+                    sqlx::query(statement)
+                        .bind(transfer.database_id)
+                        .execute(&mut tx)
+                        .await?;
+                }
+                tx.commit().await?;
+
+                Ok(db)
+            }
+
             async fn create_database(
                 &self,
                 database: &Database,
@@ -190,6 +477,239 @@ RETURNING entity_id, short_token, hash, database_id, query_permission_level, app
                 Ok(db)
             }
 
+            async fn create_team(&self, team: &Team) -> Result<InstantiatedTeam, AybError> {
+                let team: InstantiatedTeam = sqlx::query_as(
+                    r#"
+INSERT INTO team ( organization_id, slug )
+VALUES ( $1, $2 )
+RETURNING id, organization_id, slug
+                "#,
+                )
+                .bind(team.organization_id)
+                .bind(&team.slug)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::Database(db_error)
+                        if self.is_duplicate_constraint_error(&*db_error) =>
+                    {
+                        Err(AybError::Other {
+                            message: format!("Team {} already exists", team.slug),
+                        })
+                    }
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(team)
+            }
+
+            async fn create_webhook(
+                &self,
+                webhook: &Webhook,
+            ) -> Result<InstantiatedWebhook, AybError> {
+                let webhook: InstantiatedWebhook = sqlx::query_as(
+                    r#"
+INSERT INTO webhook ( database_id, url, tables, events, secret, creator_id )
+VALUES ( $1, $2, $3, $4, $5, $6 )
+RETURNING id, database_id, url, tables, events, secret, creator_id, created_at
+                "#,
+                )
+                .bind(webhook.database_id)
+                .bind(&webhook.url)
+                .bind(&webhook.tables)
+                .bind(&webhook.events)
+                .bind(&webhook.secret)
+                .bind(webhook.creator_id)
+                .fetch_one(&self.pool)
+                .await?;
+
+                Ok(webhook)
+            }
+
+            async fn create_webhook_delivery(
+                &self,
+                delivery: &WebhookDelivery,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO webhook_delivery ( webhook_id, payload, status, next_attempt_at )
+VALUES ( $1, $2, $3, $4 )
+                "#,
+                )
+                .bind(delivery.webhook_id)
+                .bind(&delivery.payload)
+                .bind(WebhookDeliveryStatus::Pending as i16)
+                .bind(delivery.next_attempt_at)
+                .execute(&self.pool)
+                .await?;
+
+                Ok(())
+            }
+
+            async fn create_forked_database(
+                &self,
+                database: &Database,
+                upstream_database_id: i32,
+                upstream_snapshot_id: Option<&str>,
+            ) -> Result<InstantiatedDatabase, AybError> {
+                let mut tx = self.pool.begin().await?;
+                let db: InstantiatedDatabase = sqlx::query_as(
+                    r#"
+                INSERT INTO database ( entity_id, slug, db_type, public_sharing_level )
+                VALUES ( $1, $2, $3, $4 )
+                RETURNING id, entity_id, slug, db_type, public_sharing_level
+                "#,
+                )
+                .bind(database.entity_id)
+                .bind(&database.slug)
+                .bind(database.db_type)
+                .bind(database.public_sharing_level)
+                .fetch_one(&mut tx)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::Database(db_error)
+                        if self.is_duplicate_constraint_error(&*db_error) =>
+                    {
+                        Err(AybError::Other {
+                            message: format!("Database {} already exists", database.slug),
+                        })
+                    }
+                    _ => Err(AybError::from(err)),
+                })?;
+                sqlx::query(
+                    r#"
+INSERT INTO database_fork (database_id, upstream_database_id, upstream_snapshot_id)
+VALUES ($1, $2, $3)
+                "#,
+                )
+                .bind(db.id)
+                .bind(upstream_database_id)
+                .bind(upstream_snapshot_id)
+                .execute(&mut tx)
+                .await?;
+                tx.commit().await?;
+
+                Ok(db)
+            }
+
+            async fn create_or_replace_database_transfer(
+                &self,
+                transfer: &DatabaseTransfer,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO database_transfer (database_id, from_entity_id, to_entity_id, former_owner_sharing_level)
+VALUES ($1, $2, $3, $4)
+ON CONFLICT (database_id) DO UPDATE
+    SET from_entity_id = $2,
+        to_entity_id = $3,
+        former_owner_sharing_level = $4,
+        created_at = CURRENT_TIMESTAMP
+            "#,
+                )
+                .bind(transfer.database_id)
+                .bind(transfer.from_entity_id)
+                .bind(transfer.to_entity_id)
+                .bind(transfer.former_owner_sharing_level)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn delete_audit_log_entries_before(
+                &self,
+                cutoff: chrono::NaiveDateTime,
+            ) -> Result<u64, AybError> {
+                let result = sqlx::query("DELETE FROM audit_log WHERE created_at < $1")
+                    .bind(cutoff)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(result.rows_affected())
+            }
+
+            async fn delete_database(&self, database_id: i32) -> Result<(), AybError> {
+                // Scoped API tokens and OAuth authorization requests
+                // reference the database, so they're removed along with
+                // it. Nulling out a token's `database_id` instead would
+                // silently widen its scope to every database.
+                let mut tx = self.pool.begin().await?;
+                for statement in [
+                    "DELETE FROM entity_database_permission WHERE database_id = $1",
+                    "DELETE FROM team_database_permission WHERE database_id = $1",
+                    "DELETE FROM database_object_permission WHERE database_id = $1",
+                    "DELETE FROM api_token WHERE database_id = $1",
+                    "DELETE FROM oauth_authorization_request WHERE database_id = $1",
+                    "DELETE FROM published_endpoint WHERE database_id = $1",
+                    "DELETE FROM webhook_delivery WHERE webhook_id IN (SELECT id FROM webhook WHERE database_id = $1)",
+                    "DELETE FROM webhook WHERE database_id = $1",
+                    "DELETE FROM saved_query WHERE database_id = $1",
+                    "DELETE FROM snapshot_policy WHERE database_id = $1",
+                    "DELETE FROM database_transfer WHERE database_id = $1",
+                    "DELETE FROM database_fork WHERE database_id = $1",
+                    // The database's audit log entries outlive it, but
+                    // are detached from its id, which might be reused.
+                    "UPDATE audit_log SET database_id = NULL WHERE database_id = $1",
+                    // Forks of this database outlive it, but lose the
+                    // link to their upstream.
+                    "UPDATE database_fork SET upstream_database_id = NULL WHERE upstream_database_id = $1",
+                ] {
+                    sqlx::query(statement)
+                        .bind(database_id)
+                        .execute(&mut tx)
+                        .await?;
+                }
+                let result = sqlx::query("DELETE FROM database WHERE id = $1")
+                    .bind(database_id)
+                    .execute(&mut tx)
+                    .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: database_id.to_string(),
+                        record_type: "database".into(),
+                    });
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+
+            async fn delete_database_object_permissions(
+                &self,
+                database_id: i32,
+                entity_id: Option<i32>,
+                object_name: Option<&str>,
+            ) -> Result<(), AybError> {
+                // A `None` entity refers to the grants on public access,
+                // and a `None` object removes all of the grantee's grants.
+                sqlx::query(
+                    r#"
+DELETE FROM database_object_permission
+WHERE database_id = $1
+    AND (entity_id = $2 OR (entity_id IS NULL AND $2 IS NULL))
+    AND ($3 IS NULL OR object_name = $3)
+                "#,
+                )
+                .bind(database_id)
+                .bind(entity_id)
+                .bind(object_name)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn delete_database_transfer(&self, database_id: i32) -> Result<(), AybError> {
+                let result = sqlx::query("DELETE FROM database_transfer WHERE database_id = $1")
+                    .bind(database_id)
+                    .execute(&self.pool)
+                    .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: database_id.to_string(),
+                        record_type: "database_transfer".into(),
+                    });
+                }
+                Ok(())
+            }
+
             async fn delete_entity_database_permission(
                 &self,
                 entity_id: i32,
@@ -278,11 +798,81 @@ WHERE
                 Ok(db)
             }
 
-            async fn get_entity_by_slug(
+            async fn get_database_fork(
                 &self,
-                entity_slug: &str,
-            ) -> Result<InstantiatedEntity, AybError> {
-                let entity: InstantiatedEntity = sqlx::query_as(
+                database_id: i32,
+            ) -> Result<Option<DatabaseForkWithSlugs>, AybError> {
+                let fork: Option<DatabaseForkWithSlugs> = sqlx::query_as(
+                    r#"
+SELECT
+    entity.slug as upstream_entity_slug,
+    database.slug as upstream_database_slug,
+    database_fork.upstream_snapshot_id,
+    database_fork.created_at
+FROM database_fork
+LEFT JOIN database ON database_fork.upstream_database_id = database.id
+LEFT JOIN entity ON database.entity_id = entity.id
+WHERE database_fork.database_id = $1
+        "#,
+                )
+                .bind(database_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                Ok(fork)
+            }
+
+            async fn get_snapshot_policy(
+                &self,
+                database_id: i32,
+            ) -> Result<Option<SnapshotPolicy>, AybError> {
+                let policy: Option<SnapshotPolicy> = sqlx::query_as(
+                    r#"
+SELECT
+    database_id,
+    retain_hourly,
+    retain_daily,
+    retain_weekly,
+    snapshot_interval,
+    automatic_snapshots
+FROM snapshot_policy
+WHERE database_id = $1
+        "#,
+                )
+                .bind(database_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                Ok(policy)
+            }
+
+            async fn get_database_transfer(
+                &self,
+                database_id: i32,
+            ) -> Result<Option<DatabaseTransfer>, AybError> {
+                let transfer: Option<DatabaseTransfer> = sqlx::query_as(
+                    r#"
+SELECT
+    database_id,
+    from_entity_id,
+    to_entity_id,
+    former_owner_sharing_level
+FROM database_transfer
+WHERE database_id = $1
+        "#,
+                )
+                .bind(database_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                Ok(transfer)
+            }
+
+            async fn get_entity_by_slug(
+                &self,
+                entity_slug: &str,
+            ) -> Result<InstantiatedEntity, AybError> {
+                let entity: InstantiatedEntity = sqlx::query_as(
                     r#"
 SELECT
     id,
@@ -292,7 +882,8 @@ SELECT
     description,
     organization,
     location,
-    links
+    links,
+    member_sharing_level
 FROM entity
 WHERE slug = $1
         "#,
@@ -325,7 +916,8 @@ SELECT
     description,
     organization,
     location,
-    links
+    links,
+    member_sharing_level
 FROM entity
 WHERE id = $1
         "#,
@@ -364,6 +956,251 @@ WHERE entity_id = $1
                 Ok(permission)
             }
 
+            async fn get_organization_membership(
+                &self,
+                organization_id: i32,
+                member_id: i32,
+            ) -> Result<Option<OrganizationMembership>, AybError> {
+                let membership: Option<OrganizationMembership> = sqlx::query_as(
+                    r#"
+SELECT
+    organization_id,
+    member_id,
+    role
+FROM organization_membership
+WHERE organization_id = $1 AND member_id = $2
+        "#,
+                )
+                .bind(organization_id)
+                .bind(member_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+                Ok(membership)
+            }
+
+            async fn get_published_endpoint(
+                &self,
+                database_id: i32,
+                name: &str,
+            ) -> Result<PublishedEndpointWithPublisher, AybError> {
+                let endpoint: PublishedEndpointWithPublisher = sqlx::query_as(
+                    r#"
+SELECT
+    published_endpoint.name,
+    published_endpoint.saved_query_name,
+    published_endpoint.query_text,
+    published_endpoint.parameters,
+    published_endpoint.max_age_seconds,
+    entity.slug AS publisher_slug,
+    published_endpoint.published_at
+FROM published_endpoint
+JOIN entity ON entity.id = published_endpoint.publisher_id
+WHERE published_endpoint.database_id = $1 AND published_endpoint.name = $2
+                "#,
+                )
+                .bind(database_id)
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: name.to_string(),
+                        record_type: "published_endpoint".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(endpoint)
+            }
+
+            async fn get_saved_query(
+                &self,
+                database_id: i32,
+                name: &str,
+            ) -> Result<SavedQueryWithAuthor, AybError> {
+                let saved_query: SavedQueryWithAuthor = sqlx::query_as(
+                    r#"
+SELECT
+    saved_query.id,
+    saved_query.name,
+    saved_query.query_text,
+    saved_query.description,
+    saved_query.parameters,
+    saved_query.author_id,
+    entity.slug AS author_slug,
+    saved_query.updated_at
+FROM saved_query
+JOIN entity ON entity.id = saved_query.author_id
+WHERE saved_query.database_id = $1 AND saved_query.name = $2
+                "#,
+                )
+                .bind(database_id)
+                .bind(name)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: name.to_string(),
+                        record_type: "saved_query".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(saved_query)
+            }
+
+            async fn get_team(
+                &self,
+                organization_id: i32,
+                team_slug: &str,
+            ) -> Result<InstantiatedTeam, AybError> {
+                let team: InstantiatedTeam = sqlx::query_as(
+                    r#"
+SELECT id, organization_id, slug
+FROM team
+WHERE organization_id = $1 AND slug = $2
+                "#,
+                )
+                .bind(organization_id)
+                .bind(team_slug)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: team_slug.to_string(),
+                        record_type: "team".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(team)
+            }
+
+            async fn get_team_sharing_levels(
+                &self,
+                entity: &InstantiatedEntity,
+                database: &InstantiatedDatabase,
+            ) -> Result<Vec<i16>, AybError> {
+                let levels: Vec<(i16,)> = sqlx::query_as(
+                    r#"
+SELECT team_database_permission.sharing_level
+FROM team_database_permission
+JOIN team_membership ON team_membership.team_id = team_database_permission.team_id
+WHERE team_membership.member_id = $1
+  AND team_database_permission.database_id = $2
+                "#,
+                )
+                .bind(entity.id)
+                .bind(database.id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(levels.into_iter().map(|(level,)| level).collect())
+            }
+
+            async fn get_webhook(&self, webhook_id: i32) -> Result<InstantiatedWebhook, AybError> {
+                let webhook: InstantiatedWebhook = sqlx::query_as(
+                    r#"
+SELECT id, database_id, url, tables, events, secret, creator_id, created_at
+FROM webhook
+WHERE id = $1
+                "#,
+                )
+                .bind(webhook_id)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: webhook_id.to_string(),
+                        record_type: "webhook".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(webhook)
+            }
+
+            async fn rename_database(
+                &self,
+                database_id: i32,
+                database_slug: &str,
+            ) -> Result<InstantiatedDatabase, AybError> {
+                let db: InstantiatedDatabase = sqlx::query_as(
+                    r#"
+UPDATE database
+SET slug = $1
+WHERE id = $2
+RETURNING id, entity_id, slug, db_type, public_sharing_level
+                "#,
+                )
+                .bind(database_slug)
+                .bind(database_id)
+                .fetch_one(&self.pool)
+                .await
+                .or_else(|err| match err {
+                    sqlx::Error::Database(db_error)
+                        if self.is_duplicate_constraint_error(&*db_error) =>
+                    {
+                        Err(AybError::Other {
+                            message: format!("Database {} already exists", database_slug),
+                        })
+                    }
+                    sqlx::Error::RowNotFound => Err(AybError::RecordNotFound {
+                        id: database_id.to_string(),
+                        record_type: "database".into(),
+                    }),
+                    _ => Err(AybError::from(err)),
+                })?;
+
+                Ok(db)
+            }
+
+            async fn replace_database_object_permission(
+                &self,
+                database_id: i32,
+                entity_id: Option<i32>,
+                object_name: &str,
+                columns: Option<&[String]>,
+            ) -> Result<(), AybError> {
+                // Each granted column gets its own row, and a single row
+                // with a NULL column grants the whole object.
+                let mut tx = self.pool.begin().await?;
+                sqlx::query(
+                    r#"
+DELETE FROM database_object_permission
+WHERE database_id = $1
+    AND (entity_id = $2 OR (entity_id IS NULL AND $2 IS NULL))
+    AND object_name = $3
+                "#,
+                )
+                .bind(database_id)
+                .bind(entity_id)
+                .bind(object_name)
+                .execute(&mut tx)
+                .await?;
+                let column_names: Vec<Option<&str>> = match columns {
+                    Some(columns) => columns.iter().map(|column| Some(column.as_str())).collect(),
+                    None => vec![None],
+                };
+                for column_name in column_names {
+                    sqlx::query(
+                        r#"
+INSERT INTO database_object_permission (database_id, entity_id, object_name, column_name)
+VALUES ($1, $2, $3, $4)
+                    "#,
+                    )
+                    .bind(database_id)
+                    .bind(entity_id)
+                    .bind(object_name)
+                    .bind(column_name)
+                    .execute(&mut tx)
+                    .await?;
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+
             async fn update_database_by_id(&self, database_id: i32, database: &PartialDatabase) -> Result<InstantiatedDatabase, AybError> {
                 let mut query = QueryBuilder::new("UPDATE database SET");
                 let mut updated_field = false;
@@ -444,7 +1281,7 @@ WHERE entity_id = $1
 
                 query.push(" WHERE entity.id = ");
                 query.push_bind(entity_id);
-                query.push(" RETURNING id, slug, entity_type, display_name, description, organization, location, links;");
+                query.push(" RETURNING id, slug, entity_type, display_name, description, organization, location, links, member_sharing_level;");
 
                 let entity: InstantiatedEntity = query.build_query_as()
                     .fetch_one(&self.pool)
@@ -460,6 +1297,19 @@ WHERE entity_id = $1
                 Ok(entity)
             }
 
+            async fn update_member_sharing_level(
+                &self,
+                organization_id: i32,
+                member_sharing_level: i16,
+            ) -> Result<(), AybError> {
+                sqlx::query("UPDATE entity SET member_sharing_level = $1 WHERE id = $2")
+                    .bind(member_sharing_level)
+                    .bind(organization_id)
+                    .execute(&self.pool)
+                    .await?;
+                Ok(())
+            }
+
             async fn update_or_create_entity_database_permission(
                 &self,
                 permission: &EntityDatabasePermission,
@@ -480,80 +1330,481 @@ ON CONFLICT (entity_id, database_id) DO UPDATE
                 Ok(())
             }
 
-
-
-            async fn get_or_create_entity(&self, entity: &Entity) -> Result<InstantiatedEntity, AybError> {
-                // Get or create logic inspired by https://stackoverflow.com/a/66337293
+            async fn delete_organization_membership(
+                &self,
+                organization_id: i32,
+                member_id: i32,
+            ) -> Result<(), AybError> {
+                // Leaving an organization also means leaving its teams.
                 let mut tx = self.pool.begin().await?;
                 sqlx::query(
                     r#"
-INSERT INTO entity ( slug, entity_type )
-VALUES ( $1, $2 )
-ON CONFLICT (slug) DO UPDATE
-    SET entity_type = $2
-    WHERE false;
+DELETE FROM team_membership
+WHERE member_id = $2
+  AND team_id IN (SELECT id FROM team WHERE organization_id = $1)
                 "#,
                 )
-                .bind(&entity.slug)
-                .bind(entity.entity_type)
+                .bind(organization_id)
+                .bind(member_id)
                 .execute(&mut tx)
                 .await?;
-                let entity: InstantiatedEntity = sqlx::query_as(
+                let result = sqlx::query(
                     r#"
-SELECT id, slug, entity_type, display_name, description, organization, location, links
-FROM entity
-WHERE slug = $1;
+DELETE FROM organization_membership
+WHERE organization_id = $1 AND member_id = $2
                 "#,
                 )
-                .bind(&entity.slug)
-                .fetch_one(&mut tx)
+                .bind(organization_id)
+                .bind(member_id)
+                .execute(&mut tx)
                 .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: format!("{organization_id}/{member_id}"),
+                        record_type: "organization_membership".into(),
+                    });
+                }
                 tx.commit().await?;
-                Ok(entity)
+                Ok(())
             }
 
-            async fn list_authentication_methods(
+            async fn delete_published_endpoint(
                 &self,
-                entity: &InstantiatedEntity,
-            ) -> Result<Vec<InstantiatedAuthenticationMethod>, AybError> {
-                let methods: Vec<InstantiatedAuthenticationMethod> = sqlx::query_as(
+                database_id: i32,
+                name: &str,
+            ) -> Result<(), AybError> {
+                let result = sqlx::query(
                     r#"
-SELECT
-    id,
-    entity_id,
-    method_type,
-    status,
-    email_address
-FROM authentication_method
-WHERE entity_id = $1
-        "#,
+DELETE FROM published_endpoint
+WHERE database_id = $1 AND name = $2
+                "#,
                 )
-                .bind(entity.id)
-                .fetch_all(&self.pool)
+                .bind(database_id)
+                .bind(name)
+                .execute(&self.pool)
                 .await?;
-
-                Ok(methods)
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: name.to_string(),
+                        record_type: "published_endpoint".into(),
+                    });
+                }
+                Ok(())
             }
 
-            async fn list_databases_by_entity(
+            async fn delete_saved_query(
                 &self,
-                entity: &InstantiatedEntity,
-            ) -> Result<Vec<InstantiatedDatabase>, AybError> {
-                let databases: Vec<InstantiatedDatabase> = sqlx::query_as(
+                database_id: i32,
+                name: &str,
+            ) -> Result<(), AybError> {
+                let result = sqlx::query(
                     r#"
-SELECT
-    id,
-    entity_id,
-    slug,
-    db_type,
-    public_sharing_level
-FROM database
-WHERE database.entity_id = $1
-ORDER BY id DESC
-                    "#,
+DELETE FROM saved_query
+WHERE database_id = $1 AND name = $2
+                "#,
                 )
-                .bind(entity.id)
-                .fetch_all(&self.pool)
+                .bind(database_id)
+                .bind(name)
+                .execute(&self.pool)
+                .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: name.to_string(),
+                        record_type: "saved_query".into(),
+                    });
+                }
+                Ok(())
+            }
+
+            async fn delete_team(&self, team_id: i32) -> Result<(), AybError> {
+                let mut tx = self.pool.begin().await?;
+                for statement in [
+                    "DELETE FROM team_database_permission WHERE team_id = $1",
+                    "DELETE FROM team_membership WHERE team_id = $1",
+                ] {
+                    sqlx::query(statement)
+                        .bind(team_id)
+                        .execute(&mut tx)
+                        .await?;
+                }
+                let result = sqlx::query("DELETE FROM team WHERE id = $1")
+                    .bind(team_id)
+                    .execute(&mut tx)
+                    .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: team_id.to_string(),
+                        record_type: "team".into(),
+                    });
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+
+            async fn delete_webhook(&self, webhook_id: i32) -> Result<(), AybError> {
+                let mut tx = self.pool.begin().await?;
+                sqlx::query("DELETE FROM webhook_delivery WHERE webhook_id = $1")
+                    .bind(webhook_id)
+                    .execute(&mut tx)
+                    .await?;
+                let result = sqlx::query("DELETE FROM webhook WHERE id = $1")
+                    .bind(webhook_id)
+                    .execute(&mut tx)
+                    .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: webhook_id.to_string(),
+                        record_type: "webhook".into(),
+                    });
+                }
+                tx.commit().await?;
+                Ok(())
+            }
+
+            async fn delete_team_database_permission(
+                &self,
+                team_id: i32,
+                database_id: i32,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+DELETE FROM team_database_permission
+WHERE team_id = $1 AND database_id = $2
+                "#,
+                )
+                .bind(team_id)
+                .bind(database_id)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn delete_team_membership(
+                &self,
+                team_id: i32,
+                member_id: i32,
+            ) -> Result<(), AybError> {
+                let result = sqlx::query(
+                    r#"
+DELETE FROM team_membership
+WHERE team_id = $1 AND member_id = $2
+                "#,
+                )
+                .bind(team_id)
+                .bind(member_id)
+                .execute(&self.pool)
+                .await?;
+                if result.rows_affected() == 0 {
+                    return Err(AybError::RecordNotFound {
+                        id: format!("{team_id}/{member_id}"),
+                        record_type: "team_membership".into(),
+                    });
+                }
+                Ok(())
+            }
+
+            async fn update_or_create_organization_membership(
+                &self,
+                membership: &OrganizationMembership,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO organization_membership (organization_id, member_id, role)
+VALUES ($1, $2, $3)
+ON CONFLICT (organization_id, member_id) DO UPDATE
+    SET role = $3
+            "#,
+                )
+                .bind(membership.organization_id)
+                .bind(membership.member_id)
+                .bind(membership.role)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn update_or_create_published_endpoint(
+                &self,
+                endpoint: &PublishedEndpoint,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO published_endpoint (database_id, name, saved_query_name, query_text, parameters, max_age_seconds, publisher_id)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+ON CONFLICT (database_id, name) DO UPDATE
+    SET saved_query_name = $3,
+        query_text = $4,
+        parameters = $5,
+        max_age_seconds = $6,
+        publisher_id = $7,
+        published_at = CURRENT_TIMESTAMP
+            "#,
+                )
+                .bind(endpoint.database_id)
+                .bind(&endpoint.name)
+                .bind(&endpoint.saved_query_name)
+                .bind(&endpoint.query_text)
+                .bind(&endpoint.parameters)
+                .bind(endpoint.max_age_seconds)
+                .bind(endpoint.publisher_id)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn update_or_create_saved_query(
+                &self,
+                saved_query: &SavedQuery,
+            ) -> Result<(), AybError> {
+                // Updating a saved query keeps its original author.
+                sqlx::query(
+                    r#"
+INSERT INTO saved_query (database_id, name, query_text, description, parameters, author_id)
+VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT (database_id, name) DO UPDATE
+    SET query_text = $3,
+        description = $4,
+        parameters = $5,
+        updated_at = CURRENT_TIMESTAMP
+            "#,
+                )
+                .bind(saved_query.database_id)
+                .bind(&saved_query.name)
+                .bind(&saved_query.query_text)
+                .bind(&saved_query.description)
+                .bind(&saved_query.parameters)
+                .bind(saved_query.author_id)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn update_or_create_snapshot_policy(
+                &self,
+                policy: &SnapshotPolicy,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO snapshot_policy (database_id, retain_hourly, retain_daily, retain_weekly, snapshot_interval, automatic_snapshots)
+VALUES ($1, $2, $3, $4, $5, $6)
+ON CONFLICT (database_id) DO UPDATE
+    SET retain_hourly = $2,
+        retain_daily = $3,
+        retain_weekly = $4,
+        snapshot_interval = $5,
+        automatic_snapshots = $6,
+        updated_at = CURRENT_TIMESTAMP
+            "#,
+                )
+                .bind(policy.database_id)
+                .bind(policy.retain_hourly)
+                .bind(policy.retain_daily)
+                .bind(policy.retain_weekly)
+                .bind(&policy.snapshot_interval)
+                .bind(policy.automatic_snapshots)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn update_or_create_team_database_permission(
+                &self,
+                permission: &TeamDatabasePermission,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO team_database_permission (team_id, database_id, sharing_level)
+VALUES ($1, $2, $3)
+ON CONFLICT (team_id, database_id) DO UPDATE
+    SET sharing_level = $3
+            "#,
+                )
+                .bind(permission.team_id)
+                .bind(permission.database_id)
+                .bind(permission.sharing_level)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn update_or_create_team_membership(
+                &self,
+                team_id: i32,
+                member_id: i32,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+INSERT INTO team_membership (team_id, member_id)
+VALUES ($1, $2)
+ON CONFLICT (team_id, member_id) DO NOTHING
+            "#,
+                )
+                .bind(team_id)
+                .bind(member_id)
+                .execute(&self.pool)
+                .await?;
+                Ok(())
+            }
+
+            async fn get_or_create_entity(&self, entity: &Entity) -> Result<InstantiatedEntity, AybError> {
+                // Get or create logic inspired by https://stackoverflow.com/a/66337293
+                let mut tx = self.pool.begin().await?;
+                sqlx::query(
+                    r#"
+INSERT INTO entity ( slug, entity_type )
+VALUES ( $1, $2 )
+ON CONFLICT (slug) DO UPDATE
+    SET entity_type = $2
+    WHERE false;
+                "#,
+                )
+                .bind(&entity.slug)
+                .bind(entity.entity_type)
+                .execute(&mut tx)
+                .await?;
+                let entity: InstantiatedEntity = sqlx::query_as(
+                    r#"
+SELECT id, slug, entity_type, display_name, description, organization, location, links, member_sharing_level
+FROM entity
+WHERE slug = $1;
+                "#,
+                )
+                .bind(&entity.slug)
+                .fetch_one(&mut tx)
+                .await?;
+                tx.commit().await?;
+                Ok(entity)
+            }
+
+            async fn update_webhook_delivery(
+                &self,
+                delivery: &InstantiatedWebhookDelivery,
+            ) -> Result<(), AybError> {
+                sqlx::query(
+                    r#"
+UPDATE webhook_delivery
+SET status = $1, attempts = $2, next_attempt_at = $3, response_status = $4, error = $5,
+    delivered_at = $6
+WHERE id = $7
+                "#,
+                )
+                .bind(delivery.status)
+                .bind(delivery.attempts)
+                .bind(delivery.next_attempt_at)
+                .bind(delivery.response_status)
+                .bind(&delivery.error)
+                .bind(delivery.delivered_at)
+                .bind(delivery.id)
+                .execute(&self.pool)
+                .await?;
+
+                Ok(())
+            }
+
+            async fn list_audit_log_entries(
+                &self,
+                entity_id: i32,
+                filter: AuditLogFilter<'_>,
+                before_id: Option<i32>,
+                limit: i64,
+            ) -> Result<Vec<AuditLogEntryWithSlugs>, AybError> {
+                let (database_id, deleted_database_slug) = match filter {
+                    AuditLogFilter::Entity => (None, None),
+                    AuditLogFilter::Database(database_id) => (Some(database_id), None),
+                    AuditLogFilter::DeletedDatabase(slug) => (None, Some(slug)),
+                };
+                let entries: Vec<AuditLogEntryWithSlugs> = sqlx::query_as(
+                    r#"
+SELECT
+    audit_log.id,
+    audit_log.database_slug,
+    entity.slug as actor_slug,
+    audit_log.token_short_id,
+    audit_log.action,
+    audit_log.details,
+    audit_log.created_at
+FROM audit_log
+JOIN entity ON audit_log.actor_id = entity.id
+WHERE (
+        ($2 IS NOT NULL AND audit_log.database_id = $2)
+        OR ($2 IS NULL AND audit_log.entity_id = $1)
+    )
+    AND ($3 IS NULL OR (audit_log.database_slug = $3 AND audit_log.database_id IS NULL))
+    AND ($4 IS NULL OR audit_log.id < $4)
+ORDER BY audit_log.id DESC
+LIMIT $5
+                    "#,
+                )
+                .bind(entity_id)
+                .bind(database_id)
+                .bind(deleted_database_slug)
+                .bind(before_id)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+                Ok(entries)
+            }
+
+            async fn list_authentication_methods(
+                &self,
+                entity: &InstantiatedEntity,
+            ) -> Result<Vec<InstantiatedAuthenticationMethod>, AybError> {
+                let methods: Vec<InstantiatedAuthenticationMethod> = sqlx::query_as(
+                    r#"
+SELECT
+    id,
+    entity_id,
+    method_type,
+    status,
+    email_address
+FROM authentication_method
+WHERE entity_id = $1
+        "#,
+                )
+                .bind(entity.id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(methods)
+            }
+
+            async fn list_all_databases(&self) -> Result<Vec<DatabaseWithSlugs>, AybError> {
+                let databases: Vec<DatabaseWithSlugs> = sqlx::query_as(
+                    r#"
+SELECT
+    entity.slug AS entity_slug,
+    database.slug AS database_slug,
+    database.db_type
+FROM database
+JOIN entity ON entity.id = database.entity_id
+ORDER BY entity.slug, database.slug
+        "#,
+                )
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(databases)
+            }
+
+            async fn list_databases_by_entity(
+                &self,
+                entity: &InstantiatedEntity,
+            ) -> Result<Vec<InstantiatedDatabase>, AybError> {
+                let databases: Vec<InstantiatedDatabase> = sqlx::query_as(
+                    r#"
+SELECT
+    id,
+    entity_id,
+    slug,
+    db_type,
+    public_sharing_level
+FROM database
+WHERE database.entity_id = $1
+ORDER BY id DESC
+                    "#,
+                )
+                .bind(entity.id)
+                .fetch_all(&self.pool)
                 .await?;
 
                 Ok(databases)
@@ -563,14 +1814,27 @@ ORDER BY id DESC
                 &self,
                 database: &InstantiatedDatabase,
             ) -> Result<Vec<DatabasePermission>, AybError> {
-                let permissions: Vec<(String, i16)> = sqlx::query_as(
+                // Direct grants come first, followed by one row for each
+                // member of each team with a grant.
+                let permissions: Vec<(String, i16, Option<String>)> = sqlx::query_as(
                     r#"
 SELECT
     entity.slug,
-    entity_database_permission.sharing_level
+    entity_database_permission.sharing_level,
+    NULL AS team_slug
 FROM entity_database_permission
 JOIN entity ON entity_database_permission.entity_id = entity.id
 WHERE entity_database_permission.database_id = $1
+UNION ALL
+SELECT
+    entity.slug,
+    team_database_permission.sharing_level,
+    team.slug AS team_slug
+FROM team_database_permission
+JOIN team ON team_database_permission.team_id = team.id
+JOIN team_membership ON team_membership.team_id = team.id
+JOIN entity ON team_membership.member_id = entity.id
+WHERE team_database_permission.database_id = $1
                     "#,
                 )
                 .bind(database.id)
@@ -579,7 +1843,7 @@ WHERE entity_database_permission.database_id = $1
 
                 let sharing_entries = permissions
                     .into_iter()
-                    .map(|(entity_slug, sharing_level)| {
+                    .map(|(entity_slug, sharing_level, team_slug)| {
                         let sharing_level_enum = EntityDatabaseSharingLevel::try_from(sharing_level)
                             .map_err(|_| AybError::Other {
                                 message: format!("Invalid sharing level: {}", sharing_level),
@@ -587,6 +1851,10 @@ WHERE entity_database_permission.database_id = $1
                         Ok(DatabasePermission {
                             entity_slug,
                             sharing_level: sharing_level_enum.to_str().to_string(),
+                            source: match team_slug {
+                                Some(team_slug) => format!("team:{team_slug}"),
+                                None => "direct".to_string(),
+                            },
                         })
                     })
                     .collect::<Result<Vec<_>, AybError>>()?;
@@ -594,6 +1862,102 @@ WHERE entity_database_permission.database_id = $1
                 Ok(sharing_entries)
             }
 
+            async fn list_database_object_grants(
+                &self,
+                database: &InstantiatedDatabase,
+            ) -> Result<Vec<DatabaseObjectGrant>, AybError> {
+                let rows: Vec<(Option<String>, String, Option<String>)> = sqlx::query_as(
+                    r#"
+SELECT
+    entity.slug,
+    database_object_permission.object_name,
+    database_object_permission.column_name
+FROM database_object_permission
+LEFT JOIN entity ON database_object_permission.entity_id = entity.id
+WHERE database_object_permission.database_id = $1
+ORDER BY entity.slug IS NOT NULL, entity.slug, database_object_permission.object_name, database_object_permission.column_name
+                    "#,
+                )
+                .bind(database.id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                // Collapse the per-column rows into one grant per object.
+                let mut grants: Vec<DatabaseObjectGrant> = Vec::new();
+                for (entity_slug, object, column) in rows {
+                    let entity_slug = entity_slug.unwrap_or_else(|| "public".to_string());
+                    let column = column.unwrap_or_else(|| "*".to_string());
+                    match grants.last_mut() {
+                        Some(grant) if grant.entity_slug == entity_slug && grant.object == object => {
+                            grant.columns = format!("{}, {}", grant.columns, column);
+                        }
+                        _ => grants.push(DatabaseObjectGrant {
+                            entity_slug,
+                            object,
+                            columns: column,
+                        }),
+                    }
+                }
+                Ok(grants)
+            }
+
+            async fn list_database_object_permissions(
+                &self,
+                database_id: i32,
+                entity_id: Option<i32>,
+            ) -> Result<Vec<DatabaseObjectPermission>, AybError> {
+                let permissions: Vec<DatabaseObjectPermission> = sqlx::query_as(
+                    r#"
+SELECT database_id, entity_id, object_name, column_name
+FROM database_object_permission
+WHERE database_id = $1
+    AND (entity_id = $2 OR (entity_id IS NULL AND $2 IS NULL))
+                    "#,
+                )
+                .bind(database_id)
+                .bind(entity_id)
+                .fetch_all(&self.pool)
+                .await?;
+                Ok(permissions)
+            }
+
+            async fn list_database_transfers(
+                &self,
+                entity: &InstantiatedEntity,
+            ) -> Result<Vec<DatabaseTransferWithSlugs>, AybError> {
+                let transfers: Vec<DatabaseTransferWithSlugs> = sqlx::query_as(
+                    r#"
+SELECT
+    database.slug as database_slug,
+    from_entity.slug as from_entity_slug,
+    to_entity.slug as to_entity_slug,
+    database_transfer.former_owner_sharing_level,
+    database_transfer.created_at
+FROM database_transfer
+JOIN database ON database_transfer.database_id = database.id
+JOIN entity AS from_entity ON database_transfer.from_entity_id = from_entity.id
+JOIN entity AS to_entity ON database_transfer.to_entity_id = to_entity.id
+WHERE database_transfer.from_entity_id = $1
+   OR database_transfer.to_entity_id = $1
+   OR database_transfer.from_entity_id IN (
+       SELECT organization_id FROM organization_membership WHERE member_id = $1 AND role >= $2
+   )
+   OR database_transfer.to_entity_id IN (
+       SELECT organization_id FROM organization_membership WHERE member_id = $1 AND role >= $2
+   )
+ORDER BY database_transfer.created_at DESC
+                    "#,
+                )
+                .bind(entity.id)
+                // Organization owners and admins handle transfers on
+                // the organization's behalf.
+                .bind(OrganizationRole::Admin as i16)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(transfers)
+            }
+
             async fn list_api_tokens(
                 &self,
                 entity: &InstantiatedEntity,
@@ -626,6 +1990,226 @@ ORDER BY api_token.created_at DESC NULLS LAST
                 Ok(tokens)
             }
 
+            async fn list_organization_members(
+                &self,
+                organization: &InstantiatedEntity,
+            ) -> Result<Vec<OrganizationMember>, AybError> {
+                let members: Vec<OrganizationMember> = sqlx::query_as(
+                    r#"
+SELECT
+    entity.slug as member_slug,
+    organization_membership.role
+FROM organization_membership
+JOIN entity ON organization_membership.member_id = entity.id
+WHERE organization_membership.organization_id = $1
+ORDER BY organization_membership.role DESC, entity.slug
+                    "#,
+                )
+                .bind(organization.id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(members)
+            }
+
+            async fn list_team_members(&self, team_id: i32) -> Result<Vec<String>, AybError> {
+                let members: Vec<(String,)> = sqlx::query_as(
+                    r#"
+SELECT entity.slug
+FROM team_membership
+JOIN entity ON team_membership.member_id = entity.id
+WHERE team_membership.team_id = $1
+ORDER BY entity.slug
+                "#,
+                )
+                .bind(team_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(members.into_iter().map(|(slug,)| slug).collect())
+            }
+
+            async fn list_published_endpoints(
+                &self,
+                database_id: i32,
+            ) -> Result<Vec<PublishedEndpointWithPublisher>, AybError> {
+                let endpoints: Vec<PublishedEndpointWithPublisher> = sqlx::query_as(
+                    r#"
+SELECT
+    published_endpoint.name,
+    published_endpoint.saved_query_name,
+    published_endpoint.query_text,
+    published_endpoint.parameters,
+    published_endpoint.max_age_seconds,
+    entity.slug AS publisher_slug,
+    published_endpoint.published_at
+FROM published_endpoint
+JOIN entity ON entity.id = published_endpoint.publisher_id
+WHERE published_endpoint.database_id = $1
+ORDER BY published_endpoint.name
+                "#,
+                )
+                .bind(database_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(endpoints)
+            }
+
+            async fn list_snapshot_schedules(
+                &self,
+            ) -> Result<Vec<SnapshotScheduleWithSlugs>, AybError> {
+                let schedules: Vec<SnapshotScheduleWithSlugs> = sqlx::query_as(
+                    r#"
+SELECT
+    entity.slug AS entity_slug,
+    database.slug AS database_slug,
+    snapshot_policy.snapshot_interval,
+    snapshot_policy.automatic_snapshots
+FROM snapshot_policy
+JOIN database ON database.id = snapshot_policy.database_id
+JOIN entity ON entity.id = database.entity_id
+WHERE snapshot_policy.snapshot_interval IS NOT NULL
+    OR snapshot_policy.automatic_snapshots IS NOT NULL
+        "#,
+                )
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(schedules)
+            }
+
+            async fn list_saved_queries(
+                &self,
+                database_id: i32,
+            ) -> Result<Vec<SavedQueryWithAuthor>, AybError> {
+                let saved_queries: Vec<SavedQueryWithAuthor> = sqlx::query_as(
+                    r#"
+SELECT
+    saved_query.id,
+    saved_query.name,
+    saved_query.query_text,
+    saved_query.description,
+    saved_query.parameters,
+    saved_query.author_id,
+    entity.slug AS author_slug,
+    saved_query.updated_at
+FROM saved_query
+JOIN entity ON entity.id = saved_query.author_id
+WHERE saved_query.database_id = $1
+ORDER BY saved_query.name
+                "#,
+                )
+                .bind(database_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(saved_queries)
+            }
+
+            async fn list_teams(
+                &self,
+                organization: &InstantiatedEntity,
+            ) -> Result<Vec<InstantiatedTeam>, AybError> {
+                let teams: Vec<InstantiatedTeam> = sqlx::query_as(
+                    r#"
+SELECT id, organization_id, slug
+FROM team
+WHERE organization_id = $1
+ORDER BY slug
+                "#,
+                )
+                .bind(organization.id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(teams)
+            }
+
+            async fn list_due_webhook_deliveries(
+                &self,
+                now: chrono::NaiveDateTime,
+                limit: i64,
+            ) -> Result<Vec<InstantiatedWebhookDelivery>, AybError> {
+                let deliveries: Vec<InstantiatedWebhookDelivery> = sqlx::query_as(
+                    r#"
+SELECT
+    id,
+    webhook_id,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    response_status,
+    error,
+    created_at,
+    delivered_at
+FROM webhook_delivery
+WHERE status = $1 AND next_attempt_at <= $2
+ORDER BY next_attempt_at, id
+LIMIT $3
+                "#,
+                )
+                .bind(WebhookDeliveryStatus::Pending as i16)
+                .bind(now)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(deliveries)
+            }
+
+            async fn list_webhook_deliveries(
+                &self,
+                webhook_id: i32,
+                limit: i64,
+            ) -> Result<Vec<InstantiatedWebhookDelivery>, AybError> {
+                let deliveries: Vec<InstantiatedWebhookDelivery> = sqlx::query_as(
+                    r#"
+SELECT
+    id,
+    webhook_id,
+    payload,
+    status,
+    attempts,
+    next_attempt_at,
+    response_status,
+    error,
+    created_at,
+    delivered_at
+FROM webhook_delivery
+WHERE webhook_id = $1
+ORDER BY id DESC
+LIMIT $2
+                "#,
+                )
+                .bind(webhook_id)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(deliveries)
+            }
+
+            async fn list_webhooks(
+                &self,
+                database_id: i32,
+            ) -> Result<Vec<InstantiatedWebhook>, AybError> {
+                let webhooks: Vec<InstantiatedWebhook> = sqlx::query_as(
+                    r#"
+SELECT id, database_id, url, tables, events, secret, creator_id, created_at
+FROM webhook
+WHERE database_id = $1
+ORDER BY id
+                "#,
+                )
+                .bind(database_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+                Ok(webhooks)
+            }
+
             async fn revoke_api_token(
                 &self,
                 entity: &InstantiatedEntity,
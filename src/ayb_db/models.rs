@@ -241,6 +241,7 @@ pub struct InstantiatedEntity {
     pub organization: Option<String>,
     pub location: Option<String>,
     pub links: Option<sqlx::types::Json<Vec<Link>>>,
+    pub member_sharing_level: i16,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -298,13 +299,15 @@ pub struct APITokenWithDatabase {
 pub enum EntityDatabaseSharingLevel {
     NoAccess = 0,
     ReadOnly = 1,
-    ReadWrite = 2,
-    Manager = 3,
+    ReadWriteData = 2, // Can write data, but not change the schema
+    ReadWrite = 3,
+    Manager = 4,
 }
 
 from_str!(EntityDatabaseSharingLevel, {
     "no-access" => EntityDatabaseSharingLevel::NoAccess,
     "read-only" => EntityDatabaseSharingLevel::ReadOnly,
+    "read-write-data" => EntityDatabaseSharingLevel::ReadWriteData,
     "read-write" => EntityDatabaseSharingLevel::ReadWrite,
     "manager" => EntityDatabaseSharingLevel::Manager
 });
@@ -312,8 +315,9 @@ from_str!(EntityDatabaseSharingLevel, {
 try_from_i16!(EntityDatabaseSharingLevel, {
     0 => EntityDatabaseSharingLevel::NoAccess,
     1 => EntityDatabaseSharingLevel::ReadOnly,
-    2 => EntityDatabaseSharingLevel::ReadWrite,
-    3 => EntityDatabaseSharingLevel::Manager
+    2 => EntityDatabaseSharingLevel::ReadWriteData,
+    3 => EntityDatabaseSharingLevel::ReadWrite,
+    4 => EntityDatabaseSharingLevel::Manager
 });
 
 impl EntityDatabaseSharingLevel {
@@ -321,6 +325,7 @@ impl EntityDatabaseSharingLevel {
         match self {
             EntityDatabaseSharingLevel::NoAccess => "no-access",
             EntityDatabaseSharingLevel::ReadOnly => "read-only",
+            EntityDatabaseSharingLevel::ReadWriteData => "read-write-data",
             EntityDatabaseSharingLevel::ReadWrite => "read-write",
             EntityDatabaseSharingLevel::Manager => "manager",
         }
@@ -338,6 +343,156 @@ pub struct EntityDatabasePermission {
 pub struct DatabasePermission {
     pub entity_slug: String,
     pub sharing_level: String,
+    pub source: String, // "direct", or "team:<team slug>" for grants through a team
+}
+
+/// Limits an entity (or, when `entity_id` is `None`, the public) to
+/// `column_name` of `object_name`. A `None` column grants every column.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseObjectPermission {
+    pub database_id: i32,
+    pub entity_id: Option<i32>,
+    pub object_name: String,
+    pub column_name: Option<String>,
+}
+
+/// An object grant with the grantee's slug, or "public" for grants
+/// that limit public access. `columns` is "*" for the whole object.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseObjectGrant {
+    pub entity_slug: String,
+    pub object: String,
+    pub columns: String,
+}
+
+#[derive(
+    Serialize_repr, Deserialize_repr, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum,
+)]
+#[repr(i16)]
+pub enum OrganizationRole {
+    Member = 0,
+    Admin = 1,
+    Owner = 2,
+}
+
+from_str!(OrganizationRole, {
+    "member" => OrganizationRole::Member,
+    "admin" => OrganizationRole::Admin,
+    "owner" => OrganizationRole::Owner
+});
+
+try_from_i16!(OrganizationRole, {
+    0 => OrganizationRole::Member,
+    1 => OrganizationRole::Admin,
+    2 => OrganizationRole::Owner
+});
+
+impl OrganizationRole {
+    pub fn to_str(&self) -> &str {
+        match self {
+            OrganizationRole::Member => "member",
+            OrganizationRole::Admin => "admin",
+            OrganizationRole::Owner => "owner",
+        }
+    }
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct OrganizationMembership {
+    pub organization_id: i32,
+    pub member_id: i32,
+    pub role: i16,
+}
+
+/// Organization membership enriched with the JOINed member slug.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct OrganizationMember {
+    pub member_slug: String,
+    pub role: i16,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Team {
+    pub organization_id: i32,
+    pub slug: String,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct InstantiatedTeam {
+    pub id: i32,
+    pub organization_id: i32,
+    pub slug: String,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct TeamDatabasePermission {
+    pub team_id: i32,
+    pub database_id: i32,
+    pub sharing_level: i16,
+}
+
+/// A pending proposal to transfer ownership of a database from
+/// `from_entity_id` (its current owner) to `to_entity_id`.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseTransfer {
+    pub database_id: i32,
+    pub from_entity_id: i32,
+    pub to_entity_id: i32,
+    pub former_owner_sharing_level: i16,
+}
+
+/// Pending database transfer enriched with JOINed entity and database slugs.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseTransferWithSlugs {
+    pub database_slug: String,
+    pub from_entity_slug: String,
+    pub to_entity_slug: String,
+    pub former_owner_sharing_level: i16,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Upstream of a forked database enriched with JOINed entity and
+/// database slugs, which are `None` if the upstream was deleted.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseForkWithSlugs {
+    pub upstream_entity_slug: Option<String>,
+    pub upstream_database_slug: Option<String>,
+    pub upstream_snapshot_id: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// A database's overrides of the server's snapshot settings. Unset
+/// settings fall back to the server's configuration.
+#[derive(Debug, Default, FromRow, Serialize, Deserialize)]
+pub struct SnapshotPolicy {
+    pub database_id: i32,
+    // How many hours, days, and weeks back to keep the newest
+    // snapshot of each.
+    pub retain_hourly: Option<i32>,
+    pub retain_daily: Option<i32>,
+    pub retain_weekly: Option<i32>,
+    // How often to snapshot the database automatically, in the same
+    // format as the server's interval, and whether to at all.
+    pub snapshot_interval: Option<String>,
+    pub automatic_snapshots: Option<bool>,
+}
+
+/// The snapshot schedule a database set for itself, enriched with the
+/// JOINed entity and database slugs.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct SnapshotScheduleWithSlugs {
+    pub entity_slug: String,
+    pub database_slug: String,
+    pub snapshot_interval: Option<String>,
+    pub automatic_snapshots: Option<bool>,
+}
+
+/// A database enriched with the JOINed entity slug.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct DatabaseWithSlugs {
+    pub entity_slug: String,
+    pub database_slug: String,
+    pub db_type: i16,
 }
 
 /// OAuth authorization request for pending authorization codes.
@@ -391,3 +546,254 @@ pub struct NewOAuthAuthorizationRequest {
     pub query_permission_level: i16,
     pub expires_at: chrono::NaiveDateTime,
 }
+
+#[derive(
+    Serialize_repr, Deserialize_repr, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum,
+)]
+#[repr(i16)]
+pub enum AuditAction {
+    CreateDatabase = 0,
+    DeleteDatabase = 1,
+    Share = 2,
+    CreateToken = 3,
+    RevokeToken = 4,
+    RestoreSnapshot = 5,
+    Query = 6,
+    SaveQuery = 7,
+    DeleteSavedQuery = 8,
+    PublishEndpoint = 9,
+    UnpublishEndpoint = 10,
+    CreateWebhook = 11,
+    DeleteWebhook = 12,
+    CreateSnapshot = 13,
+    UpdateSnapshotRetention = 14,
+    UpdateSnapshotSchedule = 15,
+}
+
+from_str!(AuditAction, {
+    "create-database" => AuditAction::CreateDatabase,
+    "delete-database" => AuditAction::DeleteDatabase,
+    "share" => AuditAction::Share,
+    "create-token" => AuditAction::CreateToken,
+    "revoke-token" => AuditAction::RevokeToken,
+    "restore-snapshot" => AuditAction::RestoreSnapshot,
+    "query" => AuditAction::Query,
+    "save-query" => AuditAction::SaveQuery,
+    "delete-saved-query" => AuditAction::DeleteSavedQuery,
+    "publish-endpoint" => AuditAction::PublishEndpoint,
+    "unpublish-endpoint" => AuditAction::UnpublishEndpoint,
+    "create-webhook" => AuditAction::CreateWebhook,
+    "delete-webhook" => AuditAction::DeleteWebhook,
+    "create-snapshot" => AuditAction::CreateSnapshot,
+    "update-snapshot-retention" => AuditAction::UpdateSnapshotRetention,
+    "update-snapshot-schedule" => AuditAction::UpdateSnapshotSchedule
+});
+
+try_from_i16!(AuditAction, {
+    0 => AuditAction::CreateDatabase,
+    1 => AuditAction::DeleteDatabase,
+    2 => AuditAction::Share,
+    3 => AuditAction::CreateToken,
+    4 => AuditAction::RevokeToken,
+    5 => AuditAction::RestoreSnapshot,
+    6 => AuditAction::Query,
+    7 => AuditAction::SaveQuery,
+    8 => AuditAction::DeleteSavedQuery,
+    9 => AuditAction::PublishEndpoint,
+    10 => AuditAction::UnpublishEndpoint,
+    11 => AuditAction::CreateWebhook,
+    12 => AuditAction::DeleteWebhook,
+    13 => AuditAction::CreateSnapshot,
+    14 => AuditAction::UpdateSnapshotRetention,
+    15 => AuditAction::UpdateSnapshotSchedule
+});
+
+impl AuditAction {
+    pub fn to_str(&self) -> &str {
+        match self {
+            AuditAction::CreateDatabase => "create-database",
+            AuditAction::DeleteDatabase => "delete-database",
+            AuditAction::Share => "share",
+            AuditAction::CreateToken => "create-token",
+            AuditAction::RevokeToken => "revoke-token",
+            AuditAction::RestoreSnapshot => "restore-snapshot",
+            AuditAction::Query => "query",
+            AuditAction::SaveQuery => "save-query",
+            AuditAction::DeleteSavedQuery => "delete-saved-query",
+            AuditAction::PublishEndpoint => "publish-endpoint",
+            AuditAction::UnpublishEndpoint => "unpublish-endpoint",
+            AuditAction::CreateWebhook => "create-webhook",
+            AuditAction::DeleteWebhook => "delete-webhook",
+            AuditAction::CreateSnapshot => "create-snapshot",
+            AuditAction::UpdateSnapshotRetention => "update-snapshot-retention",
+            AuditAction::UpdateSnapshotSchedule => "update-snapshot-schedule",
+        }
+    }
+}
+
+/// An action `actor_id` took in the namespace of `entity_id`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub entity_id: i32,
+    pub database_id: Option<i32>,
+    pub database_slug: Option<String>,
+    pub actor_id: i32,
+    pub token_short_id: Option<String>,
+    pub action: i16,
+    pub details: String,
+}
+
+/// Which entries of an entity's audit log to list.
+#[derive(Clone, Copy, Debug)]
+pub enum AuditLogFilter<'a> {
+    /// Every entry in the entity's namespace.
+    Entity,
+    /// Every entry about the database with this id, including ones from
+    /// before it was renamed or transferred.
+    Database(i32),
+    /// The entries about a database with this slug that has since been
+    /// deleted from the entity's namespace.
+    DeletedDatabase(&'a str),
+}
+
+/// Audit log entry enriched with the JOINed actor slug.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct AuditLogEntryWithSlugs {
+    pub id: i32,
+    pub database_slug: Option<String>,
+    pub actor_slug: String,
+    pub token_short_id: Option<String>,
+    pub action: i16,
+    pub details: String,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// A named query attached to `database_id`. `parameters` holds the
+/// JSON-encoded `SavedQueryParameter`s the query accepts.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub database_id: i32,
+    pub name: String,
+    pub query_text: String,
+    pub description: Option<String>,
+    pub parameters: String,
+    pub author_id: i32,
+}
+
+/// Saved query enriched with the JOINed author slug.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct SavedQueryWithAuthor {
+    pub id: i32,
+    pub name: String,
+    pub query_text: String,
+    pub description: Option<String>,
+    pub parameters: String,
+    pub author_id: i32,
+    pub author_slug: String,
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+/// A saved query of `database_id` published at `name`, with a copy of
+/// the query and its parameters as they were when it was published.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishedEndpoint {
+    pub database_id: i32,
+    pub name: String,
+    pub saved_query_name: String,
+    pub query_text: String,
+    pub parameters: String,
+    pub max_age_seconds: i32,
+    pub publisher_id: i32,
+}
+
+/// Published endpoint enriched with the JOINed publisher slug.
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct PublishedEndpointWithPublisher {
+    pub name: String,
+    pub saved_query_name: String,
+    pub query_text: String,
+    pub parameters: String,
+    pub max_age_seconds: i32,
+    pub publisher_slug: String,
+    pub published_at: chrono::NaiveDateTime,
+}
+
+/// A subscription that notifies `url` about committed changes to
+/// `database_id`. `tables` and `events` hold JSON-encoded lists of
+/// table names and `ChangeOperation`s.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Webhook {
+    pub database_id: i32,
+    pub url: String,
+    pub tables: String,
+    pub events: String,
+    pub secret: String,
+    pub creator_id: i32,
+}
+
+#[derive(Clone, Debug, FromRow, Serialize, Deserialize)]
+pub struct InstantiatedWebhook {
+    pub id: i32,
+    pub database_id: i32,
+    pub url: String,
+    pub tables: String,
+    pub events: String,
+    pub secret: String,
+    pub creator_id: i32,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(
+    Serialize_repr, Deserialize_repr, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum,
+)]
+#[repr(i16)]
+pub enum WebhookDeliveryStatus {
+    Pending = 0,
+    Delivered = 1,
+    Failed = 2,
+}
+
+from_str!(WebhookDeliveryStatus, {
+    "pending" => WebhookDeliveryStatus::Pending,
+    "delivered" => WebhookDeliveryStatus::Delivered,
+    "failed" => WebhookDeliveryStatus::Failed
+});
+
+try_from_i16!(WebhookDeliveryStatus, {
+    0 => WebhookDeliveryStatus::Pending,
+    1 => WebhookDeliveryStatus::Delivered,
+    2 => WebhookDeliveryStatus::Failed
+});
+
+impl WebhookDeliveryStatus {
+    pub fn to_str(&self) -> &str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// A change to deliver to `webhook_id`, first attempted at
+/// `next_attempt_at`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub webhook_id: i32,
+    pub payload: String,
+    pub next_attempt_at: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, FromRow, Serialize, Deserialize)]
+pub struct InstantiatedWebhookDelivery {
+    pub id: i32,
+    pub webhook_id: i32,
+    pub payload: String,
+    pub status: i16,
+    pub attempts: i32,
+    pub next_attempt_at: chrono::NaiveDateTime,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+    pub delivered_at: Option<chrono::NaiveDateTime>,
+}
@@ -1,6 +1,7 @@
 use ayb::client::cli::{client_commands, execute_client_command};
 use ayb::server::config::{config_to_toml, default_server_config};
 use ayb::server::server_runner::run_server;
+use ayb::server::snapshots::recovery::recover_server;
 use clap::{arg, command, value_parser, Command};
 use std::path::PathBuf;
 
@@ -19,6 +20,20 @@ async fn main() -> std::io::Result<()> {
                         .value_parser(value_parser!(PathBuf))
                         .env("AYB_SERVER_CONFIG_FILE")
                         .default_value("./ayb.toml"),
+                )
+                .subcommand(
+                    Command::new("recover")
+                        .about("Rebuild a server from its snapshot storage")
+                        .long_about("Rebuild a server from its snapshot storage, e.g. on a fresh machine.\n\
+                            Restores the metadata database from its latest backup (unless it already\n\
+                            exists), and then the latest version of every database it lists that\n\
+                            isn't on disk.")
+                        .arg(
+                            arg!(--config <FILE> "Path to the server's configuration file (optional if using env vars)")
+                                .value_parser(value_parser!(PathBuf))
+                                .env("AYB_SERVER_CONFIG_FILE")
+                                .default_value("./ayb.toml"),
+                        ),
                 ),
         )
         .subcommand(
@@ -29,7 +44,14 @@ async fn main() -> std::io::Result<()> {
         .get_matches();
 
     if let Some(matches) = matches.subcommand_matches("server") {
-        if let Some(config) = matches.get_one::<PathBuf>("config") {
+        if let Some(matches) = matches.subcommand_matches("recover") {
+            if let Some(config) = matches.get_one::<PathBuf>("config") {
+                if let Err(err) = recover_server(config).await {
+                    eprintln!("Error: {err}");
+                    std::process::exit(1);
+                }
+            }
+        } else if let Some(config) = matches.get_one::<PathBuf>("config") {
             run_server(config).await?;
         }
     } else if let Some(_matches) = matches.subcommand_matches("default_server_config") {
@@ -1,6 +1,7 @@
 use ayb::ayb_db::models::DBType;
 use ayb::hosted_db::engine::DbEngine;
-use ayb::hosted_db::sandbox::apply_sandbox;
+use ayb::hosted_db::restrictions::QueryRestrictions;
+use ayb::hosted_db::sandbox::{apply_sandbox, READ_ONLY};
 use ayb::hosted_db::{engine_for, QueryMode};
 use serde::{Deserialize, Serialize};
 use std::env;
@@ -12,18 +13,37 @@ use std::str::FromStr;
 struct QueryRequest {
     query: String,
     query_mode: i16,
+    #[serde(default)]
+    restrictions: Option<QueryRestrictions>,
+    #[serde(default)]
+    capture_changes: bool,
+    #[serde(default)]
+    retain_wal: bool,
 }
 
 /// This binary runs as a persistent daemon that executes queries
 /// against a database and returns results in QueryResult format.
 ///
 /// Usage:
-/// $ ayb_query_daemon <database_file> <db_type>
+/// $ ayb_query_daemon <database_file> <db_type> [read-only]
 ///
 /// The daemon reads line-delimited JSON requests from stdin:
-/// {"query":"SELECT * FROM x","query_mode":[0=read-only|1=read-write]}
+/// {"query":"SELECT * FROM x","query_mode":[0=read-only|1=read-write-data|2=read-write]}
 ///
-/// And writes line-delimited JSON responses to stdout.
+/// Requests can also carry `restrictions`, which limit the query to
+/// specific tables, views, and columns (see `QueryRestrictions`), and
+/// `capture_changes`, which asks for the rows the query changed, and
+/// `retain_wal`, which leaves the write-ahead log for the server to
+/// replicate.
+///
+/// In read-only mode (e.g., for querying a snapshot), every query runs
+/// read-only regardless of its `query_mode`, and the sandbox only lets
+/// the daemon read the database's directory.
+///
+/// And writes line-delimited JSON responses to stdout. Responses to
+/// queries that captured changes list them in `changes` (see
+/// `QueryOutput`). The daemon can't reach the network, so the server
+/// acts on those changes (e.g., by delivering webhooks).
 ///
 /// At startup the daemon applies as much sandboxing as the host
 /// supports (Landlock filesystem/network restrictions, setrlimit
@@ -33,28 +53,32 @@ struct QueryRequest {
 /// See src/hosted_db/sandbox.rs.
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
-    let (db_file, db_type) = parse_args(&args)?;
+    let (db_file, db_type, read_only) = parse_args(&args)?;
 
     let engine = engine_for(&db_type);
-    apply_sandbox(&db_file)?;
+    apply_sandbox(&db_file, read_only)?;
 
-    run(db_file, engine)
+    run(db_file, engine, read_only)
 }
 
-fn parse_args(args: &[String]) -> Result<(PathBuf, DBType), Box<dyn std::error::Error>> {
+fn parse_args(args: &[String]) -> Result<(PathBuf, DBType, bool), Box<dyn std::error::Error>> {
     match args.len() {
-        3 => {
+        3 | 4 if args.get(3).is_none_or(|mode| mode == READ_ONLY) => {
             let db_type = DBType::from_str(&args[2])?;
-            Ok((PathBuf::from(&args[1]), db_type))
+            Ok((PathBuf::from(&args[1]), db_type, args.len() == 4))
         }
         _ => {
-            eprintln!("Usage: ayb_query_daemon <database_file> <db_type>");
+            eprintln!("Usage: ayb_query_daemon <database_file> <db_type> [{READ_ONLY}]");
             std::process::exit(1);
         }
     }
 }
 
-fn run(db_file: PathBuf, engine: &dyn DbEngine) -> Result<(), Box<dyn std::error::Error>> {
+fn run(
+    db_file: PathBuf,
+    engine: &dyn DbEngine,
+    read_only: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let stdin = io::stdin();
     let mut stdout = io::stdout();
 
@@ -74,10 +98,11 @@ fn run(db_file: PathBuf, engine: &dyn DbEngine) -> Result<(), Box<dyn std::error
         };
 
         let query_mode = match QueryMode::try_from(request.query_mode) {
+            Ok(_) if read_only => QueryMode::ReadOnly,
             Ok(mode) => mode,
             Err(_) => {
                 let error_response = serde_json::json!({
-                    "error": "Invalid query_mode, must be 0 or 1"
+                    "error": "Invalid query_mode, must be 0, 1, or 2"
                 });
                 writeln!(stdout, "{error_response}")?;
                 stdout.flush()?;
@@ -85,7 +110,14 @@ fn run(db_file: PathBuf, engine: &dyn DbEngine) -> Result<(), Box<dyn std::error
             }
         };
 
-        let result = engine.query(&db_file, &request.query, query_mode);
+        let result = engine.query(
+            &db_file,
+            &request.query,
+            query_mode,
+            request.restrictions.as_ref(),
+            request.capture_changes,
+            request.retain_wal,
+        );
 
         match result {
             Ok(result) => {
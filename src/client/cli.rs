@@ -1,11 +1,19 @@
-use crate::ayb_db::models::{DBType, EntityDatabaseSharingLevel, EntityType, PublicSharingLevel};
+use crate::ayb_db::models::{
+    DBType, EntityDatabaseSharingLevel, EntityType, OrganizationRole, PublicSharingLevel,
+};
 use crate::client::config::ClientConfig;
 use crate::client::http::AybClient;
 use crate::error::AybError;
 use crate::formatting::TabularFormatter;
-use crate::http::structs::{EntityDatabasePath, ProfileLinkUpdate};
+use crate::hosted_db::{ChangeOperation, QueryResult};
+use crate::http::structs::{
+    AuditLogQuery, EndpointPublication, EntityDatabasePath, ProfileLinkUpdate, SavedQueryParameter,
+    SavedQueryParameterType, SavedQueryUpdate, SnapshotRetentionUpdate, SnapshotScheduleUpdate,
+    WebhookCreate, WebhookDeliveryQuery,
+};
+use chrono::{DateTime, Utc};
 use clap::builder::ValueParser;
-use clap::{arg, value_parser, ArgMatches, Command, ValueEnum};
+use clap::{arg, value_parser, ArgGroup, ArgMatches, Command, ValueEnum};
 use directories::ProjectDirs;
 use regex::Regex;
 use rustyline::error::ReadlineError;
@@ -26,14 +34,56 @@ pub fn entity_database_parser(value: &str) -> Result<EntityDatabasePath, String>
     }
 }
 
+pub fn saved_query_parameter_parser(value: &str) -> Result<SavedQueryParameter, String> {
+    let re = Regex::new(r"^(\w+):(text|integer|real)(?:=(.*))?$").unwrap();
+    match re.captures(value) {
+        Some(captures) => Ok(SavedQueryParameter {
+            name: captures[1].to_string(),
+            parameter_type: match &captures[2] {
+                "integer" => SavedQueryParameterType::Integer,
+                "real" => SavedQueryParameterType::Real,
+                _ => SavedQueryParameterType::Text,
+            },
+            default: captures.get(3).map(|m| m.as_str().to_string()),
+        }),
+        None => Err(
+            "Parameter must be formatted as 'name:type' or 'name:type=default', where type is text, integer, or real"
+                .to_string(),
+        ),
+    }
+}
+
+pub fn saved_query_value_parser(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, value)) => Ok((name.to_string(), value.to_string())),
+        None => Err("Value must be formatted as 'name=value'".to_string()),
+    }
+}
+
 pub async fn query_and_display(
     client: &AybClient,
     entity: &str,
     database: &str,
+    snapshot_id: Option<&str>,
     query: &str,
     format: &OutputFormat,
 ) -> Result<(), std::io::Error> {
-    match client.query(entity, database, query).await {
+    let result = match snapshot_id {
+        Some(snapshot_id) => {
+            client
+                .query_snapshot(entity, database, snapshot_id, query)
+                .await
+        }
+        None => client.query(entity, database, query).await,
+    };
+    display_query_result(result, format)
+}
+
+fn display_query_result(
+    result: Result<QueryResult, AybError>,
+    format: &OutputFormat,
+) -> Result<(), std::io::Error> {
+    match result {
         Ok(query_result) => {
             if !query_result.rows.is_empty() {
                 match format {
@@ -113,12 +163,177 @@ pub fn client_commands() -> Command {
                 )
                 .arg(arg!(<query> "The query to execute. If not provided, an interactive session to write queries will be launched.")
                      .required(false))
+                .arg(arg!(--snapshot <snapshot_id> "Query this snapshot of the database, read-only, instead of the database itself")
+                     .required(false))
                 .arg(
                     arg!(--format <type> "The format in which to output the result")
                         .value_parser(value_parser!(OutputFormat))
                         .default_value(OutputFormat::Table.to_str())
                         .required(false)),
         )
+        .subcommand(
+            Command::new("saved")
+                .about("Manage and run a database's saved queries")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("List a database's saved queries")
+                        .arg(arg!(<database> "The database whose saved queries to list (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(
+                            arg!(--format <type> "The format in which to output the result")
+                                .value_parser(value_parser!(OutputFormat))
+                                .default_value(OutputFormat::Table.to_str())
+                                .required(false))
+                )
+                .subcommand(
+                    Command::new("save")
+                        .about("Save a query, or replace a saved query with the same name")
+                        .arg(arg!(<database> "The database the query belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<name> "The name of the saved query").required(true))
+                        .arg(arg!(<query> "The query, which can refer to parameters as :name").required(true))
+                        .arg(arg!(--description <value> "What the query does").required(false))
+                        .arg(
+                            arg!(--parameters <value> "The parameters the query accepts (e.g., min_score:integer or country:text=US)")
+                                .value_parser(ValueParser::new(saved_query_parameter_parser))
+                                .required(false)
+                                .num_args(1..)
+                        )
+                )
+                .subcommand(
+                    Command::new("run")
+                        .about("Run a saved query")
+                        .arg(arg!(<database> "The database the query belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<name> "The name of the saved query").required(true))
+                        .arg(
+                            arg!(--values <value> "Values for the query's parameters (e.g., min_score=10)")
+                                .value_parser(ValueParser::new(saved_query_value_parser))
+                                .required(false)
+                                .num_args(1..)
+                        )
+                        .arg(
+                            arg!(--format <type> "The format in which to output the result")
+                                .value_parser(value_parser!(OutputFormat))
+                                .default_value(OutputFormat::Table.to_str())
+                                .required(false))
+                )
+                .subcommand(
+                    Command::new("delete")
+                        .about("Delete a saved query")
+                        .arg(arg!(<database> "The database the query belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<name> "The name of the saved query").required(true))
+                )
+        )
+        .subcommand(
+            Command::new("endpoints")
+                .about("Publish saved queries as public, read-only HTTP endpoints")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("list")
+                        .about("List a database's published endpoints")
+                        .arg(arg!(<database> "The database whose endpoints to list (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(
+                            arg!(--format <type> "The format in which to output the result")
+                                .value_parser(value_parser!(OutputFormat))
+                                .default_value(OutputFormat::Table.to_str())
+                                .required(false))
+                )
+                .subcommand(
+                    Command::new("publish")
+                        .about("Publish a saved query, or republish an endpoint with its current contents")
+                        .arg(arg!(<database> "The database the saved query belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<name> "The name of the endpoint").required(true))
+                        .arg(arg!(<saved_query> "The saved query to publish").required(true))
+                        .arg(
+                            arg!(--max_age <seconds> "How long clients and proxies can cache responses (default 60)")
+                                .value_parser(value_parser!(u32))
+                                .required(false))
+                )
+                .subcommand(
+                    Command::new("unpublish")
+                        .about("Stop serving a published endpoint")
+                        .arg(arg!(<database> "The database the endpoint belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<name> "The name of the endpoint").required(true))
+                )
+        )
+        .subcommand(
+            Command::new("webhooks")
+                .about("Notify HTTP endpoints of changes to a database")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("create")
+                        .about("Send a signed request to a URL after each query that changes the database")
+                        .arg(arg!(<database> "The database to watch (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<url> "The http or https URL to send changes to").required(true))
+                        .arg(
+                            arg!(--tables <table> "Only send changes to these tables (default: all tables)")
+                                .required(false)
+                                .num_args(1..)
+                        )
+                        .arg(
+                            arg!(--events <event> "Only send these kinds of changes (default: all of them)")
+                                .value_parser(value_parser!(ChangeOperation))
+                                .required(false)
+                                .num_args(1..)
+                        )
+                )
+                .subcommand(
+                    Command::new("list")
+                        .about("List a database's webhooks")
+                        .arg(arg!(<database> "The database whose webhooks to list (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(
+                            arg!(--format <type> "The format in which to output the result")
+                                .value_parser(value_parser!(OutputFormat))
+                                .default_value(OutputFormat::Table.to_str())
+                                .required(false))
+                )
+                .subcommand(
+                    Command::new("delete")
+                        .about("Delete a webhook and its delivery log")
+                        .arg(arg!(<database> "The database the webhook belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<webhook_id> "The ID of the webhook")
+                             .value_parser(value_parser!(i32))
+                             .required(true))
+                )
+                .subcommand(
+                    Command::new("deliveries")
+                        .about("List a webhook's most recent deliveries")
+                        .arg(arg!(<database> "The database the webhook belongs to (e.g., entity/database.sqlite)")
+                             .value_parser(ValueParser::new(entity_database_parser))
+                             .required(true))
+                        .arg(arg!(<webhook_id> "The ID of the webhook")
+                             .value_parser(value_parser!(i32))
+                             .required(true))
+                        .arg(
+                            arg!(--limit <count> "The number of deliveries to list (default 50)")
+                                .value_parser(value_parser!(i64))
+                                .required(false))
+                        .arg(
+                            arg!(--format <type> "The format in which to output the result")
+                                .value_parser(value_parser!(OutputFormat))
+                                .default_value(OutputFormat::Table.to_str())
+                                .required(false))
+                )
+        )
         .subcommand(
             Command::new("register")
                 .about("Register a user/organization")
@@ -190,6 +405,92 @@ pub fn client_commands() -> Command {
                 // As we add other updateable properties, this one won't be required anymore.
                 .arg(arg!(--public_sharing_level <value> "The level of public access to enable for this database").value_parser(value_parser!(PublicSharingLevel)).required(true))
         )
+        .subcommand(
+            Command::new("rename_database")
+                .about("Change the slug of a database")
+                .arg(arg!(<database> "The database to rename (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(<new_database> "The new slug for the database (e.g., renamed.sqlite)")
+                     .required(true))
+        )
+        .subcommand(
+            Command::new("delete_database")
+                .about("Delete a database along with its snapshots")
+                .arg(arg!(<database> "The database to delete (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("fork_database")
+                .about("Copy a database you can see into your own account")
+                .arg(arg!(<database> "The database to fork (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!([fork] "Where to create the fork (e.g., your_entity/copy.sqlite). Defaults to the same name under your account")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(false))
+        )
+        .subcommand(
+            Command::new("transfer_database")
+                .about("Propose transferring ownership of a database to another entity")
+                .arg(arg!(<database> "The database to transfer (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(<entity> "The entity that will own the database once it accepts")
+                     .required(true))
+                .arg(arg!(--former_owner_sharing_level <value> "The level of access you'll keep after the transfer")
+                     .value_parser(value_parser!(EntityDatabaseSharingLevel))
+                     .default_value(EntityDatabaseSharingLevel::Manager.to_str())
+                     .required(false))
+        )
+        .subcommand(
+            Command::new("accept_transfer")
+                .about("Accept a pending transfer of a database to you")
+                .arg(arg!(<database> "The database being transferred, under its current owner (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("cancel_transfer")
+                .about("Cancel (as the owner) or decline (as the recipient) a pending database transfer")
+                .arg(arg!(<database> "The database being transferred, under its current owner (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("list_transfers")
+                .about("List pending database transfers to or from you")
+                .arg(
+                    arg!(--format <type> "The format in which to output the result")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
+        .subcommand(
+            Command::new("audit_log")
+                .about("List an entity's audit log, newest first")
+                .arg(arg!(<entity> "The entity whose audit log to list")
+                     .required(true))
+                .arg(arg!(--database <value> "Only list entries about this database (e.g., database.sqlite)").required(false))
+                .arg(arg!(--before <id> "Only list entries older than this id, to page through the log")
+                     .value_parser(value_parser!(i32))
+                     .required(false))
+                .arg(arg!(--limit <count> "The maximum number of entries to list")
+                     .value_parser(value_parser!(i64))
+                     .required(false))
+                .arg(
+                    arg!(--format <type> "The format in which to output the result")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
         .subcommand(
             Command::new("set_default_url")
                 .about("Set the default server URL for future requests in ayb.json")
@@ -203,9 +504,32 @@ pub fn client_commands() -> Command {
                      .value_parser(ValueParser::new(entity_database_parser))
                      .required(true)
                 )
-                .arg(arg!(<entity> "The entity with which to share")
+                .arg(arg!(<entity> "The entity with which to share, or public to limit public access to --object")
                      .required(true))
                 .arg(arg!(<sharing_level> "The level of access for this entity").value_parser(value_parser!(EntityDatabaseSharingLevel)).required(true))
+                .arg(arg!(--object <value> "Limit the entity to this table or view (no-access revokes it)").required(false))
+                .arg(arg!(--columns <value> "Comma-separated columns of --object to limit the entity to").required(false).requires("object"))
+        )
+        .subcommand(
+            Command::new("share_with_team")
+                .about("Share an organization's database with one of its teams")
+                .arg(arg!(<database> "The database to share (e.g., organization/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(<team> "The team with which to share")
+                     .required(true))
+                .arg(arg!(<sharing_level> "The level of access for the team's members").value_parser(value_parser!(EntityDatabaseSharingLevel)).required(true))
+        )
+        .subcommand(
+            Command::new("create_snapshot")
+                .about("Take a snapshot/backup of a database right away")
+                .arg(arg!(<database> "The database to snapshot (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(--label <label> "A label to help identify the snapshot later (e.g., before-migration)")
+                     .required(false))
         )
         .subcommand(
             Command::new("list_snapshots")
@@ -220,6 +544,90 @@ pub fn client_commands() -> Command {
                         .default_value(OutputFormat::Table.to_str())
                         .required(false))
         )
+        .subcommand(
+            Command::new("diff")
+                .about("Show how a database changed between two snapshots, or since a snapshot")
+                .arg(arg!(<database> "The database to compare versions of (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(<from> "The id of the snapshot to compare from")
+                     .required(true))
+                .arg(arg!([to] "The id of the snapshot to compare to (default: the database as it is now)")
+                     .required(false))
+                .arg(
+                    arg!(--format <type> "The format in which to output the result")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
+        .subcommand(
+            Command::new("snapshot_retention")
+                .about("Show how long a database's snapshots are kept, and which snapshots would be pruned right now")
+                .arg(arg!(<database> "The database for which to show snapshot retention (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(
+                    arg!(--format <type> "The format in which to output the snapshots that would be pruned")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
+        .subcommand(
+            Command::new("update_snapshot_retention")
+                .about("Keep the newest snapshot of each recent hour, day, and week of a database, in addition to its most recent snapshots")
+                .arg(arg!(<database> "The database for which to update snapshot retention (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(--hourly <count> "How many hours back to keep the newest snapshot of each hour")
+                     .value_parser(value_parser!(u32))
+                     .required(false))
+                .arg(arg!(--daily <count> "How many days back to keep the newest snapshot of each day")
+                     .value_parser(value_parser!(u32))
+                     .required(false))
+                .arg(arg!(--weekly <count> "How many weeks back to keep the newest snapshot of each week")
+                     .value_parser(value_parser!(u32))
+                     .required(false))
+                .arg(arg!(--reset "Go back to the server's snapshot retention")
+                     .conflicts_with_all(["hourly", "daily", "weekly"])
+                     .required(false))
+                .group(ArgGroup::new("retention")
+                       .args(["hourly", "daily", "weekly", "reset"])
+                       .multiple(true)
+                       .required(true))
+        )
+        .subcommand(
+            Command::new("snapshot_schedule")
+                .about("Show how often a database is snapshotted automatically")
+                .arg(arg!(<database> "The database for which to show the snapshot schedule (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+        )
+        .subcommand(
+            Command::new("update_snapshot_schedule")
+                .about("Change how often a database is snapshotted automatically, or whether it is at all")
+                .arg(arg!(<database> "The database for which to update the snapshot schedule (e.g., entity/database.sqlite)")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(true)
+                )
+                .arg(arg!(--interval <interval> "How often to snapshot the database (e.g., 30m or 1h)")
+                     .required(false))
+                .arg(arg!(--enable "Snapshot the database automatically")
+                     .conflicts_with("disable")
+                     .required(false))
+                .arg(arg!(--disable "Don't snapshot the database automatically (e.g., for a scratch database)")
+                     .required(false))
+                .arg(arg!(--reset "Go back to the server's snapshot schedule")
+                     .conflicts_with_all(["interval", "enable", "disable"])
+                     .required(false))
+                .group(ArgGroup::new("schedule")
+                       .args(["interval", "enable", "disable", "reset"])
+                       .multiple(true)
+                       .required(true))
+        )
         .subcommand(
             Command::new("database_details")
                 .about("Show detailed information about a database")
@@ -235,7 +643,15 @@ pub fn client_commands() -> Command {
                      .value_parser(ValueParser::new(entity_database_parser))
                      .required(true)
                 )
-                .arg(arg!(<snapshot_id> "The id of the snapshot to load").required(true))
+                .arg(arg!([snapshot_id] "The id of the snapshot to load")
+                     .required_unless_present("at"))
+                .arg(arg!(--at <timestamp> "Instead of a snapshot, restore the database to how it was at this time (e.g., 2024-08-18T15:04:40Z), using its point-in-time backups")
+                     .value_parser(value_parser!(DateTime<Utc>))
+                     .conflicts_with("snapshot_id")
+                     .required(false))
+                .arg(arg!(--into <database> "Restore into this new database (e.g., entity/yesterday.sqlite) instead, leaving the original untouched")
+                     .value_parser(ValueParser::new(entity_database_parser))
+                     .required(false))
         )
         .subcommand(
             Command::new("list_database_permissions")
@@ -250,6 +666,91 @@ pub fn client_commands() -> Command {
                         .default_value(OutputFormat::Table.to_str())
                         .required(false))
         )
+        .subcommand(
+            Command::new("list_members")
+                .about("List the members of an organization")
+                .arg(arg!(<organization> "The organization whose members to list")
+                     .required(true))
+                .arg(
+                    arg!(--format <type> "The format in which to output the result")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
+        .subcommand(
+            Command::new("add_member")
+                .about("Add a user to an organization, or change their role")
+                .arg(arg!(<organization> "The organization to add the member to")
+                     .required(true))
+                .arg(arg!(<entity> "The user to add")
+                     .required(true))
+                .arg(arg!(<role> "The member's role in the organization")
+                     .value_parser(value_parser!(OrganizationRole))
+                     .default_value(OrganizationRole::Member.to_str())
+                     .required(false))
+        )
+        .subcommand(
+            Command::new("remove_member")
+                .about("Remove a user from an organization")
+                .arg(arg!(<organization> "The organization to remove the member from")
+                     .required(true))
+                .arg(arg!(<entity> "The member to remove")
+                     .required(true))
+        )
+        .subcommand(
+            Command::new("set_member_sharing_level")
+                .about("Set the level of access members have to an organization's databases")
+                .arg(arg!(<organization> "The organization to update")
+                     .required(true))
+                .arg(arg!(<sharing_level> "The level of access for members").value_parser(value_parser!(EntityDatabaseSharingLevel)).required(true))
+        )
+        .subcommand(
+            Command::new("list_teams")
+                .about("List the teams of an organization")
+                .arg(arg!(<organization> "The organization whose teams to list")
+                     .required(true))
+                .arg(
+                    arg!(--format <type> "The format in which to output the result")
+                        .value_parser(value_parser!(OutputFormat))
+                        .default_value(OutputFormat::Table.to_str())
+                        .required(false))
+        )
+        .subcommand(
+            Command::new("create_team")
+                .about("Create a team within an organization")
+                .arg(arg!(<organization> "The organization to create the team in")
+                     .required(true))
+                .arg(arg!(<team> "The name of the team")
+                     .required(true))
+        )
+        .subcommand(
+            Command::new("delete_team")
+                .about("Delete a team, revoking any access it granted")
+                .arg(arg!(<organization> "The organization the team belongs to")
+                     .required(true))
+                .arg(arg!(<team> "The team to delete")
+                     .required(true))
+        )
+        .subcommand(
+            Command::new("add_team_member")
+                .about("Add a member of an organization to one of its teams")
+                .arg(arg!(<organization> "The organization the team belongs to")
+                     .required(true))
+                .arg(arg!(<team> "The team to add the member to")
+                     .required(true))
+                .arg(arg!(<entity> "The member to add")
+                     .required(true))
+        )
+        .subcommand(
+            Command::new("remove_team_member")
+                .about("Remove a member from a team")
+                .arg(arg!(<organization> "The organization the team belongs to")
+                     .required(true))
+                .arg(arg!(<team> "The team to remove the member from")
+                     .required(true))
+                .arg(arg!(<entity> "The member to remove")
+                     .required(true))
+        )
         .subcommand(
             Command::new("list_tokens")
                 .about("List your API tokens")
@@ -457,25 +958,30 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
             matches.get_one::<EntityDatabasePath>("database"),
             matches.get_one::<OutputFormat>("format"),
         ) {
+            let snapshot_id = matches.get_one::<String>("snapshot").map(String::as_str);
             if let Some(query) = matches.get_one::<String>("query") {
                 query_and_display(
                     &client,
                     &entity_database.entity,
                     &entity_database.database,
+                    snapshot_id,
                     query,
                     format,
                 )
                 .await?;
             } else {
-                println!(
-                    "Launching an interactive session for {}/{}",
-                    entity_database.entity, entity_database.database
-                );
+                let session = match snapshot_id {
+                    Some(snapshot_id) => format!(
+                        "{}/{}@{}",
+                        entity_database.entity, entity_database.database, snapshot_id
+                    ),
+                    None => format!("{}/{}", entity_database.entity, entity_database.database),
+                };
+                println!("Launching an interactive session for {session}");
 
                 match DefaultEditor::new() {
                     Ok(mut rl) => loop {
-                        let prompt =
-                            format!("{}/{}> ", entity_database.entity, entity_database.database);
+                        let prompt = format!("{session}> ");
                         let line = rl.readline(&prompt);
                         match line {
                             Ok(line) if line.is_empty() => {}
@@ -488,6 +994,7 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                                     &client,
                                     &entity_database.entity,
                                     &entity_database.database,
+                                    snapshot_id,
                                     &query,
                                     format,
                                 )
@@ -506,70 +1013,537 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                 }
             }
         }
-    } else if let Some(matches) = matches.subcommand_matches("list_snapshots") {
-        if let (Some(entity_database), Some(format)) = (
-            matches.get_one::<EntityDatabasePath>("database"),
-            matches.get_one::<OutputFormat>("format"),
-        ) {
-            match client
-                .list_snapshots(&entity_database.entity, &entity_database.database)
-                .await
-            {
-                Ok(response) => {
-                    if response.snapshots.is_empty() {
-                        println!(
-                            "No snapshots for {}/{}",
-                            entity_database.entity, entity_database.database
-                        );
-                    } else {
-                        match format {
-                            OutputFormat::Table => response.snapshots.generate_table()?,
-                            OutputFormat::Csv => response.snapshots.generate_csv()?,
+    } else if let Some(matches) = matches.subcommand_matches("saved") {
+        match matches.subcommand() {
+            Some(("list", matches)) => {
+                if let (Some(entity_database), Some(format)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<OutputFormat>("format"),
+                ) {
+                    match client
+                        .list_saved_queries(&entity_database.entity, &entity_database.database)
+                        .await
+                    {
+                        Ok(response) => {
+                            if response.saved_queries.is_empty() {
+                                println!(
+                                    "No saved queries for {}/{}",
+                                    entity_database.entity, entity_database.database
+                                );
+                            } else {
+                                match format {
+                                    OutputFormat::Table => {
+                                        response.saved_queries.generate_table()?
+                                    }
+                                    OutputFormat::Csv => response.saved_queries.generate_csv()?,
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
                         }
                     }
                 }
-                Err(err) => {
-                    println!("Error: {err}");
-                }
             }
-        }
-    } else if let Some(matches) = matches.subcommand_matches("restore_snapshot") {
-        if let (Some(entity_database), Some(snapshot_id)) = (
-            matches.get_one::<EntityDatabasePath>("database"),
-            matches.get_one::<String>("snapshot_id"),
-        ) {
-            match client
-                .restore_snapshot(
-                    &entity_database.entity,
-                    &entity_database.database,
-                    snapshot_id,
-                )
-                .await
-            {
-                Ok(_response) => {
-                    println!(
-                        "Restored {}/{} to snapshot {}",
-                        entity_database.entity, entity_database.database, snapshot_id
-                    );
+            Some(("save", matches)) => {
+                if let (Some(entity_database), Some(name), Some(query)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("name"),
+                    matches.get_one::<String>("query"),
+                ) {
+                    let saved_query = SavedQueryUpdate {
+                        query: query.clone(),
+                        description: matches.get_one::<String>("description").cloned(),
+                        parameters: matches
+                            .get_many::<SavedQueryParameter>("parameters")
+                            .map(|parameters| parameters.cloned().collect())
+                            .unwrap_or_default(),
+                    };
+                    match client
+                        .save_query(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            name,
+                            &saved_query,
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            println!(
+                                "Saved query {name} on {}/{}",
+                                entity_database.entity, entity_database.database
+                            );
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
                 }
-                Err(AybError::SnapshotDoesNotExistError) => {
-                    println!(
-                        "Error: Snapshot {} does not exist for {}/{}",
-                        snapshot_id, entity_database.entity, entity_database.database
-                    )
+            }
+            Some(("run", matches)) => {
+                if let (Some(entity_database), Some(name), Some(format)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("name"),
+                    matches.get_one::<OutputFormat>("format"),
+                ) {
+                    let values: HashMap<String, String> = matches
+                        .get_many::<(String, String)>("values")
+                        .map(|values| values.cloned().collect())
+                        .unwrap_or_default();
+                    display_query_result(
+                        client
+                            .run_saved_query(
+                                &entity_database.entity,
+                                &entity_database.database,
+                                name,
+                                &values,
+                            )
+                            .await,
+                        format,
+                    )?;
                 }
-                Err(err) => {
-                    println!("Error: {err}");
+            }
+            Some(("delete", matches)) => {
+                if let (Some(entity_database), Some(name)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("name"),
+                ) {
+                    match client
+                        .delete_saved_query(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            name,
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            println!(
+                                "Deleted saved query {name} from {}/{}",
+                                entity_database.entity, entity_database.database
+                            );
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
                 }
             }
+            _ => {}
         }
-    } else if let Some(matches) = matches.subcommand_matches("update_database") {
-        if let (Some(entity_database), Some(public_sharing_level)) = (
-            matches.get_one::<EntityDatabasePath>("database"),
-            matches.get_one::<PublicSharingLevel>("public_sharing_level"),
-        ) {
-            match client
-                .update_database(
+    } else if let Some(matches) = matches.subcommand_matches("endpoints") {
+        match matches.subcommand() {
+            Some(("list", matches)) => {
+                if let (Some(entity_database), Some(format)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<OutputFormat>("format"),
+                ) {
+                    match client
+                        .list_published_endpoints(
+                            &entity_database.entity,
+                            &entity_database.database,
+                        )
+                        .await
+                    {
+                        Ok(response) => {
+                            if response.endpoints.is_empty() {
+                                println!(
+                                    "No published endpoints for {}/{}",
+                                    entity_database.entity, entity_database.database
+                                );
+                            } else {
+                                match format {
+                                    OutputFormat::Table => response.endpoints.generate_table()?,
+                                    OutputFormat::Csv => response.endpoints.generate_csv()?,
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            Some(("publish", matches)) => {
+                if let (Some(entity_database), Some(name), Some(saved_query)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("name"),
+                    matches.get_one::<String>("saved_query"),
+                ) {
+                    let publication = EndpointPublication {
+                        saved_query: saved_query.clone(),
+                        max_age: matches.get_one::<u32>("max_age").copied(),
+                    };
+                    match client
+                        .publish_endpoint(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            name,
+                            &publication,
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            println!(
+                                "Published {saved_query} at /v1/{}/{}/endpoints/{name}",
+                                entity_database.entity, entity_database.database
+                            );
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            Some(("unpublish", matches)) => {
+                if let (Some(entity_database), Some(name)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("name"),
+                ) {
+                    match client
+                        .unpublish_endpoint(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            name,
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            println!(
+                                "Unpublished endpoint {name} from {}/{}",
+                                entity_database.entity, entity_database.database
+                            );
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    } else if let Some(matches) = matches.subcommand_matches("webhooks") {
+        match matches.subcommand() {
+            Some(("create", matches)) => {
+                if let (Some(entity_database), Some(url)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<String>("url"),
+                ) {
+                    let webhook = WebhookCreate {
+                        url: url.clone(),
+                        tables: matches
+                            .get_many::<String>("tables")
+                            .map(|tables| tables.cloned().collect())
+                            .unwrap_or_default(),
+                        events: matches
+                            .get_many::<ChangeOperation>("events")
+                            .map(|events| events.copied().collect())
+                            .unwrap_or_default(),
+                    };
+                    match client
+                        .create_webhook(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            &webhook,
+                        )
+                        .await
+                    {
+                        Ok(response) => {
+                            println!(
+                                "Created webhook {} for {}/{}",
+                                response.id, entity_database.entity, entity_database.database
+                            );
+                            if let Some(secret) = response.secret {
+                                println!(
+                                    "Signing secret (shown only once; use it to verify deliveries): {secret}"
+                                );
+                            }
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            Some(("list", matches)) => {
+                if let (Some(entity_database), Some(format)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<OutputFormat>("format"),
+                ) {
+                    match client
+                        .list_webhooks(&entity_database.entity, &entity_database.database)
+                        .await
+                    {
+                        Ok(response) => {
+                            if response.webhooks.is_empty() {
+                                println!(
+                                    "No webhooks for {}/{}",
+                                    entity_database.entity, entity_database.database
+                                );
+                            } else {
+                                match format {
+                                    OutputFormat::Table => response.webhooks.generate_table()?,
+                                    OutputFormat::Csv => response.webhooks.generate_csv()?,
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            Some(("delete", matches)) => {
+                if let (Some(entity_database), Some(webhook_id)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<i32>("webhook_id"),
+                ) {
+                    match client
+                        .delete_webhook(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            *webhook_id,
+                        )
+                        .await
+                    {
+                        Ok(_) => {
+                            println!(
+                                "Deleted webhook {webhook_id} from {}/{}",
+                                entity_database.entity, entity_database.database
+                            );
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            Some(("deliveries", matches)) => {
+                if let (Some(entity_database), Some(webhook_id), Some(format)) = (
+                    matches.get_one::<EntityDatabasePath>("database"),
+                    matches.get_one::<i32>("webhook_id"),
+                    matches.get_one::<OutputFormat>("format"),
+                ) {
+                    let query = WebhookDeliveryQuery {
+                        limit: matches.get_one::<i64>("limit").copied(),
+                    };
+                    match client
+                        .list_webhook_deliveries(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            *webhook_id,
+                            &query,
+                        )
+                        .await
+                    {
+                        Ok(response) => {
+                            if response.deliveries.is_empty() {
+                                println!("No deliveries for webhook {webhook_id}");
+                            } else {
+                                match format {
+                                    OutputFormat::Table => response.deliveries.generate_table()?,
+                                    OutputFormat::Csv => response.deliveries.generate_csv()?,
+                                }
+                            }
+                        }
+                        Err(err) => {
+                            println!("Error: {err}");
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    } else if let Some(matches) = matches.subcommand_matches("create_snapshot") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            match client
+                .create_snapshot(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    matches
+                        .get_one::<String>("label")
+                        .map(|label| label.as_str()),
+                )
+                .await
+            {
+                Ok(response) => {
+                    println!(
+                        "Created snapshot {} of {}/{}",
+                        response.snapshot_id, entity_database.entity, entity_database.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("list_snapshots") {
+        if let (Some(entity_database), Some(format)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            match client
+                .list_snapshots(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(response) => {
+                    if response.snapshots.is_empty() {
+                        println!(
+                            "No snapshots for {}/{}",
+                            entity_database.entity, entity_database.database
+                        );
+                    } else {
+                        match format {
+                            OutputFormat::Table => response.snapshots.generate_table()?,
+                            OutputFormat::Csv => response.snapshots.generate_csv()?,
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("diff") {
+        if let (Some(entity_database), Some(from), Some(format)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<String>("from"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            match client
+                .diff(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    from,
+                    matches.get_one::<String>("to").map(String::as_str),
+                )
+                .await
+            {
+                Ok(response) => {
+                    if response.schema_changes.is_empty() && response.tables.is_empty() {
+                        println!("No changes");
+                    }
+                    if !response.schema_changes.is_empty() {
+                        println!("Schema changes:");
+                        match format {
+                            OutputFormat::Table => response.schema_changes.generate_table()?,
+                            OutputFormat::Csv => response.schema_changes.generate_csv()?,
+                        }
+                    }
+                    if !response.tables.is_empty() {
+                        println!("Row changes:");
+                        match format {
+                            OutputFormat::Table => response.tables.generate_table()?,
+                            OutputFormat::Csv => response.tables.generate_csv()?,
+                        }
+                    }
+                }
+                Err(AybError::SnapshotDoesNotExistError) => {
+                    println!(
+                        "Error: Snapshot does not exist for {}/{}",
+                        entity_database.entity, entity_database.database
+                    )
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("restore_snapshot") {
+        if let (Some(entity_database), Some(into)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<EntityDatabasePath>("into"),
+        ) {
+            match client
+                .restore_snapshot_into(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    matches.get_one::<String>("snapshot_id").map(String::as_str),
+                    matches.get_one::<DateTime<Utc>>("at"),
+                    Some(&into.entity),
+                    Some(&into.database),
+                )
+                .await
+            {
+                Ok(response) => {
+                    println!(
+                        "Restored {}/{} into {}/{}",
+                        entity_database.entity,
+                        entity_database.database,
+                        response.entity,
+                        response.database
+                    );
+                }
+                Err(AybError::SnapshotDoesNotExistError) => {
+                    println!(
+                        "Error: Snapshot {} does not exist for {}/{}",
+                        matches
+                            .get_one::<String>("snapshot_id")
+                            .map(String::as_str)
+                            .unwrap_or_default(),
+                        entity_database.entity,
+                        entity_database.database
+                    )
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        } else if let (Some(entity_database), Some(at)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<DateTime<Utc>>("at"),
+        ) {
+            match client
+                .restore_point_in_time(&entity_database.entity, &entity_database.database, at)
+                .await
+            {
+                Ok(_response) => {
+                    println!(
+                        "Restored {}/{} to {}",
+                        entity_database.entity,
+                        entity_database.database,
+                        at.to_rfc3339()
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        } else if let (Some(entity_database), Some(snapshot_id)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<String>("snapshot_id"),
+        ) {
+            match client
+                .restore_snapshot(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    snapshot_id,
+                )
+                .await
+            {
+                Ok(_response) => {
+                    println!(
+                        "Restored {}/{} to snapshot {}",
+                        entity_database.entity, entity_database.database, snapshot_id
+                    );
+                }
+                Err(AybError::SnapshotDoesNotExistError) => {
+                    println!(
+                        "Error: Snapshot {} does not exist for {}/{}",
+                        snapshot_id, entity_database.entity, entity_database.database
+                    )
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("update_database") {
+        if let (Some(entity_database), Some(public_sharing_level)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<PublicSharingLevel>("public_sharing_level"),
+        ) {
+            match client
+                .update_database(
                     &entity_database.entity,
                     &entity_database.database,
                     public_sharing_level,
@@ -587,25 +1561,244 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                 }
             }
         }
+    } else if let Some(matches) = matches.subcommand_matches("rename_database") {
+        if let (Some(entity_database), Some(new_database)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<String>("new_database"),
+        ) {
+            match client
+                .rename_database(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    new_database,
+                )
+                .await
+            {
+                Ok(response) => {
+                    println!(
+                        "Renamed {}/{} to {}/{}",
+                        entity_database.entity,
+                        entity_database.database,
+                        response.entity,
+                        response.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("delete_database") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            match client
+                .delete_database(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(_) => {
+                    println!(
+                        "Deleted {}/{}",
+                        entity_database.entity, entity_database.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("fork_database") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            let fork = matches.get_one::<EntityDatabasePath>("fork");
+            match client
+                .fork_database(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    fork.map(|fork| fork.entity.as_str()),
+                    fork.map(|fork| fork.database.as_str()),
+                )
+                .await
+            {
+                Ok(response) => {
+                    println!(
+                        "Forked {}/{} to {}/{}",
+                        entity_database.entity,
+                        entity_database.database,
+                        response.entity,
+                        response.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("transfer_database") {
+        if let (Some(entity_database), Some(entity), Some(former_owner_sharing_level)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<String>("entity"),
+            matches.get_one::<EntityDatabaseSharingLevel>("former_owner_sharing_level"),
+        ) {
+            match client
+                .transfer_database(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    entity,
+                    former_owner_sharing_level,
+                )
+                .await
+            {
+                Ok(_) => {
+                    println!(
+                        "Proposed transferring {}/{} to {}",
+                        entity_database.entity, entity_database.database, entity
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("accept_transfer") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            match client
+                .accept_database_transfer(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(response) => {
+                    println!(
+                        "Transferred {}/{} to {}/{}",
+                        entity_database.entity,
+                        entity_database.database,
+                        response.entity,
+                        response.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("cancel_transfer") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            match client
+                .cancel_database_transfer(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(_) => {
+                    println!(
+                        "Canceled transfer of {}/{}",
+                        entity_database.entity, entity_database.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("list_transfers") {
+        if let Some(format) = matches.get_one::<OutputFormat>("format") {
+            match client.list_database_transfers().await {
+                Ok(response) => {
+                    if response.transfers.is_empty() {
+                        println!("No pending database transfers");
+                    } else {
+                        match format {
+                            OutputFormat::Table => response.transfers.generate_table()?,
+                            OutputFormat::Csv => response.transfers.generate_csv()?,
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("audit_log") {
+        if let (Some(entity), Some(format)) = (
+            matches.get_one::<String>("entity"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            let query = AuditLogQuery {
+                database: matches.get_one::<String>("database").cloned(),
+                before: matches.get_one::<i32>("before").copied(),
+                limit: matches.get_one::<i64>("limit").copied(),
+            };
+            match client.list_audit_log(entity, &query).await {
+                Ok(response) => {
+                    if response.entries.is_empty() {
+                        println!("No audit log entries");
+                    } else {
+                        match format {
+                            OutputFormat::Table => response.entries.generate_table()?,
+                            OutputFormat::Csv => response.entries.generate_csv()?,
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
     } else if let Some(matches) = matches.subcommand_matches("share") {
         if let (Some(entity_database), Some(entity), Some(sharing_level)) = (
             matches.get_one::<EntityDatabasePath>("database"),
             matches.get_one::<String>("entity"),
             matches.get_one::<EntityDatabaseSharingLevel>("sharing_level"),
+        ) {
+            let result = match matches.get_one::<String>("object") {
+                Some(object) => {
+                    client
+                        .share_object(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            entity,
+                            sharing_level,
+                            object,
+                            matches.get_one::<String>("columns").map(String::as_str),
+                        )
+                        .await
+                }
+                None => {
+                    client
+                        .share(
+                            &entity_database.entity,
+                            &entity_database.database,
+                            entity,
+                            sharing_level,
+                        )
+                        .await
+                }
+            };
+            match result {
+                Ok(_response) => {
+                    println!(
+                        "Permissions for {} on {}/{} updated successfully",
+                        entity, entity_database.entity, entity_database.database
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("share_with_team") {
+        if let (Some(entity_database), Some(team), Some(sharing_level)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<String>("team"),
+            matches.get_one::<EntityDatabaseSharingLevel>("sharing_level"),
         ) {
             match client
-                .share(
+                .share_with_team(
                     &entity_database.entity,
                     &entity_database.database,
-                    entity,
+                    team,
                     sharing_level,
                 )
                 .await
             {
                 Ok(_response) => {
                     println!(
-                        "Permissions for {} on {}/{} updated successfully",
-                        entity, entity_database.entity, entity_database.database
+                        "Permissions for team {} on {}/{} updated successfully",
+                        team, entity_database.entity, entity_database.database
                     );
                 }
                 Err(err) => {
@@ -613,6 +1806,134 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                 }
             }
         }
+    } else if let Some(matches) = matches.subcommand_matches("snapshot_retention") {
+        if let (Some(entity_database), Some(format)) = (
+            matches.get_one::<EntityDatabasePath>("database"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            match client
+                .snapshot_retention(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(retention) => {
+                    match retention.max_snapshots {
+                        Some(max_snapshots) => println!("Max snapshots: {max_snapshots}"),
+                        None => println!("Max snapshots: none (snapshots aren't automated)"),
+                    }
+                    match (&retention.policy, &retention.source) {
+                        (Some(policy), Some(source)) => println!(
+                            "Retention policy: hourly {}, daily {}, weekly {} (set on the {})",
+                            policy.hourly, policy.daily, policy.weekly, source
+                        ),
+                        _ => println!("Retention policy: none"),
+                    }
+                    if retention.snapshots_to_prune.is_empty() {
+                        println!("No snapshots would be pruned");
+                    } else {
+                        println!("Snapshots that would be pruned:");
+                        match format {
+                            OutputFormat::Table => retention.snapshots_to_prune.generate_table()?,
+                            OutputFormat::Csv => retention.snapshots_to_prune.generate_csv()?,
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("update_snapshot_retention") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            let reset = matches.get_flag("reset");
+            let retention = SnapshotRetentionUpdate {
+                hourly: matches.get_one::<u32>("hourly").copied(),
+                daily: matches.get_one::<u32>("daily").copied(),
+                weekly: matches.get_one::<u32>("weekly").copied(),
+            };
+            match client
+                .update_snapshot_retention(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    &retention,
+                )
+                .await
+            {
+                Ok(_response) => {
+                    if reset {
+                        println!(
+                            "Reset snapshot retention of {}/{} to the server's",
+                            entity_database.entity, entity_database.database
+                        );
+                    } else {
+                        println!(
+                            "Updated snapshot retention of {}/{}",
+                            entity_database.entity, entity_database.database
+                        );
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("snapshot_schedule") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            match client
+                .snapshot_schedule(&entity_database.entity, &entity_database.database)
+                .await
+            {
+                Ok(schedule) => match (schedule.automatic_snapshots, &schedule.interval) {
+                    (true, Some(interval)) => println!(
+                        "Automatic snapshots: every {} (set on the {})",
+                        interval, schedule.source
+                    ),
+                    _ => println!("Automatic snapshots: off (set on the {})", schedule.source),
+                },
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("update_snapshot_schedule") {
+        if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
+            let reset = matches.get_flag("reset");
+            let automatic_snapshots = if matches.get_flag("enable") {
+                Some(true)
+            } else if matches.get_flag("disable") {
+                Some(false)
+            } else {
+                None
+            };
+            let schedule = SnapshotScheduleUpdate {
+                interval: matches.get_one::<String>("interval").cloned(),
+                automatic_snapshots,
+            };
+            match client
+                .update_snapshot_schedule(
+                    &entity_database.entity,
+                    &entity_database.database,
+                    &schedule,
+                )
+                .await
+            {
+                Ok(_response) => {
+                    if reset {
+                        println!(
+                            "Reset snapshot schedule of {}/{} to the server's",
+                            entity_database.entity, entity_database.database
+                        );
+                    } else {
+                        println!(
+                            "Updated snapshot schedule of {}/{}",
+                            entity_database.entity, entity_database.database
+                        );
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
     } else if let Some(matches) = matches.subcommand_matches("database_details") {
         if let Some(entity_database) = matches.get_one::<EntityDatabasePath>("database") {
             match client
@@ -632,6 +1953,16 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                     };
                     println!("Access level: {access_level}");
 
+                    if let Some(forked_from) = &details.forked_from {
+                        println!(
+                            "Forked from: {}",
+                            forked_from
+                                .database
+                                .as_deref()
+                                .unwrap_or("a since-deleted database")
+                        );
+                    }
+
                     if details.can_manage_database {
                         println!("You have management permissions for this database");
                     }
@@ -651,15 +1982,131 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                 .await
             {
                 Ok(response) => {
-                    if response.permissions.is_empty() {
+                    if response.permissions.is_empty() && response.object_grants.is_empty() {
                         println!(
                             "No shared permissions for {}/{}",
                             entity_database.entity, entity_database.database
                         );
+                    } else {
+                        if !response.permissions.is_empty() {
+                            match format {
+                                OutputFormat::Table => response.permissions.generate_table()?,
+                                OutputFormat::Csv => response.permissions.generate_csv()?,
+                            }
+                        }
+                        if !response.object_grants.is_empty() {
+                            println!("Limited to objects:");
+                            match format {
+                                OutputFormat::Table => response.object_grants.generate_table()?,
+                                OutputFormat::Csv => response.object_grants.generate_csv()?,
+                            }
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("list_members") {
+        if let (Some(organization), Some(format)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            match client.list_organization_members(organization).await {
+                Ok(response) => {
+                    println!(
+                        "Members have {} access to {}'s databases",
+                        response.member_sharing_level, organization
+                    );
+                    if response.members.is_empty() {
+                        println!("No members in {organization}");
+                    } else {
+                        match format {
+                            OutputFormat::Table => response.members.generate_table()?,
+                            OutputFormat::Csv => response.members.generate_csv()?,
+                        }
+                    }
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("add_member") {
+        if let (Some(organization), Some(entity), Some(role)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("entity"),
+            matches.get_one::<OrganizationRole>("role"),
+        ) {
+            match client
+                .update_organization_member(organization, entity, role)
+                .await
+            {
+                Ok(_) => {
+                    println!(
+                        "{} now has the {} role in {}",
+                        entity,
+                        role.to_str(),
+                        organization
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("remove_member") {
+        if let (Some(organization), Some(entity)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("entity"),
+        ) {
+            match client
+                .remove_organization_member(organization, entity)
+                .await
+            {
+                Ok(_) => {
+                    println!("Removed {entity} from {organization}");
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("set_member_sharing_level") {
+        if let (Some(organization), Some(sharing_level)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<EntityDatabaseSharingLevel>("sharing_level"),
+        ) {
+            match client
+                .update_member_sharing_level(organization, sharing_level)
+                .await
+            {
+                Ok(_) => {
+                    println!(
+                        "Members of {} now have {} access to its databases",
+                        organization,
+                        sharing_level.to_str()
+                    );
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("list_teams") {
+        if let (Some(organization), Some(format)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<OutputFormat>("format"),
+        ) {
+            match client.list_teams(organization).await {
+                Ok(response) => {
+                    if response.teams.is_empty() {
+                        println!("No teams in {organization}");
                     } else {
                         match format {
-                            OutputFormat::Table => response.permissions.generate_table()?,
-                            OutputFormat::Csv => response.permissions.generate_csv()?,
+                            OutputFormat::Table => response.teams.generate_table()?,
+                            OutputFormat::Csv => response.teams.generate_csv()?,
                         }
                     }
                 }
@@ -668,6 +2115,64 @@ pub async fn execute_client_command(matches: &ArgMatches) -> std::io::Result<()>
                 }
             }
         }
+    } else if let Some(matches) = matches.subcommand_matches("create_team") {
+        if let (Some(organization), Some(team)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("team"),
+        ) {
+            match client.create_team(organization, team).await {
+                Ok(_) => {
+                    println!("Created team {team} in {organization}");
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("delete_team") {
+        if let (Some(organization), Some(team)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("team"),
+        ) {
+            match client.delete_team(organization, team).await {
+                Ok(_) => {
+                    println!("Deleted team {team} from {organization}");
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("add_team_member") {
+        if let (Some(organization), Some(team), Some(entity)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("team"),
+            matches.get_one::<String>("entity"),
+        ) {
+            match client.add_team_member(organization, team, entity).await {
+                Ok(_) => {
+                    println!("Added {entity} to team {team} in {organization}");
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
+    } else if let Some(matches) = matches.subcommand_matches("remove_team_member") {
+        if let (Some(organization), Some(team), Some(entity)) = (
+            matches.get_one::<String>("organization"),
+            matches.get_one::<String>("team"),
+            matches.get_one::<String>("entity"),
+        ) {
+            match client.remove_team_member(organization, team, entity).await {
+                Ok(_) => {
+                    println!("Removed {entity} from team {team} in {organization}");
+                }
+                Err(err) => {
+                    println!("Error: {err}");
+                }
+            }
+        }
     } else if let Some(matches) = matches.subcommand_matches("list_tokens") {
         if let Some(format) = matches.get_one::<OutputFormat>("format") {
             match client.list_tokens().await {
@@ -1,10 +1,18 @@
-use crate::ayb_db::models::{DBType, EntityDatabaseSharingLevel, EntityType, PublicSharingLevel};
+use crate::ayb_db::models::{
+    DBType, EntityDatabaseSharingLevel, EntityType, OrganizationRole, PublicSharingLevel,
+};
 use crate::error::AybError;
+use crate::hosted_db::diff::DatabaseDiff;
 use crate::hosted_db::QueryResult;
 use crate::http::structs::{
-    APIToken, Database, DatabaseDetails, DatabasePermissions, EmptyResponse, EntityQueryResponse,
-    SnapshotList, TokenList,
+    APIToken, AuditLog, AuditLogQuery, CreatedSnapshot, Database, DatabaseDetails,
+    DatabasePermissions, DatabaseTransferList, EmptyResponse, EndpointPublication,
+    EntityQueryResponse, OrganizationMembers, OrganizationTeams, PublishedEndpoints, SavedQueries,
+    SavedQueryUpdate, SnapshotList, SnapshotRetention, SnapshotRetentionUpdate, SnapshotSchedule,
+    SnapshotScheduleUpdate, TokenList, WebhookCreate, WebhookDeliveries, WebhookDeliveryQuery,
+    WebhookDetails, Webhooks,
 };
+use chrono::{DateTime, Utc};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use serde::de::DeserializeOwned;
 use std::collections::HashMap;
@@ -125,6 +133,67 @@ impl AybClient {
             .await
     }
 
+    pub async fn fork_database(
+        &self,
+        entity: &str,
+        database: &str,
+        entity_for_fork: Option<&str>,
+        database_for_fork: Option<&str>,
+    ) -> Result<Database, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        if let Some(entity_for_fork) = entity_for_fork {
+            headers.insert(
+                HeaderName::from_static("entity-for-fork"),
+                HeaderValue::from_str(entity_for_fork).unwrap(),
+            );
+        }
+
+        if let Some(database_for_fork) = database_for_fork {
+            headers.insert(
+                HeaderName::from_static("database-for-fork"),
+                HeaderValue::from_str(database_for_fork).unwrap(),
+            );
+        }
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/fork")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::CREATED)
+            .await
+    }
+
+    pub async fn create_snapshot(
+        &self,
+        entity: &str,
+        database: &str,
+        label: Option<&str>,
+    ) -> Result<CreatedSnapshot, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+        if let Some(label) = label {
+            headers.insert(
+                HeaderName::from_static("snapshot-label"),
+                HeaderValue::from_str(label).map_err(|err| AybError::Other {
+                    message: format!("Invalid snapshot label: {err}"),
+                })?,
+            );
+        }
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/create_snapshot")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::CREATED)
+            .await
+    }
+
     pub async fn list_snapshots(
         &self,
         entity: &str,
@@ -143,6 +212,122 @@ impl AybClient {
             .await
     }
 
+    pub async fn diff(
+        &self,
+        entity: &str,
+        database: &str,
+        from_snapshot_id: &str,
+        to_snapshot_id: Option<&str>,
+    ) -> Result<DatabaseDiff, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let mut url = url::Url::parse(&self.make_url(format!("{entity}/{database}/diff")))?;
+        url.query_pairs_mut().append_pair("from", from_snapshot_id);
+        if let Some(to_snapshot_id) = to_snapshot_id {
+            url.query_pairs_mut().append_pair("to", to_snapshot_id);
+        }
+
+        let response = reqwest::Client::new()
+            .get(url)
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn snapshot_retention(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<SnapshotRetention, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/snapshot_retention")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn update_snapshot_retention(
+        &self,
+        entity: &str,
+        database: &str,
+        retention: &SnapshotRetentionUpdate,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/update_snapshot_retention")))
+            .headers(headers)
+            .body(serde_json::to_string(retention)?)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn snapshot_schedule(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<SnapshotSchedule, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/snapshot_schedule")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn update_snapshot_schedule(
+        &self,
+        entity: &str,
+        database: &str,
+        schedule: &SnapshotScheduleUpdate,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/update_snapshot_schedule")))
+            .headers(headers)
+            .body(serde_json::to_string(schedule)?)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
     pub async fn log_in(&self, entity: &str) -> Result<EmptyResponse, AybError> {
         let mut headers = HeaderMap::new();
         headers.insert(
@@ -194,29 +379,23 @@ impl AybClient {
             .await
     }
 
-    pub async fn register(
+    pub async fn query_snapshot(
         &self,
         entity: &str,
-        email_address: &str,
-        entity_type: &EntityType,
-    ) -> Result<EmptyResponse, AybError> {
+        database: &str,
+        snapshot_id: &str,
+        query: &str,
+    ) -> Result<QueryResult, AybError> {
         let mut headers = HeaderMap::new();
-        headers.insert(
-            HeaderName::from_static("entity"),
-            HeaderValue::from_str(entity).unwrap(),
-        );
-        headers.insert(
-            HeaderName::from_static("email-address"),
-            HeaderValue::from_str(email_address).unwrap(),
-        );
-        headers.insert(
-            HeaderName::from_static("entity-type"),
-            HeaderValue::from_str(entity_type.to_str()).unwrap(),
-        );
+        self.add_bearer_token(&mut headers, false)?;
+
+        let mut url = url::Url::parse(&self.make_url(format!("{entity}/{database}/query")))?;
+        url.query_pairs_mut().append_pair("snapshot", snapshot_id);
 
         let response = reqwest::Client::new()
-            .post(self.make_url("register".to_string()))
+            .post(url)
             .headers(headers)
+            .body(query.to_owned())
             .send()
             .await?;
 
@@ -224,30 +403,30 @@ impl AybClient {
             .await
     }
 
-    pub async fn restore_snapshot(
+    pub async fn list_saved_queries(
         &self,
         entity: &str,
         database: &str,
-        snapshot_id: &str,
-    ) -> Result<(), AybError> {
+    ) -> Result<SavedQueries, AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
 
         let response = reqwest::Client::new()
-            .post(self.make_url(format!("{entity}/{database}/restore_snapshot")))
+            .get(self.make_url(format!("{entity}/{database}/saved_queries")))
             .headers(headers)
-            .body(snapshot_id.to_owned())
             .send()
             .await?;
 
-        self.handle_empty_response(response, reqwest::StatusCode::OK)
+        self.handle_response(response, reqwest::StatusCode::OK)
             .await
     }
 
-    pub async fn update_profile(
+    pub async fn save_query(
         &self,
         entity: &str,
-        profile_update: &HashMap<String, Option<String>>,
+        database: &str,
+        name: &str,
+        saved_query: &SavedQueryUpdate,
     ) -> Result<(), AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
@@ -260,27 +439,37 @@ impl AybClient {
         );
 
         let response = reqwest::Client::new()
-            .patch(self.make_url(format!("entity/{entity}")))
+            .post(self.make_url(format!("{entity}/{database}/saved_queries/{name}")))
             .headers(headers)
-            .body(serde_json::to_string(profile_update)?)
+            .body(serde_json::to_string(saved_query)?)
             .send()
             .await?;
 
-        self.handle_empty_response(response, reqwest::StatusCode::OK)
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
             .await
     }
 
-    pub async fn database_details(
+    pub async fn run_saved_query(
         &self,
         entity: &str,
         database: &str,
-    ) -> Result<DatabaseDetails, AybError> {
+        name: &str,
+        values: &HashMap<String, String>,
+    ) -> Result<QueryResult, AybError> {
         let mut headers = HeaderMap::new();
-        self.add_bearer_token(&mut headers, true)?;
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
+        );
 
         let response = reqwest::Client::new()
-            .get(self.make_url(format!("{entity}/{database}/details")))
+            .post(self.make_url(format!("{entity}/{database}/saved_queries/{name}/run")))
             .headers(headers)
+            .body(serde_json::to_string(values)?)
             .send()
             .await?;
 
@@ -288,22 +477,17 @@ impl AybClient {
             .await
     }
 
-    pub async fn update_database(
+    pub async fn delete_saved_query(
         &self,
         entity: &str,
         database: &str,
-        public_sharing_level: &PublicSharingLevel,
+        name: &str,
     ) -> Result<(), AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
 
-        headers.insert(
-            HeaderName::from_static("public-sharing-level"),
-            HeaderValue::from_str(public_sharing_level.to_str()).unwrap(),
-        );
-
         let response = reqwest::Client::new()
-            .patch(self.make_url(format!("{entity}/{database}/update")))
+            .delete(self.make_url(format!("{entity}/{database}/saved_queries/{name}")))
             .headers(headers)
             .send()
             .await?;
@@ -312,29 +496,45 @@ impl AybClient {
             .await
     }
 
-    pub async fn share(
+    pub async fn list_published_endpoints(
         &self,
-        entity_for_database: &str,
+        entity: &str,
         database: &str,
-        entity_for_permission: &str,
-        sharing_level: &EntityDatabaseSharingLevel,
-    ) -> Result<(), AybError> {
+    ) -> Result<PublishedEndpoints, AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
 
-        headers.insert(
-            HeaderName::from_static("entity-for-permission"),
-            HeaderValue::from_str(entity_for_permission).unwrap(),
-        );
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/endpoints")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn publish_endpoint(
+        &self,
+        entity: &str,
+        database: &str,
+        name: &str,
+        publication: &EndpointPublication,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
 
         headers.insert(
-            HeaderName::from_static("sharing-level"),
-            HeaderValue::from_str(sharing_level.to_str()).unwrap(),
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
         );
 
         let response = reqwest::Client::new()
-            .post(self.make_url(format!("{entity_for_database}/{database}/share")))
+            .post(self.make_url(format!("{entity}/{database}/endpoints/{name}")))
             .headers(headers)
+            .body(serde_json::to_string(publication)?)
             .send()
             .await?;
 
@@ -342,17 +542,45 @@ impl AybClient {
             .await
     }
 
-    pub async fn list_database_permissions(
+    pub async fn unpublish_endpoint(
         &self,
         entity: &str,
         database: &str,
-    ) -> Result<DatabasePermissions, AybError> {
+        name: &str,
+    ) -> Result<(), AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
 
         let response = reqwest::Client::new()
-            .get(self.make_url(format!("{entity}/{database}/permissions")))
+            .delete(self.make_url(format!("{entity}/{database}/endpoints/{name}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn create_webhook(
+        &self,
+        entity: &str,
+        database: &str,
+        webhook: &WebhookCreate,
+    ) -> Result<WebhookDetails, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/webhooks")))
             .headers(headers)
+            .body(serde_json::to_string(webhook)?)
             .send()
             .await?;
 
@@ -360,6 +588,700 @@ impl AybClient {
             .await
     }
 
+    pub async fn list_webhooks(&self, entity: &str, database: &str) -> Result<Webhooks, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/webhooks")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn delete_webhook(
+        &self,
+        entity: &str,
+        database: &str,
+        webhook_id: i32,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!("{entity}/{database}/webhooks/{webhook_id}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn list_webhook_deliveries(
+        &self,
+        entity: &str,
+        database: &str,
+        webhook_id: i32,
+        query: &WebhookDeliveryQuery,
+    ) -> Result<WebhookDeliveries, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let mut url = url::Url::parse(&self.make_url(format!(
+            "{entity}/{database}/webhooks/{webhook_id}/deliveries"
+        )))?;
+        if let Some(limit) = query.limit {
+            url.query_pairs_mut()
+                .append_pair("limit", &limit.to_string());
+        }
+
+        let response = reqwest::Client::new()
+            .get(url)
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn register(
+        &self,
+        entity: &str,
+        email_address: &str,
+        entity_type: &EntityType,
+    ) -> Result<EmptyResponse, AybError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("entity"),
+            HeaderValue::from_str(entity).unwrap(),
+        );
+        headers.insert(
+            HeaderName::from_static("email-address"),
+            HeaderValue::from_str(email_address).unwrap(),
+        );
+        headers.insert(
+            HeaderName::from_static("entity-type"),
+            HeaderValue::from_str(entity_type.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url("register".to_string()))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn delete_database(&self, entity: &str, database: &str) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!("{entity}/{database}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn rename_database(
+        &self,
+        entity: &str,
+        database: &str,
+        new_database: &str,
+    ) -> Result<Database, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("new-database-slug"),
+            HeaderValue::from_str(new_database).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/rename")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn transfer_database(
+        &self,
+        entity: &str,
+        database: &str,
+        entity_for_transfer: &str,
+        former_owner_sharing_level: &EntityDatabaseSharingLevel,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("entity-for-transfer"),
+            HeaderValue::from_str(entity_for_transfer).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("former-owner-sharing-level"),
+            HeaderValue::from_str(former_owner_sharing_level.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/transfer")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn accept_database_transfer(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<Database, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/accept_transfer")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn cancel_database_transfer(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!("{entity}/{database}/transfer")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn list_audit_log(
+        &self,
+        entity: &str,
+        query: &AuditLogQuery,
+    ) -> Result<AuditLog, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let mut url = url::Url::parse(&self.make_url(format!("entity/{entity}/audit_log")))?;
+        let pairs = [
+            ("database", query.database.clone()),
+            ("before", query.before.map(|before| before.to_string())),
+            ("limit", query.limit.map(|limit| limit.to_string())),
+        ];
+        for (name, value) in pairs {
+            if let Some(value) = value {
+                url.query_pairs_mut().append_pair(name, &value);
+            }
+        }
+
+        let response = reqwest::Client::new()
+            .get(url)
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn list_database_transfers(&self) -> Result<DatabaseTransferList, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url("transfers".to_owned()))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn restore_snapshot(
+        &self,
+        entity: &str,
+        database: &str,
+        snapshot_id: &str,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity}/{database}/restore_snapshot")))
+            .headers(headers)
+            .body(snapshot_id.to_owned())
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn restore_point_in_time(
+        &self,
+        entity: &str,
+        database: &str,
+        at: &DateTime<Utc>,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let mut url =
+            url::Url::parse(&self.make_url(format!("{entity}/{database}/restore_snapshot")))?;
+        url.query_pairs_mut().append_pair("at", &at.to_rfc3339());
+
+        let response = reqwest::Client::new()
+            .post(url)
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    /// Restores snapshot `snapshot_id` of `{entity}/{database}` (or,
+    /// with `at`, the database as it was at that time) into a new
+    /// database, leaving `{entity}/{database}` untouched.
+    pub async fn restore_snapshot_into(
+        &self,
+        entity: &str,
+        database: &str,
+        snapshot_id: Option<&str>,
+        at: Option<&DateTime<Utc>>,
+        entity_for_restore: Option<&str>,
+        database_for_restore: Option<&str>,
+    ) -> Result<Database, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        if let Some(entity_for_restore) = entity_for_restore {
+            headers.insert(
+                HeaderName::from_static("entity-for-restore"),
+                HeaderValue::from_str(entity_for_restore).unwrap(),
+            );
+        }
+
+        if let Some(database_for_restore) = database_for_restore {
+            headers.insert(
+                HeaderName::from_static("database-for-restore"),
+                HeaderValue::from_str(database_for_restore).unwrap(),
+            );
+        }
+
+        let mut url =
+            url::Url::parse(&self.make_url(format!("{entity}/{database}/restore_snapshot")))?;
+        if let Some(at) = at {
+            url.query_pairs_mut().append_pair("at", &at.to_rfc3339());
+        }
+
+        let response = reqwest::Client::new()
+            .post(url)
+            .headers(headers)
+            .body(snapshot_id.unwrap_or_default().to_owned())
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::CREATED)
+            .await
+    }
+
+    pub async fn update_profile(
+        &self,
+        entity: &str,
+        profile_update: &HashMap<String, Option<String>>,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            "Content-Type",
+            "application/json"
+                .parse()
+                .expect("const value must be valid"),
+        );
+
+        let response = reqwest::Client::new()
+            .patch(self.make_url(format!("entity/{entity}")))
+            .headers(headers)
+            .body(serde_json::to_string(profile_update)?)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn database_details(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<DatabaseDetails, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, true)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/details")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn update_database(
+        &self,
+        entity: &str,
+        database: &str,
+        public_sharing_level: &PublicSharingLevel,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("public-sharing-level"),
+            HeaderValue::from_str(public_sharing_level.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .patch(self.make_url(format!("{entity}/{database}/update")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn share(
+        &self,
+        entity_for_database: &str,
+        database: &str,
+        entity_for_permission: &str,
+        sharing_level: &EntityDatabaseSharingLevel,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("entity-for-permission"),
+            HeaderValue::from_str(entity_for_permission).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("sharing-level"),
+            HeaderValue::from_str(sharing_level.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity_for_database}/{database}/share")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    /// Like `share`, but limits `entity_for_permission` (or "public")
+    /// to `object`, and to `columns` of it when provided.
+    pub async fn share_object(
+        &self,
+        entity_for_database: &str,
+        database: &str,
+        entity_for_permission: &str,
+        sharing_level: &EntityDatabaseSharingLevel,
+        object: &str,
+        columns: Option<&str>,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("entity-for-permission"),
+            HeaderValue::from_str(entity_for_permission).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("sharing-level"),
+            HeaderValue::from_str(sharing_level.to_str()).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("object-for-permission"),
+            HeaderValue::from_str(object).unwrap(),
+        );
+
+        if let Some(columns) = columns {
+            headers.insert(
+                HeaderName::from_static("columns-for-permission"),
+                HeaderValue::from_str(columns).unwrap(),
+            );
+        }
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity_for_database}/{database}/share")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    pub async fn share_with_team(
+        &self,
+        entity_for_database: &str,
+        database: &str,
+        team: &str,
+        sharing_level: &EntityDatabaseSharingLevel,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("team-for-permission"),
+            HeaderValue::from_str(team).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("sharing-level"),
+            HeaderValue::from_str(sharing_level.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("{entity_for_database}/{database}/share")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    pub async fn list_database_permissions(
+        &self,
+        entity: &str,
+        database: &str,
+    ) -> Result<DatabasePermissions, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("{entity}/{database}/permissions")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn list_organization_members(
+        &self,
+        organization: &str,
+    ) -> Result<OrganizationMembers, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("entity/{organization}/members")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn update_organization_member(
+        &self,
+        organization: &str,
+        entity_for_membership: &str,
+        role: &OrganizationRole,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("entity-for-membership"),
+            HeaderValue::from_str(entity_for_membership).unwrap(),
+        );
+
+        headers.insert(
+            HeaderName::from_static("organization-role"),
+            HeaderValue::from_str(role.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("entity/{organization}/members")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    pub async fn remove_organization_member(
+        &self,
+        organization: &str,
+        member: &str,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!("entity/{organization}/members/{member}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn update_member_sharing_level(
+        &self,
+        organization: &str,
+        sharing_level: &EntityDatabaseSharingLevel,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("sharing-level"),
+            HeaderValue::from_str(sharing_level.to_str()).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("entity/{organization}/member_sharing_level")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn list_teams(&self, organization: &str) -> Result<OrganizationTeams, AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .get(self.make_url(format!("entity/{organization}/teams")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn create_team(&self, organization: &str, team: &str) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("entity/{organization}/teams/{team}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    pub async fn delete_team(&self, organization: &str, team: &str) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!("entity/{organization}/teams/{team}")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
+    pub async fn add_team_member(
+        &self,
+        organization: &str,
+        team: &str,
+        entity_for_membership: &str,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        headers.insert(
+            HeaderName::from_static("entity-for-membership"),
+            HeaderValue::from_str(entity_for_membership).unwrap(),
+        );
+
+        let response = reqwest::Client::new()
+            .post(self.make_url(format!("entity/{organization}/teams/{team}/members")))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::NO_CONTENT)
+            .await
+    }
+
+    pub async fn remove_team_member(
+        &self,
+        organization: &str,
+        team: &str,
+        member: &str,
+    ) -> Result<(), AybError> {
+        let mut headers = HeaderMap::new();
+        self.add_bearer_token(&mut headers, false)?;
+
+        let response = reqwest::Client::new()
+            .delete(self.make_url(format!(
+                "entity/{organization}/teams/{team}/members/{member}"
+            )))
+            .headers(headers)
+            .send()
+            .await?;
+
+        self.handle_empty_response(response, reqwest::StatusCode::OK)
+            .await
+    }
+
     pub async fn list_tokens(&self) -> Result<TokenList, AybError> {
         let mut headers = HeaderMap::new();
         self.add_bearer_token(&mut headers, false)?;
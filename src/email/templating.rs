@@ -42,6 +42,8 @@ mod tests {
                 origin: "*".to_string(),
             },
             snapshots: None,
+            audit_log: None,
+            webhooks: None,
         }
     }
 
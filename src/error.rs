@@ -23,6 +23,7 @@ pub enum AybError {
     EmailError { message: String },
     InvalidSlug { message: String },
     InvalidToken { message: String },
+    NoObjectAccessError { message: String },
     NoWriteAccessError { message: String },
     QueryError { message: String },
     RecordNotFound { id: String, record_type: String },
@@ -45,6 +46,7 @@ impl Display for AybError {
             AybError::EmailError { message } => write!(f, "{message}"),
             AybError::InvalidSlug { message } => write!(f, "{message}"),
             AybError::InvalidToken { message } => write!(f, "{message}"),
+            AybError::NoObjectAccessError { message } => write!(f, "{message}"),
             AybError::NoWriteAccessError { message } => write!(f, "{message}"),
             AybError::RegistrationError { message } => write!(f, "{message}"),
             AybError::EmptyUpdateError { message } => write!(f, "{message}"),
@@ -13,4 +13,12 @@ pub trait TabularFormatter {
         table.to_csv(std::io::stdout())?;
         Ok(())
     }
+    fn to_csv_string(&self) -> Result<String, std::io::Error> {
+        let table = self.to_table();
+        let bytes = table
+            .to_csv(Vec::new())?
+            .into_inner()
+            .map_err(|err| err.into_error())?;
+        String::from_utf8(bytes).map_err(std::io::Error::other)
+    }
 }
@@ -1,7 +1,9 @@
 pub mod daemon_registry;
+pub mod diff;
 pub mod duckdb;
 pub mod engine;
 pub mod paths;
+pub mod restrictions;
 pub mod sandbox;
 pub mod sqlite;
 
@@ -15,6 +17,7 @@ use crate::hosted_db::duckdb::DuckdbEngine;
 use crate::hosted_db::engine::DbEngine;
 use crate::hosted_db::sqlite::SqliteEngine;
 use crate::try_from_i16;
+use clap::ValueEnum;
 use prettytable::{Cell, Row, Table};
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -25,16 +28,21 @@ use std::vec::Vec;
 #[repr(i16)]
 pub enum QueryMode {
     ReadOnly = 0,
-    ReadWrite = 1,
+    /// Allows inserting, updating, and deleting rows, but not schema
+    /// changes like `CREATE`, `ALTER`, or `DROP`.
+    ReadWriteData = 1,
+    ReadWrite = 2,
 }
 
 try_from_i16!(QueryMode, {
     0 => QueryMode::ReadOnly,
-    1 => QueryMode::ReadWrite
+    1 => QueryMode::ReadWriteData,
+    2 => QueryMode::ReadWrite
 });
 
 from_str!(QueryMode, {
     "read-only" => QueryMode::ReadOnly,
+    "read-write-data" => QueryMode::ReadWriteData,
     "read-write" => QueryMode::ReadWrite
 });
 
@@ -42,6 +50,7 @@ impl QueryMode {
     pub fn to_str(&self) -> &str {
         match self {
             QueryMode::ReadOnly => "read-only",
+            QueryMode::ReadWriteData => "read-write-data",
             QueryMode::ReadWrite => "read-write",
         }
     }
@@ -83,6 +92,60 @@ impl TabularFormatter for QueryResult {
     }
 }
 
+#[derive(Serialize, Debug, Deserialize, Copy, Clone, PartialEq, Eq, Hash, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeOperation {
+    Insert,
+    Update,
+    Delete,
+}
+
+from_str!(ChangeOperation, {
+    "insert" => ChangeOperation::Insert,
+    "update" => ChangeOperation::Update,
+    "delete" => ChangeOperation::Delete
+});
+
+impl ChangeOperation {
+    pub fn to_str(&self) -> &str {
+        match self {
+            ChangeOperation::Insert => "insert",
+            ChangeOperation::Update => "update",
+            ChangeOperation::Delete => "delete",
+        }
+    }
+}
+
+/// The most rowids reported for a single table and operation. Larger
+/// changes still report how many rows changed.
+pub const MAX_REPORTED_ROWIDS: usize = 1000;
+
+/// Rows of one table that a committed query inserted, updated, or
+/// deleted.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
+pub struct TableChange {
+    pub table: String,
+    pub operation: ChangeOperation,
+    pub row_count: u64,
+    /// The changed rows' rowids, up to `MAX_REPORTED_ROWIDS` of them.
+    /// Always empty for DuckDB, which detects changes per table rather
+    /// than per row.
+    pub rowids: Vec<i64>,
+}
+
+/// What the query daemon returns for each query: its results, whether
+/// it committed any change to the database, and, if the server asked
+/// for them, the changes it committed.
+#[derive(Serialize, Debug, Deserialize)]
+pub struct QueryOutput {
+    #[serde(flatten)]
+    pub result: QueryResult,
+    #[serde(default)]
+    pub wrote: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<TableChange>,
+}
+
 /// Render `path` as a single-quoted SQL string literal, doubling any
 /// embedded single quotes.
 ///
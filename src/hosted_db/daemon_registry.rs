@@ -1,8 +1,9 @@
 use crate::ayb_db::models::DBType;
 use crate::error::AybError;
 use crate::hosted_db::paths::canonical_db_path;
+use crate::hosted_db::restrictions::QueryRestrictions;
 use crate::hosted_db::sandbox::build_daemon_command;
-use crate::hosted_db::{QueryMode, QueryResult};
+use crate::hosted_db::{QueryMode, QueryOutput};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -16,6 +17,9 @@ use tokio::sync::Mutex;
 struct QueryRequest {
     query: String,
     query_mode: i16,
+    restrictions: Option<QueryRestrictions>,
+    capture_changes: bool,
+    retain_wal: bool,
 }
 
 /// Handle to a running daemon process for a specific database
@@ -31,6 +35,9 @@ impl DaemonHandle {
         &mut self,
         query: &str,
         query_mode: QueryMode,
+        restrictions: Option<&QueryRestrictions>,
+        capture_changes: bool,
+        retain_wal: bool,
     ) -> Result<String, AybError> {
         let stdin = self.stdin.as_mut().ok_or(AybError::Other {
             message: "Daemon stdin has been closed".to_string(),
@@ -40,6 +47,9 @@ impl DaemonHandle {
         let request = QueryRequest {
             query: query.to_string(),
             query_mode: query_mode as i16,
+            restrictions: restrictions.cloned(),
+            capture_changes,
+            retain_wal,
         };
         let request_json = serde_json::to_string(&request)?;
 
@@ -69,6 +79,7 @@ impl DaemonHandle {
 /// Registry of daemon processes, one per database path
 pub struct DaemonRegistry {
     daemons: Arc<Mutex<HashMap<PathBuf, Arc<Mutex<DaemonHandle>>>>>,
+    retain_wal: bool,
 }
 
 impl Default for DaemonRegistry {
@@ -82,15 +93,24 @@ impl DaemonRegistry {
     pub fn new() -> Self {
         Self {
             daemons: Arc::new(Mutex::new(HashMap::new())),
+            retain_wal: false,
         }
     }
 
+    /// Have queries leave each SQLite database's write-ahead log in
+    /// place, for WAL replication to ship and checkpoint.
+    pub fn retain_wal(mut self, retain_wal: bool) -> Self {
+        self.retain_wal = retain_wal;
+        self
+    }
+
     /// Get or create a daemon for the given database path
     /// Returns an Arc<Mutex<DaemonHandle>> that can be used across threads
     async fn get_or_create_daemon(
         &self,
         db_path: &Path,
         db_type: &DBType,
+        read_only: bool,
     ) -> Result<Arc<Mutex<DaemonHandle>>, AybError> {
         // Canonicalize the path to ensure consistency
         let canonical_path = canonical_db_path(db_path)?;
@@ -105,7 +125,9 @@ impl DaemonRegistry {
         }
 
         // Spawn the daemon process while holding the lock
-        let daemon_handle = self.spawn_daemon(&canonical_path, db_type).await?;
+        let daemon_handle = self
+            .spawn_daemon(&canonical_path, db_type, read_only)
+            .await?;
         let daemon_arc = Arc::new(Mutex::new(daemon_handle));
 
         // Insert into registry
@@ -113,27 +135,77 @@ impl DaemonRegistry {
         Ok(daemon_arc)
     }
 
-    /// Execute a query by getting/creating daemon, locking, and executing
+    /// Execute a query by getting/creating daemon, locking, and executing.
+    /// With `capture_changes`, the output lists the changes the query
+    /// committed.
     pub async fn execute_query(
         &self,
         db_path: &Path,
         query: &str,
         db_type: &DBType,
         query_mode: QueryMode,
-    ) -> Result<QueryResult, AybError> {
-        let daemon_arc = self.get_or_create_daemon(db_path, db_type).await?;
+        restrictions: Option<&QueryRestrictions>,
+        capture_changes: bool,
+    ) -> Result<QueryOutput, AybError> {
+        let daemon_arc = self.get_or_create_daemon(db_path, db_type, false).await?;
         let mut daemon = daemon_arc.lock().await;
-        let response = daemon.execute_query(query, query_mode).await?;
+        let response = daemon
+            .execute_query(
+                query,
+                query_mode,
+                restrictions,
+                capture_changes,
+                self.retain_wal,
+            )
+            .await?;
         parse_response(&response)
     }
 
+    /// Execute a query against a database that must never be written
+    /// to (e.g., a copy of a snapshot), through a daemon that runs in
+    /// read-only mode. Don't mix this with `execute_query` for the same
+    /// path: whichever runs first decides the daemon's mode.
+    pub async fn execute_read_only_query(
+        &self,
+        db_path: &Path,
+        query: &str,
+        db_type: &DBType,
+    ) -> Result<QueryOutput, AybError> {
+        let daemon_arc = self.get_or_create_daemon(db_path, db_type, true).await?;
+        let mut daemon = daemon_arc.lock().await;
+        let response = daemon
+            .execute_query(query, QueryMode::ReadOnly, None, false, false)
+            .await?;
+        parse_response(&response)
+    }
+
+    /// Run `f` while no query runs against the database at `db_path`.
+    pub async fn with_exclusive_access<T>(
+        &self,
+        db_path: &Path,
+        f: impl FnOnce() -> T,
+    ) -> Result<T, AybError> {
+        let canonical_path = canonical_db_path(db_path)?;
+        let daemons = self.daemons.lock().await;
+        match daemons.get(&canonical_path).cloned() {
+            Some(daemon_arc) => {
+                drop(daemons);
+                let _daemon = daemon_arc.lock().await;
+                Ok(f())
+            }
+            // Holding the registry's lock keeps a daemon from starting.
+            None => Ok(f()),
+        }
+    }
+
     /// Spawn a new daemon process for the given database
     async fn spawn_daemon(
         &self,
         db_path: &Path,
         db_type: &DBType,
+        read_only: bool,
     ) -> Result<DaemonHandle, AybError> {
-        let mut cmd = build_daemon_command(db_path, db_type)?;
+        let mut cmd = build_daemon_command(db_path, db_type, read_only)?;
 
         // Spawn the process with piped stdin/stdout. Inherit stderr so
         // crashes (panic backtraces, aborts) surface in the server log
@@ -185,10 +257,10 @@ impl DaemonRegistry {
     }
 }
 
-/// Parse a JSON response from daemon into QueryResult or AybError
-fn parse_response(response: &str) -> Result<QueryResult, AybError> {
-    // Try to parse as QueryResult first
-    if let Ok(result) = serde_json::from_str::<QueryResult>(response) {
+/// Parse a JSON response from daemon into QueryOutput or AybError
+fn parse_response(response: &str) -> Result<QueryOutput, AybError> {
+    // Try to parse as QueryOutput first
+    if let Ok(result) = serde_json::from_str::<QueryOutput>(response) {
         return Ok(result);
     }
 
@@ -207,6 +279,7 @@ impl Clone for DaemonRegistry {
     fn clone(&self) -> Self {
         Self {
             daemons: self.daemons.clone(),
+            retain_wal: self.retain_wal,
         }
     }
 }
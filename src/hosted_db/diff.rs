@@ -0,0 +1,346 @@
+use crate::error::AybError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The most keys reported for a single table and kind of change.
+/// Larger changes still report how many rows changed.
+pub const MAX_REPORTED_KEYS: usize = 100;
+
+#[derive(Serialize, Debug, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl SchemaChangeKind {
+    pub fn to_str(&self) -> &str {
+        match self {
+            SchemaChangeKind::Added => "added",
+            SchemaChangeKind::Removed => "removed",
+            SchemaChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// A table, view, index, or trigger that differs between two versions
+/// of a database.
+#[derive(Serialize, Debug, Deserialize, Clone, PartialEq)]
+pub struct SchemaChange {
+    pub object_type: String,
+    pub name: String,
+    pub change: SchemaChangeKind,
+    /// The object's definition in each version, if it exists there.
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// The rows of one table that were inserted, updated, or deleted
+/// between two versions of a database.
+#[derive(Serialize, Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct TableDiff {
+    pub table: String,
+    /// The columns that rows are matched by. Tables without a primary
+    /// key (or whose primary key changed) are compared a whole row at a
+    /// time, so their changes show up as inserts and deletes, and
+    /// duplicate rows count once.
+    pub primary_key: Vec<String>,
+    pub inserted: u64,
+    pub updated: u64,
+    pub deleted: u64,
+    /// The primary keys of changed rows, up to `MAX_REPORTED_KEYS` of
+    /// each kind.
+    pub inserted_keys: Vec<Vec<Option<String>>>,
+    pub updated_keys: Vec<Vec<Option<String>>>,
+    pub deleted_keys: Vec<Vec<Option<String>>>,
+}
+
+/// How one version of a database differs from another.
+#[derive(Serialize, Debug, Deserialize, Clone, Default, PartialEq)]
+pub struct DatabaseDiff {
+    pub schema_changes: Vec<SchemaChange>,
+    /// Only tables whose rows changed are listed.
+    pub tables: Vec<TableDiff>,
+}
+
+/// A table, view, index, or trigger, as listed by `DiffSource`.
+pub(crate) struct SchemaObject {
+    pub object_type: String,
+    pub name: String,
+    pub sql: Option<String>,
+}
+
+/// A connection to which two versions of a database are attached under
+/// different schema names. Engines implement this, and
+/// `diff_databases` compares the versions with SQL that both engines
+/// understand.
+pub(crate) trait DiffSource {
+    /// An operator that's true when two values differ, and treats NULLs
+    /// as equal to each other.
+    const DISTINCT_FROM: &'static str;
+
+    /// Run `query` and return its rows as strings.
+    fn rows(&self, query: &str) -> Result<Vec<Vec<Option<String>>>, AybError>;
+
+    /// The user-defined tables, views, indexes, and triggers in
+    /// `schema`.
+    fn schema_objects(&self, schema: &str) -> Result<Vec<SchemaObject>, AybError>;
+
+    /// The columns of `table` in `schema`, and the ones in its primary
+    /// key, in key order.
+    fn table_columns(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<(Vec<String>, Vec<String>), AybError>;
+}
+
+/// Compare the version of a database attached to `source` as `from`
+/// with the one attached as `to`.
+pub(crate) fn diff_databases<S: DiffSource>(
+    source: &S,
+    from: &str,
+    to: &str,
+) -> Result<DatabaseDiff, AybError> {
+    let before = source.schema_objects(from)?;
+    let after = source.schema_objects(to)?;
+    let tables_in = |objects: &[SchemaObject]| -> BTreeSet<String> {
+        objects
+            .iter()
+            .filter(|object| object.object_type == "table")
+            .map(|object| object.name.clone())
+            .collect()
+    };
+    let (tables_before, tables_after) = (tables_in(&before), tables_in(&after));
+
+    let mut tables = Vec::new();
+    for table in tables_before.union(&tables_after) {
+        let diff = diff_table(
+            source,
+            tables_before.contains(table).then_some(from),
+            tables_after.contains(table).then_some(to),
+            table,
+        )?;
+        if diff.inserted + diff.updated + diff.deleted > 0 {
+            tables.push(diff);
+        }
+    }
+    Ok(DatabaseDiff {
+        schema_changes: diff_schema(before, after),
+        tables,
+    })
+}
+
+fn diff_schema(before: Vec<SchemaObject>, after: Vec<SchemaObject>) -> Vec<SchemaChange> {
+    let by_name = |objects: Vec<SchemaObject>| -> BTreeMap<(String, String), Option<String>> {
+        objects
+            .into_iter()
+            .map(|object| ((object.object_type, object.name), object.sql))
+            .collect()
+    };
+    let (mut before, mut after) = (by_name(before), by_name(after));
+    let names: BTreeSet<(String, String)> = before.keys().chain(after.keys()).cloned().collect();
+    names
+        .into_iter()
+        .filter_map(|(object_type, name)| {
+            let key = (object_type.clone(), name.clone());
+            let (sql_before, sql_after) = (before.remove(&key), after.remove(&key));
+            let change = match (&sql_before, &sql_after) {
+                (None, _) => SchemaChangeKind::Added,
+                (_, None) => SchemaChangeKind::Removed,
+                (Some(sql_before), Some(sql_after)) if sql_before != sql_after => {
+                    SchemaChangeKind::Changed
+                }
+                _ => return None,
+            };
+            Some(SchemaChange {
+                object_type,
+                name,
+                change,
+                before: sql_before.flatten(),
+                after: sql_after.flatten(),
+            })
+        })
+        .collect()
+}
+
+/// Compare `table` in `from` with `table` in `to`, either of which is
+/// `None` if the table doesn't exist in that version.
+fn diff_table<S: DiffSource>(
+    source: &S,
+    from: Option<&str>,
+    to: Option<&str>,
+    table: &str,
+) -> Result<TableDiff, AybError> {
+    let columns_in = |schema: Option<&str>| match schema {
+        Some(schema) => source.table_columns(schema, table),
+        None => Ok((Vec::new(), Vec::new())),
+    };
+    let (from_columns, from_key) = columns_in(from)?;
+    let (to_columns, to_key) = columns_in(to)?;
+    let (columns, primary_key) = match (from, to) {
+        (Some(_), Some(_)) => {
+            let columns: Vec<String> = to_columns
+                .into_iter()
+                .filter(|column| from_columns.contains(column))
+                .collect();
+            let primary_key = match from_key == to_key {
+                true => to_key,
+                false => Vec::new(),
+            };
+            (columns, primary_key)
+        }
+        (Some(_), None) => (from_columns, from_key),
+        _ => (to_columns, to_key),
+    };
+
+    let (inserted, inserted_keys) = missing_rows(source, to, from, table, &primary_key, &columns)?;
+    let (deleted, deleted_keys) = missing_rows(source, from, to, table, &primary_key, &columns)?;
+    let (updated, updated_keys) = match (from, to) {
+        (Some(from), Some(to)) => updated_rows(source, from, to, table, &primary_key, &columns)?,
+        _ => (0, Vec::new()),
+    };
+    Ok(TableDiff {
+        table: table.to_string(),
+        primary_key,
+        inserted,
+        updated,
+        deleted,
+        inserted_keys,
+        updated_keys,
+        deleted_keys,
+    })
+}
+
+/// How many rows changed, and the primary keys of some of them.
+type ChangedRows = (u64, Vec<Vec<Option<String>>>);
+
+/// Count the rows of `table` in `present` that aren't in `absent`, and
+/// list the keys of some of them.
+fn missing_rows<S: DiffSource>(
+    source: &S,
+    present: Option<&str>,
+    absent: Option<&str>,
+    table: &str,
+    primary_key: &[String],
+    columns: &[String],
+) -> Result<ChangedRows, AybError> {
+    let Some(present) = present else {
+        return Ok((0, Vec::new()));
+    };
+    let present_table = qualified_table(present, table);
+    if primary_key.is_empty() {
+        let query = match absent {
+            Some(_) if columns.is_empty() => return Ok((0, Vec::new())),
+            Some(absent) => {
+                let columns = column_list(None, columns);
+                format!(
+                    "SELECT COUNT(*) FROM (SELECT {columns} FROM {present_table} EXCEPT SELECT {columns} FROM {}) AS missing",
+                    qualified_table(absent, table)
+                )
+            }
+            None => format!("SELECT COUNT(*) FROM {present_table}"),
+        };
+        return Ok((count(source, &query)?, Vec::new()));
+    }
+
+    let condition = match absent {
+        Some(absent) => format!(
+            " WHERE NOT EXISTS (SELECT 1 FROM {} AS b WHERE {})",
+            qualified_table(absent, table),
+            key_join(primary_key)
+        ),
+        None => String::new(),
+    };
+    let count = count(
+        source,
+        &format!("SELECT COUNT(*) FROM {present_table} AS a{condition}"),
+    )?;
+    let keys = source.rows(&format!(
+        "SELECT {keys} FROM {present_table} AS a{condition} ORDER BY {keys} LIMIT {MAX_REPORTED_KEYS}",
+        keys = column_list(Some("a"), primary_key),
+    ))?;
+    Ok((count, keys))
+}
+
+/// Count the rows of `table` whose primary key is in both versions, but
+/// whose other columns differ, and list the keys of some of them.
+fn updated_rows<S: DiffSource>(
+    source: &S,
+    from: &str,
+    to: &str,
+    table: &str,
+    primary_key: &[String],
+    columns: &[String],
+) -> Result<ChangedRows, AybError> {
+    let changed: Vec<String> = columns
+        .iter()
+        .filter(|column| !primary_key.contains(column))
+        .map(|column| {
+            let column = quote_identifier(column);
+            format!("a.{column} {} b.{column}", S::DISTINCT_FROM)
+        })
+        .collect();
+    if primary_key.is_empty() || changed.is_empty() {
+        return Ok((0, Vec::new()));
+    }
+    let rows = format!(
+        "FROM {} AS a JOIN {} AS b ON {} WHERE {}",
+        qualified_table(to, table),
+        qualified_table(from, table),
+        key_join(primary_key),
+        changed.join(" OR ")
+    );
+    let count = count(source, &format!("SELECT COUNT(*) {rows}"))?;
+    let keys = source.rows(&format!(
+        "SELECT {keys} {rows} ORDER BY {keys} LIMIT {MAX_REPORTED_KEYS}",
+        keys = column_list(Some("a"), primary_key),
+    ))?;
+    Ok((count, keys))
+}
+
+fn count<S: DiffSource>(source: &S, query: &str) -> Result<u64, AybError> {
+    source
+        .rows(query)?
+        .first()
+        .and_then(|row| row.first().cloned().flatten())
+        .and_then(|count| count.parse().ok())
+        .ok_or_else(|| AybError::Other {
+            message: format!("Unable to count rows with: {query}"),
+        })
+}
+
+/// Quote `identifier` (e.g., a table or column name, which users
+/// choose) so that it can be interpolated into SQL. Both SQLite and
+/// DuckDB quote identifiers with double quotes, doubling any embedded
+/// ones.
+pub(crate) fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn qualified_table(schema: &str, table: &str) -> String {
+    format!("{}.{}", quote_identifier(schema), quote_identifier(table))
+}
+
+fn column_list(alias: Option<&str>, columns: &[String]) -> String {
+    columns
+        .iter()
+        .map(|column| match alias {
+            Some(alias) => format!("{alias}.{}", quote_identifier(column)),
+            None => quote_identifier(column),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn key_join(primary_key: &[String]) -> String {
+    primary_key
+        .iter()
+        .map(|column| {
+            let column = quote_identifier(column);
+            format!("a.{column} = b.{column}")
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
@@ -1,8 +1,16 @@
 use crate::error::AybError;
+use crate::hosted_db::diff::{diff_databases, DatabaseDiff, DiffSource, SchemaObject};
 use crate::hosted_db::engine::DbEngine;
-use crate::hosted_db::{sql_string_literal, QueryMode, QueryResult};
+use crate::hosted_db::restrictions::QueryRestrictions;
+use crate::hosted_db::{
+    sql_string_literal, ChangeOperation, QueryMode, QueryOutput, QueryResult, TableChange,
+};
 use duckdb::types::{TimeUnit, Value};
+use serde_json::Value as JsonValue;
+use std::collections::HashMap;
+use std::ffi::CString;
 use std::path::Path;
+use std::ptr;
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -14,8 +22,19 @@ impl DbEngine for DuckdbEngine {
         path: &Path,
         query: &str,
         query_mode: QueryMode,
-    ) -> Result<QueryResult, AybError> {
-        query_duckdb(path, query, false, query_mode)
+        restrictions: Option<&QueryRestrictions>,
+        capture_changes: bool,
+        // DuckDB checkpoints its WAL itself, and it isn't replicated.
+        _retain_wal: bool,
+    ) -> Result<QueryOutput, AybError> {
+        run_duckdb(
+            path,
+            query,
+            false,
+            query_mode,
+            restrictions,
+            capture_changes,
+        )
     }
 
     fn create_snapshot(&self, db_path: &Path, snapshot_path: &Path) -> Result<(), AybError> {
@@ -40,19 +59,169 @@ impl DbEngine for DuckdbEngine {
             "SELECT count(*) FROM information_schema.tables;",
             false,
             QueryMode::ReadOnly,
+            None,
         )?;
         Ok(())
     }
+
+    fn prepare_read_only(&self, _path: &Path) -> Result<(), AybError> {
+        // Snapshots are checkpointed when they're written, and DuckDB
+        // doesn't write anything next to a database it opens read-only.
+        Ok(())
+    }
+
+    fn diff(&self, from_path: &Path, to_path: &Path) -> Result<DatabaseDiff, AybError> {
+        let attach = format!(
+            "ATTACH {} AS diff_from (READ_ONLY); ATTACH {} AS diff_to (READ_ONLY);",
+            sql_string_literal(from_path),
+            sql_string_literal(to_path)
+        );
+        // A read-only daemon may be querying either version, which only
+        // conflicts with a read-only attach while it's opening.
+        let conn = with_lock_retry(|| {
+            let conn = duckdb::Connection::open_in_memory_with_flags(snapshot_config()?)?;
+            conn.execute_batch(&attach)?;
+            Ok(conn)
+        })?;
+        diff_databases(&DuckdbDiffSource(conn), "diff_from", "diff_to")
+    }
+}
+
+/// An in-memory connection with the versions of a database being
+/// diffed attached as `diff_from` and `diff_to`. Only their `main`
+/// schemas are compared.
+struct DuckdbDiffSource(duckdb::Connection);
+
+impl DiffSource for DuckdbDiffSource {
+    const DISTINCT_FROM: &'static str = "IS DISTINCT FROM";
+
+    fn rows(&self, query: &str) -> Result<Vec<Vec<Option<String>>>, AybError> {
+        self.rows_with(query, &[])
+    }
+
+    fn schema_objects(&self, schema: &str) -> Result<Vec<SchemaObject>, AybError> {
+        let rows = self.rows_with(
+            "SELECT 'table', table_name, sql FROM duckdb_tables()
+              WHERE database_name = ?1 AND schema_name = 'main' AND NOT internal
+             UNION ALL
+             SELECT 'view', view_name, sql FROM duckdb_views()
+              WHERE database_name = ?1 AND schema_name = 'main' AND NOT internal
+             UNION ALL
+             SELECT 'index', index_name, sql FROM duckdb_indexes()
+              WHERE database_name = ?1 AND schema_name = 'main';",
+            &[schema],
+        )?;
+        Ok(rows
+            .into_iter()
+            .map(|mut row| SchemaObject {
+                sql: row.pop().flatten(),
+                name: row.pop().flatten().unwrap_or_default(),
+                object_type: row.pop().flatten().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    fn table_columns(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<(Vec<String>, Vec<String>), AybError> {
+        let names = |rows: Vec<Vec<Option<String>>>| -> Vec<String> {
+            rows.into_iter()
+                .filter_map(|row| row.into_iter().next().flatten())
+                .collect()
+        };
+        let columns = self.rows_with(
+            "SELECT column_name FROM duckdb_columns()
+              WHERE database_name = ?1 AND schema_name = 'main' AND table_name = ?2
+              ORDER BY column_index;",
+            &[schema, table],
+        )?;
+        let primary_key = self.rows_with(
+            "SELECT unnest(constraint_column_names) FROM duckdb_constraints()
+              WHERE database_name = ?1 AND schema_name = 'main' AND table_name = ?2
+                AND constraint_type = 'PRIMARY KEY';",
+            &[schema, table],
+        )?;
+        Ok((names(columns), names(primary_key)))
+    }
+}
+
+impl DuckdbDiffSource {
+    fn rows_with(
+        &self,
+        query: &str,
+        params: &[&str],
+    ) -> Result<Vec<Vec<Option<String>>>, AybError> {
+        let mut prepared = self.0.prepare(query).map_err(map_duckdb_error)?;
+        let mut rows = prepared
+            .query(duckdb::params_from_iter(params))
+            .map_err(map_duckdb_error)?;
+        let num_columns = rows
+            .as_ref()
+            .map_or(0, |statement| statement.column_count());
+        let mut results = Vec::new();
+        while let Some(row) = rows.next().map_err(map_duckdb_error)? {
+            let mut result = Vec::with_capacity(num_columns);
+            for column_index in 0..num_columns {
+                let value: Value = row.get(column_index).map_err(map_duckdb_error)?;
+                result.push(duckdb_value_to_string(value));
+            }
+            results.push(result);
+        }
+        Ok(results)
+    }
 }
 
+/// `restrictions`, when provided, are enforced by inspecting the
+/// query's physical plan before running it (see `check_plan`). DuckDB
+/// has no equivalent of SQLite's authorizer, and the plan only shows
+/// which tables and columns are read, so restricted queries always run
+/// read-only. `QueryMode::ReadWriteData` is enforced the same way, by
+/// rejecting plans that change the schema (see `check_data_only`).
 fn query_duckdb(
     path: &Path,
     query: &str,
     allow_unsafe: bool,
     query_mode: QueryMode,
+    restrictions: Option<&QueryRestrictions>,
 ) -> Result<QueryResult, AybError> {
+    run_duckdb(path, query, allow_unsafe, query_mode, restrictions, false)
+        .map(|output| output.result)
+}
+
+/// `query_duckdb`, optionally reporting the changes the query committed
+/// (see `diff_tables`).
+fn run_duckdb(
+    path: &Path,
+    query: &str,
+    allow_unsafe: bool,
+    query_mode: QueryMode,
+    restrictions: Option<&QueryRestrictions>,
+    capture_changes: bool,
+) -> Result<QueryOutput, AybError> {
+    let query_mode = match restrictions {
+        Some(_) => QueryMode::ReadOnly,
+        None => query_mode,
+    };
+    let inspect_plan = restrictions.is_some() || query_mode == QueryMode::ReadWriteData;
+    if inspect_plan && statement_count(query)? > 1 {
+        // Only the last statement of a batch is planned by `EXPLAIN`.
+        return Err(AybError::NoObjectAccessError {
+            message: "Queries against this database must contain a single statement".to_string(),
+        });
+    }
     let conn = open_with_retry(path, query_mode)?;
 
+    if restrictions.is_some() {
+        // Pushed-down filters and late materialization move column
+        // references out of a scan's projections, where `check_plan`
+        // looks for them.
+        conn.execute_batch(
+            "SET disabled_optimizers='filter_pushdown,join_filter_pushdown,late_materialization';",
+        )?;
+    }
+
     if !allow_unsafe {
         // Disable extension install/load and external (file/network)
         // access, then lock the configuration so a query can't re-enable
@@ -66,6 +235,21 @@ fn query_duckdb(
         )?;
     }
 
+    if let Some(restrictions) = restrictions {
+        check_plan(&conn, query, restrictions)?;
+    } else if query_mode == QueryMode::ReadWriteData {
+        check_data_only(&conn, query)?;
+    }
+
+    let before = match capture_changes && query_mode != QueryMode::ReadOnly {
+        true => Some(table_fingerprints(&conn)?),
+        false => None,
+    };
+    let size_before = match query_mode {
+        QueryMode::ReadOnly => None,
+        _ => Some(storage_size(&conn)?),
+    };
+
     let mut prepared = conn.prepare(query).map_err(map_duckdb_error)?;
 
     let mut rows = prepared.query([]).map_err(map_duckdb_error)?;
@@ -94,12 +278,358 @@ fn query_duckdb(
         }
         results.push(result);
     }
-    Ok(QueryResult {
-        fields,
-        rows: results,
+    drop(rows);
+    drop(prepared);
+
+    let changes = match before {
+        // A second connection to the same database only sees committed
+        // data, so changes in a transaction the query left open (and
+        // that will roll back when `conn` closes) don't count.
+        Some(before) => diff_tables(&before, &table_fingerprints(&conn.try_clone()?)?),
+        None => Vec::new(),
+    };
+    let wrote = match size_before {
+        Some(size_before) => storage_size(&conn)? != size_before,
+        None => false,
+    };
+    Ok(QueryOutput {
+        result: QueryResult {
+            fields,
+            rows: results,
+        },
+        wrote,
+        changes,
     })
 }
 
+/// The blocks the database file uses and the size of its write-ahead
+/// log. Every committed write appends to the log (or, once the log is
+/// checkpointed, changes the blocks), while reads change neither, so a
+/// query wrote to the database if this changed while it ran.
+fn storage_size(conn: &duckdb::Connection) -> Result<Vec<String>, AybError> {
+    conn.query_row(
+        "SELECT total_blocks::VARCHAR, used_blocks::VARCHAR, free_blocks::VARCHAR, wal_size
+         FROM pragma_database_size() WHERE database_name = current_database()",
+        [],
+        |row| (0..4).map(|index| row.get(index)).collect(),
+    )
+    .map_err(map_duckdb_error)
+}
+
+/// A table's row count and the sum of its rows' hashes.
+#[derive(Debug, PartialEq)]
+struct TableFingerprint {
+    row_count: u64,
+    hash_sum: String,
+}
+
+/// Fingerprint every table in the database. This scans each table in
+/// full, which is why changes are only captured when they're needed.
+fn table_fingerprints(
+    conn: &duckdb::Connection,
+) -> Result<HashMap<String, TableFingerprint>, AybError> {
+    let mut statement = conn
+        .prepare(
+            "SELECT schema_name, table_name FROM duckdb_tables()
+             WHERE database_name = current_database() AND NOT temporary AND NOT internal",
+        )
+        .map_err(map_duckdb_error)?;
+    let tables = statement
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(map_duckdb_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(map_duckdb_error)?;
+
+    let mut fingerprints = HashMap::new();
+    for (schema, table) in tables {
+        let quoted = format!(
+            "\"{}\".\"{}\"",
+            schema.replace('"', "\"\""),
+            table.replace('"', "\"\"")
+        );
+        // Summing (rather than XORing) hashes keeps duplicate rows from
+        // cancelling each other out.
+        let fingerprint = conn
+            .query_row(
+                &format!("SELECT count(*), coalesce(sum(hash(t)), 0)::VARCHAR FROM {quoted} AS t"),
+                [],
+                |row| {
+                    Ok(TableFingerprint {
+                        row_count: row.get::<_, i64>(0)? as u64,
+                        hash_sum: row.get(1)?,
+                    })
+                },
+            )
+            .map_err(map_duckdb_error)?;
+        let name = match schema.as_str() {
+            "main" => table,
+            _ => format!("{schema}.{table}"),
+        };
+        fingerprints.insert(name, fingerprint);
+    }
+    Ok(fingerprints)
+}
+
+/// Compare fingerprints taken before and after a query. DuckDB has no
+/// equivalent of SQLite's update hook, so changes are detected per
+/// table: a table that grew reports inserts, one that shrank reports
+/// deletes, and one whose contents changed at the same size reports
+/// updates (with a `row_count` of 0, since the number of updated rows
+/// is unknown). Dropped tables aren't reported.
+fn diff_tables(
+    before: &HashMap<String, TableFingerprint>,
+    after: &HashMap<String, TableFingerprint>,
+) -> Vec<TableChange> {
+    let mut changes: Vec<TableChange> = after
+        .iter()
+        .filter_map(|(table, after)| {
+            let (before_count, changed) = match before.get(table) {
+                Some(before) => (before.row_count, before != after),
+                None => (0, after.row_count > 0),
+            };
+            if !changed {
+                return None;
+            }
+            let (operation, row_count) = match after.row_count.cmp(&before_count) {
+                std::cmp::Ordering::Greater => {
+                    (ChangeOperation::Insert, after.row_count - before_count)
+                }
+                std::cmp::Ordering::Less => {
+                    (ChangeOperation::Delete, before_count - after.row_count)
+                }
+                std::cmp::Ordering::Equal => (ChangeOperation::Update, 0),
+            };
+            Some(TableChange {
+                table: table.clone(),
+                operation,
+                row_count,
+                rowids: Vec::new(),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| a.table.cmp(&b.table));
+    changes
+}
+
+/// Scans DuckDB runs over intermediate results rather than over tables
+/// or table functions.
+const INTERNAL_SCANS: [&str; 9] = [
+    "CHUNK_SCAN",
+    "COLUMN_DATA_SCAN",
+    "CTE_SCAN",
+    "DELIM_SCAN",
+    "DUMMY_SCAN",
+    "EXPRESSION_SCAN",
+    "POSITIONAL_SCAN",
+    "REC_CTE_SCAN",
+    "REC_REC_CTE_SCAN",
+];
+
+/// Reject `query` unless every table it scans, and every column it
+/// reads from those tables, is allowed by `restrictions`. Scans of
+/// anything but tables (e.g., `duckdb_tables()` or other table
+/// functions) are rejected outright. Views are expanded in the plan, so
+/// they grant nothing on their own: the tables behind them must be
+/// granted.
+fn check_plan(
+    conn: &duckdb::Connection,
+    query: &str,
+    restrictions: &QueryRestrictions,
+) -> Result<(), AybError> {
+    for nodes in explain(conn, query)? {
+        check_plan_nodes(&nodes, restrictions).map_err(|denied| AybError::NoObjectAccessError {
+            message: format!("Not permitted to {denied} in this database"),
+        })?;
+    }
+    Ok(())
+}
+
+/// Walks a JSON-rendered plan, returning a description of the first
+/// access `restrictions` don't allow.
+fn check_plan_nodes(nodes: &JsonValue, restrictions: &QueryRestrictions) -> Result<(), String> {
+    let nodes = match nodes {
+        JsonValue::Array(nodes) => nodes.iter().collect(),
+        JsonValue::Object(_) => vec![nodes],
+        _ => Vec::new(),
+    };
+    for node in nodes {
+        let name = node["name"].as_str().unwrap_or_default().trim();
+        let extra_info = &node["extra_info"];
+        if let Some(table) = extra_info["Table"].as_str() {
+            // Tables are rendered as `catalog.schema.table`.
+            let table = table.rsplit('.').next().unwrap_or(table).trim_matches('"');
+            if !restrictions.can_read(table, "") {
+                return Err(format!("read {table}"));
+            }
+            for column in plan_lines(&extra_info["Projections"]) {
+                // Struct fields are rendered as `column.field`.
+                let column = column.split('.').next().unwrap_or(column).trim_matches('"');
+                if !restrictions.can_read(table, column) {
+                    return Err(format!("read {table}.{column}"));
+                }
+            }
+            // Filters are rendered as expressions that start with the
+            // column they filter on.
+            for filter in
+                plan_lines(&extra_info["Filters"]).chain(plan_lines(&extra_info["Dynamic Filters"]))
+            {
+                let filter = filter
+                    .trim_start_matches("optional: ")
+                    .trim_start_matches('"');
+                let column: String = filter
+                    .chars()
+                    .take_while(|c| c.is_alphanumeric() || *c == '_')
+                    .collect();
+                if !restrictions.can_read(table, &column) || column.is_empty() {
+                    return Err(format!("filter on {table}"));
+                }
+            }
+        } else if extra_info["Function"].is_string()
+            || (name.contains("SCAN") && !INTERNAL_SCANS.contains(&name))
+        {
+            // Depending on DuckDB's version, table functions are either
+            // scanned by a `TABLE_SCAN` or by a node named after them,
+            // but either way they're listed as the `Function`.
+            let scanned = extra_info["Function"].as_str().unwrap_or(name);
+            return Err(format!("scan {}", scanned.to_lowercase()));
+        }
+        check_plan_nodes(&node["children"], restrictions)?;
+    }
+    Ok(())
+}
+
+/// Plan details with one entry are rendered as a string, and details
+/// with several as an array of strings.
+fn plan_lines(value: &JsonValue) -> impl Iterator<Item = &str> {
+    let lines: Vec<&str> = match value {
+        JsonValue::String(line) => vec![line.as_str()],
+        JsonValue::Array(lines) => lines.iter().filter_map(|line| line.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    lines.into_iter().filter(|line| !line.is_empty())
+}
+
+/// Physical operators that change the schema or settings of a database
+/// rather than its data.
+const SCHEMA_OPERATORS: [&str; 25] = [
+    "ALTER",
+    "ATTACH",
+    "BATCH_CREATE_TABLE_AS",
+    "COPY_DATABASE",
+    "CREATE_INDEX",
+    "CREATE_MACRO",
+    "CREATE_SCHEMA",
+    "CREATE_SECRET",
+    "CREATE_SEQUENCE",
+    "CREATE_TABLE",
+    "CREATE_TABLE_AS",
+    "CREATE_TYPE",
+    "CREATE_VIEW",
+    "DETACH",
+    "DROP",
+    "EXECUTE",
+    "EXPORT",
+    "EXTENSION",
+    "LOAD",
+    "PRAGMA",
+    "PREPARE",
+    "RESET",
+    "SET",
+    "UPDATE_EXTENSIONS",
+    "VACUUM",
+];
+
+/// Reject `query` if its plan changes the schema. DuckDB plans DDL as
+/// its own physical operators, so inspecting the plan's operators is
+/// equivalent to inspecting the statement's type.
+fn check_data_only(conn: &duckdb::Connection, query: &str) -> Result<(), AybError> {
+    for nodes in explain(conn, query)? {
+        if plan_contains(&nodes, &SCHEMA_OPERATORS) {
+            return Err(AybError::NoObjectAccessError {
+                message: "Not permitted to change the schema in this database".to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn plan_contains(nodes: &JsonValue, operators: &[&str]) -> bool {
+    let nodes = match nodes {
+        JsonValue::Array(nodes) => nodes.iter().collect(),
+        JsonValue::Object(_) => vec![nodes],
+        _ => Vec::new(),
+    };
+    nodes.into_iter().any(|node| {
+        let name = node["name"].as_str().unwrap_or_default().trim();
+        operators.contains(&name) || plan_contains(&node["children"], operators)
+    })
+}
+
+/// The JSON-rendered physical plans of `query`.
+fn explain(conn: &duckdb::Connection, query: &str) -> Result<Vec<JsonValue>, AybError> {
+    let mut statement = conn
+        .prepare(&format!("EXPLAIN (FORMAT JSON) {query}"))
+        .map_err(map_duckdb_error)?;
+    let plans = statement
+        .query_map([], |row| row.get::<_, String>(1))
+        .map_err(map_duckdb_error)?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(map_duckdb_error)?;
+    plans
+        .iter()
+        .map(|plan| Ok(serde_json::from_str(plan)?))
+        .collect()
+}
+
+/// The number of statements in `query`, as split by DuckDB's own
+/// parser. The `duckdb` crate runs all but the last statement of a
+/// batch while preparing it and doesn't expose statement extraction, so
+/// this parses `query` on a throwaway in-memory database, sized like
+/// `base_config`. Queries that fail to parse count as zero statements
+/// and fail again when they're prepared.
+fn statement_count(query: &str) -> Result<usize, AybError> {
+    use duckdb::ffi;
+
+    let query = CString::new(query).map_err(|err| AybError::Other {
+        message: err.to_string(),
+    })?;
+    unsafe {
+        let mut config: ffi::duckdb_config = ptr::null_mut();
+        if ffi::duckdb_create_config(&mut config) != ffi::DuckDBSuccess {
+            return Err(AybError::Other {
+                message: "Unable to configure DuckDB parser".to_string(),
+            });
+        }
+        for (name, value) in [(c"threads", c"1"), (c"max_memory", c"128MB")] {
+            ffi::duckdb_set_config(config, name.as_ptr(), value.as_ptr());
+        }
+        let mut database: ffi::duckdb_database = ptr::null_mut();
+        let opened = ffi::duckdb_open_ext(ptr::null(), &mut database, config, ptr::null_mut());
+        ffi::duckdb_destroy_config(&mut config);
+        if opened != ffi::DuckDBSuccess {
+            return Err(AybError::Other {
+                message: "Unable to open DuckDB parser".to_string(),
+            });
+        }
+        let mut connection: ffi::duckdb_connection = ptr::null_mut();
+        let count = if ffi::duckdb_connect(database, &mut connection) == ffi::DuckDBSuccess {
+            let mut extracted: ffi::duckdb_extracted_statements = ptr::null_mut();
+            let count = ffi::duckdb_extract_statements(connection, query.as_ptr(), &mut extracted);
+            ffi::duckdb_destroy_extracted(&mut extracted);
+            ffi::duckdb_disconnect(&mut connection);
+            Some(count as usize)
+        } else {
+            None
+        };
+        ffi::duckdb_close(&mut database);
+        count.ok_or_else(|| AybError::Other {
+            message: "Unable to connect to DuckDB parser".to_string(),
+        })
+    }
+}
+
 /// How long to keep retrying an operation blocked by a DuckDB file lock,
 /// and how long to wait between attempts. The total matches the
 /// `busy_timeout` we give SQLite, so both engines wait the same amount
@@ -145,7 +675,7 @@ fn with_lock_retry<T>(mut op: impl FnMut() -> Result<T, duckdb::Error>) -> Resul
 fn open_with_retry(path: &Path, query_mode: QueryMode) -> Result<duckdb::Connection, AybError> {
     let access_mode = match query_mode {
         QueryMode::ReadOnly => duckdb::AccessMode::ReadOnly,
-        QueryMode::ReadWrite => duckdb::AccessMode::ReadWrite,
+        QueryMode::ReadWriteData | QueryMode::ReadWrite => duckdb::AccessMode::ReadWrite,
     };
     with_lock_retry(|| {
         let config = base_config()?.access_mode(access_mode.clone())?;
@@ -264,6 +794,7 @@ fn duckdb_value_to_string(value: Value) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::hosted_db::diff::{SchemaChangeKind, TableDiff};
     use std::fs;
 
     #[test]
@@ -276,6 +807,7 @@ mod tests {
             "CREATE TABLE t(id INTEGER, name VARCHAR);",
             false,
             QueryMode::ReadWrite,
+            None,
         )
         .unwrap();
         assert!(r.rows.is_empty());
@@ -285,6 +817,7 @@ mod tests {
             "INSERT INTO t VALUES (1, 'hello'), (2, 'world');",
             false,
             QueryMode::ReadWrite,
+            None,
         )
         .unwrap();
         assert_eq!(r.fields, vec!["Count"]);
@@ -295,6 +828,7 @@ mod tests {
             "SELECT * FROM t ORDER BY id;",
             false,
             QueryMode::ReadOnly,
+            None,
         )
         .unwrap();
         assert_eq!(r.fields, vec!["id", "name"]);
@@ -323,6 +857,7 @@ mod tests {
             "CREATE TABLE t(id INTEGER);",
             false,
             QueryMode::ReadWrite,
+            None,
         )
         .unwrap();
 
@@ -383,6 +918,7 @@ mod tests {
             "CREATE TABLE t(id INTEGER);",
             false,
             QueryMode::ReadWrite,
+            None,
         )
         .unwrap();
 
@@ -391,7 +927,241 @@ mod tests {
             "INSERT INTO t VALUES (1);",
             false,
             QueryMode::ReadOnly,
+            None,
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_duckdb_restrictions_limit_tables_and_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_restricted.duckdb");
+        query_duckdb(
+            &path,
+            "CREATE TABLE customers(id INTEGER, name VARCHAR, email VARCHAR);
+             INSERT INTO customers VALUES (1, 'ada', 'ada@example.org');
+             CREATE TABLE orders(id INTEGER, customer_id INTEGER);
+             CREATE TABLE invoices(id INTEGER);",
+            false,
+            QueryMode::ReadWrite,
+            None,
+        )
+        .unwrap();
+        let restrictions = QueryRestrictions::from_pairs(vec![
+            ("customers".to_string(), Some("id".to_string())),
+            ("customers".to_string(), Some("name".to_string())),
+            ("orders".to_string(), None),
+        ]);
+        let run = |query: &str| {
+            query_duckdb(
+                &path,
+                query,
+                false,
+                QueryMode::ReadWrite,
+                Some(&restrictions),
+            )
+        };
+
+        let r = run("SELECT name FROM customers WHERE id = 1;").unwrap();
+        assert_eq!(r.rows, vec![vec![Some("ada".to_string())]]);
+        let r = run("SELECT count(*) FROM customers;").unwrap();
+        assert_eq!(r.rows, vec![vec![Some("1".to_string())]]);
+        let r = run("SELECT * FROM orders;").unwrap();
+        assert!(r.rows.is_empty());
+
+        for (query, denied) in [
+            ("SELECT * FROM customers;", "read customers.email"),
+            (
+                "SELECT name FROM customers WHERE email LIKE 'ada%';",
+                "read customers.email",
+            ),
+            ("SELECT count(*) FROM invoices;", "read invoices"),
+            ("SELECT * FROM duckdb_tables();", "scan duckdb_tables"),
+        ] {
+            match run(query) {
+                Err(AybError::NoObjectAccessError { message }) => {
+                    assert_eq!(
+                        message,
+                        format!("Not permitted to {denied} in this database")
+                    )
+                }
+                result => panic!("{query} wasn't denied: {result:?}"),
+            }
+        }
+
+        // Restricted queries run read-only, even on granted tables.
+        assert!(matches!(
+            run("INSERT INTO orders VALUES (1, 1);"),
+            Err(AybError::NoWriteAccessError { .. })
+        ));
+        // Batches are rejected, since only their last statement is
+        // planned.
+        assert!(matches!(
+            run("SELECT id FROM customers; SELECT email FROM customers;"),
+            Err(AybError::NoObjectAccessError { .. })
+        ));
+
+        fs::remove_dir_all(dir.path()).ok();
+    }
+
+    #[test]
+    fn test_duckdb_read_write_data_prevents_schema_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_read_write_data.duckdb");
+        query_duckdb(
+            &path,
+            "CREATE TABLE test(x INTEGER);",
+            false,
+            QueryMode::ReadWrite,
+            None,
+        )
+        .unwrap();
+        let run = |query: &str| query_duckdb(&path, query, false, QueryMode::ReadWriteData, None);
+
+        run("INSERT INTO test VALUES (1), (2);").unwrap();
+        run("UPDATE test SET x = 3 WHERE x = 2;").unwrap();
+        run("DELETE FROM test WHERE x = 1;").unwrap();
+        let r = run("SELECT x FROM test;").unwrap();
+        assert_eq!(r.rows, vec![vec![Some("3".to_string())]]);
+
+        for query in [
+            "CREATE TABLE other(x INTEGER);",
+            "CREATE TABLE other AS SELECT * FROM test;",
+            "CREATE VIEW test_view AS SELECT * FROM test;",
+            "CREATE INDEX test_index ON test(x);",
+            "ALTER TABLE test ADD COLUMN y INTEGER;",
+            "DROP TABLE test;",
+            "INSERT INTO test VALUES (4); DROP TABLE test;",
+        ] {
+            assert!(
+                matches!(run(query), Err(AybError::NoObjectAccessError { .. })),
+                "{query} wasn't denied"
+            );
+        }
+        let r = run("SELECT x FROM test;").unwrap();
+        assert_eq!(r.rows, vec![vec![Some("3".to_string())]]);
+
+        fs::remove_dir_all(dir.path()).ok();
+    }
+
+    #[test]
+    fn test_duckdb_captures_committed_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_changes.duckdb");
+        let run = |query: &str| {
+            run_duckdb(&path, query, false, QueryMode::ReadWrite, None, true)
+                .unwrap()
+                .changes
+        };
+        let change = |table: &str, operation, row_count| TableChange {
+            table: table.to_string(),
+            operation,
+            row_count,
+            rowids: Vec::new(),
+        };
+
+        assert_eq!(run("CREATE TABLE test(x INTEGER);"), vec![]);
+        assert_eq!(
+            run("INSERT INTO test VALUES (1), (1), (2);"),
+            vec![change("test", ChangeOperation::Insert, 3)]
+        );
+        assert_eq!(
+            run("UPDATE test SET x = 3 WHERE x = 2;"),
+            vec![change("test", ChangeOperation::Update, 0)]
+        );
+        assert_eq!(
+            run("DELETE FROM test WHERE x = 1;"),
+            vec![change("test", ChangeOperation::Delete, 2)]
+        );
+        assert_eq!(run("SELECT * FROM test;"), vec![]);
+        // Changes in a transaction that's never committed roll back.
+        assert_eq!(run("BEGIN; INSERT INTO test VALUES (4);"), vec![]);
+        let r = query_duckdb(
+            &path,
+            "SELECT x FROM test;",
+            false,
+            QueryMode::ReadOnly,
+            None,
+        )
+        .unwrap();
+        assert_eq!(r.rows, vec![vec![Some("3".to_string())]]);
+
+        fs::remove_dir_all(dir.path()).ok();
+    }
+
+    #[test]
+    fn test_duckdb_reports_whether_queries_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_wrote.duckdb");
+        let wrote = |query: &str| {
+            run_duckdb(&path, query, false, QueryMode::ReadWrite, None, false)
+                .unwrap()
+                .wrote
+        };
+
+        assert!(wrote("CREATE TABLE test(x INTEGER);"));
+        assert!(wrote("INSERT INTO test VALUES (1), (2);"));
+        assert!(!wrote("SELECT * FROM test;"));
+        assert!(wrote("DELETE FROM test WHERE x = 1;"));
+        // Writes in a transaction that's never committed roll back.
+        assert!(!wrote("BEGIN; INSERT INTO test VALUES (3);"));
+
+        fs::remove_dir_all(dir.path()).ok();
+    }
+
+    #[test]
+    fn test_duckdb_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("from.duckdb"), dir.path().join("to.duckdb"));
+        let run = |query: &str| {
+            run_duckdb(&to, query, false, QueryMode::ReadWrite, None, false).unwrap();
+        };
+        run("CREATE TABLE people(id INTEGER PRIMARY KEY, name TEXT);");
+        run("CREATE TABLE notes(body TEXT);");
+        run("INSERT INTO people VALUES (1, 'a'), (2, NULL);");
+        run("INSERT INTO notes VALUES ('first');");
+        DuckdbEngine.create_snapshot(&to, &from).unwrap();
+        run("INSERT INTO people VALUES (3, 'c');");
+        run("UPDATE people SET name = 'b' WHERE id = 2;");
+        run("DELETE FROM people WHERE id = 1;");
+        run("INSERT INTO notes VALUES ('second');");
+        run("CREATE VIEW named AS SELECT name FROM people;");
+
+        let diff = DuckdbEngine.diff(&from, &to).unwrap();
+        let changes: Vec<(&str, &str, SchemaChangeKind)> = diff
+            .schema_changes
+            .iter()
+            .map(|change| {
+                (
+                    change.object_type.as_str(),
+                    change.name.as_str(),
+                    change.change,
+                )
+            })
+            .collect();
+        assert_eq!(changes, vec![("view", "named", SchemaChangeKind::Added)]);
+        let key = |id: &str| vec![vec![Some(id.to_string())]];
+        assert_eq!(
+            diff.tables,
+            vec![
+                TableDiff {
+                    table: "notes".to_string(),
+                    inserted: 1,
+                    ..Default::default()
+                },
+                TableDiff {
+                    table: "people".to_string(),
+                    primary_key: vec!["id".to_string()],
+                    inserted: 1,
+                    updated: 1,
+                    deleted: 1,
+                    inserted_keys: key("3"),
+                    updated_keys: key("2"),
+                    deleted_keys: key("1"),
+                },
+            ]
+        );
+
+        fs::remove_dir_all(dir.path()).ok();
+    }
 }
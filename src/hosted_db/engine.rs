@@ -1,5 +1,7 @@
 use crate::error::AybError;
-use crate::hosted_db::{QueryMode, QueryResult};
+use crate::hosted_db::diff::DatabaseDiff;
+use crate::hosted_db::restrictions::QueryRestrictions;
+use crate::hosted_db::{QueryMode, QueryOutput};
 use std::path::Path;
 
 /// A hosted database engine (SQLite or DuckDB).
@@ -12,14 +14,35 @@ use std::path::Path;
 /// (e.g., snapshots need ATTACH) but that stays an implementation detail
 /// rather than a parameter callers can pass.
 pub trait DbEngine: Send + Sync {
+    /// Run `query` against the database at `path`. When `restrictions`
+    /// are provided, queries that touch tables, views, or columns
+    /// outside of them fail with `NoObjectAccessError`. When
+    /// `capture_changes` is set, the output also lists the rows the
+    /// query changed, leaving out changes that were rolled back. When
+    /// `retain_wal` is set, engines with a write-ahead log leave it in
+    /// place for replication rather than checkpointing it.
     fn query(
         &self,
         path: &Path,
         query: &str,
         query_mode: QueryMode,
-    ) -> Result<QueryResult, AybError>;
+        restrictions: Option<&QueryRestrictions>,
+        capture_changes: bool,
+        retain_wal: bool,
+    ) -> Result<QueryOutput, AybError>;
 
     /// Write a consistent copy of the database at `db_path` to
     /// `snapshot_path`, then verify the copy is readable.
     fn create_snapshot(&self, db_path: &Path, snapshot_path: &Path) -> Result<(), AybError>;
+
+    /// Prepare the database at `path`, which no one will write to
+    /// again, to be queried by a daemon that can only read its
+    /// directory (see `DaemonRegistry::execute_read_only_query`).
+    fn prepare_read_only(&self, path: &Path) -> Result<(), AybError>;
+
+    /// Compare the database at `from_path` with the one at `to_path`,
+    /// neither of which is being written to (e.g., snapshots). Like
+    /// snapshots, this runs in the server process, attaching both
+    /// databases read-only.
+    fn diff(&self, from_path: &Path, to_path: &Path) -> Result<DatabaseDiff, AybError>;
 }
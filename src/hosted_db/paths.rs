@@ -25,6 +25,7 @@ pub const CURRENT: &str = "current";
 pub const CURRENT_TMP: &str = "current.tmp";
 const DATABASES: &str = "databases";
 const SNAPSHOTS: &str = "snapshots";
+const SNAPSHOT_CACHE: &str = "snapshot_cache";
 
 pub fn database_parent_path(data_path: &str, create_path: bool) -> Result<PathBuf, AybError> {
     let path: PathBuf = [data_path, DATABASES].iter().collect();
@@ -134,6 +135,27 @@ pub fn database_snapshot_path(
     Ok(fs::canonicalize(path)?)
 }
 
+/// Returns `{data_path}/snapshot_cache`, where snapshots are fetched
+/// to be queried.
+pub fn snapshot_cache_parent_path(data_path: &str) -> PathBuf {
+    [data_path, SNAPSHOT_CACHE].iter().collect()
+}
+
+/// Returns the directory that snapshot `snapshot_id` of
+/// `{entity_slug}/{database_slug}` is fetched to in order to be
+/// queried. The format for this path is
+/// `{data_path}/snapshot_cache/{entity_slug}/{database_slug}/{snapshot_id}/`.
+pub fn snapshot_cache_path(
+    entity_slug: &str,
+    database_slug: &str,
+    snapshot_id: &str,
+    data_path: &str,
+) -> PathBuf {
+    let mut path = snapshot_cache_parent_path(data_path);
+    path.extend([entity_slug, database_slug, snapshot_id]);
+    path
+}
+
 pub fn pathbuf_to_file_name(path: &Path) -> Result<String, AybError> {
     Ok(path
         .file_name()
@@ -206,3 +228,122 @@ pub async fn set_current_database_and_clean_up(
 
     Ok(())
 }
+
+/// Returns `{data_path}/databases/{entity_slug}/{database_slug}`, the
+/// directory holding every version of a database.
+fn database_versions_path(entity_slug: &str, database_slug: &str, data_path: &str) -> PathBuf {
+    [data_path, DATABASES, entity_slug, database_slug]
+        .iter()
+        .collect()
+}
+
+/// Shuts down the daemon serving `{entity_slug}/{database_slug}` and
+/// removes all of its versions from disk.
+pub async fn remove_database_directory(
+    entity_slug: &str,
+    database_slug: &str,
+    data_path: &str,
+    daemon_registry: &DaemonRegistry,
+) -> Result<(), AybError> {
+    if let Ok(current_path) = current_database_path(entity_slug, database_slug, data_path) {
+        daemon_registry.shut_down_daemon(&current_path).await?;
+    }
+    let versions_path = database_versions_path(entity_slug, database_slug, data_path);
+    if versions_path.exists() {
+        fs::remove_dir_all(versions_path)?;
+    }
+    Ok(())
+}
+
+/// Moves the current version of `{from_entity_slug}/{from_database_slug}`
+/// to `{to_entity_slug}/{to_database_slug}`. Database files are named
+/// after their slug (as are sidecar files like SQLite's `-wal` or
+/// DuckDB's `.wal`), so each file is renamed along the way. The old
+/// database's daemon is shut down and its directory is removed.
+pub async fn move_database_directory(
+    from_entity_slug: &str,
+    from_database_slug: &str,
+    to_entity_slug: &str,
+    to_database_slug: &str,
+    data_path: &str,
+    daemon_registry: &DaemonRegistry,
+) -> Result<(), AybError> {
+    if from_entity_slug == to_entity_slug && from_database_slug == to_database_slug {
+        return Ok(());
+    }
+    // A database without a current version (e.g., one recovered without
+    // any snapshots) has no files to move, only leftover versions.
+    let Ok(current_path) = current_database_path(from_entity_slug, from_database_slug, data_path)
+    else {
+        let versions_path = database_versions_path(from_entity_slug, from_database_slug, data_path);
+        if versions_path.exists() {
+            fs::remove_dir_all(versions_path)?;
+        }
+        return Ok(());
+    };
+    daemon_registry.shut_down_daemon(&current_path).await?;
+
+    let new_path = new_database_path(to_entity_slug, to_database_slug, data_path)?;
+    for entry in fs::read_dir(pathbuf_to_parent(&current_path)?)? {
+        let entry = entry?;
+        let file_name = pathbuf_to_file_name(&entry.path())?;
+        let new_file_name = match file_name.strip_prefix(from_database_slug) {
+            Some(suffix) => format!("{to_database_slug}{suffix}"),
+            None => file_name,
+        };
+        fs::rename(entry.path(), new_path.join(new_file_name))?;
+    }
+    set_current_database_and_clean_up(&new_path, daemon_registry).await?;
+
+    fs::remove_dir_all(database_versions_path(
+        from_entity_slug,
+        from_database_slug,
+        data_path,
+    ))?;
+    Ok(())
+}
+
+/// Renames the files in `directory` that belong to `from_database_slug`
+/// (the database file and any sidecar files, which are named after it)
+/// so that they belong to `to_database_slug` instead.
+pub fn rename_database_files(
+    directory: &Path,
+    from_database_slug: &str,
+    to_database_slug: &str,
+) -> Result<(), AybError> {
+    if from_database_slug == to_database_slug {
+        return Ok(());
+    }
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let file_name = pathbuf_to_file_name(&entry.path())?;
+        if let Some(suffix) = file_name.strip_prefix(from_database_slug) {
+            fs::rename(
+                entry.path(),
+                directory.join(format!("{to_database_slug}{suffix}")),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves every file in `source_directory` (e.g., a freshly written
+/// copy of another database) into a new version of
+/// `{entity_slug}/{database_slug}` and makes that version current.
+pub async fn install_database_files(
+    source_directory: &Path,
+    entity_slug: &str,
+    database_slug: &str,
+    data_path: &str,
+    daemon_registry: &DaemonRegistry,
+) -> Result<(), AybError> {
+    let new_path = new_database_path(entity_slug, database_slug, data_path)?;
+    for entry in fs::read_dir(source_directory)? {
+        let entry = entry?;
+        fs::rename(
+            entry.path(),
+            new_path.join(pathbuf_to_file_name(&entry.path())?),
+        )?;
+    }
+    set_current_database_and_clean_up(&new_path, daemon_registry).await
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+/// Access to a single table or view. `columns` is `None` when every
+/// column of the object is granted.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ObjectGrant {
+    pub object: String,
+    pub columns: Option<Vec<String>>,
+}
+
+/// The tables, views, and columns a query is limited to. Engines
+/// reject queries that touch anything else, including schema changes
+/// and pragmas. Object and column names match case-insensitively, like
+/// SQL identifiers do.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryRestrictions {
+    pub grants: Vec<ObjectGrant>,
+}
+
+impl QueryRestrictions {
+    /// Builds restrictions from `(object, column)` pairs, where a
+    /// `None` column grants the whole object.
+    pub fn from_pairs<I>(pairs: I) -> QueryRestrictions
+    where
+        I: IntoIterator<Item = (String, Option<String>)>,
+    {
+        let mut restrictions = QueryRestrictions::default();
+        for (object, column) in pairs {
+            let position = restrictions
+                .grants
+                .iter()
+                .position(|grant| grant.object.eq_ignore_ascii_case(&object));
+            let grant = match position {
+                Some(position) => &mut restrictions.grants[position],
+                None => {
+                    restrictions.grants.push(ObjectGrant {
+                        object,
+                        columns: Some(Vec::new()),
+                    });
+                    restrictions.grants.last_mut().unwrap()
+                }
+            };
+            match (column, &mut grant.columns) {
+                (None, columns) => *columns = None,
+                (Some(column), Some(columns)) => columns.push(column),
+                // The whole object is already granted.
+                (Some(_), None) => {}
+            }
+        }
+        restrictions
+    }
+
+    fn grant(&self, object: &str) -> Option<&ObjectGrant> {
+        self.grants
+            .iter()
+            .find(|grant| grant.object.eq_ignore_ascii_case(object))
+    }
+
+    /// Whether `column` of `object` can be read. An empty column is
+    /// how SQLite reports reads that don't reference a column (e.g.,
+    /// `COUNT(*)`), which any grant on the object allows.
+    pub fn can_read(&self, object: &str, column: &str) -> bool {
+        match self.grant(object) {
+            None => false,
+            Some(ObjectGrant { columns: None, .. }) => true,
+            Some(ObjectGrant {
+                columns: Some(columns),
+                ..
+            }) => column.is_empty() || columns.iter().any(|c| c.eq_ignore_ascii_case(column)),
+        }
+    }
+
+    /// Whether every column of `object` is granted, which is required
+    /// to insert or delete rows.
+    pub fn can_access_all_columns(&self, object: &str) -> bool {
+        matches!(self.grant(object), Some(ObjectGrant { columns: None, .. }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn restrictions() -> QueryRestrictions {
+        QueryRestrictions::from_pairs(vec![
+            ("customers".to_string(), Some("id".to_string())),
+            ("customers".to_string(), Some("name".to_string())),
+            ("orders".to_string(), None),
+        ])
+    }
+
+    #[test]
+    fn groups_columns_by_object() {
+        assert_eq!(
+            restrictions().grants,
+            vec![
+                ObjectGrant {
+                    object: "customers".to_string(),
+                    columns: Some(vec!["id".to_string(), "name".to_string()]),
+                },
+                ObjectGrant {
+                    object: "orders".to_string(),
+                    columns: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn whole_object_grant_wins_over_columns() {
+        let restrictions = QueryRestrictions::from_pairs(vec![
+            ("orders".to_string(), Some("id".to_string())),
+            ("orders".to_string(), None),
+            ("orders".to_string(), Some("total".to_string())),
+        ]);
+        assert!(restrictions.can_access_all_columns("orders"));
+    }
+
+    #[test]
+    fn checks_objects_and_columns() {
+        let restrictions = restrictions();
+        assert!(restrictions.can_read("customers", "id"));
+        assert!(restrictions.can_read("Customers", "NAME"));
+        assert!(restrictions.can_read("customers", ""));
+        assert!(!restrictions.can_read("customers", "email"));
+        assert!(!restrictions.can_access_all_columns("customers"));
+        assert!(restrictions.can_read("orders", "anything"));
+        assert!(restrictions.can_access_all_columns("ORDERS"));
+        assert!(!restrictions.can_read("invoices", "id"));
+        assert!(!restrictions.can_access_all_columns("invoices"));
+    }
+}
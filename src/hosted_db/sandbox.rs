@@ -101,8 +101,8 @@ fn print_warning_banner(details: &[&str]) {
 /// processing any queries.
 ///
 /// On Linux with Landlock enforced (kernel 5.13+):
-/// - Filesystem: only the database file (read-write) and shared
-///   libraries (read-only) are accessible.
+/// - Filesystem: only the database file (read-write, or read-only
+///   with `read_only`) and shared libraries (read-only) are accessible.
 /// - Network: all TCP bind/connect denied (on kernel 6.7+).
 /// - Memory: 256 MB virtual memory limit (RLIMIT_AS).
 /// - File size: 256 MB max file size (RLIMIT_FSIZE).
@@ -115,10 +115,10 @@ fn print_warning_banner(details: &[&str]) {
 /// Configurable per-database limits and per-process CPU/thread
 /// limitation is future work.
 #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
-pub fn apply_sandbox(db_path: &Path) -> Result<(), AybError> {
+pub fn apply_sandbox(db_path: &Path, read_only: bool) -> Result<(), AybError> {
     #[cfg(target_os = "linux")]
     {
-        apply_landlock_restrictions(db_path)?;
+        apply_landlock_restrictions(db_path, read_only)?;
         apply_resource_limits()?;
     }
     Ok(())
@@ -126,7 +126,7 @@ pub fn apply_sandbox(db_path: &Path) -> Result<(), AybError> {
 
 /// Apply Landlock filesystem and network restrictions.
 #[cfg(target_os = "linux")]
-fn apply_landlock_restrictions(db_path: &Path) -> Result<(), AybError> {
+fn apply_landlock_restrictions(db_path: &Path, read_only: bool) -> Result<(), AybError> {
     use landlock::{
         path_beneath_rules, Access, AccessFs, AccessNet, Ruleset, RulesetAttr, RulesetCreatedAttr,
         ABI,
@@ -188,7 +188,9 @@ fn apply_landlock_restrictions(db_path: &Path) -> Result<(), AybError> {
     }
 
     // Allow read-write access to the database file's parent directory.
-    // Both SQLite and DuckDB need this for journal/WAL/temp files.
+    // Both SQLite and DuckDB need this for journal/WAL/temp files. A
+    // read-only daemon only serves databases that were prepared to be
+    // read without them, so it can only read the directory.
     let db_dir = db_path.parent().ok_or(AybError::Other {
         message: format!(
             "Cannot determine parent directory of database: {}",
@@ -196,7 +198,10 @@ fn apply_landlock_restrictions(db_path: &Path) -> Result<(), AybError> {
         ),
     })?;
     ruleset_created = ruleset_created
-        .add_rules(path_beneath_rules(&[db_dir], access_all))
+        .add_rules(path_beneath_rules(
+            &[db_dir],
+            if read_only { access_read } else { access_all },
+        ))
         .map_err(|e| AybError::Other {
             message: format!("Landlock: failed to add database directory rule: {e}"),
         })?;
@@ -243,16 +248,24 @@ fn set_rlimit(resource: libc::__rlimit_resource_t, limit: u64) -> Result<(), Ayb
     Ok(())
 }
 
-/// Build command for running the query daemon.
+/// The argument that starts a query daemon in read-only mode.
+pub const READ_ONLY: &str = "read-only";
+
+/// Build command for running the query daemon. A `read_only` daemon
+/// runs every query read-only, whatever the request asks for.
 pub fn build_daemon_command(
     db_path: &Path,
     db_type: &DBType,
+    read_only: bool,
 ) -> Result<tokio::process::Command, AybError> {
     let ayb_path = current_exe()?;
     let query_daemon_path = pathbuf_to_parent(&ayb_path)?.join("ayb_query_daemon");
 
     let mut cmd = tokio::process::Command::new(&query_daemon_path);
     cmd.arg(db_path).arg(db_type.to_str());
+    if read_only {
+        cmd.arg(READ_ONLY);
+    }
 
     // Run with an empty environment so the daemon inherits none of the
     // server's secrets (fernet key, S3 credentials, SMTP password): they
@@ -1,10 +1,21 @@
 use crate::error::AybError;
+use crate::hosted_db::diff::{
+    diff_databases, quote_identifier, DatabaseDiff, DiffSource, SchemaObject,
+};
 use crate::hosted_db::engine::DbEngine;
-use crate::hosted_db::{sql_string_literal, QueryMode, QueryResult};
+use crate::hosted_db::restrictions::QueryRestrictions;
+use crate::hosted_db::{
+    sql_string_literal, ChangeOperation, QueryMode, QueryOutput, QueryResult, TableChange,
+    MAX_REPORTED_ROWIDS,
+};
 use rusqlite;
 use rusqlite::config::DbConfig;
+use rusqlite::ffi;
 use rusqlite::limits::Limit;
 use rusqlite::types::ValueRef;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
 use std::path::Path;
 
 pub struct SqliteEngine;
@@ -15,8 +26,19 @@ impl DbEngine for SqliteEngine {
         path: &Path,
         query: &str,
         query_mode: QueryMode,
-    ) -> Result<QueryResult, AybError> {
-        query_sqlite(path, query, false, query_mode)
+        restrictions: Option<&QueryRestrictions>,
+        capture_changes: bool,
+        retain_wal: bool,
+    ) -> Result<QueryOutput, AybError> {
+        query_sqlite(
+            path,
+            query,
+            false,
+            query_mode,
+            restrictions,
+            capture_changes,
+            retain_wal,
+        )
     }
 
     fn create_snapshot(&self, db_path: &Path, snapshot_path: &Path) -> Result<(), AybError> {
@@ -25,7 +47,16 @@ impl DbEngine for SqliteEngine {
         // rather than interpolated raw. (Single quotes, not the double
         // quotes SQLite would read as an identifier.)
         let backup_query = format!("VACUUM INTO {}", sql_string_literal(snapshot_path));
-        let result = query_sqlite(db_path, &backup_query, true, QueryMode::ReadOnly)?;
+        let result = query_sqlite(
+            db_path,
+            &backup_query,
+            true,
+            QueryMode::ReadOnly,
+            None,
+            false,
+            false,
+        )?
+        .result;
         if !result.rows.is_empty() {
             return Err(AybError::SnapshotError {
                 message: format!("Unexpected snapshot result: {result:?}"),
@@ -36,7 +67,11 @@ impl DbEngine for SqliteEngine {
             "PRAGMA integrity_check;",
             false,
             QueryMode::ReadOnly,
-        )?;
+            None,
+            false,
+            false,
+        )?
+        .result;
         if result.fields.len() != 1
             || result.rows.len() != 1
             || result.rows[0][0] != Some("ok".to_string())
@@ -47,24 +82,408 @@ impl DbEngine for SqliteEngine {
         }
         Ok(())
     }
+
+    fn prepare_read_only(&self, path: &Path) -> Result<(), AybError> {
+        // Reading a database in WAL mode means writing its shared
+        // memory file, so switch it back to a rollback journal, which
+        // readers leave alone.
+        let result = query_sqlite(
+            path,
+            "PRAGMA journal_mode=DELETE;",
+            false,
+            QueryMode::ReadWrite,
+            None,
+            false,
+            false,
+        )?
+        .result;
+        if result.rows.len() != 1 || result.rows[0][0] != Some("delete".to_string()) {
+            return Err(AybError::SnapshotError {
+                message: format!("Unable to prepare snapshot for reading: {result:?}"),
+            });
+        }
+        Ok(())
+    }
+
+    fn diff(&self, from_path: &Path, to_path: &Path) -> Result<DatabaseDiff, AybError> {
+        // Attached databases are opened with the same flags as the main
+        // one, so both versions are read-only.
+        let conn = rusqlite::Connection::open_with_flags(
+            to_path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+        )?;
+        conn.pragma_update(None, "busy_timeout", 5000)?;
+        conn.execute_batch(&format!(
+            "ATTACH {} AS diff_from;",
+            sql_string_literal(from_path)
+        ))?;
+        diff_databases(&SqliteDiffSource(conn), "diff_from", "main")
+    }
+}
+
+/// A connection with the version of a database being diffed from
+/// attached as `diff_from`, and the one being diffed to as `main`.
+struct SqliteDiffSource(rusqlite::Connection);
+
+impl DiffSource for SqliteDiffSource {
+    // SQLite's `IS NOT` treats NULLs as equal, like the standard
+    // `IS DISTINCT FROM`, which older versions don't support.
+    const DISTINCT_FROM: &'static str = "IS NOT";
+
+    fn rows(&self, query: &str) -> Result<Vec<Vec<Option<String>>>, AybError> {
+        let mut prepared = self.0.prepare(query)?;
+        let num_columns = prepared.column_count();
+        let rows = prepared.query_map([], |row| {
+            (0..num_columns)
+                .map(|column_index| {
+                    Ok(match row.get_ref(column_index)? {
+                        ValueRef::Null => None,
+                        ValueRef::Integer(i) => Some(i.to_string()),
+                        ValueRef::Real(f) => Some(f.to_string()),
+                        ValueRef::Text(t) | ValueRef::Blob(t) => {
+                            Some(String::from_utf8_lossy(t).to_string())
+                        }
+                    })
+                })
+                .collect()
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    fn schema_objects(&self, schema: &str) -> Result<Vec<SchemaObject>, AybError> {
+        // Internal objects, like the indexes behind `UNIQUE`
+        // constraints, are named `sqlite_*`.
+        let rows = self.rows(&format!(
+            "SELECT type, name, sql FROM {}.sqlite_master WHERE name NOT LIKE 'sqlite\\_%' ESCAPE '\\';",
+            quote_identifier(schema)
+        ))?;
+        Ok(rows
+            .into_iter()
+            .map(|mut row| SchemaObject {
+                sql: row.pop().flatten(),
+                name: row.pop().flatten().unwrap_or_default(),
+                object_type: row.pop().flatten().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    fn table_columns(
+        &self,
+        schema: &str,
+        table: &str,
+    ) -> Result<(Vec<String>, Vec<String>), AybError> {
+        let mut prepared = self
+            .0
+            .prepare("SELECT name, pk FROM pragma_table_info(?1, ?2) ORDER BY cid;")?;
+        let columns = prepared
+            .query_map([table, schema], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        // `pk` is a column's position in the primary key, or 0 if it
+        // isn't part of it.
+        let mut primary_key: Vec<&(String, i64)> =
+            columns.iter().filter(|(_, pk)| *pk > 0).collect();
+        primary_key.sort_by_key(|(_, pk)| *pk);
+        Ok((
+            columns.iter().map(|(name, _)| name.clone()).collect(),
+            primary_key
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+        ))
+    }
+}
+
+/// State shared with `authorize`, which records the first access it
+/// denied so the resulting error can name it, and whether it allowed a
+/// schema change.
+struct Authorizer<'a> {
+    restrictions: Option<&'a QueryRestrictions>,
+    allow_schema_changes: bool,
+    capture_changes: bool,
+    denied: RefCell<Option<String>>,
+    changes_schema: Cell<bool>,
+}
+
+/// Actions that change the database's schema or settings rather than
+/// its data. Pragmas only count when they assign a value.
+fn is_schema_change(action: c_int, pragma_value: Option<&str>) -> bool {
+    match action {
+        ffi::SQLITE_PRAGMA => pragma_value.is_some(),
+        ffi::SQLITE_CREATE_INDEX
+        | ffi::SQLITE_CREATE_TABLE
+        | ffi::SQLITE_CREATE_TEMP_INDEX
+        | ffi::SQLITE_CREATE_TEMP_TABLE
+        | ffi::SQLITE_CREATE_TEMP_TRIGGER
+        | ffi::SQLITE_CREATE_TEMP_VIEW
+        | ffi::SQLITE_CREATE_TRIGGER
+        | ffi::SQLITE_CREATE_VIEW
+        | ffi::SQLITE_CREATE_VTABLE
+        | ffi::SQLITE_DROP_INDEX
+        | ffi::SQLITE_DROP_TABLE
+        | ffi::SQLITE_DROP_TEMP_INDEX
+        | ffi::SQLITE_DROP_TEMP_TABLE
+        | ffi::SQLITE_DROP_TEMP_TRIGGER
+        | ffi::SQLITE_DROP_TEMP_VIEW
+        | ffi::SQLITE_DROP_TRIGGER
+        | ffi::SQLITE_DROP_VIEW
+        | ffi::SQLITE_DROP_VTABLE
+        | ffi::SQLITE_ALTER_TABLE
+        | ffi::SQLITE_REINDEX
+        | ffi::SQLITE_ANALYZE
+        | ffi::SQLITE_ATTACH
+        | ffi::SQLITE_DETACH => true,
+        _ => false,
+    }
+}
+
+impl Authorizer<'_> {
+    /// Decides whether to allow one of the actions SQLite reports while
+    /// compiling a statement
+    /// (https://www.sqlite.org/c3ref/c_alter_table.html). Returns a
+    /// description of the action when it's denied.
+    fn check(
+        &self,
+        action: c_int,
+        arg1: Option<&str>,
+        arg2: Option<&str>,
+        accessor: Option<&str>,
+    ) -> Result<(), String> {
+        if !self.allow_schema_changes && is_schema_change(action, arg2) {
+            return Err("change the schema".to_string());
+        }
+        let Some(restrictions) = self.restrictions else {
+            return Ok(());
+        };
+        match action {
+            ffi::SQLITE_SELECT
+            | ffi::SQLITE_FUNCTION
+            | ffi::SQLITE_RECURSIVE
+            | ffi::SQLITE_TRANSACTION
+            | ffi::SQLITE_SAVEPOINT => Ok(()),
+            ffi::SQLITE_READ => {
+                let (table, column) = (arg1.unwrap_or_default(), arg2.unwrap_or_default());
+                // Reads made on behalf of a granted view are allowed,
+                // which lets views expose a subset of a table.
+                let via_granted_view =
+                    accessor.is_some_and(|view| restrictions.can_access_all_columns(view));
+                if via_granted_view || restrictions.can_read(table, column) {
+                    Ok(())
+                } else if column.is_empty() {
+                    Err(format!("read {table}"))
+                } else {
+                    Err(format!("read {table}.{column}"))
+                }
+            }
+            ffi::SQLITE_UPDATE => {
+                let (table, column) = (arg1.unwrap_or_default(), arg2.unwrap_or_default());
+                if restrictions.can_read(table, column) {
+                    Ok(())
+                } else {
+                    Err(format!("update {table}.{column}"))
+                }
+            }
+            ffi::SQLITE_INSERT | ffi::SQLITE_DELETE => {
+                let table = arg1.unwrap_or_default();
+                if restrictions.can_access_all_columns(table) {
+                    Ok(())
+                } else if action == ffi::SQLITE_INSERT {
+                    Err(format!("insert into {table}"))
+                } else {
+                    Err(format!("delete from {table}"))
+                }
+            }
+            // Schema changes, pragmas, and everything else could reveal
+            // or alter objects outside of the grants.
+            _ => Err("run this statement".to_string()),
+        }
+    }
+}
+
+unsafe fn optional_str<'a>(value: *const c_char) -> Option<&'a str> {
+    if value.is_null() {
+        None
+    } else {
+        CStr::from_ptr(value).to_str().ok()
+    }
+}
+
+/// The callback registered with `sqlite3_set_authorizer`. `user_data`
+/// points to an `Authorizer` that outlives the connection.
+unsafe extern "C" fn authorize(
+    user_data: *mut c_void,
+    action: c_int,
+    arg1: *const c_char,
+    arg2: *const c_char,
+    _database: *const c_char,
+    accessor: *const c_char,
+) -> c_int {
+    let authorizer = &*(user_data as *const Authorizer);
+    let checked = authorizer.check(
+        action,
+        optional_str(arg1),
+        optional_str(arg2),
+        optional_str(accessor),
+    );
+    if checked.is_ok() && is_schema_change(action, optional_str(arg2)) {
+        authorizer.changes_schema.set(true);
+    }
+    match checked {
+        // SQLite skips the update hook when it empties a table all at
+        // once, but ignoring the delete makes it remove (and report)
+        // rows one by one instead.
+        Ok(()) if authorizer.capture_changes && action == ffi::SQLITE_DELETE => ffi::SQLITE_IGNORE,
+        Ok(()) => ffi::SQLITE_OK,
+        Err(denied) => {
+            if let Ok(mut first_denied) = authorizer.denied.try_borrow_mut() {
+                first_denied.get_or_insert(denied);
+            }
+            ffi::SQLITE_DENY
+        }
+    }
+}
+
+/// A row changed by the query, as (table, operation, rowid).
+type RowChange = (String, ChangeOperation, i64);
+
+/// Rows reported by SQLite's update hook. They move from `pending` to
+/// `committed` when their transaction commits, and are dropped if it
+/// rolls back.
+#[derive(Default)]
+struct ChangeCollector {
+    pending: RefCell<Vec<RowChange>>,
+    committed: RefCell<Vec<RowChange>>,
+}
+
+impl ChangeCollector {
+    /// Group the committed changes by table and operation, in the order
+    /// they first happened.
+    fn into_table_changes(self) -> Vec<TableChange> {
+        let mut changes: Vec<TableChange> = Vec::new();
+        for (table, operation, rowid) in self.committed.into_inner() {
+            let index = match changes
+                .iter()
+                .position(|change| change.table == table && change.operation == operation)
+            {
+                Some(index) => index,
+                None => {
+                    changes.push(TableChange {
+                        table,
+                        operation,
+                        row_count: 0,
+                        rowids: Vec::new(),
+                    });
+                    changes.len() - 1
+                }
+            };
+            let change = &mut changes[index];
+            change.row_count += 1;
+            if change.rowids.len() < MAX_REPORTED_ROWIDS {
+                change.rowids.push(rowid);
+            }
+        }
+        changes
+    }
+}
+
+/// The callback registered with `sqlite3_update_hook`. `user_data`
+/// points to a `ChangeCollector` that outlives the connection.
+unsafe extern "C" fn record_change(
+    user_data: *mut c_void,
+    action: c_int,
+    database: *const c_char,
+    table: *const c_char,
+    rowid: ffi::sqlite3_int64,
+) {
+    let collector = &*(user_data as *const ChangeCollector);
+    // Changes to temporary tables aren't visible outside of the query.
+    if optional_str(database) != Some("main") {
+        return;
+    }
+    let operation = match action {
+        ffi::SQLITE_INSERT => ChangeOperation::Insert,
+        ffi::SQLITE_UPDATE => ChangeOperation::Update,
+        ffi::SQLITE_DELETE => ChangeOperation::Delete,
+        _ => return,
+    };
+    if let (Some(table), Ok(mut pending)) =
+        (optional_str(table), collector.pending.try_borrow_mut())
+    {
+        pending.push((table.to_string(), operation, rowid));
+    }
+}
+
+/// The callback registered with `sqlite3_commit_hook`. Returning zero
+/// lets the commit proceed.
+unsafe extern "C" fn commit_changes(user_data: *mut c_void) -> c_int {
+    let collector = &*(user_data as *const ChangeCollector);
+    if let (Ok(mut pending), Ok(mut committed)) = (
+        collector.pending.try_borrow_mut(),
+        collector.committed.try_borrow_mut(),
+    ) {
+        committed.append(&mut pending);
+    }
+    0
+}
+
+/// The callback registered with `sqlite3_rollback_hook`.
+unsafe extern "C" fn discard_changes(user_data: *mut c_void) {
+    let collector = &*(user_data as *const ChangeCollector);
+    if let Ok(mut pending) = collector.pending.try_borrow_mut() {
+        pending.clear();
+    }
 }
 
 /// `allow_unsafe` disables features that prevent abuse but also
 /// prevent backups/snapshots. The only known use case in the codebase
 /// is for snapshots.
+///
+/// `restrictions`, when provided, are enforced with SQLite's
+/// authorizer, which rejects statements that touch anything outside of
+/// the granted tables, views, and columns when they're compiled. The
+/// authorizer also rejects schema changes in `QueryMode::ReadWriteData`.
+///
+/// Whether the query wrote to the database is told apart by the rows it
+/// changed (`sqlite3_total_changes`) and by the schema changes the
+/// authorizer allowed.
+///
+/// `capture_changes` records the rows the query changes with SQLite's
+/// update hook. The hook doesn't fire for `WITHOUT ROWID` tables or for
+/// rows replaced by `INSERT OR REPLACE`, so those changes go unreported.
+///
+/// `retain_wal` keeps writes from checkpointing the write-ahead log,
+/// either automatically or when the connection closes, so that WAL
+/// replication can ship its frames before calling `checkpoint_wal`.
 fn query_sqlite(
     path: &Path,
     query: &str,
     allow_unsafe: bool,
     query_mode: QueryMode,
-) -> Result<QueryResult, AybError> {
+    restrictions: Option<&QueryRestrictions>,
+    capture_changes: bool,
+    retain_wal: bool,
+) -> Result<QueryOutput, AybError> {
+    // Declared before the connection so that they're dropped after it.
+    let allow_schema_changes = query_mode != QueryMode::ReadWriteData;
+    let capture_changes = capture_changes && query_mode != QueryMode::ReadOnly;
+    let authorizer =
+        (restrictions.is_some() || query_mode != QueryMode::ReadOnly).then(|| Authorizer {
+            restrictions,
+            allow_schema_changes,
+            capture_changes,
+            denied: RefCell::new(None),
+            changes_schema: Cell::new(false),
+        });
+    let collector = capture_changes.then(ChangeCollector::default);
+
     // The flags below are the default `open` flags in `rusqlite`
     // except for `..READ_ONLY` and `..READ_WRITE`.
     let mut open_flags =
         rusqlite::OpenFlags::SQLITE_OPEN_URI | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX;
     open_flags |= match query_mode {
         QueryMode::ReadOnly => rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
-        QueryMode::ReadWrite => {
+        QueryMode::ReadWriteData | QueryMode::ReadWrite => {
             rusqlite::OpenFlags::SQLITE_OPEN_CREATE | rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE
         }
     };
@@ -74,7 +493,7 @@ fn query_sqlite(
     conn.pragma_update(None, "busy_timeout", 5000)?;
 
     // Configure SQLite for optimal ayb usage
-    if matches!(query_mode, QueryMode::ReadWrite) {
+    if query_mode != QueryMode::ReadOnly {
         // Enable WAL (Write-Ahead Logging) mode for better concurrency and performance.
         // This operation is idempotent and will convert non-WAL DBs to WAL ones.
         let _mode: String = conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
@@ -84,6 +503,11 @@ fn query_sqlite(
 
         // Enable foreign key constraints
         conn.pragma_update(None, "foreign_keys", true)?;
+
+        if retain_wal {
+            conn.pragma_update(None, "wal_autocheckpoint", 0)?;
+            conn.set_db_config(DbConfig::SQLITE_DBCONFIG_NO_CKPT_ON_CLOSE, true)?;
+        }
     }
 
     if !allow_unsafe {
@@ -95,7 +519,42 @@ fn query_sqlite(
         conn.db_config(DbConfig::SQLITE_DBCONFIG_DEFENSIVE)?;
     }
 
-    let mut prepared = conn.prepare(query)?;
+    if let Some(authorizer) = &authorizer {
+        // Registered after the pragmas above, which the authorizer
+        // would otherwise deny.
+        unsafe {
+            ffi::sqlite3_set_authorizer(
+                conn.handle(),
+                Some(authorize),
+                authorizer as *const Authorizer as *mut c_void,
+            );
+        }
+    }
+
+    if let Some(collector) = &collector {
+        let user_data = collector as *const ChangeCollector as *mut c_void;
+        unsafe {
+            ffi::sqlite3_update_hook(conn.handle(), Some(record_change), user_data);
+            ffi::sqlite3_commit_hook(conn.handle(), Some(commit_changes), user_data);
+            ffi::sqlite3_rollback_hook(conn.handle(), Some(discard_changes), user_data);
+        }
+    }
+
+    let mut prepared = conn.prepare(query).map_err(|err| match err {
+        rusqlite::Error::SqliteFailure(ref code, _)
+            if code.code == rusqlite::ErrorCode::AuthorizationForStatementDenied =>
+        {
+            let denied = authorizer
+                .as_ref()
+                .and_then(|authorizer| authorizer.denied.borrow_mut().take())
+                .unwrap_or_else(|| "run this statement".to_string());
+            AybError::NoObjectAccessError {
+                message: format!("Not permitted to {denied} in this database"),
+            }
+        }
+        _ => AybError::from(err),
+    })?;
+    let changes_before = unsafe { ffi::sqlite3_total_changes(conn.handle()) };
     let num_columns = prepared.column_count();
     let mut fields: Vec<String> = Vec::new();
     for column_index in 0..num_columns {
@@ -129,8 +588,205 @@ fn query_sqlite(
         }
         results.push(result);
     }
-    Ok(QueryResult {
-        fields,
-        rows: results,
+    // Finish the statement, which commits its changes unless the query
+    // left a transaction open, before reading what was committed.
+    drop(rows);
+    drop(prepared);
+    let wrote = unsafe { ffi::sqlite3_total_changes(conn.handle()) } != changes_before
+        || authorizer
+            .as_ref()
+            .is_some_and(|authorizer| authorizer.changes_schema.get());
+    Ok(QueryOutput {
+        result: QueryResult {
+            fields,
+            rows: results,
+        },
+        wrote,
+        changes: collector
+            .map(ChangeCollector::into_table_changes)
+            .unwrap_or_default(),
     })
 }
+
+/// Copy every frame in the write-ahead log of the SQLite database at
+/// `path` into the database file, and empty the log. Fails rather than
+/// leaving frames behind if another connection is using the log.
+pub fn checkpoint_wal(path: &Path) -> Result<(), AybError> {
+    let conn = rusqlite::Connection::open_with_flags(
+        path,
+        rusqlite::OpenFlags::SQLITE_OPEN_READ_WRITE | rusqlite::OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    conn.pragma_update(None, "busy_timeout", 5000)?;
+    let busy: i64 = conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| row.get(0))?;
+    if busy != 0 {
+        return Err(AybError::SnapshotError {
+            message: format!("Unable to checkpoint {}: database is busy", path.display()),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hosted_db::diff::{SchemaChangeKind, TableDiff};
+
+    #[test]
+    fn test_sqlite_captures_committed_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_changes.sqlite");
+        let run = |query: &str| {
+            query_sqlite(&path, query, false, QueryMode::ReadWrite, None, true, false)
+                .unwrap()
+                .changes
+        };
+        let change = |operation, rowids: Vec<i64>| TableChange {
+            table: "test".to_string(),
+            operation,
+            row_count: rowids.len() as u64,
+            rowids,
+        };
+
+        assert_eq!(run("CREATE TABLE test(x INTEGER);"), vec![]);
+        assert_eq!(
+            run("INSERT INTO test VALUES (1), (2), (3);"),
+            vec![change(ChangeOperation::Insert, vec![1, 2, 3])]
+        );
+        assert_eq!(
+            run("UPDATE test SET x = 4 WHERE x = 2;"),
+            vec![change(ChangeOperation::Update, vec![2])]
+        );
+        assert_eq!(run("SELECT * FROM test;"), vec![]);
+        // Emptying a table reports each row rather than skipping the
+        // update hook.
+        assert_eq!(
+            run("DELETE FROM test;"),
+            vec![change(ChangeOperation::Delete, vec![1, 2, 3])]
+        );
+    }
+
+    #[test]
+    fn test_sqlite_reports_whether_queries_wrote() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_wrote.sqlite");
+        let wrote = |query: &str| {
+            query_sqlite(
+                &path,
+                query,
+                false,
+                QueryMode::ReadWrite,
+                None,
+                false,
+                false,
+            )
+            .unwrap()
+            .wrote
+        };
+
+        assert!(wrote("CREATE TABLE test(x INTEGER);"));
+        assert!(wrote("INSERT INTO test VALUES (1), (2);"));
+        assert!(!wrote("SELECT * FROM test;"));
+        assert!(!wrote("UPDATE test SET x = 3 WHERE x = 4;"));
+        assert!(wrote("DELETE FROM test WHERE x = 1;"));
+        assert!(wrote("PRAGMA user_version = 1;"));
+        assert!(!wrote("PRAGMA user_version;"));
+    }
+
+    #[test]
+    fn test_sqlite_prepare_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test_read_only.sqlite");
+        let run = |query: &str, query_mode| {
+            query_sqlite(&path, query, false, query_mode, None, false, true)
+                .unwrap()
+                .result
+                .rows
+        };
+        run("CREATE TABLE test(x INTEGER);", QueryMode::ReadWrite);
+        run("INSERT INTO test VALUES (1), (2);", QueryMode::ReadWrite);
+        assert!(dir.path().join("test_read_only.sqlite-wal").exists());
+
+        SqliteEngine.prepare_read_only(&path).unwrap();
+        assert!(!dir.path().join("test_read_only.sqlite-wal").exists());
+        assert_eq!(
+            run("PRAGMA journal_mode;", QueryMode::ReadOnly),
+            vec![vec![Some("delete".to_string())]]
+        );
+        assert_eq!(
+            run("SELECT COUNT(*) FROM test;", QueryMode::ReadOnly),
+            vec![vec![Some("2".to_string())]]
+        );
+    }
+
+    #[test]
+    fn test_sqlite_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let (from, to) = (dir.path().join("from.sqlite"), dir.path().join("to.sqlite"));
+        let run = |query: &str| {
+            query_sqlite(&to, query, false, QueryMode::ReadWrite, None, false, false).unwrap();
+        };
+        run("CREATE TABLE people(id INTEGER PRIMARY KEY, name TEXT);");
+        run("CREATE TABLE notes(body TEXT);");
+        run("CREATE TABLE dropped(x INTEGER);");
+        run("INSERT INTO people VALUES (1, 'a'), (2, NULL);");
+        run("INSERT INTO notes VALUES ('first');");
+        run("INSERT INTO dropped VALUES (1);");
+        SqliteEngine.create_snapshot(&to, &from).unwrap();
+        run("INSERT INTO people VALUES (3, 'c');");
+        run("UPDATE people SET name = 'b' WHERE id = 2;");
+        run("DELETE FROM people WHERE id = 1;");
+        run("INSERT INTO notes VALUES ('second');");
+        run("DROP TABLE dropped;");
+        run("CREATE INDEX people_name ON people(name);");
+
+        let diff = SqliteEngine.diff(&from, &to).unwrap();
+        let changes: Vec<(&str, &str, SchemaChangeKind)> = diff
+            .schema_changes
+            .iter()
+            .map(|change| {
+                (
+                    change.object_type.as_str(),
+                    change.name.as_str(),
+                    change.change,
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("index", "people_name", SchemaChangeKind::Added),
+                ("table", "dropped", SchemaChangeKind::Removed)
+            ]
+        );
+        let key = |id: &str| vec![vec![Some(id.to_string())]];
+        assert_eq!(
+            diff.tables,
+            vec![
+                TableDiff {
+                    table: "dropped".to_string(),
+                    deleted: 1,
+                    ..Default::default()
+                },
+                TableDiff {
+                    table: "notes".to_string(),
+                    inserted: 1,
+                    ..Default::default()
+                },
+                TableDiff {
+                    table: "people".to_string(),
+                    primary_key: vec!["id".to_string()],
+                    inserted: 1,
+                    updated: 1,
+                    deleted: 1,
+                    inserted_keys: key("3"),
+                    updated_keys: key("2"),
+                    deleted_keys: key("1"),
+                },
+            ]
+        );
+        assert_eq!(
+            SqliteEngine.diff(&to, &to).unwrap(),
+            DatabaseDiff::default()
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use crate::ayb_db::models::{
-    APITokenWithDatabase, DBType, DatabasePermission, EntityType,
-    InstantiatedDatabase as PersistedDatabase, InstantiatedDatabase,
-    InstantiatedEntity as PersistedEntity,
+    APITokenWithDatabase, AuditAction, AuditLogEntryWithSlugs, DBType, DatabaseForkWithSlugs,
+    DatabaseObjectGrant, DatabasePermission, DatabaseTransferWithSlugs, EntityDatabaseSharingLevel,
+    EntityType, InstantiatedDatabase as PersistedDatabase, InstantiatedDatabase,
+    InstantiatedEntity as PersistedEntity, InstantiatedWebhook, InstantiatedWebhookDelivery,
+    OrganizationMember as PersistedOrganizationMember, OrganizationRole,
+    PublishedEndpointWithPublisher, SavedQueryWithAuthor, WebhookDeliveryStatus,
 };
 use crate::formatting::TabularFormatter;
-use crate::hosted_db::QueryMode;
+use crate::hosted_db::diff::{SchemaChange, TableDiff};
+use crate::hosted_db::{ChangeOperation, QueryMode};
 use crate::server::snapshots::models::ListSnapshotResult;
+use crate::server::snapshots::retention::RetentionPolicy;
+use chrono::{DateTime, Utc};
 use prettytable::{Cell, Row, Table};
 use serde::{Deserialize, Serialize};
 
@@ -58,6 +64,46 @@ pub struct EntityPath {
     pub entity: String,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityMemberPath {
+    pub entity: String,
+    pub member: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityTeamPath {
+    pub entity: String,
+    pub team: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityTeamMemberPath {
+    pub entity: String,
+    pub team: String,
+    pub member: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityDatabaseSavedQueryPath {
+    pub entity: String,
+    pub database: String,
+    pub saved_query: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityDatabaseEndpointPath {
+    pub entity: String,
+    pub database: String,
+    pub endpoint: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EntityDatabaseWebhookPath {
+    pub entity: String,
+    pub database: String,
+    pub webhook_id: i32,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ProfileLinkUpdate {
     pub url: String,
@@ -81,11 +127,13 @@ pub struct EntityProfile {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EntityPermissions {
     pub can_create_database: bool,
+    pub can_manage_organization: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct EntityQueryResponse {
     pub slug: String,
+    pub entity_type: String,
     pub profile: EntityProfile,
     pub databases: Vec<EntityDatabase>,
     pub permissions: EntityPermissions,
@@ -188,9 +236,76 @@ pub struct SnapshotList {
     pub snapshots: Vec<ListSnapshotResult>,
 }
 
+/// The retention policy that applies to a database, and a dry run of
+/// it: the snapshots it would prune right now.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotRetention {
+    pub max_snapshots: Option<u16>,
+    pub policy: Option<RetentionPolicy>,
+    // Whether `policy` was set on the database or the server.
+    pub source: Option<String>,
+    pub snapshots_to_prune: Vec<ListSnapshotResult>,
+}
+
+/// A database's own retention policy. Leaving every setting out
+/// returns the database to the server's policy.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotRetentionUpdate {
+    pub hourly: Option<u32>,
+    pub daily: Option<u32>,
+    pub weekly: Option<u32>,
+}
+
+/// When a database is snapshotted automatically.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotSchedule {
+    pub automatic_snapshots: bool,
+    pub interval: Option<String>,
+    // Whether the schedule was set on the database or the server.
+    pub source: String,
+}
+
+/// Changes to a database's own snapshot schedule. Leaving every
+/// setting out returns the database to the server's schedule.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotScheduleUpdate {
+    pub interval: Option<String>,
+    pub automatic_snapshots: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreatedSnapshot {
+    pub snapshot_id: String,
+    pub label: Option<String>,
+}
+
+/// Restores a database to how it was at a point in time, rather than
+/// to a snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreSnapshotQuery {
+    pub at: Option<DateTime<Utc>>,
+}
+
+/// Queries a snapshot of a database, read-only, rather than the
+/// database itself.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuerySnapshotQuery {
+    pub snapshot: Option<String>,
+}
+
+/// Compares snapshot `from` with snapshot `to`, or with the current
+/// version of the database if `to` is omitted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DiffQuery {
+    pub from: String,
+    pub to: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DatabasePermissions {
     pub permissions: Vec<DatabasePermission>,
+    #[serde(default)]
+    pub object_grants: Vec<DatabaseObjectGrant>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -200,7 +315,27 @@ pub struct DatabaseDetails {
     pub database_type: String,
     pub highest_query_access_level: Option<QueryMode>,
     pub can_manage_database: bool,
+    pub can_administer_database: bool,
     pub public_sharing_level: String,
+    pub forked_from: Option<ForkedFrom>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForkedFrom {
+    pub database: Option<String>, // entity/database, or `None` if the upstream was deleted
+    pub snapshot_id: Option<String>,
+}
+
+impl From<DatabaseForkWithSlugs> for ForkedFrom {
+    fn from(fork: DatabaseForkWithSlugs) -> Self {
+        Self {
+            database: fork
+                .upstream_entity_slug
+                .zip(fork.upstream_database_slug)
+                .map(|(entity, database)| format!("{entity}/{database}")),
+            snapshot_id: fork.upstream_snapshot_id,
+        }
+    }
 }
 
 impl TabularFormatter for Vec<ListSnapshotResult> {
@@ -209,13 +344,108 @@ impl TabularFormatter for Vec<ListSnapshotResult> {
         table.set_titles(Row::new(vec![
             Cell::new("Name"),
             Cell::new("Last modified"),
+            Cell::new("Type"),
+            Cell::new("Label"),
+            Cell::new("Database type"),
+            Cell::new("Size"),
+            Cell::new("Compressed size"),
+            Cell::new("Triggered by"),
+            Cell::new("ayb version"),
+            Cell::new("Source database"),
         ]));
 
         self.iter()
             .map(|v| {
+                let size =
+                    |size: Option<u64>| size.map(|size| size.to_string()).unwrap_or_default();
                 Row::new(vec![
                     Cell::new(&v.snapshot_id),
                     Cell::new(&v.last_modified_at.to_rfc3339()),
+                    Cell::new(&v.snapshot_type),
+                    Cell::new(v.label.as_deref().unwrap_or("")),
+                    Cell::new(v.db_type.as_deref().unwrap_or("")),
+                    Cell::new(&size(v.uncompressed_size)),
+                    Cell::new(&size(v.compressed_size)),
+                    Cell::new(v.triggered_by.as_deref().unwrap_or("")),
+                    Cell::new(v.ayb_version.as_deref().unwrap_or("")),
+                    Cell::new(v.source_database_id.as_deref().unwrap_or("")),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+impl TabularFormatter for Vec<SchemaChange> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Type"),
+            Cell::new("Name"),
+            Cell::new("Change"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.object_type),
+                    Cell::new(&v.name),
+                    Cell::new(v.change.to_str()),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+impl TabularFormatter for Vec<TableDiff> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Table"),
+            Cell::new("Primary key"),
+            Cell::new("Inserted"),
+            Cell::new("Updated"),
+            Cell::new("Deleted"),
+            Cell::new("Inserted keys"),
+            Cell::new("Updated keys"),
+            Cell::new("Deleted keys"),
+        ]));
+
+        // Single-column keys are listed as-is, and composite ones as
+        // tuples, e.g. `(1, a), (1, b)`.
+        let keys = |keys: &Vec<Vec<Option<String>>>| {
+            keys.iter()
+                .map(|key| {
+                    let values: Vec<&str> = key
+                        .iter()
+                        .map(|value| value.as_deref().unwrap_or("NULL"))
+                        .collect();
+                    match values.len() {
+                        1 => values[0].to_string(),
+                        _ => format!("({})", values.join(", ")),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.table),
+                    Cell::new(&v.primary_key.join(", ")),
+                    Cell::new(&v.inserted.to_string()),
+                    Cell::new(&v.updated.to_string()),
+                    Cell::new(&v.deleted.to_string()),
+                    Cell::new(&keys(&v.inserted_keys)),
+                    Cell::new(&keys(&v.updated_keys)),
+                    Cell::new(&keys(&v.deleted_keys)),
                 ])
             })
             .for_each(|c| {
@@ -232,10 +462,42 @@ impl TabularFormatter for Vec<DatabasePermission> {
         table.set_titles(Row::new(vec![
             Cell::new("Entity"),
             Cell::new("Sharing level"),
+            Cell::new("Source"),
         ]));
 
         self.iter()
-            .map(|v| Row::new(vec![Cell::new(&v.entity_slug), Cell::new(&v.sharing_level)]))
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.entity_slug),
+                    Cell::new(&v.sharing_level),
+                    Cell::new(&v.source),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+impl TabularFormatter for Vec<DatabaseObjectGrant> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Entity"),
+            Cell::new("Object"),
+            Cell::new("Columns"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.entity_slug),
+                    Cell::new(&v.object),
+                    Cell::new(&v.columns),
+                ])
+            })
             .for_each(|c| {
                 table.add_row(c);
             });
@@ -251,7 +513,7 @@ impl TabularFormatter for Vec<DatabasePermission> {
 pub struct APITokenInfo {
     pub short_token: String,
     pub scoped_database: Option<String>, // entity/database or None for unscoped
-    pub permission_level: Option<String>, // "read-only" or "read-write" or None
+    pub permission_level: Option<String>, // "read-only", "read-write-data", "read-write", or None
     pub app_name: Option<String>,
     pub created_at: Option<String>,
     pub expires_at: Option<String>,
@@ -319,6 +581,62 @@ impl TabularFormatter for Vec<APITokenInfo> {
     }
 }
 
+/// API response struct for a pending database ownership transfer.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DatabaseTransfer {
+    pub database: String, // entity/database, named after its current owner
+    pub to_entity: String,
+    pub former_owner_sharing_level: String,
+    pub created_at: String,
+}
+
+impl From<DatabaseTransferWithSlugs> for DatabaseTransfer {
+    fn from(transfer: DatabaseTransferWithSlugs) -> Self {
+        Self {
+            database: format!("{}/{}", transfer.from_entity_slug, transfer.database_slug),
+            to_entity: transfer.to_entity_slug,
+            former_owner_sharing_level: EntityDatabaseSharingLevel::try_from(
+                transfer.former_owner_sharing_level,
+            )
+            .map(|level| level.to_str().to_string())
+            .unwrap_or_default(),
+            created_at: transfer.created_at.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseTransferList {
+    pub transfers: Vec<DatabaseTransfer>,
+}
+
+impl TabularFormatter for Vec<DatabaseTransfer> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Database"),
+            Cell::new("Recipient"),
+            Cell::new("Former owner's access"),
+            Cell::new("Proposed"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.database),
+                    Cell::new(&v.to_entity),
+                    Cell::new(&v.former_owner_sharing_level),
+                    Cell::new(&v.created_at),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ShortTokenPath {
     pub short_token: String,
@@ -331,7 +649,7 @@ pub struct ShortTokenPath {
 pub struct OAuthAuthorizeRequest {
     pub response_type: String,         // Must be "code"
     pub redirect_uri: String,          // Where to redirect after authorization
-    pub scope: String,                 // "read-only" or "read-write"
+    pub scope: String,                 // "read-only", "read-write-data", or "read-write"
     pub state: Option<String>,         // Opaque value for CSRF protection
     pub code_challenge: String,        // PKCE: BASE64URL(SHA256(code_verifier))
     pub code_challenge_method: String, // Must be "S256"
@@ -366,7 +684,7 @@ pub struct OAuthTokenResponse {
     pub access_token: String,
     pub token_type: String,             // Always "Bearer"
     pub database: String,               // entity/database path
-    pub query_permission_level: String, // "read-only" or "read-write"
+    pub query_permission_level: String, // "read-only", "read-write-data", or "read-write"
     pub database_url: String,           // Full URL to the database API endpoint
 }
 
@@ -376,3 +694,506 @@ pub struct OAuthErrorResponse {
     pub error: String,
     pub error_description: Option<String>,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationMember {
+    pub entity: String,
+    pub role: String,
+}
+
+impl From<PersistedOrganizationMember> for OrganizationMember {
+    fn from(member: PersistedOrganizationMember) -> Self {
+        Self {
+            entity: member.member_slug,
+            role: OrganizationRole::try_from(member.role)
+                .map(|role| role.to_str().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationMembers {
+    pub member_sharing_level: String,
+    pub members: Vec<OrganizationMember>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationTeam {
+    pub team: String,
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrganizationTeams {
+    pub teams: Vec<OrganizationTeam>,
+}
+
+impl TabularFormatter for Vec<OrganizationTeam> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("Team"), Cell::new("Members")]));
+
+        self.iter()
+            .map(|v| Row::new(vec![Cell::new(&v.team), Cell::new(&v.members.join(" "))]))
+            .for_each(|c| {
+                table.add_row(c);
+            });
+        table
+    }
+}
+
+impl TabularFormatter for Vec<OrganizationMember> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![Cell::new("Entity"), Cell::new("Role")]));
+
+        self.iter()
+            .map(|v| Row::new(vec![Cell::new(&v.entity), Cell::new(&v.role)]))
+            .for_each(|c| {
+                table.add_row(c);
+            });
+        table
+    }
+}
+
+/// Query parameters for paging through an audit log, newest first.
+/// `before` is the id of the oldest entry of the previous page.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogQuery {
+    pub database: Option<String>,
+    pub before: Option<i32>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i32,
+    pub created_at: String,
+    pub actor: String,
+    pub token: Option<String>,
+    pub database: Option<String>,
+    pub action: String,
+    pub details: String,
+}
+
+impl From<AuditLogEntryWithSlugs> for AuditLogEntry {
+    fn from(entry: AuditLogEntryWithSlugs) -> Self {
+        Self {
+            id: entry.id,
+            created_at: entry.created_at.to_string(),
+            actor: entry.actor_slug,
+            token: entry.token_short_id,
+            database: entry.database_slug,
+            action: AuditAction::try_from(entry.action)
+                .map(|action| action.to_str().to_string())
+                .unwrap_or_default(),
+            details: entry.details,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditLog {
+    pub entries: Vec<AuditLogEntry>,
+}
+
+impl TabularFormatter for Vec<AuditLogEntry> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Id"),
+            Cell::new("Time"),
+            Cell::new("Actor"),
+            Cell::new("Token"),
+            Cell::new("Database"),
+            Cell::new("Action"),
+            Cell::new("Details"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.id.to_string()),
+                    Cell::new(&v.created_at),
+                    Cell::new(&v.actor),
+                    Cell::new(v.token.as_deref().unwrap_or_default()),
+                    Cell::new(v.database.as_deref().unwrap_or_default()),
+                    Cell::new(&v.action),
+                    Cell::new(&v.details),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedQueryParameterType {
+    Text,
+    Integer,
+    Real,
+}
+
+impl SavedQueryParameterType {
+    pub fn to_str(&self) -> &str {
+        match self {
+            SavedQueryParameterType::Text => "text",
+            SavedQueryParameterType::Integer => "integer",
+            SavedQueryParameterType::Real => "real",
+        }
+    }
+}
+
+/// A `:name` placeholder in a saved query. Runs that don't provide a
+/// value for it use `default`, if there is one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedQueryParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub parameter_type: SavedQueryParameterType,
+    pub default: Option<String>,
+}
+
+impl SavedQueryParameter {
+    /// Formats the parameter the way the CLI accepts it
+    /// (`name:type[=default]`).
+    pub fn to_spec(&self) -> String {
+        let mut spec = format!("{}:{}", self.name, self.parameter_type.to_str());
+        if let Some(default) = &self.default {
+            spec.push_str(&format!("={default}"));
+        }
+        spec
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedQueryUpdate {
+    pub query: String,
+    pub description: Option<String>,
+    pub parameters: Vec<SavedQueryParameter>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedQueryDetails {
+    pub name: String,
+    pub query: String,
+    pub description: Option<String>,
+    pub parameters: Vec<SavedQueryParameter>,
+    pub author: String,
+    pub updated_at: String,
+}
+
+impl TryFrom<SavedQueryWithAuthor> for SavedQueryDetails {
+    type Error = serde_json::Error;
+
+    fn try_from(saved_query: SavedQueryWithAuthor) -> Result<Self, Self::Error> {
+        Ok(Self {
+            parameters: serde_json::from_str(&saved_query.parameters)?,
+            name: saved_query.name,
+            query: saved_query.query_text,
+            description: saved_query.description,
+            author: saved_query.author_slug,
+            updated_at: saved_query.updated_at.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedQueries {
+    pub saved_queries: Vec<SavedQueryDetails>,
+}
+
+impl TabularFormatter for Vec<SavedQueryDetails> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Name"),
+            Cell::new("Description"),
+            Cell::new("Parameters"),
+            Cell::new("Author"),
+            Cell::new("Updated at"),
+            Cell::new("Query"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.name),
+                    Cell::new(v.description.as_deref().unwrap_or_default()),
+                    Cell::new(
+                        &v.parameters
+                            .iter()
+                            .map(SavedQueryParameter::to_spec)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Cell::new(&v.author),
+                    Cell::new(&v.updated_at),
+                    Cell::new(&v.query),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EndpointPublication {
+    pub saved_query: String,
+    /// How long, in seconds, clients and proxies can cache responses.
+    pub max_age: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishedEndpointDetails {
+    pub name: String,
+    pub path: String,
+    pub saved_query: String,
+    pub parameters: Vec<SavedQueryParameter>,
+    pub max_age: i32,
+    pub publisher: String,
+    pub published_at: String,
+}
+
+impl PublishedEndpointDetails {
+    pub fn from_persisted(
+        entity_slug: &str,
+        database_slug: &str,
+        endpoint: PublishedEndpointWithPublisher,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            path: format!(
+                "/v1/{entity_slug}/{database_slug}/endpoints/{}",
+                endpoint.name
+            ),
+            parameters: serde_json::from_str(&endpoint.parameters)?,
+            name: endpoint.name,
+            saved_query: endpoint.saved_query_name,
+            max_age: endpoint.max_age_seconds,
+            publisher: endpoint.publisher_slug,
+            published_at: endpoint.published_at.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublishedEndpoints {
+    pub endpoints: Vec<PublishedEndpointDetails>,
+}
+
+impl TabularFormatter for Vec<PublishedEndpointDetails> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Name"),
+            Cell::new("Path"),
+            Cell::new("Saved query"),
+            Cell::new("Parameters"),
+            Cell::new("Max age"),
+            Cell::new("Publisher"),
+            Cell::new("Published at"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.name),
+                    Cell::new(&v.path),
+                    Cell::new(&v.saved_query),
+                    Cell::new(
+                        &v.parameters
+                            .iter()
+                            .map(SavedQueryParameter::to_spec)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Cell::new(&v.max_age.to_string()),
+                    Cell::new(&v.publisher),
+                    Cell::new(&v.published_at),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+/// A new webhook. Empty `tables` or `events` match every table or
+/// operation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookCreate {
+    pub url: String,
+    #[serde(default)]
+    pub tables: Vec<String>,
+    #[serde(default)]
+    pub events: Vec<ChangeOperation>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDetails {
+    pub id: i32,
+    pub url: String,
+    pub tables: Vec<String>,
+    pub events: Vec<ChangeOperation>,
+    pub created_at: String,
+    /// Only returned when the webhook is created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+}
+
+impl WebhookDetails {
+    pub fn from_persisted(
+        webhook: InstantiatedWebhook,
+        include_secret: bool,
+    ) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            id: webhook.id,
+            url: webhook.url,
+            tables: serde_json::from_str(&webhook.tables)?,
+            events: serde_json::from_str(&webhook.events)?,
+            created_at: webhook.created_at.to_string(),
+            secret: include_secret.then_some(webhook.secret),
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Webhooks {
+    pub webhooks: Vec<WebhookDetails>,
+}
+
+impl TabularFormatter for Vec<WebhookDetails> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Id"),
+            Cell::new("URL"),
+            Cell::new("Tables"),
+            Cell::new("Events"),
+            Cell::new("Created at"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.id.to_string()),
+                    Cell::new(&v.url),
+                    Cell::new(&v.tables.join(" ")),
+                    Cell::new(
+                        &v.events
+                            .iter()
+                            .map(ChangeOperation::to_str)
+                            .collect::<Vec<_>>()
+                            .join(" "),
+                    ),
+                    Cell::new(&v.created_at),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveryDetails {
+    pub id: i32,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: String,
+    /// When the delivery will be retried, if it's still pending.
+    pub next_attempt_at: Option<String>,
+    pub delivered_at: Option<String>,
+    pub payload: String,
+}
+
+impl From<InstantiatedWebhookDelivery> for WebhookDeliveryDetails {
+    fn from(delivery: InstantiatedWebhookDelivery) -> Self {
+        let status = WebhookDeliveryStatus::try_from(delivery.status).ok();
+        Self {
+            id: delivery.id,
+            status: status
+                .map(|status| status.to_str().to_string())
+                .unwrap_or_default(),
+            attempts: delivery.attempts,
+            response_status: delivery.response_status,
+            error: delivery.error,
+            created_at: delivery.created_at.to_string(),
+            next_attempt_at: (status == Some(WebhookDeliveryStatus::Pending))
+                .then(|| delivery.next_attempt_at.to_string()),
+            delivered_at: delivery.delivered_at.map(|at| at.to_string()),
+            payload: delivery.payload,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookDeliveries {
+    pub deliveries: Vec<WebhookDeliveryDetails>,
+}
+
+impl TabularFormatter for Vec<WebhookDeliveryDetails> {
+    fn to_table(&self) -> Table {
+        let mut table = Table::new();
+        table.set_titles(Row::new(vec![
+            Cell::new("Id"),
+            Cell::new("Status"),
+            Cell::new("Attempts"),
+            Cell::new("Response"),
+            Cell::new("Error"),
+            Cell::new("Created at"),
+            Cell::new("Next attempt at"),
+            Cell::new("Delivered at"),
+            Cell::new("Payload"),
+        ]));
+
+        self.iter()
+            .map(|v| {
+                Row::new(vec![
+                    Cell::new(&v.id.to_string()),
+                    Cell::new(&v.status),
+                    Cell::new(&v.attempts.to_string()),
+                    Cell::new(
+                        &v.response_status
+                            .map(|status| status.to_string())
+                            .unwrap_or_default(),
+                    ),
+                    Cell::new(v.error.as_deref().unwrap_or_default()),
+                    Cell::new(&v.created_at),
+                    Cell::new(v.next_attempt_at.as_deref().unwrap_or_default()),
+                    Cell::new(v.delivered_at.as_deref().unwrap_or_default()),
+                    Cell::new(&v.payload),
+                ])
+            })
+            .for_each(|c| {
+                table.add_row(c);
+            });
+
+        table
+    }
+}
+
+/// Limits a change subscription to a comma-separated list of tables.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeSubscriptionQuery {
+    pub tables: Option<String>,
+}
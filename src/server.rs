@@ -1,6 +1,10 @@
 pub mod api_endpoints;
+pub mod audit_log;
+pub mod change_feed;
 pub mod config;
 pub mod permissions;
+pub mod published_endpoints;
+pub mod saved_queries;
 pub mod server_runner;
 pub mod snapshots;
 pub mod tokens;
@@ -8,3 +12,4 @@ pub mod ui_endpoints;
 pub mod url_verification;
 pub mod utils;
 pub mod validation;
+pub mod webhooks;
@@ -0,0 +1,73 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::http::structs::{Database as APIDatabase, EntityDatabasePath};
+use crate::server::api_endpoints::rename_database::move_database;
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_accept_database_transfer;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{post, web};
+
+#[post(
+    "/{entity}/{database}/accept_transfer",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn accept_database_transfer(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<APIDatabase>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let transfer = ayb_db
+        .get_database_transfer(database.id)
+        .await?
+        .ok_or_else(|| AybError::Other {
+            message: format!("No pending transfer for database {entity_slug}/{database_slug}"),
+        })?;
+
+    if !can_accept_database_transfer(&authenticated_entity, &transfer, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't accept the transfer of database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    // Moving files onto a database the recipient already has would
+    // replace it, so its namespace is checked before anything moves.
+    let recipient = ayb_db.get_entity_by_id(transfer.to_entity_id).await?;
+    match ayb_db.get_database(&recipient.slug, database_slug).await {
+        Ok(_) => {
+            return Err(AybError::Other {
+                message: "Recipient already has a database with the same name".to_string(),
+            })
+        }
+        Err(AybError::RecordNotFound { .. }) => {}
+        Err(err) => return Err(err),
+    }
+
+    // Ownership and permissions are re-keyed in one transaction once
+    // the files have moved.
+    let transferred_database = move_database(
+        entity_slug,
+        database_slug,
+        &recipient.slug,
+        database_slug,
+        &ayb_config,
+        &daemon_registry,
+        ayb_db.complete_database_transfer(&transfer),
+    )
+    .await?;
+
+    Ok(web::Json(APIDatabase::from_persisted(
+        &recipient,
+        &transferred_database,
+    )))
+}
@@ -0,0 +1,55 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::EntityTeamPath;
+use crate::server::permissions::can_manage_organization;
+use crate::server::utils::{get_lowercased_header, unwrap_authenticated_entity};
+use actix_web::{post, web, HttpRequest, HttpResponse};
+
+#[post(
+    "/entity/{entity}/teams/{team}/members",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn add_team_member(
+    path: web::Path<EntityTeamPath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<HttpResponse, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let member = ayb_db
+        .get_entity_by_slug(&get_lowercased_header(&req, "entity-for-membership")?)
+        .await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_organization(&authenticated_entity, &organization, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage teams of {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+
+    let team = ayb_db
+        .get_team(organization.id, &path.team.to_lowercase())
+        .await?;
+    // Teams are made up of the organization's members, so leaving the
+    // organization is enough to lose any access granted through them.
+    if ayb_db
+        .get_organization_membership(organization.id, member.id)
+        .await?
+        .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!("{} is not a member of {}", member.slug, organization_slug),
+        });
+    }
+
+    ayb_db
+        .update_or_create_team_membership(team.id, member.id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
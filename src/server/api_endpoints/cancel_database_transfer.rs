@@ -0,0 +1,50 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabasePath};
+use crate::server::permissions::{can_accept_database_transfer, can_administer_database};
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/{entity}/{database}/transfer",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn cancel_database_transfer(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let transfer = ayb_db
+        .get_database_transfer(database.id)
+        .await?
+        .ok_or_else(|| AybError::Other {
+            message: format!("No pending transfer for database {entity_slug}/{database_slug}"),
+        })?;
+
+    // Either side can back out: the owner cancels, the recipient declines.
+    if can_administer_database(
+        &authenticated_entity,
+        &database,
+        api_token.as_deref(),
+        &ayb_db,
+    )
+    .await?
+        || can_accept_database_transfer(&authenticated_entity, &transfer, &ayb_db).await?
+    {
+        ayb_db.delete_database_transfer(database.id).await?;
+        Ok(web::Json(EmptyResponse {}))
+    } else {
+        Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't cancel the transfer of database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        })
+    }
+}
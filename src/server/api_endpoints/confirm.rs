@@ -1,10 +1,11 @@
 use crate::ayb_db::db_interfaces::AybDb;
 use crate::ayb_db::models::{
-    AuthenticationMethod, AuthenticationMethodStatus, AuthenticationMethodType, Entity,
-    InstantiatedAuthenticationMethod,
+    AuditAction, AuthenticationMethod, AuthenticationMethodStatus, AuthenticationMethodType,
+    Entity, InstantiatedAuthenticationMethod,
 };
 use crate::error::AybError;
 use crate::http::structs::APIToken as APIAPIToken;
+use crate::server::audit_log::record_audit_event;
 use crate::server::config::AybConfig;
 use crate::server::tokens::{decrypt_auth_token, generate_api_token};
 use crate::server::utils::get_required_header;
@@ -69,6 +70,16 @@ async fn confirm(
 
     let (api_token, token_string) = generate_api_token(created_entity.id, None)?;
     let _ = ayb_db.create_api_token(&api_token).await?;
+    record_audit_event(
+        &ayb_db,
+        created_entity.id,
+        None,
+        &created_entity,
+        None,
+        AuditAction::CreateToken,
+        format!("Created token {} by logging in", api_token.short_token),
+    )
+    .await?;
     let returned_token = APIAPIToken {
         entity: created_entity.slug,
         token: token_string,
@@ -1,5 +1,7 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::{DBType, Database, InstantiatedEntity, PublicSharingLevel};
+use crate::ayb_db::models::{
+    APIToken, AuditAction, DBType, Database, InstantiatedEntity, PublicSharingLevel,
+};
 use std::str::FromStr;
 
 use crate::error::AybError;
@@ -9,6 +11,7 @@ use crate::hosted_db::paths::{
     instantiated_new_database_path, pathbuf_to_parent, set_current_database_and_clean_up,
 };
 use crate::http::structs::{Database as APIDatabase, EntityDatabasePath};
+use crate::server::audit_log::record_audit_event;
 use crate::server::config::AybConfig;
 use crate::server::permissions::can_create_database;
 use crate::server::utils::{get_required_header, unwrap_authenticated_entity};
@@ -26,6 +29,7 @@ async fn create_database(
     ayb_config: web::Data<AybConfig>,
     daemon_registry: web::Data<DaemonRegistry>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<HttpResponse, AybError> {
     let entity_slug = &path.entity;
     validate_database_slug(&path.database)?;
@@ -40,11 +44,25 @@ async fn create_database(
         public_sharing_level: PublicSharingLevel::from_str(&public_sharing_level)? as i16,
     };
     let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
-    if can_create_database(&authenticated_entity, &entity) {
+    if can_create_database(&authenticated_entity, &entity, &ayb_db).await? {
         let created_database = ayb_db.create_database(&database).await?;
         let db_path =
             instantiated_new_database_path(entity_slug, &path.database, &ayb_config.data_path)?;
         set_current_database_and_clean_up(&pathbuf_to_parent(&db_path)?, &daemon_registry).await?;
+        record_audit_event(
+            &ayb_db,
+            entity.id,
+            Some(&created_database),
+            &authenticated_entity,
+            api_token.as_deref(),
+            AuditAction::CreateDatabase,
+            format!(
+                "Created {} database with public sharing level {}",
+                db_type.to_str(),
+                public_sharing_level
+            ),
+        )
+        .await?;
         Ok(HttpResponse::Created().json(APIDatabase::from_persisted(&entity, &created_database)))
     } else {
         Err(AybError::Other {
@@ -0,0 +1,64 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{CreatedSnapshot, EntityDatabasePath};
+use crate::server::audit_log::record_audit_event;
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_manage_database;
+use crate::server::snapshots::execution::create_manual_snapshot;
+use crate::server::utils::{get_optional_header, unwrap_authenticated_entity};
+use actix_web::{post, web, HttpRequest, HttpResponse};
+
+#[post(
+    "/{entity}/{database}/create_snapshot",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn create_snapshot(
+    path: web::Path<EntityDatabasePath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let label = get_optional_header(&req, "snapshot-label")?
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty());
+    let snapshot_id = create_manual_snapshot(
+        &ayb_config,
+        &database,
+        entity_slug,
+        label.clone(),
+        &authenticated_entity.slug,
+    )
+    .await?;
+    let details = match &label {
+        Some(label) => format!("Created snapshot {snapshot_id} ({label})"),
+        None => format!("Created snapshot {snapshot_id}"),
+    };
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::CreateSnapshot,
+        details,
+    )
+    .await?;
+    Ok(HttpResponse::Created().json(CreatedSnapshot { snapshot_id, label }))
+}
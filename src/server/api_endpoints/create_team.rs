@@ -0,0 +1,42 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{InstantiatedEntity, Team};
+use crate::error::AybError;
+use crate::http::structs::EntityTeamPath;
+use crate::server::permissions::can_manage_organization;
+use crate::server::utils::unwrap_authenticated_entity;
+use crate::server::validation::validate_team_slug;
+use actix_web::{post, web, HttpResponse};
+
+#[post(
+    "/entity/{entity}/teams/{team}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn create_team(
+    path: web::Path<EntityTeamPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<HttpResponse, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let team_slug = &path.team.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_organization(&authenticated_entity, &organization, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage teams of {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+    validate_team_slug(team_slug)?;
+
+    ayb_db
+        .create_team(&Team {
+            organization_id: organization.id,
+            slug: team_slug.to_string(),
+        })
+        .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
@@ -0,0 +1,71 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity, Webhook};
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabasePath, WebhookCreate, WebhookDetails};
+use crate::server::audit_log::record_audit_event;
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use crate::server::webhooks::{
+    allowed_webhook_hosts, generate_webhook_secret, validate_webhook_url,
+};
+use actix_web::{post, web};
+
+#[post(
+    "/{entity}/{database}/webhooks",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn create_webhook(
+    path: web::Path<EntityDatabasePath>,
+    body: web::Json<WebhookCreate>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<WebhookDetails>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    // Webhooks see every change to the tables they subscribe to, so
+    // only the database's managers can create them.
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't create webhooks for {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    validate_webhook_url(&body.url, &allowed_webhook_hosts(&ayb_config)).await?;
+    if body.tables.iter().any(|table| table.trim().is_empty()) {
+        return Err(AybError::Other {
+            message: "Webhook table names can't be empty".to_string(),
+        });
+    }
+
+    let webhook = ayb_db
+        .create_webhook(&Webhook {
+            database_id: database.id,
+            url: body.url.clone(),
+            tables: serde_json::to_string(&body.tables)?,
+            events: serde_json::to_string(&body.events)?,
+            secret: generate_webhook_secret(),
+            creator_id: authenticated_entity.id,
+        })
+        .await?;
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::CreateWebhook,
+        format!("Created webhook {} for {}", webhook.id, webhook.url),
+    )
+    .await?;
+
+    // The secret is only ever shown here, when the webhook is created.
+    Ok(web::Json(WebhookDetails::from_persisted(webhook, true)?))
+}
@@ -1,10 +1,10 @@
 use crate::ayb_db::db_interfaces::AybDb;
 use crate::ayb_db::models::{DBType, InstantiatedEntity, PublicSharingLevel};
 use crate::error::AybError;
-use crate::http::structs::{DatabaseDetails, EntityDatabasePath};
+use crate::http::structs::{DatabaseDetails, EntityDatabasePath, ForkedFrom};
 use crate::server::permissions::{
-    can_discover_database, can_manage_database, highest_query_access_level,
-    is_publicly_discoverable,
+    can_administer_database, can_discover_database, can_manage_database,
+    highest_query_access_level, is_publicly_discoverable,
 };
 use actix_web::{get, web, HttpResponse, Result};
 
@@ -31,26 +31,35 @@ pub async fn database_details(
     };
 
     if discoverable {
-        let (can_manage, access_level) = match authenticated_entity.as_ref() {
+        let (can_manage, can_administer, access_level) = match authenticated_entity.as_ref() {
             Some(entity) => {
                 let can_manage = can_manage_database(entity, &database, &ayb_db).await?;
+                let can_administer =
+                    can_administer_database(entity, &database, None, &ayb_db).await?;
                 let access_level =
                     highest_query_access_level(entity, &database, None, &ayb_db).await?;
-                (can_manage, access_level)
+                (can_manage, can_administer, access_level)
             }
-            None => (false, None),
+            None => (false, false, None),
         };
 
+        let forked_from = ayb_db
+            .get_database_fork(database.id)
+            .await?
+            .map(ForkedFrom::from);
+
         let details = DatabaseDetails {
             entity_slug: entity_slug.to_string(),
             database_slug: database.slug,
             database_type: DBType::try_from(database.db_type).unwrap().to_str().into(),
             highest_query_access_level: access_level,
             can_manage_database: can_manage,
+            can_administer_database: can_administer,
             public_sharing_level: PublicSharingLevel::try_from(database.public_sharing_level)
                 .unwrap()
                 .to_str()
                 .into(),
+            forked_from,
         };
 
         Ok(HttpResponse::Ok().json(details))
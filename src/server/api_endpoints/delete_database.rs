@@ -0,0 +1,81 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity};
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::paths::remove_database_directory;
+use crate::http::structs::{EmptyResponse, EntityDatabasePath};
+use crate::server::audit_log::record_audit_event;
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_administer_database;
+use crate::server::snapshots::storage::SnapshotStorage;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/{entity}/{database}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn delete_database(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_administer_database(
+        &authenticated_entity,
+        &database,
+        api_token.as_deref(),
+        &ayb_db,
+    )
+    .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't delete database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    // The snapshots and files are removed before the metadata, so
+    // that if removing them fails, the database is still there for the
+    // delete to be retried. A deleted database's slug can be reused, so
+    // its snapshots are deleted rather than left around to be restored
+    // into an unrelated database.
+    if let Some(ref snapshot_config) = ayb_config.snapshots {
+        SnapshotStorage::new(snapshot_config)
+            .await?
+            .delete_all_snapshots(entity_slug, database_slug)
+            .await?;
+    }
+    remove_database_directory(
+        entity_slug,
+        database_slug,
+        &ayb_config.data_path,
+        &daemon_registry,
+    )
+    .await?;
+    // The deletion is recorded before the metadata is removed so that,
+    // like the database's other entries, it's detached from the
+    // database's id when the database is deleted.
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::DeleteDatabase,
+        "Deleted database".to_string(),
+    )
+    .await?;
+    ayb_db.delete_database(database.id).await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
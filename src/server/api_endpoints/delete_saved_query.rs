@@ -0,0 +1,59 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabaseSavedQueryPath};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::{can_modify_saved_query, highest_query_access_level};
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/{entity}/{database}/saved_queries/{saved_query}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn delete_saved_query(
+    path: web::Path<EntityDatabaseSavedQueryPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let name = &path.saved_query;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let token = api_token.as_deref();
+
+    if highest_query_access_level(&authenticated_entity, &database, token, &ayb_db)
+        .await?
+        .is_none()
+        || !can_modify_saved_query(
+            &authenticated_entity,
+            &database,
+            &ayb_db.get_saved_query(database.id, name).await?,
+            &ayb_db,
+        )
+        .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't delete saved query {} on {}/{}",
+                authenticated_entity.slug, name, entity_slug, database_slug
+            ),
+        });
+    }
+
+    ayb_db.delete_saved_query(database.id, name).await?;
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        token,
+        AuditAction::DeleteSavedQuery,
+        format!("Deleted saved query {name}"),
+    )
+    .await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
@@ -0,0 +1,38 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityTeamPath};
+use crate::server::permissions::can_manage_organization;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/entity/{entity}/teams/{team}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn delete_team(
+    path: web::Path<EntityTeamPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_organization(&authenticated_entity, &organization, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage teams of {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+
+    // Deleting a team also revokes the access it granted its members.
+    let team = ayb_db
+        .get_team(organization.id, &path.team.to_lowercase())
+        .await?;
+    ayb_db.delete_team(team.id).await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
@@ -0,0 +1,54 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabaseWebhookPath};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/{entity}/{database}/webhooks/{webhook_id}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn delete_webhook(
+    path: web::Path<EntityDatabaseWebhookPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't delete webhooks of {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let webhook = ayb_db.get_webhook(path.webhook_id).await?;
+    if webhook.database_id != database.id {
+        return Err(AybError::RecordNotFound {
+            id: path.webhook_id.to_string(),
+            record_type: "webhook".into(),
+        });
+    }
+    ayb_db.delete_webhook(webhook.id).await?;
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::DeleteWebhook,
+        format!("Deleted webhook {} for {}", webhook.id, webhook.url),
+    )
+    .await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
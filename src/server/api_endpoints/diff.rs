@@ -0,0 +1,122 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, DBType, InstantiatedEntity};
+use crate::error::AybError;
+use crate::hosted_db::diff::DatabaseDiff;
+use crate::hosted_db::engine_for;
+use crate::hosted_db::paths::{current_database_path, database_snapshot_path};
+use crate::http::structs::{DiffQuery, EntityDatabasePath};
+use crate::server::config::AybConfig;
+use crate::server::permissions::{
+    can_manage_database, can_token_access_database, highest_query_access_level,
+};
+use crate::server::snapshots::cache::SnapshotCache;
+use crate::server::snapshots::storage::SnapshotStorage;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+use std::collections::HashMap;
+use std::fs;
+
+#[get(
+    "/{entity}/{database}/diff",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn diff(
+    path: web::Path<EntityDatabasePath>,
+    query: web::Query<DiffQuery>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    snapshot_cache: web::Data<SnapshotCache>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<DatabaseDiff>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    // A diff shows the rows that changed, so tokens limit it the way
+    // they limit snapshot queries.
+    let token_can_query = match api_token.as_deref() {
+        Some(token) => can_token_access_database(token, &database),
+        None => true,
+    };
+    if !token_can_query
+        || highest_query_access_level(
+            &authenticated_entity,
+            &database,
+            api_token.as_deref(),
+            &ayb_db,
+        )
+        .await?
+        .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't query database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    let Some(ref snapshot_config) = ayb_config.snapshots else {
+        return Err(AybError::Other {
+            message: "Snapshots aren't configured on this server".to_string(),
+        });
+    };
+    let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
+    let db_type = DBType::try_from(database.db_type)?;
+    // Snapshots are fetched in a consistent order so that two diffs of
+    // the same pair can't each wait on the other's.
+    let mut snapshot_ids: Vec<&str> = [Some(query.from.as_str()), query.to.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect();
+    snapshot_ids.sort();
+    snapshot_ids.dedup();
+    let mut snapshots = HashMap::new();
+    for snapshot_id in snapshot_ids {
+        let snapshot = snapshot_cache
+            .fetch(
+                &snapshot_storage,
+                &ayb_config.data_path,
+                entity_slug,
+                database_slug,
+                snapshot_id,
+                &db_type,
+            )
+            .await?;
+        snapshots.insert(snapshot_id, snapshot);
+    }
+
+    let from_path = snapshots[query.from.as_str()].db_path();
+    let diff = match query.to.as_deref() {
+        Some(to) if to == query.from => DatabaseDiff::default(),
+        Some(to) => engine_for(&db_type).diff(&from_path, &snapshots[to].db_path())?,
+        None => {
+            // Compare against a copy of the current version, which
+            // stays put while it's compared.
+            let db_path = current_database_path(entity_slug, database_slug, &ayb_config.data_path)?;
+            if !db_path.exists() {
+                return Err(AybError::Other {
+                    message: format!("Database {entity_slug}/{database_slug} has no data yet"),
+                });
+            }
+            let copy_directory =
+                database_snapshot_path(entity_slug, database_slug, &ayb_config.data_path)?;
+            let copy_path = copy_directory.join(database_slug);
+            let diff = engine_for(&db_type)
+                .create_snapshot(&db_path, &copy_path)
+                .and_then(|_| engine_for(&db_type).diff(&from_path, &copy_path));
+            fs::remove_dir_all(copy_directory)?;
+            diff?
+        }
+    };
+    Ok(web::Json(diff))
+}
@@ -1,11 +1,11 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::InstantiatedEntity;
+use crate::ayb_db::models::{EntityType, InstantiatedEntity};
 use crate::error::AybError;
 use crate::http::structs::{
     EntityPath, EntityPermissions, EntityProfile, EntityProfileLink, EntityQueryResponse,
 };
 use crate::server::permissions::{
-    can_create_database, can_discover_database, is_publicly_discoverable,
+    can_create_database, can_discover_database, can_manage_organization, is_publicly_discoverable,
 };
 use actix_web::{get, web};
 
@@ -42,13 +42,19 @@ pub async fn entity_details(
             .collect()
     });
 
-    let can_create = match authenticated_entity.as_ref() {
-        Some(entity) => can_create_database(entity, &desired_entity),
-        None => false,
+    let (can_create, can_manage) = match authenticated_entity.as_ref() {
+        Some(entity) => (
+            can_create_database(entity, &desired_entity, &ayb_db).await?,
+            can_manage_organization(entity, &desired_entity, &ayb_db).await?,
+        ),
+        None => (false, false),
     };
 
     Ok(web::Json(EntityQueryResponse {
         slug: entity_slug.to_string(),
+        entity_type: EntityType::try_from(desired_entity.entity_type)?
+            .to_str()
+            .to_string(),
         profile: EntityProfile {
             display_name: desired_entity.display_name,
             description: desired_entity.description,
@@ -59,6 +65,7 @@ pub async fn entity_details(
         databases,
         permissions: EntityPermissions {
             can_create_database: can_create,
+            can_manage_organization: can_manage,
         },
     }))
 }
@@ -0,0 +1,151 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{
+    APIToken, AuditAction, DBType, Database, InstantiatedEntity, PublicSharingLevel,
+};
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::engine_for;
+use crate::hosted_db::paths::{
+    current_database_path, database_snapshot_path, install_database_files,
+    instantiated_new_database_path, pathbuf_to_parent, set_current_database_and_clean_up,
+};
+use crate::http::structs::{Database as APIDatabase, EntityDatabasePath};
+use crate::server::audit_log::record_audit_event;
+use crate::server::config::AybConfig;
+use crate::server::permissions::{can_create_database, can_discover_database, query_restrictions};
+use crate::server::snapshots::hashes::hash_db_directory;
+use crate::server::utils::{get_optional_header, unwrap_authenticated_entity};
+use crate::server::validation::validate_database_slug;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use std::fs;
+
+#[post(
+    "/{entity}/{database}/fork",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn fork_database(
+    path: web::Path<EntityDatabasePath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let upstream = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    // By default, the fork keeps its upstream's name and lands in the
+    // authenticated entity's namespace.
+    let fork_entity = match get_optional_header(&req, "entity-for-fork")? {
+        Some(slug) => ayb_db.get_entity_by_slug(&slug.to_lowercase()).await?,
+        None => authenticated_entity.clone(),
+    };
+    let fork_slug =
+        get_optional_header(&req, "database-for-fork")?.unwrap_or(database_slug.to_string());
+    validate_database_slug(&fork_slug)?;
+
+    if !can_discover_database(&authenticated_entity, &upstream, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't fork database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    // A fork is a copy of every table, so an entity whose access is
+    // limited to some tables or columns can't fork.
+    if query_restrictions(&authenticated_entity, &upstream, &ayb_db)
+        .await?
+        .is_some()
+    {
+        return Err(AybError::NoObjectAccessError {
+            message: format!(
+                "Authenticated entity {} can only access some tables of {}/{}, so it can't fork it",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    if !can_create_database(&authenticated_entity, &fork_entity, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't create a database for entity {}",
+                authenticated_entity.slug, fork_entity.slug
+            ),
+        });
+    }
+
+    // Copy the upstream before recording the fork so the recorded
+    // snapshot id describes exactly the files the fork starts from. A
+    // database that was never written has no file to copy, and its
+    // fork starts out empty as well.
+    let db_type = DBType::try_from(upstream.db_type)?;
+    let upstream_path = current_database_path(entity_slug, database_slug, &ayb_config.data_path)?;
+    let copy_directory = if upstream_path.exists() {
+        let copy_directory =
+            database_snapshot_path(entity_slug, database_slug, &ayb_config.data_path)?;
+        engine_for(&db_type).create_snapshot(&upstream_path, &copy_directory.join(&fork_slug))?;
+        Some(copy_directory)
+    } else {
+        None
+    };
+    let snapshot_id = copy_directory
+        .as_deref()
+        .map(hash_db_directory)
+        .transpose()?;
+
+    let fork = Database {
+        entity_id: fork_entity.id,
+        slug: fork_slug.clone(),
+        db_type: upstream.db_type,
+        public_sharing_level: PublicSharingLevel::NoAccess as i16,
+    };
+    let created_database = match ayb_db
+        .create_forked_database(&fork, upstream.id, snapshot_id.as_deref())
+        .await
+    {
+        Ok(created_database) => created_database,
+        Err(err) => {
+            if let Some(ref copy_directory) = copy_directory {
+                fs::remove_dir_all(copy_directory)?;
+            }
+            return Err(err);
+        }
+    };
+
+    match copy_directory {
+        Some(copy_directory) => {
+            install_database_files(
+                &copy_directory,
+                &fork_entity.slug,
+                &fork_slug,
+                &ayb_config.data_path,
+                &daemon_registry,
+            )
+            .await?;
+            fs::remove_dir_all(copy_directory)?;
+        }
+        None => {
+            let db_path = instantiated_new_database_path(
+                &fork_entity.slug,
+                &fork_slug,
+                &ayb_config.data_path,
+            )?;
+            set_current_database_and_clean_up(&pathbuf_to_parent(&db_path)?, &daemon_registry)
+                .await?;
+        }
+    }
+    record_audit_event(
+        &ayb_db,
+        fork_entity.id,
+        Some(&created_database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::CreateDatabase,
+        format!("Forked from {entity_slug}/{database_slug}"),
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(APIDatabase::from_persisted(&fork_entity, &created_database)))
+}
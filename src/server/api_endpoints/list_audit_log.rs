@@ -0,0 +1,72 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{AuditLogFilter, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{AuditLog, AuditLogEntry, AuditLogQuery, EntityPath};
+use crate::server::permissions::can_view_audit_log;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 1000;
+
+#[get(
+    "/entity/{entity}/audit_log",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_audit_log(
+    path: web::Path<EntityPath>,
+    query: web::Query<AuditLogQuery>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<AuditLog>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let entity = ayb_db.get_entity_by_slug(entity_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    // Entries outlive the databases they describe, so a database that
+    // no longer exists can still be filtered on by those who act for
+    // its former owner.
+    let database = match &query.database {
+        Some(database_slug) => match ayb_db.get_database(entity_slug, database_slug).await {
+            Ok(database) => Some(database),
+            Err(AybError::RecordNotFound { .. }) => None,
+            Err(err) => return Err(err),
+        },
+        None => None,
+    };
+
+    if !can_view_audit_log(&authenticated_entity, &entity, database.as_ref(), &ayb_db).await? {
+        return Err(AybError::Other {
+            message: match &query.database {
+                Some(database_slug) => format!(
+                    "Authenticated entity {} can't view the audit log of {}/{}",
+                    authenticated_entity.slug, entity_slug, database_slug
+                ),
+                None => format!(
+                    "Authenticated entity {} can't view the audit log of {}",
+                    authenticated_entity.slug, entity_slug
+                ),
+            },
+        });
+    }
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    // A database's entries are found by its id, so that they include
+    // ones from before it was renamed or transferred, and not ones
+    // about a deleted database that had the same slug.
+    let filter = match (&database, &query.database) {
+        (Some(database), _) => AuditLogFilter::Database(database.id),
+        (None, Some(database_slug)) => AuditLogFilter::DeletedDatabase(database_slug),
+        (None, None) => AuditLogFilter::Entity,
+    };
+    let entries = ayb_db
+        .list_audit_log_entries(entity.id, filter, query.before, limit)
+        .await?
+        .into_iter()
+        .map(AuditLogEntry::from)
+        .collect();
+
+    Ok(web::Json(AuditLog { entries }))
+}
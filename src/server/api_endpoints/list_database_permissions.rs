@@ -35,6 +35,7 @@ async fn list_database_permissions(
 
     let permissions = DatabasePermissions {
         permissions: permissions_list,
+        object_grants: ayb_db.list_database_object_grants(&database).await?,
     };
 
     Ok(HttpResponse::Ok().json(permissions))
@@ -0,0 +1,26 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{DatabaseTransfer, DatabaseTransferList};
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/transfers",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_database_transfers(
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<DatabaseTransferList>, AybError> {
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    let transfers = ayb_db
+        .list_database_transfers(&authenticated_entity)
+        .await?
+        .into_iter()
+        .map(DatabaseTransfer::from)
+        .collect();
+
+    Ok(web::Json(DatabaseTransferList { transfers }))
+}
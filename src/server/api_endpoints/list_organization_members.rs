@@ -0,0 +1,51 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{EntityDatabaseSharingLevel, EntityType, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EntityPath, OrganizationMember, OrganizationMembers};
+use crate::server::permissions::organization_role;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/entity/{entity}/members",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_organization_members(
+    path: web::Path<EntityPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<OrganizationMembers>, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    // Any member can see who else belongs to the organization.
+    if EntityType::try_from(organization.entity_type)? != EntityType::Organization
+        || organization_role(&authenticated_entity, organization.id, &ayb_db)
+            .await?
+            .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't list members of {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+
+    let members = ayb_db
+        .list_organization_members(&organization)
+        .await?
+        .into_iter()
+        .map(OrganizationMember::from)
+        .collect();
+
+    Ok(web::Json(OrganizationMembers {
+        member_sharing_level: EntityDatabaseSharingLevel::try_from(
+            organization.member_sharing_level,
+        )?
+        .to_str()
+        .to_string(),
+        members,
+    }))
+}
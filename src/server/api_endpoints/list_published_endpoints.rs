@@ -0,0 +1,42 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabasePath, PublishedEndpointDetails, PublishedEndpoints};
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/{entity}/{database}/endpoints",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_published_endpoints(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<PublishedEndpoints>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't list endpoints of {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let endpoints = ayb_db
+        .list_published_endpoints(database.id)
+        .await?
+        .into_iter()
+        .map(|endpoint| {
+            PublishedEndpointDetails::from_persisted(entity_slug, &database.slug, endpoint)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(PublishedEndpoints { endpoints }))
+}
@@ -0,0 +1,49 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabasePath, SavedQueries, SavedQueryDetails};
+use crate::server::permissions::highest_query_access_level;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/{entity}/{database}/saved_queries",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_saved_queries(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<SavedQueries>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if highest_query_access_level(
+        &authenticated_entity,
+        &database,
+        api_token.as_deref(),
+        &ayb_db,
+    )
+    .await?
+    .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't list saved queries of {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let saved_queries = ayb_db
+        .list_saved_queries(database.id)
+        .await?
+        .into_iter()
+        .map(SavedQueryDetails::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(web::Json(SavedQueries { saved_queries }))
+}
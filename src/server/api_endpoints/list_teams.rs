@@ -0,0 +1,44 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{EntityType, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{EntityPath, OrganizationTeam, OrganizationTeams};
+use crate::server::permissions::organization_role;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/entity/{entity}/teams",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_teams(
+    path: web::Path<EntityPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<OrganizationTeams>, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if EntityType::try_from(organization.entity_type)? != EntityType::Organization
+        || organization_role(&authenticated_entity, organization.id, &ayb_db)
+            .await?
+            .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't list teams of {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+
+    let mut teams = Vec::new();
+    for team in ayb_db.list_teams(&organization).await? {
+        teams.push(OrganizationTeam {
+            members: ayb_db.list_team_members(team.id).await?,
+            team: team.slug,
+        });
+    }
+
+    Ok(web::Json(OrganizationTeams { teams }))
+}
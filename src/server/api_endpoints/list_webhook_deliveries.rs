@@ -0,0 +1,58 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{
+    EntityDatabaseWebhookPath, WebhookDeliveries, WebhookDeliveryDetails, WebhookDeliveryQuery,
+};
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 1000;
+
+/// The webhook's most recent deliveries, newest first.
+#[get(
+    "/{entity}/{database}/webhooks/{webhook_id}/deliveries",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn list_webhook_deliveries(
+    path: web::Path<EntityDatabaseWebhookPath>,
+    query: web::Query<WebhookDeliveryQuery>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<WebhookDeliveries>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't list webhook deliveries of {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let webhook = ayb_db.get_webhook(path.webhook_id).await?;
+    if webhook.database_id != database.id {
+        return Err(AybError::RecordNotFound {
+            id: path.webhook_id.to_string(),
+            record_type: "webhook".into(),
+        });
+    }
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+    let deliveries = ayb_db
+        .list_webhook_deliveries(webhook.id, limit)
+        .await?
+        .into_iter()
+        .map(WebhookDeliveryDetails::from)
+        .collect();
+
+    Ok(web::Json(WebhookDeliveries { deliveries }))
+}
@@ -1,35 +1,107 @@
+mod accept_database_transfer;
+mod add_team_member;
+mod cancel_database_transfer;
 mod confirm;
 mod create_database;
+mod create_snapshot;
+mod create_team;
+mod create_webhook;
 mod database_details;
+mod delete_database;
+mod delete_saved_query;
+mod delete_team;
+mod delete_webhook;
+mod diff;
 mod entity_details;
+mod fork_database;
 mod health;
+mod list_audit_log;
 mod list_database_permissions;
+mod list_database_transfers;
+mod list_organization_members;
+mod list_published_endpoints;
+mod list_saved_queries;
 mod list_snapshots;
+mod list_teams;
 mod list_tokens;
+mod list_webhook_deliveries;
+mod list_webhooks;
 mod log_in;
 mod oauth_token;
+mod publish_endpoint;
 mod query;
 mod register;
+mod remove_organization_member;
+mod remove_team_member;
+mod rename_database;
 mod restore_snapshot;
 mod revoke_token;
+mod run_published_endpoint;
+mod run_saved_query;
+mod save_query;
 mod share;
+mod snapshot_retention;
+mod snapshot_schedule;
+mod subscribe_to_changes;
+mod transfer_database;
+mod unpublish_endpoint;
 mod update_database;
+mod update_member_sharing_level;
+mod update_organization_member;
 mod update_profile;
+mod update_snapshot_retention;
+mod update_snapshot_schedule;
 
+pub use accept_database_transfer::accept_database_transfer as accept_database_transfer_endpoint;
+pub use add_team_member::add_team_member as add_team_member_endpoint;
+pub use cancel_database_transfer::cancel_database_transfer as cancel_database_transfer_endpoint;
 pub use confirm::confirm as confirm_endpoint;
 pub use create_database::create_database as create_database_endpoint;
+pub use create_snapshot::create_snapshot as create_snapshot_endpoint;
+pub use create_team::create_team as create_team_endpoint;
+pub use create_webhook::create_webhook as create_webhook_endpoint;
 pub use database_details::database_details as database_details_endpoint;
+pub use delete_database::delete_database as delete_database_endpoint;
+pub use delete_saved_query::delete_saved_query as delete_saved_query_endpoint;
+pub use delete_team::delete_team as delete_team_endpoint;
+pub use delete_webhook::delete_webhook as delete_webhook_endpoint;
+pub use diff::diff as diff_endpoint;
 pub use entity_details::entity_details as entity_details_endpoint;
+pub use fork_database::fork_database as fork_database_endpoint;
 pub use health::health as health_endpoint;
+pub use list_audit_log::list_audit_log as list_audit_log_endpoint;
 pub use list_database_permissions::list_database_permissions as list_database_permissions_endpoint;
+pub use list_database_transfers::list_database_transfers as list_database_transfers_endpoint;
+pub use list_organization_members::list_organization_members as list_organization_members_endpoint;
+pub use list_published_endpoints::list_published_endpoints as list_published_endpoints_endpoint;
+pub use list_saved_queries::list_saved_queries as list_saved_queries_endpoint;
 pub use list_snapshots::list_snapshots as list_snapshots_endpoint;
+pub use list_teams::list_teams as list_teams_endpoint;
 pub use list_tokens::list_tokens as list_tokens_endpoint;
+pub use list_webhook_deliveries::list_webhook_deliveries as list_webhook_deliveries_endpoint;
+pub use list_webhooks::list_webhooks as list_webhooks_endpoint;
 pub use log_in::log_in as log_in_endpoint;
 pub use oauth_token::oauth_token as oauth_token_endpoint;
+pub use publish_endpoint::publish_endpoint as publish_endpoint_endpoint;
 pub use query::query as query_endpoint;
 pub use register::register as register_endpoint;
+pub use remove_organization_member::remove_organization_member as remove_organization_member_endpoint;
+pub use remove_team_member::remove_team_member as remove_team_member_endpoint;
+pub use rename_database::rename_database as rename_database_endpoint;
 pub use restore_snapshot::restore_snapshot as restore_snapshot_endpoint;
 pub use revoke_token::revoke_token as revoke_token_endpoint;
+pub use run_published_endpoint::run_published_endpoint as run_published_endpoint_endpoint;
+pub use run_saved_query::run_saved_query as run_saved_query_endpoint;
+pub use save_query::save_query as save_query_endpoint;
 pub use share::share as share_endpoint;
+pub use snapshot_retention::snapshot_retention as snapshot_retention_endpoint;
+pub use snapshot_schedule::snapshot_schedule as snapshot_schedule_endpoint;
+pub use subscribe_to_changes::subscribe_to_changes as subscribe_to_changes_endpoint;
+pub use transfer_database::transfer_database as transfer_database_endpoint;
+pub use unpublish_endpoint::unpublish_endpoint as unpublish_endpoint_endpoint;
 pub use update_database::update_database as update_database_endpoint;
+pub use update_member_sharing_level::update_member_sharing_level as update_member_sharing_level_endpoint;
+pub use update_organization_member::update_organization_member as update_organization_member_endpoint;
 pub use update_profile::update_profile as update_profile_endpoint;
+pub use update_snapshot_retention::update_snapshot_retention as update_snapshot_retention_endpoint;
+pub use update_snapshot_schedule::update_snapshot_schedule as update_snapshot_schedule_endpoint;
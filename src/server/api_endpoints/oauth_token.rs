@@ -1,7 +1,9 @@
 use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::AuditAction;
 use crate::error::AybError;
 use crate::hosted_db::QueryMode;
 use crate::http::structs::{OAuthErrorResponse, OAuthTokenRequest, OAuthTokenResponse};
+use crate::server::audit_log::record_audit_event;
 use crate::server::config::{public_base_url, AybConfig};
 use crate::server::tokens::{generate_api_token, APITokenScope};
 use actix_web::{post, web, HttpResponse, Result};
@@ -133,6 +135,30 @@ pub async fn oauth_token(
         );
     }
 
+    let permission_str =
+        QueryMode::try_from(auth_request.query_permission_level).map(|q| q.to_str().to_string())?;
+    // Tokens are recorded in the log of the entity they belong to,
+    // which isn't necessarily the owner of the database they're scoped
+    // to.
+    let authorizing_entity = ayb_db.get_entity_by_id(auth_request.entity_id).await?;
+    record_audit_event(
+        &ayb_db,
+        authorizing_entity.id,
+        None,
+        &authorizing_entity,
+        None,
+        AuditAction::CreateToken,
+        format!(
+            "Created {} token {} for {} on {}/{}",
+            permission_str,
+            api_token.short_token,
+            auth_request.app_name,
+            auth_request.entity_slug,
+            auth_request.database_slug
+        ),
+    )
+    .await?;
+
     // Build the response
     let base_url = public_base_url(&ayb_config);
     let database_path = format!(
@@ -141,9 +167,6 @@ pub async fn oauth_token(
     );
     let database_url = format!("{base_url}/v1/{database_path}");
 
-    let permission_str =
-        QueryMode::try_from(auth_request.query_permission_level).map(|q| q.to_str().to_string())?;
-
     Ok(HttpResponse::Ok().json(OAuthTokenResponse {
         access_token: token_string,
         token_type: "Bearer".to_string(),
@@ -0,0 +1,89 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity, PublishedEndpoint};
+use crate::error::AybError;
+use crate::http::structs::{EndpointPublication, EntityDatabaseEndpointPath, SavedQueryDetails};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::can_manage_database;
+use crate::server::published_endpoints::{DEFAULT_MAX_AGE_SECONDS, FORMAT_PARAMETER};
+use crate::server::utils::unwrap_authenticated_entity;
+use crate::server::validation::validate_endpoint_name;
+use actix_web::{post, web, HttpResponse};
+
+#[post(
+    "/{entity}/{database}/endpoints/{endpoint}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn publish_endpoint(
+    path: web::Path<EntityDatabaseEndpointPath>,
+    body: web::Json<EndpointPublication>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let name = &path.endpoint;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    // Published endpoints run with the owner's read-only access, so
+    // only the database's managers can publish them.
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't publish endpoints for {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    validate_endpoint_name(name)?;
+
+    let saved_query = SavedQueryDetails::try_from(
+        ayb_db
+            .get_saved_query(database.id, &body.saved_query)
+            .await?,
+    )?;
+    if saved_query
+        .parameters
+        .iter()
+        .any(|parameter| parameter.name == FORMAT_PARAMETER)
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Saved query {} can't be published because `{FORMAT_PARAMETER}` is a reserved parameter name",
+                saved_query.name
+            ),
+        });
+    }
+    let max_age = body.max_age.unwrap_or(DEFAULT_MAX_AGE_SECONDS);
+    let max_age_seconds = i32::try_from(max_age).map_err(|_| AybError::Other {
+        message: format!("Max age {max_age} is too large"),
+    })?;
+
+    ayb_db
+        .update_or_create_published_endpoint(&PublishedEndpoint {
+            database_id: database.id,
+            name: name.to_string(),
+            saved_query_name: saved_query.name.clone(),
+            query_text: saved_query.query,
+            parameters: serde_json::to_string(&saved_query.parameters)?,
+            max_age_seconds,
+            publisher_id: authenticated_entity.id,
+        })
+        .await?;
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::PublishEndpoint,
+        format!(
+            "Published saved query {} as endpoint {name}",
+            saved_query.name
+        ),
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
@@ -1,26 +1,39 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::{APIToken, DBType, InstantiatedEntity};
+use crate::ayb_db::models::{
+    APIToken, AuditAction, DBType, InstantiatedDatabase, InstantiatedEntity,
+};
 
 use crate::error::AybError;
 use crate::hosted_db::daemon_registry::DaemonRegistry;
 use crate::hosted_db::paths::current_database_path;
-use crate::hosted_db::QueryResult;
-use crate::http::structs::EntityDatabasePath;
+use crate::hosted_db::{QueryMode, QueryResult};
+use crate::http::structs::{EntityDatabasePath, QuerySnapshotQuery};
+use crate::server::audit_log::{audit_log_records_queries, record_audit_event};
+use crate::server::change_feed::{ChangeEvent, ChangeFeed};
 use crate::server::config::AybConfig;
-use crate::server::permissions::highest_query_access_level;
+use crate::server::permissions::{
+    can_manage_database, can_token_access_database, highest_query_access_level, query_restrictions,
+};
+use crate::server::snapshots::cache::SnapshotCache;
+use crate::server::snapshots::storage::SnapshotStorage;
 use crate::server::utils::unwrap_authenticated_entity;
+use crate::server::webhooks::enqueue_webhook_deliveries;
 use actix_web::{post, web};
 
 #[post(
     "/{entity}/{database}/query",
     wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
 )]
+#[allow(clippy::too_many_arguments)]
 async fn query(
     path: web::Path<EntityDatabasePath>,
+    options: web::Query<QuerySnapshotQuery>,
     query: String,
     ayb_db: web::Data<Box<dyn AybDb>>,
     ayb_config: web::Data<AybConfig>,
     daemon_registry: web::Data<DaemonRegistry>,
+    change_feed: web::Data<ChangeFeed>,
+    snapshot_cache: web::Data<SnapshotCache>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
     api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<web::Json<QueryResult>, AybError> {
@@ -28,24 +41,188 @@ async fn query(
     let database_slug = &path.database;
     let database = ayb_db.get_database(entity_slug, database_slug).await?;
     let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
-    let token_ref = api_token.as_ref().map(|t| t.clone().into_inner());
-    let token = token_ref.as_ref();
 
+    if let Some(snapshot_id) = &options.snapshot {
+        let result = run_snapshot_query(
+            entity_slug,
+            &database,
+            snapshot_id,
+            &query,
+            &authenticated_entity,
+            api_token.as_deref(),
+            &ayb_db,
+            &ayb_config,
+            &daemon_registry,
+            &snapshot_cache,
+        )
+        .await?;
+        return Ok(web::Json(result));
+    }
+
+    let result = run_query(
+        entity_slug,
+        &database,
+        query,
+        &authenticated_entity,
+        api_token.as_deref(),
+        &ayb_db,
+        &ayb_config,
+        &daemon_registry,
+        &change_feed,
+    )
+    .await?;
+    Ok(web::Json(result))
+}
+
+/// Run `query_text` read-only against snapshot `snapshot_id` of
+/// `database`. Snapshots can be queried by the entities that can
+/// restore them, through a token that could query the live database.
+#[allow(clippy::too_many_arguments)]
+async fn run_snapshot_query(
+    entity_slug: &str,
+    database: &InstantiatedDatabase,
+    snapshot_id: &str,
+    query_text: &str,
+    authenticated_entity: &InstantiatedEntity,
+    token: Option<&APIToken>,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    ayb_config: &web::Data<AybConfig>,
+    daemon_registry: &web::Data<DaemonRegistry>,
+    snapshot_cache: &web::Data<SnapshotCache>,
+) -> Result<QueryResult, AybError> {
+    if !can_manage_database(authenticated_entity, database, ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database.slug
+            ),
+        });
+    }
+    // Tokens limit snapshot queries the way they limit live ones, so a
+    // token scoped to another database can't reach this one's history.
+    let token_can_query = match token {
+        Some(token) => can_token_access_database(token, database),
+        None => true,
+    };
+    if !token_can_query
+        || highest_query_access_level(authenticated_entity, database, token, ayb_db)
+            .await?
+            .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't query database {}/{}",
+                authenticated_entity.slug, entity_slug, database.slug
+            ),
+        });
+    }
+    let Some(ref snapshot_config) = ayb_config.snapshots else {
+        return Err(AybError::Other {
+            message: "Snapshots aren't configured on this server".to_string(),
+        });
+    };
+    let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
+    let output = snapshot_cache
+        .query(
+            &snapshot_storage,
+            daemon_registry,
+            &ayb_config.data_path,
+            entity_slug,
+            &database.slug,
+            snapshot_id,
+            &DBType::try_from(database.db_type)?,
+            query_text,
+        )
+        .await?;
+    Ok(output.result)
+}
+
+/// Run `query_text` against `database` at the highest access level
+/// `authenticated_entity` (through `token`, if any) has to it.
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn run_query(
+    entity_slug: &str,
+    database: &InstantiatedDatabase,
+    query_text: String,
+    authenticated_entity: &InstantiatedEntity,
+    token: Option<&APIToken>,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    ayb_config: &web::Data<AybConfig>,
+    daemon_registry: &web::Data<DaemonRegistry>,
+    change_feed: &web::Data<ChangeFeed>,
+) -> Result<QueryResult, AybError> {
     let access_level =
-        highest_query_access_level(&authenticated_entity, &database, token, &ayb_db).await?;
+        highest_query_access_level(authenticated_entity, database, token, ayb_db).await?;
     match access_level {
         Some(access_level) => {
             let db_type = DBType::try_from(database.db_type)?;
-            let db_path = current_database_path(entity_slug, database_slug, &ayb_config.data_path)?;
-            let result = daemon_registry
-                .execute_query(&db_path, &query, &db_type, access_level)
+            let db_path =
+                current_database_path(entity_slug, &database.slug, &ayb_config.data_path)?;
+            let restrictions = query_restrictions(authenticated_entity, database, ayb_db).await?;
+            // Capturing changes costs the daemon extra work, so it's only
+            // asked to when someone is subscribed to them.
+            let webhooks = match access_level {
+                QueryMode::ReadOnly => Vec::new(),
+                _ => ayb_db.list_webhooks(database.id).await?,
+            };
+            let capture_changes = access_level != QueryMode::ReadOnly
+                && (!webhooks.is_empty() || change_feed.has_subscribers(database.id));
+            let output = daemon_registry
+                .execute_query(
+                    &db_path,
+                    &query_text,
+                    &db_type,
+                    access_level,
+                    restrictions.as_ref(),
+                    capture_changes,
+                )
                 .await?;
-            Ok(web::Json(result))
+            if !output.changes.is_empty() {
+                // The query has already committed, so failing to queue
+                // its deliveries doesn't fail it.
+                if let Err(err) = enqueue_webhook_deliveries(
+                    ayb_db,
+                    entity_slug,
+                    database,
+                    &webhooks,
+                    &output.changes,
+                )
+                .await
+                {
+                    eprintln!("Unable to queue webhook deliveries: {err}");
+                }
+                change_feed.publish(ChangeEvent {
+                    database_id: database.id,
+                    entity: entity_slug.to_string(),
+                    database: database.slug.clone(),
+                    changes: output.changes,
+                    committed_at: chrono::Utc::now().naive_utc().to_string(),
+                });
+            }
+            // Only queries that changed the database are recorded.
+            // Like webhook deliveries, failing to record a query that
+            // has already committed doesn't fail it.
+            if output.wrote && audit_log_records_queries(ayb_config) {
+                if let Err(err) = record_audit_event(
+                    ayb_db,
+                    database.entity_id,
+                    Some(database),
+                    authenticated_entity,
+                    token,
+                    AuditAction::Query,
+                    query_text,
+                )
+                .await
+                {
+                    eprintln!("Unable to record query in the audit log: {err}");
+                }
+            }
+            Ok(output.result)
         }
         None => Err(AybError::Other {
             message: format!(
                 "Authenticated entity {} can't query database {}/{}",
-                authenticated_entity.slug, entity_slug, database_slug
+                authenticated_entity.slug, entity_slug, database.slug
             ),
         }),
     }
@@ -0,0 +1,50 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{InstantiatedEntity, OrganizationRole};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityMemberPath};
+use crate::server::permissions::can_manage_organization_member;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{delete, web};
+
+#[delete(
+    "/entity/{entity}/members/{member}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn remove_organization_member(
+    path: web::Path<EntityMemberPath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let member = ayb_db
+        .get_entity_by_slug(&path.member.to_lowercase())
+        .await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let membership = ayb_db
+        .get_organization_membership(organization.id, member.id)
+        .await?
+        .ok_or_else(|| AybError::Other {
+            message: format!("{} is not a member of {}", member.slug, organization_slug),
+        })?;
+    let role = OrganizationRole::try_from(membership.role)?;
+
+    // Members can always leave an organization on their own.
+    if authenticated_entity.id != member.id
+        && !can_manage_organization_member(&authenticated_entity, &organization, role, &ayb_db)
+            .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't remove {} from {}",
+                authenticated_entity.slug, member.slug, organization_slug
+            ),
+        });
+    }
+
+    ayb_db
+        .delete_organization_membership(organization.id, member.id)
+        .await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
@@ -0,0 +1,197 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, InstantiatedDatabase, InstantiatedEntity};
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::paths::move_database_directory;
+use crate::http::structs::{Database as APIDatabase, EntityDatabasePath};
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_administer_database;
+use crate::server::snapshots::storage::SnapshotStorage;
+use crate::server::utils::{get_required_header, unwrap_authenticated_entity};
+use crate::server::validation::validate_database_slug;
+use actix_web::{post, web, HttpRequest};
+use std::future::Future;
+
+#[post(
+    "/{entity}/{database}/rename",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn rename_database(
+    path: web::Path<EntityDatabasePath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<APIDatabase>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let new_database_slug = get_required_header(&req, "new-database-slug")?;
+    validate_database_slug(&new_database_slug)?;
+    let entity = ayb_db.get_entity_by_slug(entity_slug).await?;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_administer_database(
+        &authenticated_entity,
+        &database,
+        api_token.as_deref(),
+        &ayb_db,
+    )
+    .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't rename database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    if &new_database_slug == database_slug {
+        return Err(AybError::Other {
+            message: format!("Database {entity_slug}/{database_slug} already has that name"),
+        });
+    }
+
+    // Moving files onto a database that already has the new slug would
+    // replace it, so the slug is checked before anything moves.
+    match ayb_db.get_database(entity_slug, &new_database_slug).await {
+        Ok(_) => {
+            return Err(AybError::Other {
+                message: format!("Database {new_database_slug} already exists"),
+            })
+        }
+        Err(AybError::RecordNotFound { .. }) => {}
+        Err(err) => return Err(err),
+    }
+
+    // Permissions and scoped API tokens reference the database by id,
+    // so they follow it to its new slug.
+    let renamed_database = move_database(
+        entity_slug,
+        database_slug,
+        entity_slug,
+        &new_database_slug,
+        &ayb_config,
+        &daemon_registry,
+        ayb_db.rename_database(database.id, &new_database_slug),
+    )
+    .await?;
+
+    Ok(web::Json(APIDatabase::from_persisted(
+        &entity,
+        &renamed_database,
+    )))
+}
+
+/// Moves the files and snapshots of `{from_entity_slug}/{from_database_slug}`
+/// to `{to_entity_slug}/{to_database_slug}`, and then updates the
+/// database's metadata by awaiting `update_metadata`. If the snapshots
+/// or the metadata can't be updated, the database is moved back, so
+/// that a failed rename or transfer leaves it where it was.
+pub(super) async fn move_database(
+    from_entity_slug: &str,
+    from_database_slug: &str,
+    to_entity_slug: &str,
+    to_database_slug: &str,
+    ayb_config: &AybConfig,
+    daemon_registry: &DaemonRegistry,
+    update_metadata: impl Future<Output = Result<InstantiatedDatabase, AybError>>,
+) -> Result<InstantiatedDatabase, AybError> {
+    move_database_directory(
+        from_entity_slug,
+        from_database_slug,
+        to_entity_slug,
+        to_database_slug,
+        &ayb_config.data_path,
+        daemon_registry,
+    )
+    .await?;
+    let snapshot_storage = match ayb_config.snapshots {
+        Some(ref snapshot_config) => Some(SnapshotStorage::new(snapshot_config).await?),
+        None => None,
+    };
+
+    let err = match move_snapshots(
+        snapshot_storage.as_ref(),
+        from_entity_slug,
+        from_database_slug,
+        to_entity_slug,
+        to_database_slug,
+    )
+    .await
+    {
+        Ok(()) => match update_metadata.await {
+            Ok(database) => return Ok(database),
+            Err(err) => {
+                if let Err(undo_err) = move_snapshots(
+                    snapshot_storage.as_ref(),
+                    to_entity_slug,
+                    to_database_slug,
+                    from_entity_slug,
+                    from_database_slug,
+                )
+                .await
+                {
+                    eprintln!(
+                        "Unable to move snapshots back to {from_entity_slug}/{from_database_slug}: {undo_err}"
+                    );
+                }
+                err
+            }
+        },
+        // Snapshots are only deleted once they've all been copied, so
+        // the copies that were made are all that needs undoing.
+        Err(err) => {
+            if let Some(ref snapshot_storage) = snapshot_storage {
+                if let Err(undo_err) = snapshot_storage
+                    .delete_all_snapshots(to_entity_slug, to_database_slug)
+                    .await
+                {
+                    eprintln!(
+                        "Unable to remove snapshots copied to {to_entity_slug}/{to_database_slug}: {undo_err}"
+                    );
+                }
+            }
+            err
+        }
+    };
+    if let Err(undo_err) = move_database_directory(
+        to_entity_slug,
+        to_database_slug,
+        from_entity_slug,
+        from_database_slug,
+        &ayb_config.data_path,
+        daemon_registry,
+    )
+    .await
+    {
+        eprintln!(
+            "Unable to move database back to {from_entity_slug}/{from_database_slug}: {undo_err}"
+        );
+    }
+    Err(err)
+}
+
+async fn move_snapshots(
+    snapshot_storage: Option<&SnapshotStorage>,
+    from_entity_slug: &str,
+    from_database_slug: &str,
+    to_entity_slug: &str,
+    to_database_slug: &str,
+) -> Result<(), AybError> {
+    match snapshot_storage {
+        Some(snapshot_storage) => {
+            snapshot_storage
+                .move_snapshots(
+                    from_entity_slug,
+                    from_database_slug,
+                    to_entity_slug,
+                    to_database_slug,
+                )
+                .await
+        }
+        None => Ok(()),
+    }
+}
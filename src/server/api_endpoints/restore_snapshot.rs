@@ -1,33 +1,70 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::InstantiatedEntity;
+use crate::ayb_db::models::{
+    APIToken, AuditAction, Database, InstantiatedEntity, PublicSharingLevel,
+};
 use crate::error::AybError;
 use crate::hosted_db::daemon_registry::DaemonRegistry;
-use crate::hosted_db::paths::{new_database_path, set_current_database_and_clean_up};
-use crate::http::structs::{EmptyResponse, EntityDatabasePath};
+use crate::hosted_db::paths::{
+    database_snapshot_path, install_database_files, new_database_path, rename_database_files,
+    set_current_database_and_clean_up,
+};
+use crate::http::structs::{
+    Database as APIDatabase, EmptyResponse, EntityDatabasePath, RestoreSnapshotQuery,
+};
+use crate::server::audit_log::record_audit_event;
 use crate::server::config::AybConfig;
-use crate::server::permissions::can_manage_database;
+use crate::server::permissions::{can_create_database, can_manage_database};
 use crate::server::snapshots::storage::SnapshotStorage;
-use crate::server::utils::unwrap_authenticated_entity;
-use actix_web::{post, web, HttpResponse};
+use crate::server::snapshots::wal::restore_wal;
+use crate::server::utils::{get_optional_header, unwrap_authenticated_entity};
+use crate::server::validation::validate_database_slug;
+use actix_web::{post, web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::Path;
 
 #[post(
     "/{entity}/{database}/restore_snapshot",
     wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
 )]
+#[allow(clippy::too_many_arguments)]
 async fn restore_snapshot(
     path: web::Path<EntityDatabasePath>,
+    req: HttpRequest,
+    query: web::Query<RestoreSnapshotQuery>,
     snapshot_id: String,
     ayb_db: web::Data<Box<dyn AybDb>>,
     ayb_config: web::Data<AybConfig>,
     daemon_registry: web::Data<DaemonRegistry>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<HttpResponse, AybError> {
     let entity_slug = &path.entity.to_lowercase();
     let database_slug = &path.database;
     let database = ayb_db.get_database(entity_slug, database_slug).await?;
     let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    if query.at.is_some() && !snapshot_id.is_empty() {
+        return Err(AybError::Other {
+            message: "Restore either a snapshot or a point in time, not both".to_string(),
+        });
+    }
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
 
-    if can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+    // With either of these headers, the snapshot is restored into a
+    // new database, leaving this one untouched. The new database
+    // defaults to this one's entity and name, so at least one of them
+    // has to differ.
+    let entity_for_restore = get_optional_header(&req, "entity-for-restore")?;
+    let database_for_restore = get_optional_header(&req, "database-for-restore")?;
+    if entity_for_restore.is_none() && database_for_restore.is_none() {
         if let Some(ref snapshot_config) = ayb_config.snapshots {
             // TODO(marcua): In the future, consider quiescing
             // requests to this database during the process, and
@@ -38,19 +75,148 @@ async fn restore_snapshot(
             // directory, and set it as the current active database.
             let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
             let db_path = &new_database_path(entity_slug, database_slug, &ayb_config.data_path)?;
-
-            snapshot_storage
-                .retrieve_snapshot(entity_slug, database_slug, &snapshot_id, db_path)
-                .await?;
+            retrieve(
+                &snapshot_storage,
+                entity_slug,
+                database_slug,
+                &snapshot_id,
+                query.at,
+                db_path,
+            )
+            .await?;
             set_current_database_and_clean_up(db_path, &daemon_registry).await?;
+            record_audit_event(
+                &ayb_db,
+                database.entity_id,
+                Some(&database),
+                &authenticated_entity,
+                api_token.as_deref(),
+                AuditAction::RestoreSnapshot,
+                match query.at {
+                    Some(at) => format!("Restored to {}", at.to_rfc3339()),
+                    None => format!("Restored snapshot {snapshot_id}"),
+                },
+            )
+            .await?;
         }
-        Ok(HttpResponse::Ok().json(EmptyResponse {}))
-    } else {
-        Err(AybError::Other {
+        return Ok(HttpResponse::Ok().json(EmptyResponse {}));
+    }
+
+    let Some(ref snapshot_config) = ayb_config.snapshots else {
+        return Err(AybError::Other {
+            message: "Snapshots aren't configured on this server".to_string(),
+        });
+    };
+    let restore_entity = match entity_for_restore {
+        Some(slug) => ayb_db.get_entity_by_slug(&slug.to_lowercase()).await?,
+        None => ayb_db.get_entity_by_slug(entity_slug).await?,
+    };
+    let restore_slug = database_for_restore.unwrap_or(database_slug.to_string());
+    validate_database_slug(&restore_slug)?;
+    if !can_create_database(&authenticated_entity, &restore_entity, &ayb_db).await? {
+        return Err(AybError::Other {
             message: format!(
-                "Authenticated entity {} can't manage snapshots on database {}/{}",
-                authenticated_entity.slug, entity_slug, database_slug
+                "Authenticated entity {} can't create a database for entity {}",
+                authenticated_entity.slug, restore_entity.slug
+            ),
+        });
+    }
+
+    // Retrieve the snapshot into a scratch directory, and only record
+    // the new database once that succeeds.
+    let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
+    let restore_directory =
+        database_snapshot_path(entity_slug, database_slug, &ayb_config.data_path)?;
+    retrieve(
+        &snapshot_storage,
+        entity_slug,
+        database_slug,
+        &snapshot_id,
+        query.at,
+        &restore_directory,
+    )
+    .await?;
+    let restored = Database {
+        entity_id: restore_entity.id,
+        slug: restore_slug.clone(),
+        db_type: database.db_type,
+        public_sharing_level: PublicSharingLevel::NoAccess as i16,
+    };
+    let created_database =
+        match rename_database_files(&restore_directory, database_slug, &restore_slug) {
+            Ok(()) => ayb_db.create_database(&restored).await,
+            Err(err) => Err(err),
+        };
+    let created_database = match created_database {
+        Ok(created_database) => created_database,
+        Err(err) => {
+            fs::remove_dir_all(&restore_directory)?;
+            return Err(err);
+        }
+    };
+    install_database_files(
+        &restore_directory,
+        &restore_entity.slug,
+        &restore_slug,
+        &ayb_config.data_path,
+        &daemon_registry,
+    )
+    .await?;
+    fs::remove_dir_all(restore_directory)?;
+    record_audit_event(
+        &ayb_db,
+        restore_entity.id,
+        Some(&created_database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::CreateDatabase,
+        match query.at {
+            Some(at) => format!(
+                "Restored {entity_slug}/{database_slug} as of {}",
+                at.to_rfc3339()
             ),
-        })
+            None => format!("Restored snapshot {snapshot_id} of {entity_slug}/{database_slug}"),
+        },
+    )
+    .await?;
+
+    Ok(HttpResponse::Created().json(APIDatabase::from_persisted(
+        &restore_entity,
+        &created_database,
+    )))
+}
+
+/// Retrieves snapshot `snapshot_id` of `{entity_slug}/{database_slug}`
+/// into `{destination_path}/{database_slug}`. With a point in time, the
+/// database is rebuilt from its replicated write-ahead log instead.
+/// On failure, `destination_path` is removed.
+async fn retrieve(
+    snapshot_storage: &SnapshotStorage,
+    entity_slug: &str,
+    database_slug: &str,
+    snapshot_id: &str,
+    at: Option<DateTime<Utc>>,
+    destination_path: &Path,
+) -> Result<(), AybError> {
+    let result = match at {
+        Some(at) => {
+            restore_wal(
+                snapshot_storage,
+                entity_slug,
+                database_slug,
+                at,
+                destination_path,
+            )
+            .await
+        }
+        None => {
+            snapshot_storage
+                .retrieve_snapshot(entity_slug, database_slug, snapshot_id, destination_path)
+                .await
+        }
+    };
+    if result.is_err() {
+        fs::remove_dir_all(destination_path)?;
     }
+    result
 }
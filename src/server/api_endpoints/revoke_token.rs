@@ -1,7 +1,8 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::InstantiatedEntity;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity};
 use crate::error::AybError;
 use crate::http::structs::{EmptyResponse, ShortTokenPath};
+use crate::server::audit_log::record_audit_event;
 use crate::server::utils::unwrap_authenticated_entity;
 use actix_web::{delete, web};
 
@@ -13,12 +14,23 @@ async fn revoke_token(
     path: web::Path<ShortTokenPath>,
     ayb_db: web::Data<Box<dyn AybDb>>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<web::Json<EmptyResponse>, AybError> {
     let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
 
     ayb_db
         .revoke_api_token(&authenticated_entity, &path.short_token)
         .await?;
+    record_audit_event(
+        &ayb_db,
+        authenticated_entity.id,
+        None,
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::RevokeToken,
+        format!("Revoked token {}", path.short_token),
+    )
+    .await?;
 
     Ok(web::Json(EmptyResponse {}))
 }
@@ -0,0 +1,55 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::DBType;
+use crate::error::AybError;
+use crate::formatting::TabularFormatter;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::paths::current_database_path;
+use crate::hosted_db::QueryMode;
+use crate::http::structs::{EntityDatabaseEndpointPath, SavedQueryParameter};
+use crate::server::config::AybConfig;
+use crate::server::published_endpoints::{cached_response, EndpointFormat, FORMAT_PARAMETER};
+use crate::server::saved_queries::render_saved_query;
+use actix_web::{get, web, HttpRequest, HttpResponse};
+use std::collections::HashMap;
+
+/// Published endpoints are public: they run with the owner's read-only
+/// access to the whole database, limited to the published query.
+#[get("/{entity}/{database}/endpoints/{endpoint}")]
+async fn run_published_endpoint(
+    req: HttpRequest,
+    path: web::Path<EntityDatabaseEndpointPath>,
+    query: web::Query<HashMap<String, String>>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let endpoint = ayb_db
+        .get_published_endpoint(database.id, &path.endpoint)
+        .await?;
+
+    let mut values = query.into_inner();
+    let format = EndpointFormat::from_parameter(values.remove(FORMAT_PARAMETER).as_deref())?;
+    let parameters: Vec<SavedQueryParameter> = serde_json::from_str(&endpoint.parameters)?;
+    let query = render_saved_query(&endpoint.query_text, &parameters, &values)?;
+
+    let db_type = DBType::try_from(database.db_type)?;
+    let db_path = current_database_path(entity_slug, &database.slug, &ayb_config.data_path)?;
+    let result = daemon_registry
+        .execute_query(&db_path, &query, &db_type, QueryMode::ReadOnly, None, false)
+        .await?
+        .result;
+    let body = match format {
+        EndpointFormat::Json => serde_json::to_string(&result)?,
+        EndpointFormat::Csv => result.to_csv_string()?,
+    };
+
+    Ok(cached_response(
+        &req,
+        &format,
+        body,
+        endpoint.max_age_seconds,
+    ))
+}
@@ -0,0 +1,68 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, InstantiatedEntity};
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::QueryResult;
+use crate::http::structs::{EntityDatabaseSavedQueryPath, SavedQueryDetails};
+use crate::server::api_endpoints::query::run_query;
+use crate::server::change_feed::ChangeFeed;
+use crate::server::config::AybConfig;
+use crate::server::permissions::highest_query_access_level;
+use crate::server::saved_queries::render_saved_query;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{post, web};
+use std::collections::HashMap;
+
+#[post(
+    "/{entity}/{database}/saved_queries/{saved_query}/run",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+#[allow(clippy::too_many_arguments)]
+async fn run_saved_query(
+    path: web::Path<EntityDatabaseSavedQueryPath>,
+    values: web::Json<HashMap<String, String>>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    daemon_registry: web::Data<DaemonRegistry>,
+    change_feed: web::Data<ChangeFeed>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<QueryResult>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let name = &path.saved_query;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let token = api_token.as_deref();
+
+    // Don't reveal which saved queries exist to entities that can't
+    // query the database.
+    if highest_query_access_level(&authenticated_entity, &database, token, &ayb_db)
+        .await?
+        .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't query database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let saved_query =
+        SavedQueryDetails::try_from(ayb_db.get_saved_query(database.id, name).await?)?;
+    let query = render_saved_query(&saved_query.query, &saved_query.parameters, &values)?;
+    let result = run_query(
+        entity_slug,
+        &database,
+        query,
+        &authenticated_entity,
+        token,
+        &ayb_db,
+        &ayb_config,
+        &daemon_registry,
+        &change_feed,
+    )
+    .await?;
+    Ok(web::Json(result))
+}
@@ -0,0 +1,79 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity, SavedQuery};
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabaseSavedQueryPath, SavedQueryUpdate};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::{can_modify_saved_query, highest_query_access_level};
+use crate::server::saved_queries::validate_saved_query;
+use crate::server::utils::unwrap_authenticated_entity;
+use crate::server::validation::validate_saved_query_name;
+use actix_web::{post, web, HttpResponse};
+
+#[post(
+    "/{entity}/{database}/saved_queries/{saved_query}",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn save_query(
+    path: web::Path<EntityDatabaseSavedQueryPath>,
+    body: web::Json<SavedQueryUpdate>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let name = &path.saved_query;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let token = api_token.as_deref();
+
+    // Anyone who can query a database can save queries for it, but only
+    // a saved query's author and the database's managers can replace it.
+    let can_save = if highest_query_access_level(&authenticated_entity, &database, token, &ayb_db)
+        .await?
+        .is_none()
+    {
+        false
+    } else {
+        match ayb_db.get_saved_query(database.id, name).await {
+            Ok(existing) => {
+                can_modify_saved_query(&authenticated_entity, &database, &existing, &ayb_db).await?
+            }
+            Err(AybError::RecordNotFound { .. }) => true,
+            Err(err) => return Err(err),
+        }
+    };
+    if !can_save {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't save query {} on {}/{}",
+                authenticated_entity.slug, name, entity_slug, database_slug
+            ),
+        });
+    }
+    validate_saved_query_name(name)?;
+    validate_saved_query(&body.query, &body.parameters)?;
+
+    ayb_db
+        .update_or_create_saved_query(&SavedQuery {
+            database_id: database.id,
+            name: name.to_string(),
+            query_text: body.query.clone(),
+            description: body.description.clone(),
+            parameters: serde_json::to_string(&body.parameters)?,
+            author_id: authenticated_entity.id,
+        })
+        .await?;
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        token,
+        AuditAction::SaveQuery,
+        format!("Saved query {name}: {}", body.query),
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
@@ -1,15 +1,56 @@
 use crate::ayb_db::db_interfaces::AybDb;
 use crate::ayb_db::models::{
-    EntityDatabasePermission, EntityDatabaseSharingLevel, InstantiatedEntity,
+    APIToken, AuditAction, EntityDatabasePermission, EntityDatabaseSharingLevel,
+    InstantiatedDatabase, InstantiatedEntity, PartialDatabase, PublicSharingLevel,
+    TeamDatabasePermission,
 };
 use std::str::FromStr;
 
 use crate::error::AybError;
 use crate::http::structs::EntityDatabasePath;
+use crate::server::audit_log::record_audit_event;
 use crate::server::permissions::can_manage_database;
-use crate::server::utils::{get_required_header, unwrap_authenticated_entity};
+use crate::server::utils::{get_optional_header, get_required_header, unwrap_authenticated_entity};
 use actix_web::{post, web, HttpRequest, HttpResponse};
 
+/// Sharing with this (reserved) slug limits public access to objects.
+const PUBLIC_GRANTEE: &str = "public";
+
+/// A table or view, and the columns of it a permission covers (all of
+/// them if `None`).
+type ObjectForPermission = (String, Option<Vec<String>>);
+
+/// The table or view named in the `object-for-permission` header, and
+/// the columns of it listed in `columns-for-permission`, if any.
+fn object_for_permission(req: &HttpRequest) -> Result<Option<ObjectForPermission>, AybError> {
+    let Some(object) = get_optional_header(req, "object-for-permission")? else {
+        return Ok(None);
+    };
+    let object = object.trim().to_lowercase();
+    if object.is_empty() {
+        return Err(AybError::Other {
+            message: "Object names can't be empty".to_string(),
+        });
+    }
+    let columns = match get_optional_header(req, "columns-for-permission")? {
+        Some(columns) => {
+            let columns: Vec<String> = columns
+                .split(',')
+                .map(|column| column.trim().to_lowercase())
+                .filter(|column| !column.is_empty())
+                .collect();
+            if columns.is_empty() {
+                return Err(AybError::Other {
+                    message: format!("No columns of {object} were provided"),
+                });
+            }
+            Some(columns)
+        }
+        None => None,
+    };
+    Ok(Some((object, columns)))
+}
+
 #[post(
     "/{entity}/{database}/share",
     wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
@@ -19,6 +60,7 @@ async fn share(
     req: HttpRequest,
     ayb_db: web::Data<Box<dyn AybDb>>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<HttpResponse, AybError> {
     let entity_for_database_slug = &path.entity.to_lowercase();
     let database_slug = &path.database;
@@ -27,22 +69,121 @@ async fn share(
         .await?;
     let sharing_level =
         EntityDatabaseSharingLevel::from_str(&get_required_header(&req, "sharing-level")?)?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let (grantee, object, response) =
+        if let Some(team_slug) = get_optional_header(&req, "team-for-permission")? {
+            let team_slug = team_slug.to_lowercase();
+            let response = share_with_team(
+                &team_slug,
+                sharing_level,
+                &database,
+                &ayb_db,
+                &authenticated_entity,
+            )
+            .await?;
+            (format!("team {team_slug}"), None, response)
+        } else {
+            let object = object_for_permission(&req)?;
+            let entity_for_permission_slug =
+                get_required_header(&req, "entity-for-permission")?.to_lowercase();
+            let response = if entity_for_permission_slug == PUBLIC_GRANTEE {
+                share_publicly(
+                    object.clone(),
+                    sharing_level,
+                    &database,
+                    &ayb_db,
+                    &authenticated_entity,
+                )
+                .await?
+            } else {
+                share_with_entity(
+                    &entity_for_permission_slug,
+                    &object,
+                    sharing_level,
+                    &database,
+                    &ayb_db,
+                    &authenticated_entity,
+                )
+                .await?
+            };
+            (entity_for_permission_slug, object, response)
+        };
+
+    let mut details = format!("Set {grantee}'s access to {}", sharing_level.to_str());
+    if let Some((object_name, columns)) = object {
+        details.push_str(&format!(" on {object_name}"));
+        if let Some(columns) = columns {
+            details.push_str(&format!(" ({})", columns.join(", ")));
+        }
+    }
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::Share,
+        details,
+    )
+    .await?;
+    Ok(response)
+}
+
+async fn share_with_entity(
+    entity_for_permission_slug: &str,
+    object: &Option<(String, Option<Vec<String>>)>,
+    sharing_level: EntityDatabaseSharingLevel,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    authenticated_entity: &InstantiatedEntity,
+) -> Result<HttpResponse, AybError> {
     let entity_for_permission = ayb_db
-        .get_entity_by_slug(&get_required_header(&req, "entity-for-permission")?)
+        .get_entity_by_slug(entity_for_permission_slug)
         .await?;
-    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let owner = ayb_db.get_entity_by_id(database.entity_id).await?;
     if entity_for_permission.id == database.entity_id {
         Err(AybError::CantSetOwnerPermissions {
             message: format!(
                 "{} owns {}/{}, so their permissions can't be changed",
-                entity_for_permission.slug, entity_for_database_slug, database_slug
+                entity_for_permission.slug, owner.slug, database.slug
             ),
         })
-    } else if can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+    } else if can_manage_database(authenticated_entity, database, ayb_db).await? {
+        let entity_id = Some(entity_for_permission.id);
+        if let Some((object_name, columns)) = object {
+            if sharing_level == EntityDatabaseSharingLevel::NoAccess {
+                ayb_db
+                    .delete_database_object_permissions(database.id, entity_id, Some(object_name))
+                    .await?;
+                // Revoking the last object an entity was limited to
+                // leaves it with access to nothing.
+                let remaining = ayb_db
+                    .list_database_object_permissions(database.id, entity_id)
+                    .await?;
+                if remaining.is_empty() {
+                    ayb_db
+                        .delete_entity_database_permission(entity_for_permission.id, database.id)
+                        .await?;
+                }
+                return Ok(HttpResponse::NoContent().into());
+            }
+            ayb_db
+                .replace_database_object_permission(
+                    database.id,
+                    entity_id,
+                    object_name,
+                    columns.as_deref(),
+                )
+                .await?;
+        }
+
         if sharing_level == EntityDatabaseSharingLevel::NoAccess {
             ayb_db
                 .delete_entity_database_permission(entity_for_permission.id, database.id)
                 .await?;
+            ayb_db
+                .delete_database_object_permissions(database.id, entity_id, None)
+                .await?;
         } else {
             let permission = EntityDatabasePermission {
                 entity_id: entity_for_permission.id,
@@ -59,8 +200,117 @@ async fn share(
         Err(AybError::Other {
             message: format!(
                 "Authenticated entity {} can't set permissions for database {}/{}",
-                authenticated_entity.slug, entity_for_database_slug, database_slug
+                authenticated_entity.slug, owner.slug, database.slug
             ),
         })
     }
 }
+
+/// Teams belong to an organization, so only the teams of the
+/// organization that owns `database` can be granted access to it.
+async fn share_with_team(
+    team_slug: &str,
+    sharing_level: EntityDatabaseSharingLevel,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    authenticated_entity: &InstantiatedEntity,
+) -> Result<HttpResponse, AybError> {
+    if !can_manage_database(authenticated_entity, database, ayb_db).await? {
+        let owner = ayb_db.get_entity_by_id(database.entity_id).await?;
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't set permissions for database {}/{}",
+                authenticated_entity.slug, owner.slug, database.slug
+            ),
+        });
+    }
+
+    let team = ayb_db.get_team(database.entity_id, team_slug).await?;
+    if sharing_level == EntityDatabaseSharingLevel::NoAccess {
+        ayb_db
+            .delete_team_database_permission(team.id, database.id)
+            .await?;
+    } else {
+        let permission = TeamDatabasePermission {
+            team_id: team.id,
+            database_id: database.id,
+            sharing_level: sharing_level as i16,
+        };
+        ayb_db
+            .update_or_create_team_database_permission(&permission)
+            .await?;
+    }
+
+    Ok(HttpResponse::NoContent().into())
+}
+
+/// Limits public access to `database` to specific objects. Granting an
+/// object makes the database publicly readable, and revoking the last
+/// one takes public access away.
+async fn share_publicly(
+    object: Option<(String, Option<Vec<String>>)>,
+    sharing_level: EntityDatabaseSharingLevel,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    authenticated_entity: &InstantiatedEntity,
+) -> Result<HttpResponse, AybError> {
+    let owner = ayb_db.get_entity_by_id(database.entity_id).await?;
+    if !can_manage_database(authenticated_entity, database, ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't set permissions for database {}/{}",
+                authenticated_entity.slug, owner.slug, database.slug
+            ),
+        });
+    }
+    let Some((object_name, columns)) = object else {
+        return Err(AybError::Other {
+            message: format!(
+                "Use update_database to change the public sharing level of {}/{}",
+                owner.slug, database.slug
+            ),
+        });
+    };
+
+    let public_sharing_level = match sharing_level {
+        EntityDatabaseSharingLevel::ReadOnly => {
+            ayb_db
+                .replace_database_object_permission(
+                    database.id,
+                    None,
+                    &object_name,
+                    columns.as_deref(),
+                )
+                .await?;
+            PublicSharingLevel::ReadOnly
+        }
+        EntityDatabaseSharingLevel::NoAccess => {
+            ayb_db
+                .delete_database_object_permissions(database.id, None, Some(&object_name))
+                .await?;
+            let remaining = ayb_db
+                .list_database_object_permissions(database.id, None)
+                .await?;
+            if !remaining.is_empty() {
+                return Ok(HttpResponse::NoContent().into());
+            }
+            PublicSharingLevel::NoAccess
+        }
+        _ => {
+            return Err(AybError::Other {
+                message: "Public access can only be limited to objects at the read-only level"
+                    .to_string(),
+            });
+        }
+    };
+    ayb_db
+        .update_database_by_id(
+            database.id,
+            &PartialDatabase {
+                public_sharing_level: Some(public_sharing_level as i16),
+            },
+        )
+        .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
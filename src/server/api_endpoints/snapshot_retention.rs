@@ -0,0 +1,79 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabasePath, SnapshotRetention};
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_manage_database;
+use crate::server::snapshots::retention::{retention_policy, snapshots_to_prune};
+use crate::server::snapshots::storage::SnapshotStorage;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+use chrono::Utc;
+
+#[get(
+    "/{entity}/{database}/snapshot_retention",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn snapshot_retention(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<SnapshotRetention>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let Some(ref snapshot_config) = ayb_config.snapshots else {
+        return Ok(web::Json(SnapshotRetention {
+            max_snapshots: None,
+            policy: None,
+            source: None,
+            snapshots_to_prune: Vec::new(),
+        }));
+    };
+    let policy = retention_policy(snapshot_config, &ayb_db, &database).await?;
+    let max_snapshots = snapshot_config
+        .automation
+        .as_ref()
+        .map(|automation| automation.max_snapshots);
+    // Snapshots are only pruned when they're taken automatically.
+    let mut pruned = Vec::new();
+    if let Some(max_snapshots) = max_snapshots {
+        let snapshots = SnapshotStorage::new(snapshot_config)
+            .await?
+            .list_snapshots(entity_slug, database_slug)
+            .await?;
+        let ids_to_prune: Vec<String> = snapshots_to_prune(
+            &snapshots,
+            max_snapshots.into(),
+            policy.as_ref().map(|(policy, _)| policy),
+            Utc::now(),
+        )
+        .into_iter()
+        .map(|snapshot| snapshot.snapshot_id.clone())
+        .collect();
+        pruned = snapshots
+            .into_iter()
+            .filter(|snapshot| ids_to_prune.contains(&snapshot.snapshot_id))
+            .collect();
+    }
+    Ok(web::Json(SnapshotRetention {
+        max_snapshots,
+        source: policy
+            .as_ref()
+            .map(|(_, source)| source.to_str().to_string()),
+        policy: policy.map(|(policy, _)| policy),
+        snapshots_to_prune: pruned,
+    }))
+}
@@ -0,0 +1,60 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::InstantiatedEntity;
+use crate::error::AybError;
+use crate::http::structs::{EntityDatabasePath, SnapshotSchedule};
+use crate::server::config::AybConfig;
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{get, web};
+
+#[get(
+    "/{entity}/{database}/snapshot_schedule",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn snapshot_schedule(
+    path: web::Path<EntityDatabasePath>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    ayb_config: web::Data<AybConfig>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<SnapshotSchedule>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    // Without automation on the server, no database is snapshotted
+    // automatically, whatever its own schedule says.
+    let server_interval = ayb_config
+        .snapshots
+        .as_ref()
+        .and_then(|snapshot_config| snapshot_config.automation.as_ref())
+        .map(|automation| automation.interval.clone());
+    let policy = ayb_db.get_snapshot_policy(database.id).await?;
+    let (interval, automatic_snapshots) = policy
+        .map(|policy| (policy.snapshot_interval, policy.automatic_snapshots))
+        .unwrap_or((None, None));
+    let source = if interval.is_some() || automatic_snapshots.is_some() {
+        "database"
+    } else {
+        "server"
+    };
+    let is_automated = server_interval.is_some();
+    Ok(web::Json(SnapshotSchedule {
+        automatic_snapshots: is_automated && automatic_snapshots != Some(false),
+        interval: if is_automated {
+            interval.or(server_interval)
+        } else {
+            None
+        },
+        source: source.to_string(),
+    }))
+}
@@ -0,0 +1,168 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, InstantiatedEntity};
+use crate::error::AybError;
+use crate::http::structs::{ChangeSubscriptionQuery, EntityDatabasePath};
+use crate::server::change_feed::{visible_changes, ChangeEvent, ChangeFeed, ChangeSubscription};
+use crate::server::permissions::{highest_query_access_level, query_restrictions};
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::http::header::{self, ContentEncoding};
+use actix_web::rt::time::timeout;
+use actix_web::web::Bytes;
+use actix_web::{get, web, HttpResponse};
+use futures_util::stream::{self, StreamExt};
+use std::convert::Infallible;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Idle connections get a comment this often, so that proxies don't
+/// close them.
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Everything a subscription's stream needs to re-check the
+/// subscriber's access before sending each event.
+struct Subscriber {
+    subscription: ChangeSubscription,
+    entity_slug: String,
+    database_slug: String,
+    authenticated_entity: InstantiatedEntity,
+    api_token: Option<APIToken>,
+    tables: Vec<String>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+}
+
+impl Subscriber {
+    /// The changes in `event` the subscriber may see, or `None` if it
+    /// can no longer read the database (or the database is gone).
+    async fn visible_event(&self, event: &ChangeEvent) -> Result<Option<ChangeEvent>, AybError> {
+        let database = self
+            .ayb_db
+            .get_database(&self.entity_slug, &self.database_slug)
+            .await?;
+        if database.id != event.database_id
+            || highest_query_access_level(
+                &self.authenticated_entity,
+                &database,
+                self.api_token.as_ref(),
+                &self.ayb_db,
+            )
+            .await?
+            .is_none()
+        {
+            return Ok(None);
+        }
+        let restrictions =
+            query_restrictions(&self.authenticated_entity, &database, &self.ayb_db).await?;
+        Ok(Some(ChangeEvent {
+            changes: visible_changes(&event.changes, &self.tables, restrictions.as_ref()),
+            ..event.clone()
+        }))
+    }
+
+    /// The next message to send, or `None` to end the stream.
+    async fn next_message(&mut self) -> Option<Bytes> {
+        loop {
+            match timeout(KEEP_ALIVE_INTERVAL, self.subscription.recv()).await {
+                Err(_) => return Some(Bytes::from_static(b": keep-alive\n\n")),
+                Ok(Err(RecvError::Closed)) => return None,
+                Ok(Err(RecvError::Lagged(missed))) => {
+                    return Some(server_sent_event(
+                        "lagged",
+                        &serde_json::json!({ "missed": missed }).to_string(),
+                    ))
+                }
+                Ok(Ok(event)) => match self.visible_event(&event).await {
+                    Ok(Some(event)) if event.changes.is_empty() => continue,
+                    Ok(Some(event)) => {
+                        return serde_json::to_string(&event)
+                            .ok()
+                            .map(|data| server_sent_event("change", &data))
+                    }
+                    Ok(None) => return None,
+                    Err(err) => {
+                        eprintln!("Unable to check change subscriber's access: {err}");
+                        return None;
+                    }
+                },
+            }
+        }
+    }
+}
+
+fn server_sent_event(event: &str, data: &str) -> Bytes {
+    Bytes::from(format!("event: {event}\ndata: {data}\n\n"))
+}
+
+/// Stream the changes committed to a database as Server-Sent Events.
+/// Subscribers see changes to the tables they can read, and the stream
+/// ends if they lose access to the database.
+#[get(
+    "/{entity}/{database}/changes",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn subscribe_to_changes(
+    path: web::Path<EntityDatabasePath>,
+    query: web::Query<ChangeSubscriptionQuery>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    change_feed: web::Data<ChangeFeed>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = path.entity.to_lowercase();
+    let database_slug = path.database.clone();
+    let database = ayb_db.get_database(&entity_slug, &database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+    let api_token = api_token.map(|token| token.into_inner());
+
+    if highest_query_access_level(
+        &authenticated_entity,
+        &database,
+        api_token.as_ref(),
+        &ayb_db,
+    )
+    .await?
+    .is_none()
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't subscribe to changes in database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let tables = query
+        .tables
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|table| !table.is_empty())
+        .map(str::to_string)
+        .collect();
+    let subscriber = Subscriber {
+        subscription: change_feed.subscribe(database.id),
+        entity_slug,
+        database_slug,
+        authenticated_entity,
+        api_token,
+        tables,
+        ayb_db,
+    };
+    // The first message tells the client that it's subscribed, so it
+    // won't miss changes committed from then on.
+    let messages = stream::once(async { Bytes::from_static(b": subscribed\n\n") }).chain(
+        stream::unfold(subscriber, |mut subscriber| async move {
+            subscriber
+                .next_message()
+                .await
+                .map(|message| (message, subscriber))
+        }),
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Compressing the stream would buffer events.
+        .insert_header(ContentEncoding::Identity)
+        .streaming(messages.map(Ok::<_, Infallible>)))
+}
@@ -0,0 +1,76 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{
+    APIToken, DatabaseTransfer, EntityDatabaseSharingLevel, InstantiatedEntity,
+};
+use std::str::FromStr;
+
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabasePath};
+use crate::server::permissions::can_administer_database;
+use crate::server::utils::{
+    get_lowercased_header, get_optional_header, unwrap_authenticated_entity,
+};
+use actix_web::{post, web, HttpRequest};
+
+#[post(
+    "/{entity}/{database}/transfer",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn transfer_database(
+    path: web::Path<EntityDatabasePath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let recipient = ayb_db
+        .get_entity_by_slug(&get_lowercased_header(&req, "entity-for-transfer")?)
+        .await?;
+    // Unless told otherwise, the former owner keeps managing the
+    // database they're handing off.
+    let former_owner_sharing_level = match get_optional_header(&req, "former-owner-sharing-level")?
+    {
+        Some(level) => EntityDatabaseSharingLevel::from_str(&level)?,
+        None => EntityDatabaseSharingLevel::Manager,
+    };
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_administer_database(
+        &authenticated_entity,
+        &database,
+        api_token.as_deref(),
+        &ayb_db,
+    )
+    .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't transfer database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    if recipient.id == database.entity_id {
+        return Err(AybError::Other {
+            message: format!(
+                "{} already owns {entity_slug}/{database_slug}",
+                recipient.slug
+            ),
+        });
+    }
+
+    // Proposing a new transfer replaces any pending one for the database.
+    ayb_db
+        .create_or_replace_database_transfer(&DatabaseTransfer {
+            database_id: database.id,
+            from_entity_id: database.entity_id,
+            to_entity_id: recipient.id,
+            former_owner_sharing_level: former_owner_sharing_level as i16,
+        })
+        .await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
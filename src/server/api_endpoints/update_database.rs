@@ -1,9 +1,12 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::{InstantiatedEntity, PartialDatabase, PublicSharingLevel};
+use crate::ayb_db::models::{
+    APIToken, AuditAction, InstantiatedEntity, PartialDatabase, PublicSharingLevel,
+};
 use std::str::FromStr;
 
 use crate::error::AybError;
 use crate::http::structs::{EmptyResponse, EntityDatabasePath};
+use crate::server::audit_log::record_audit_event;
 use crate::server::permissions::can_manage_database;
 use crate::server::utils::{get_optional_header, unwrap_authenticated_entity};
 use actix_web::{patch, web, HttpRequest, HttpResponse};
@@ -17,6 +20,7 @@ async fn update_database(
     req: HttpRequest,
     ayb_db: web::Data<Box<dyn AybDb>>,
     authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
 ) -> Result<HttpResponse, AybError> {
     let entity_slug = &path.entity.to_lowercase();
     let database_slug = &path.database;
@@ -27,9 +31,11 @@ async fn update_database(
         let mut partial_database = PartialDatabase {
             public_sharing_level: None,
         };
+        let mut changes = Vec::new();
         if let Some(level) = public_sharing_level {
-            partial_database.public_sharing_level =
-                Some(PublicSharingLevel::from_str(&level)? as i16);
+            let level = PublicSharingLevel::from_str(&level)?;
+            partial_database.public_sharing_level = Some(level as i16);
+            changes.push(format!("public sharing level to {}", level.to_str()));
         }
 
         // Check if there are any fields to update
@@ -44,6 +50,16 @@ async fn update_database(
         ayb_db
             .update_database_by_id(database.id, &partial_database)
             .await?;
+        record_audit_event(
+            &ayb_db,
+            database.entity_id,
+            Some(&database),
+            &authenticated_entity,
+            api_token.as_deref(),
+            AuditAction::Share,
+            format!("Set {}", changes.join(", ")),
+        )
+        .await?;
         Ok(HttpResponse::Ok().json(EmptyResponse {}))
     } else {
         Err(AybError::Other {
@@ -0,0 +1,41 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{EntityDatabaseSharingLevel, InstantiatedEntity};
+use std::str::FromStr;
+
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityPath};
+use crate::server::permissions::can_manage_organization;
+use crate::server::utils::{get_required_header, unwrap_authenticated_entity};
+use actix_web::{post, web, HttpRequest};
+
+#[post(
+    "/entity/{entity}/member_sharing_level",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn update_member_sharing_level(
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<web::Json<EmptyResponse>, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let sharing_level =
+        EntityDatabaseSharingLevel::from_str(&get_required_header(&req, "sharing-level")?)?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_organization(&authenticated_entity, &organization, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage organization {}",
+                authenticated_entity.slug, organization_slug
+            ),
+        });
+    }
+
+    ayb_db
+        .update_member_sharing_level(organization.id, sharing_level as i16)
+        .await?;
+
+    Ok(web::Json(EmptyResponse {}))
+}
@@ -0,0 +1,73 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{
+    EntityType, InstantiatedEntity, OrganizationMembership, OrganizationRole,
+};
+use std::str::FromStr;
+
+use crate::error::AybError;
+use crate::http::structs::EntityPath;
+use crate::server::permissions::can_manage_organization_member;
+use crate::server::utils::{
+    get_lowercased_header, get_required_header, unwrap_authenticated_entity,
+};
+use actix_web::{post, web, HttpRequest, HttpResponse};
+
+#[post(
+    "/entity/{entity}/members",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn update_organization_member(
+    path: web::Path<EntityPath>,
+    req: HttpRequest,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+) -> Result<HttpResponse, AybError> {
+    let organization_slug = &path.entity.to_lowercase();
+    let organization = ayb_db.get_entity_by_slug(organization_slug).await?;
+    let member = ayb_db
+        .get_entity_by_slug(&get_lowercased_header(&req, "entity-for-membership")?)
+        .await?;
+    let role = OrganizationRole::from_str(&get_required_header(&req, "organization-role")?)?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    // Changing an existing owner's role takes the same rights as
+    // making someone an owner.
+    let existing_role = match ayb_db
+        .get_organization_membership(organization.id, member.id)
+        .await?
+    {
+        Some(membership) => Some(OrganizationRole::try_from(membership.role)?),
+        None => None,
+    };
+    let role_at_stake = existing_role.map_or(role, |existing_role| existing_role.max(role));
+    if !can_manage_organization_member(&authenticated_entity, &organization, role_at_stake, &ayb_db)
+        .await?
+    {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't grant or revoke the {} role in {}",
+                authenticated_entity.slug,
+                role_at_stake.to_str(),
+                organization_slug
+            ),
+        });
+    }
+    if EntityType::try_from(member.entity_type)? != EntityType::User {
+        return Err(AybError::Other {
+            message: format!(
+                "{} is an organization, and only users can be members of an organization",
+                member.slug
+            ),
+        });
+    }
+
+    ayb_db
+        .update_or_create_organization_membership(&OrganizationMembership {
+            organization_id: organization.id,
+            member_id: member.id,
+            role: role as i16,
+        })
+        .await?;
+
+    Ok(HttpResponse::NoContent().into())
+}
@@ -0,0 +1,80 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity, SnapshotPolicy};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabasePath, SnapshotRetentionUpdate};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::can_manage_database;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{post, web, HttpResponse};
+
+#[post(
+    "/{entity}/{database}/update_snapshot_retention",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn update_snapshot_retention(
+    path: web::Path<EntityDatabasePath>,
+    body: web::Json<SnapshotRetentionUpdate>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+
+    let to_setting = |count: Option<u32>| {
+        count
+            .map(i32::try_from)
+            .transpose()
+            .map_err(|err| AybError::Other {
+                message: format!("Invalid snapshot retention: {err}"),
+            })
+    };
+    // Settings that are left out keep no snapshots of their period,
+    // unless every setting is left out, in which case the server's
+    // policy applies again.
+    let reset = body.hourly.is_none() && body.daily.is_none() && body.weekly.is_none();
+    let mut policy = ayb_db
+        .get_snapshot_policy(database.id)
+        .await?
+        .unwrap_or(SnapshotPolicy {
+            database_id: database.id,
+            ..Default::default()
+        });
+    policy.retain_hourly = to_setting(body.hourly.or((!reset).then_some(0)))?;
+    policy.retain_daily = to_setting(body.daily.or((!reset).then_some(0)))?;
+    policy.retain_weekly = to_setting(body.weekly.or((!reset).then_some(0)))?;
+    ayb_db.update_or_create_snapshot_policy(&policy).await?;
+
+    let details = if reset {
+        "Reset snapshot retention to the server's".to_string()
+    } else {
+        format!(
+            "Set snapshot retention to hourly {}, daily {}, weekly {}",
+            body.hourly.unwrap_or(0),
+            body.daily.unwrap_or(0),
+            body.weekly.unwrap_or(0)
+        )
+    };
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::UpdateSnapshotRetention,
+        details,
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(EmptyResponse {}))
+}
@@ -0,0 +1,80 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{APIToken, AuditAction, InstantiatedEntity, SnapshotPolicy};
+use crate::error::AybError;
+use crate::http::structs::{EmptyResponse, EntityDatabasePath, SnapshotScheduleUpdate};
+use crate::server::audit_log::record_audit_event;
+use crate::server::permissions::can_manage_database;
+use crate::server::snapshots::execution::parse_snapshot_interval;
+use crate::server::utils::unwrap_authenticated_entity;
+use actix_web::{post, web, HttpResponse};
+
+#[post(
+    "/{entity}/{database}/update_snapshot_schedule",
+    wrap = "actix_web_httpauth::middleware::HttpAuthentication::bearer(crate::server::server_runner::entity_validator)"
+)]
+async fn update_snapshot_schedule(
+    path: web::Path<EntityDatabasePath>,
+    body: web::Json<SnapshotScheduleUpdate>,
+    ayb_db: web::Data<Box<dyn AybDb>>,
+    authenticated_entity: Option<web::ReqData<InstantiatedEntity>>,
+    api_token: Option<web::ReqData<APIToken>>,
+) -> Result<HttpResponse, AybError> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database;
+    let database = ayb_db.get_database(entity_slug, database_slug).await?;
+    let authenticated_entity = unwrap_authenticated_entity(&authenticated_entity)?;
+
+    if !can_manage_database(&authenticated_entity, &database, &ayb_db).await? {
+        return Err(AybError::Other {
+            message: format!(
+                "Authenticated entity {} can't manage snapshots on database {}/{}",
+                authenticated_entity.slug, entity_slug, database_slug
+            ),
+        });
+    }
+    if let Some(interval) = &body.interval {
+        parse_snapshot_interval(interval)?;
+    }
+
+    let mut policy = ayb_db
+        .get_snapshot_policy(database.id)
+        .await?
+        .unwrap_or(SnapshotPolicy {
+            database_id: database.id,
+            ..Default::default()
+        });
+    // Settings that are left out stay as they were, unless every
+    // setting is left out, in which case the server's schedule applies
+    // again.
+    let mut changes = Vec::new();
+    if body.interval.is_none() && body.automatic_snapshots.is_none() {
+        policy.snapshot_interval = None;
+        policy.automatic_snapshots = None;
+        changes.push("Reset snapshot schedule to the server's".to_string());
+    }
+    if let Some(interval) = &body.interval {
+        policy.snapshot_interval = Some(interval.clone());
+        changes.push(format!("Set snapshot interval to {interval}"));
+    }
+    if let Some(automatic_snapshots) = body.automatic_snapshots {
+        policy.automatic_snapshots = Some(automatic_snapshots);
+        changes.push(if automatic_snapshots {
+            "Turned on automatic snapshots".to_string()
+        } else {
+            "Turned off automatic snapshots".to_string()
+        });
+    }
+    ayb_db.update_or_create_snapshot_policy(&policy).await?;
+
+    record_audit_event(
+        &ayb_db,
+        database.entity_id,
+        Some(&database),
+        &authenticated_entity,
+        api_token.as_deref(),
+        AuditAction::UpdateSnapshotSchedule,
+        changes.join(", "),
+    )
+    .await?;
+    Ok(HttpResponse::Ok().json(EmptyResponse {}))
+}
@@ -0,0 +1,76 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{
+    APIToken, AuditAction, AuditLogEntry, InstantiatedDatabase, InstantiatedEntity,
+};
+use crate::error::AybError;
+use crate::server::config::AybConfig;
+use actix_web::web;
+use std::time::Duration;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+/// How often entries older than the retention period are deleted.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Append an entry to the audit log of the namespace of `entity_id`,
+/// attributed to `actor` and, if they used one, `token`. Entries about
+/// a database are keyed by its id.
+pub async fn record_audit_event(
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    entity_id: i32,
+    database: Option<&InstantiatedDatabase>,
+    actor: &InstantiatedEntity,
+    token: Option<&APIToken>,
+    action: AuditAction,
+    details: String,
+) -> Result<(), AybError> {
+    ayb_db
+        .create_audit_log_entry(&AuditLogEntry {
+            entity_id,
+            database_id: database.map(|database| database.id),
+            database_slug: database.map(|database| database.slug.clone()),
+            actor_id: actor.id,
+            token_short_id: token.map(|token| token.short_token.clone()),
+            action: action as i16,
+            details,
+        })
+        .await
+}
+
+/// Whether queries (rather than only administrative actions) should
+/// be recorded in the audit log.
+pub fn audit_log_records_queries(config: &AybConfig) -> bool {
+    config
+        .audit_log
+        .as_ref()
+        .and_then(|audit_log| audit_log.log_queries)
+        .unwrap_or(false)
+}
+
+pub async fn schedule_audit_log_pruning(
+    config: AybConfig,
+    ayb_db: Box<dyn AybDb>,
+) -> Result<(), AybError> {
+    let Some(retention_days) = config
+        .audit_log
+        .as_ref()
+        .and_then(|audit_log| audit_log.retention_days)
+    else {
+        return Ok(());
+    };
+    let scheduler = JobScheduler::new().await?;
+    scheduler
+        .add(Job::new_repeated_async(PRUNE_INTERVAL, move |_, _| {
+            let ayb_db = ayb_db.clone();
+            Box::pin(async move {
+                let cutoff =
+                    chrono::Utc::now().naive_utc() - chrono::Duration::days(retention_days.into());
+                if let Err(err) = ayb_db.delete_audit_log_entries_before(cutoff).await {
+                    eprintln!("Unable to delete expired audit log entries: {err}");
+                }
+            })
+        })?)
+        .await?;
+    scheduler.shutdown_on_ctrl_c();
+    scheduler.start().await?;
+    Ok(())
+}
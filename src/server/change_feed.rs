@@ -0,0 +1,203 @@
+use crate::hosted_db::restrictions::QueryRestrictions;
+use crate::hosted_db::TableChange;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// How many of a database's events a subscriber can fall behind
+/// before it starts missing them.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// The changes one committed query made to a database.
+#[derive(Clone, Debug, Serialize)]
+pub struct ChangeEvent {
+    #[serde(skip)]
+    pub database_id: i32,
+    pub entity: String,
+    pub database: String,
+    pub changes: Vec<TableChange>,
+    pub committed_at: String,
+}
+
+/// Fans the changes queries commit out to live subscribers. Each
+/// database gets its own channel while it has subscribers, so that a
+/// busy database can't make another's subscribers miss events. Queries
+/// only capture changes while a database has subscribers (or webhooks),
+/// which the feed tells them from whether it has a channel.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    channels: Arc<Mutex<HashMap<i32, broadcast::Sender<Arc<ChangeEvent>>>>>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeFeed {
+    pub fn new() -> Self {
+        Self {
+            channels: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn has_subscribers(&self, database_id: i32) -> bool {
+        self.channels
+            .lock()
+            .map(|channels| channels.contains_key(&database_id))
+            .unwrap_or(false)
+    }
+
+    pub fn subscribe(&self, database_id: i32) -> ChangeSubscription {
+        let mut channels = self
+            .channels
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let receiver = channels
+            .entry(database_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe();
+        ChangeSubscription {
+            feed: self.clone(),
+            database_id,
+            receiver,
+        }
+    }
+
+    pub fn publish(&self, event: ChangeEvent) {
+        if let Ok(channels) = self.channels.lock() {
+            if let Some(sender) = channels.get(&event.database_id) {
+                // Sending only fails when nobody is subscribed.
+                let _ = sender.send(Arc::new(event));
+            }
+        }
+    }
+}
+
+/// A subscription to one database's changes. The database's channel
+/// is removed when its last subscription is dropped.
+pub struct ChangeSubscription {
+    feed: ChangeFeed,
+    database_id: i32,
+    receiver: broadcast::Receiver<Arc<ChangeEvent>>,
+}
+
+impl ChangeSubscription {
+    /// The next event for the subscribed database. Fails with
+    /// `RecvError::Lagged` if the subscriber fell too far behind and
+    /// missed some of them.
+    pub async fn recv(&mut self) -> Result<Arc<ChangeEvent>, broadcast::error::RecvError> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for ChangeSubscription {
+    fn drop(&mut self) {
+        if let Ok(mut channels) = self.feed.channels.lock() {
+            // This subscription's receiver is only dropped after this
+            // runs, so it's the last one if it's the only one left.
+            if channels
+                .get(&self.database_id)
+                .is_some_and(|sender| sender.receiver_count() <= 1)
+            {
+                channels.remove(&self.database_id);
+            }
+        }
+    }
+}
+
+/// The `changes` a subscriber may see: those to `tables` (or to every
+/// table, if it's empty) that `restrictions` let it read.
+pub fn visible_changes(
+    changes: &[TableChange],
+    tables: &[String],
+    restrictions: Option<&QueryRestrictions>,
+) -> Vec<TableChange> {
+    changes
+        .iter()
+        .filter(|change| {
+            (tables.is_empty()
+                || tables
+                    .iter()
+                    .any(|table| table.eq_ignore_ascii_case(&change.table)))
+                && restrictions.is_none_or(|restrictions| restrictions.can_read(&change.table, ""))
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hosted_db::ChangeOperation;
+
+    #[test]
+    fn test_visible_changes() {
+        let change = |table: &str| TableChange {
+            table: table.to_string(),
+            operation: ChangeOperation::Insert,
+            row_count: 1,
+            rowids: vec![1],
+        };
+        let changes = vec![change("scores"), change("secrets")];
+        let restrictions =
+            QueryRestrictions::from_pairs([("Scores".to_string(), Some("name".to_string()))]);
+
+        assert_eq!(visible_changes(&changes, &[], None), changes);
+        assert_eq!(
+            visible_changes(&changes, &["SECRETS".to_string()], None),
+            changes[1..]
+        );
+        assert_eq!(
+            visible_changes(&changes, &[], Some(&restrictions)),
+            changes[..1]
+        );
+        assert_eq!(
+            visible_changes(&changes, &["secrets".to_string()], Some(&restrictions)),
+            vec![]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_only_see_their_database() {
+        let feed = ChangeFeed::new();
+        assert!(!feed.has_subscribers(1));
+        let mut subscription = feed.subscribe(1);
+        let second_subscription = feed.subscribe(1);
+        assert!(feed.has_subscribers(1));
+        assert!(!feed.has_subscribers(2));
+
+        let event = |database_id: i32| ChangeEvent {
+            database_id,
+            entity: "entity".to_string(),
+            database: format!("{database_id}.sqlite"),
+            changes: Vec::new(),
+            committed_at: String::new(),
+        };
+        feed.publish(event(2));
+        feed.publish(event(1));
+        assert_eq!(subscription.recv().await.unwrap().database, "1.sqlite");
+
+        // Another database's events don't count against a subscriber's
+        // capacity.
+        let mut other_subscription = feed.subscribe(2);
+        for _ in 0..CHANNEL_CAPACITY + 1 {
+            feed.publish(event(2));
+        }
+        feed.publish(event(1));
+        assert_eq!(subscription.recv().await.unwrap().database, "1.sqlite");
+        assert!(matches!(
+            other_subscription.recv().await,
+            Err(broadcast::error::RecvError::Lagged(_))
+        ));
+        drop(other_subscription);
+        assert!(!feed.has_subscribers(2));
+
+        drop(subscription);
+        assert!(feed.has_subscribers(1));
+        drop(second_subscription);
+        assert!(!feed.has_subscribers(1));
+    }
+}
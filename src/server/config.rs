@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 use crate::error::AybError;
+use crate::server::snapshots::encryption::SnapshotKeys;
+use crate::server::snapshots::execution::parse_snapshot_interval;
 
 pub fn local_base_url(config: &AybConfig) -> String {
     format!("http://localhost:{}", config.port)
@@ -63,13 +65,47 @@ impl AybConfigEmailBackends {
 pub struct AybConfigSnapshotsAutomation {
     pub interval: String, // A time interval in Go's time.ParseDuration format (e.g., "5m" means "every 5 minutes",
     pub max_snapshots: u16,
+    // Keep older snapshots beyond `max_snapshots` on a
+    // grandfather-father-son schedule.
+    pub retention: Option<AybConfigSnapshotsRetention>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigSnapshotsRetention {
+    pub hourly: Option<u32>, // Keep the newest snapshot of each of the last `hourly` hours
+    pub daily: Option<u32>,  // ...of each of the last `daily` days
+    pub weekly: Option<u32>, // ...and of each of the last `weekly` weeks
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigSnapshotsPointInTime {
+    pub interval: String, // How often to ship new write-ahead log frames, in the same format as `automation.interval`
+    pub retention: String, // How far back restores can go, in the same format
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigSnapshotsEncryption {
+    pub key: String, // A base64-encoded 32-byte key that encrypts new snapshots
+    // Keys that encrypted snapshots before `key` replaced them, which
+    // are needed to restore those snapshots until they're pruned.
+    pub previous_keys: Option<Vec<String>>,
+    // Restore snapshots stored before encryption was turned on, which
+    // aren't encrypted (defaults to false, so that no one who can
+    // write to snapshot storage can slip in a snapshot of their own).
+    pub read_unencrypted: Option<bool>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigSnapshotsLocal {
+    pub path: String, // The directory to store snapshots in
 }
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AybConfigSnapshots {
-    pub access_key_id: String,
-    pub secret_access_key: String,
-    pub bucket: String,
+    // S3-compatible storage, which is required unless `local` is set.
+    pub access_key_id: Option<String>,
+    pub secret_access_key: Option<String>,
+    pub bucket: Option<String>,
     pub path_prefix: String,
     pub endpoint_url: Option<String>,
     pub region: Option<String>,
@@ -79,7 +115,61 @@ pub struct AybConfigSnapshots {
     // `true` (it defaults to `false`), we include the bucket
     // in the path.
     pub force_path_style: Option<bool>,
+    // Store snapshots in a local directory rather than S3.
+    pub local: Option<AybConfigSnapshotsLocal>,
     pub automation: Option<AybConfigSnapshotsAutomation>,
+    pub point_in_time: Option<AybConfigSnapshotsPointInTime>,
+    // Encrypt snapshots before storing them.
+    pub encryption: Option<AybConfigSnapshotsEncryption>,
+    // How long a snapshot fetched to be queried stays on disk after its
+    // last query, in the same format as `automation.interval` (it
+    // defaults to "10m").
+    pub query_cache_idle: Option<String>,
+}
+
+impl AybConfigSnapshots {
+    pub fn validate(&self) -> Result<(), AybError> {
+        let s3_settings = [&self.access_key_id, &self.secret_access_key, &self.bucket];
+        if self.local.is_some() {
+            if s3_settings.iter().any(|setting| setting.is_some()) {
+                return Err(AybError::ConfigurationError {
+                    message: "Snapshots can be stored in S3 or a local directory, not both. See snapshot configuration documentation at https://github.com/marcua/ayb#snapshots--backups".to_string(),
+                });
+            }
+        } else if s3_settings.iter().any(|setting| setting.is_none()) {
+            return Err(AybError::ConfigurationError {
+                message: "Snapshots stored in S3 require `access_key_id`, `secret_access_key`, and `bucket`. See snapshot configuration documentation at https://github.com/marcua/ayb#snapshots--backups".to_string(),
+            });
+        }
+        if let Some(encryption) = &self.encryption {
+            SnapshotKeys::from_config(encryption)?;
+        }
+        if let Some(query_cache_idle) = &self.query_cache_idle {
+            parse_snapshot_interval(query_cache_idle)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigAuditLog {
+    // Administrative actions (sharing, tokens, snapshot restores, and
+    // creating or deleting databases) are always logged. When
+    // `log_queries` is `true` (it defaults to `false`), queries that
+    // change a database's data or schema are logged too.
+    pub log_queries: Option<bool>,
+    // Entries older than this many days are deleted. Entries are kept
+    // forever when unset.
+    pub retention_days: Option<u32>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AybConfigWebhooks {
+    // Webhooks can't deliver to loopback, private, link-local, or other
+    // addresses that aren't publicly routable, except on these hosts
+    // (e.g., "127.0.0.1" or "hooks.internal"), which are compared to
+    // the host of each webhook's URL.
+    pub allowed_hosts: Option<Vec<String>>,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -93,6 +183,8 @@ pub struct AybConfig {
     pub email: AybConfigEmailBackends,
     pub cors: AybConfigCors,
     pub snapshots: Option<AybConfigSnapshots>,
+    pub audit_log: Option<AybConfigAuditLog>,
+    pub webhooks: Option<AybConfigWebhooks>,
 }
 
 pub fn config_to_toml(ayb_config: AybConfig) -> Result<String, AybError> {
@@ -127,6 +219,8 @@ pub fn default_server_config() -> AybConfig {
             origin: "*".to_string(),
         },
         snapshots: None,
+        audit_log: None,
+        webhooks: None,
     }
 }
 
@@ -163,8 +257,11 @@ pub fn read_config(config_path: &Path) -> Result<AybConfig, AybError> {
         }
     })?;
 
-    // Validate email configuration
+    // Validate email and snapshot configuration
     ayb_config.email.validate()?;
+    if let Some(snapshots) = &ayb_config.snapshots {
+        snapshots.validate()?;
+    }
 
     Ok(ayb_config)
 }
@@ -231,6 +328,76 @@ mod tests {
         assert!(error_message.contains("https://github.com/marcua/ayb#email-configuration"));
     }
 
+    fn snapshot_config() -> AybConfigSnapshots {
+        AybConfigSnapshots {
+            access_key_id: None,
+            secret_access_key: None,
+            bucket: None,
+            path_prefix: "".to_string(),
+            endpoint_url: None,
+            region: None,
+            force_path_style: None,
+            local: None,
+            automation: None,
+            point_in_time: None,
+            encryption: None,
+            query_cache_idle: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshots_validation_s3() {
+        let mut config = snapshot_config();
+        config.access_key_id = Some("key".to_string());
+        config.secret_access_key = Some("secret".to_string());
+        assert!(config.validate().is_err());
+        config.bucket = Some("bucket".to_string());
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_snapshots_validation_local() {
+        let mut config = snapshot_config();
+        config.local = Some(AybConfigSnapshotsLocal {
+            path: "/tmp/snapshots".to_string(),
+        });
+        assert!(config.validate().is_ok());
+        config.bucket = Some("bucket".to_string());
+        let error_message = config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("not both"));
+    }
+
+    #[test]
+    fn test_snapshots_validation_encryption() {
+        let mut config = snapshot_config();
+        config.local = Some(AybConfigSnapshotsLocal {
+            path: "/tmp/snapshots".to_string(),
+        });
+        config.encryption = Some(AybConfigSnapshotsEncryption {
+            key: "7Bfo0GZ2vDmoFq3Xu0nEXnuNzNoSLjjgV/jZOtYUVAY=".to_string(),
+            previous_keys: Some(vec!["not-a-key".to_string()]),
+            read_unencrypted: None,
+        });
+        let error_message = config.validate().unwrap_err().to_string();
+        assert!(error_message.contains("32 bytes"));
+        config.encryption.as_mut().unwrap().previous_keys = None;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_snapshots_validation_query_cache_idle() {
+        let mut config = snapshot_config();
+        config.local = Some(AybConfigSnapshotsLocal {
+            path: "/tmp/snapshots".to_string(),
+        });
+        for query_cache_idle in ["soon", "0s"] {
+            config.query_cache_idle = Some(query_cache_idle.to_string());
+            assert!(config.validate().is_err());
+        }
+        config.query_cache_idle = Some("10m".to_string());
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_env_var_override_public_url() {
         use std::env;
@@ -1,14 +1,87 @@
 use crate::ayb_db::db_interfaces::AybDb;
 use crate::ayb_db::models::{
-    APIToken, EntityDatabaseSharingLevel, InstantiatedDatabase, InstantiatedEntity,
-    PublicSharingLevel,
+    APIToken, DatabaseTransfer, EntityDatabaseSharingLevel, EntityType, InstantiatedDatabase,
+    InstantiatedEntity, OrganizationRole, PublicSharingLevel, SavedQueryWithAuthor,
 };
 use crate::error::AybError;
+use crate::hosted_db::restrictions::QueryRestrictions;
 use crate::hosted_db::QueryMode;
 use actix_web::web;
 
-fn is_owner(authenticated_entity: &InstantiatedEntity, database: &InstantiatedDatabase) -> bool {
-    authenticated_entity.id == database.entity_id
+/// The role `authenticated_entity` holds in the namespace of the entity
+/// with `entity_id`. An entity acting on its own behalf has the same
+/// rights as an owner of an organization.
+pub async fn organization_role(
+    authenticated_entity: &InstantiatedEntity,
+    entity_id: i32,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<Option<OrganizationRole>, AybError> {
+    if authenticated_entity.id == entity_id {
+        return Ok(Some(OrganizationRole::Owner));
+    }
+    match ayb_db
+        .get_organization_membership(entity_id, authenticated_entity.id)
+        .await?
+    {
+        Some(membership) => Ok(Some(OrganizationRole::try_from(membership.role)?)),
+        None => Ok(None),
+    }
+}
+
+/// Owners and admins of an organization act on its behalf.
+async fn can_act_for_entity(
+    authenticated_entity: &InstantiatedEntity,
+    entity_id: i32,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    Ok(matches!(
+        organization_role(authenticated_entity, entity_id, ayb_db).await?,
+        Some(OrganizationRole::Owner | OrganizationRole::Admin)
+    ))
+}
+
+/// The highest sharing level `authenticated_entity` has on `database`,
+/// whether through acting for its owner, an explicit permission, a
+/// team it belongs to, or the default level for members of the owning
+/// organization.
+async fn effective_sharing_level(
+    authenticated_entity: &InstantiatedEntity,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<Option<EntityDatabaseSharingLevel>, AybError> {
+    let member_level =
+        match organization_role(authenticated_entity, database.entity_id, ayb_db).await? {
+            Some(OrganizationRole::Owner | OrganizationRole::Admin) => {
+                return Ok(Some(EntityDatabaseSharingLevel::Manager));
+            }
+            Some(OrganizationRole::Member) => {
+                let organization = ayb_db.get_entity_by_id(database.entity_id).await?;
+                Some(EntityDatabaseSharingLevel::try_from(
+                    organization.member_sharing_level,
+                )?)
+            }
+            None => None,
+        };
+
+    let explicit_level = match ayb_db
+        .get_entity_database_permission(authenticated_entity, database)
+        .await?
+    {
+        Some(permission) => Some(EntityDatabaseSharingLevel::try_from(
+            permission.sharing_level,
+        )?),
+        None => None,
+    };
+
+    let mut team_level = None;
+    for level in ayb_db
+        .get_team_sharing_levels(authenticated_entity, database)
+        .await?
+    {
+        team_level = team_level.max(Some(EntityDatabaseSharingLevel::try_from(level)?));
+    }
+
+    Ok(member_level.max(explicit_level).max(team_level))
 }
 
 pub fn is_publicly_discoverable(database: &InstantiatedDatabase) -> Result<bool, AybError> {
@@ -19,11 +92,12 @@ pub fn is_publicly_discoverable(database: &InstantiatedDatabase) -> Result<bool,
     ))
 }
 
-pub fn can_create_database(
+pub async fn can_create_database(
     authenticated_entity: &InstantiatedEntity,
     desired_entity: &InstantiatedEntity,
-) -> bool {
-    authenticated_entity.id == desired_entity.id
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    can_act_for_entity(authenticated_entity, desired_entity.id, ayb_db).await
 }
 
 pub async fn can_discover_database(
@@ -31,43 +105,119 @@ pub async fn can_discover_database(
     database: &InstantiatedDatabase,
     ayb_db: &web::Data<Box<dyn AybDb>>,
 ) -> Result<bool, AybError> {
-    if is_owner(authenticated_entity, database) || is_publicly_discoverable(database)? {
+    if is_publicly_discoverable(database)? {
         return Ok(true);
     }
 
-    let permission = ayb_db
-        .get_entity_database_permission(authenticated_entity, database)
-        .await?;
-    match permission {
-        Some(permission) => match EntityDatabaseSharingLevel::try_from(permission.sharing_level)? {
+    Ok(matches!(
+        effective_sharing_level(authenticated_entity, database, ayb_db).await?,
+        Some(
             EntityDatabaseSharingLevel::Manager
-            | EntityDatabaseSharingLevel::ReadWrite
-            | EntityDatabaseSharingLevel::ReadOnly => Ok(true),
-            _ => Ok(false),
-        },
+                | EntityDatabaseSharingLevel::ReadWrite
+                | EntityDatabaseSharingLevel::ReadWriteData
+                | EntityDatabaseSharingLevel::ReadOnly
+        )
+    ))
+}
+
+pub async fn can_manage_database(
+    authenticated_entity: &InstantiatedEntity,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    Ok(
+        effective_sharing_level(authenticated_entity, database, ayb_db).await?
+            == Some(EntityDatabaseSharingLevel::Manager),
+    )
+}
+
+/// Deleting, renaming, or transferring a database is reserved for its
+/// owner (or an owner/admin of the owning organization). Managers can
+/// change who has access to a database, but not make it go away.
+/// Tokens scoped to a database or capped at a query permission level
+/// were granted to query, so they can't administer any database.
+pub async fn can_administer_database(
+    authenticated_entity: &InstantiatedEntity,
+    database: &InstantiatedDatabase,
+    token: Option<&APIToken>,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    if let Some(token) = token {
+        if !can_token_access_database(token, database) || token.query_permission_level.is_some() {
+            return Ok(false);
+        }
+    }
+    can_act_for_entity(authenticated_entity, database.entity_id, ayb_db).await
+}
+
+/// An entity's audit log can be read by anyone who acts for the
+/// entity. The entries about one of its databases can also be read by
+/// that database's managers.
+pub async fn can_view_audit_log(
+    authenticated_entity: &InstantiatedEntity,
+    entity: &InstantiatedEntity,
+    database: Option<&InstantiatedDatabase>,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    if can_act_for_entity(authenticated_entity, entity.id, ayb_db).await? {
+        return Ok(true);
+    }
+    match database {
+        Some(database) => can_manage_database(authenticated_entity, database, ayb_db).await,
         None => Ok(false),
     }
 }
 
-pub async fn can_manage_database(
+/// A saved query can be changed or deleted by its author and by the
+/// entities that manage its database.
+pub async fn can_modify_saved_query(
     authenticated_entity: &InstantiatedEntity,
     database: &InstantiatedDatabase,
+    saved_query: &SavedQueryWithAuthor,
     ayb_db: &web::Data<Box<dyn AybDb>>,
 ) -> Result<bool, AybError> {
-    if is_owner(authenticated_entity, database) {
+    if saved_query.author_id == authenticated_entity.id {
         return Ok(true);
     }
+    can_manage_database(authenticated_entity, database, ayb_db).await
+}
 
-    let permission = ayb_db
-        .get_entity_database_permission(authenticated_entity, database)
-        .await?;
-    match permission {
-        Some(permission) => match EntityDatabaseSharingLevel::try_from(permission.sharing_level)? {
-            EntityDatabaseSharingLevel::Manager => Ok(true),
-            _ => Ok(false),
-        },
-        None => Ok(false),
+/// Only the recipient of a database transfer (or an owner/admin of the
+/// recipient organization) can accept it.
+pub async fn can_accept_database_transfer(
+    authenticated_entity: &InstantiatedEntity,
+    transfer: &DatabaseTransfer,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    can_act_for_entity(authenticated_entity, transfer.to_entity_id, ayb_db).await
+}
+
+/// Owners and admins manage an organization's members and settings.
+pub async fn can_manage_organization(
+    authenticated_entity: &InstantiatedEntity,
+    organization: &InstantiatedEntity,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    Ok(
+        EntityType::try_from(organization.entity_type)? == EntityType::Organization
+            && can_act_for_entity(authenticated_entity, organization.id, ayb_db).await?,
+    )
+}
+
+/// Like `can_manage_organization`, but only owners can grant, change,
+/// or take away the owner role.
+pub async fn can_manage_organization_member(
+    authenticated_entity: &InstantiatedEntity,
+    organization: &InstantiatedEntity,
+    role: OrganizationRole,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<bool, AybError> {
+    if !can_manage_organization(authenticated_entity, organization, ayb_db).await? {
+        return Ok(false);
     }
+    Ok(role != OrganizationRole::Owner
+        || organization_role(authenticated_entity, organization.id, ayb_db).await?
+            == Some(OrganizationRole::Owner))
 }
 
 /// Check if a token can access a specific database.
@@ -101,11 +251,7 @@ fn apply_token_permission_cap(
     let token_perm = QueryMode::try_from(token_perm_level)?;
 
     // Return the more restrictive permission
-    Ok(match (user_perm, token_perm) {
-        (QueryMode::ReadOnly, _) => Some(QueryMode::ReadOnly),
-        (_, QueryMode::ReadOnly) => Some(QueryMode::ReadOnly),
-        (QueryMode::ReadWrite, QueryMode::ReadWrite) => Some(QueryMode::ReadWrite),
-    })
+    Ok(Some(user_perm.min(token_perm)))
 }
 
 pub async fn highest_query_access_level(
@@ -121,25 +267,15 @@ pub async fn highest_query_access_level(
         }
     }
 
-    let user_permission = if is_owner(authenticated_entity, database) {
-        Some(QueryMode::ReadWrite)
-    } else {
-        let permission = ayb_db
-            .get_entity_database_permission(authenticated_entity, database)
-            .await?;
-        match permission {
-            Some(permission) => {
-                match EntityDatabaseSharingLevel::try_from(permission.sharing_level)? {
-                    EntityDatabaseSharingLevel::Manager | EntityDatabaseSharingLevel::ReadWrite => {
-                        Some(QueryMode::ReadWrite)
-                    }
-                    EntityDatabaseSharingLevel::ReadOnly => Some(QueryMode::ReadOnly),
-                    _ => None,
-                }
+    let user_permission =
+        match effective_sharing_level(authenticated_entity, database, ayb_db).await? {
+            Some(EntityDatabaseSharingLevel::Manager | EntityDatabaseSharingLevel::ReadWrite) => {
+                Some(QueryMode::ReadWrite)
             }
-            None => None,
-        }
-    };
+            Some(EntityDatabaseSharingLevel::ReadWriteData) => Some(QueryMode::ReadWriteData),
+            Some(EntityDatabaseSharingLevel::ReadOnly) => Some(QueryMode::ReadOnly),
+            _ => None,
+        };
 
     // If user has explicit permission, apply token cap
     if user_permission.is_some() {
@@ -154,3 +290,48 @@ pub async fn highest_query_access_level(
 
     Ok(None)
 }
+
+/// The tables, views, and columns `authenticated_entity`'s queries on
+/// `database` are limited to, or `None` if they can touch all of them.
+/// Managers are never restricted. Otherwise an entity's own object
+/// grants apply, and entities that only reach the database through
+/// public access are bound by the grants on public access.
+pub async fn query_restrictions(
+    authenticated_entity: &InstantiatedEntity,
+    database: &InstantiatedDatabase,
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+) -> Result<Option<QueryRestrictions>, AybError> {
+    let sharing_level = effective_sharing_level(authenticated_entity, database, ayb_db).await?;
+    if sharing_level == Some(EntityDatabaseSharingLevel::Manager) {
+        return Ok(None);
+    }
+
+    let entity_grants = ayb_db
+        .list_database_object_permissions(database.id, Some(authenticated_entity.id))
+        .await?;
+    let grants = if !entity_grants.is_empty() {
+        entity_grants
+    } else if matches!(
+        sharing_level,
+        Some(
+            EntityDatabaseSharingLevel::ReadOnly
+                | EntityDatabaseSharingLevel::ReadWriteData
+                | EntityDatabaseSharingLevel::ReadWrite
+        )
+    ) {
+        return Ok(None);
+    } else {
+        ayb_db
+            .list_database_object_permissions(database.id, None)
+            .await?
+    };
+
+    if grants.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(QueryRestrictions::from_pairs(
+        grants
+            .into_iter()
+            .map(|grant| (grant.object_name, grant.column_name)),
+    )))
+}
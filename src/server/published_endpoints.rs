@@ -0,0 +1,132 @@
+use crate::error::AybError;
+use actix_web::http::header;
+use actix_web::{HttpRequest, HttpResponse};
+
+/// How long responses can be cached when an endpoint is published
+/// without a max age.
+pub const DEFAULT_MAX_AGE_SECONDS: u32 = 60;
+
+/// The query string parameter that picks a published endpoint's output
+/// format rather than providing a value for the query.
+pub const FORMAT_PARAMETER: &str = "format";
+
+#[derive(Debug, PartialEq)]
+pub enum EndpointFormat {
+    Json,
+    Csv,
+}
+
+impl EndpointFormat {
+    pub fn from_parameter(value: Option<&str>) -> Result<EndpointFormat, AybError> {
+        match value {
+            None | Some("json") => Ok(EndpointFormat::Json),
+            Some("csv") => Ok(EndpointFormat::Csv),
+            Some(other) => Err(AybError::Other {
+                message: format!("Unsupported format {other}. Use json or csv"),
+            }),
+        }
+    }
+
+    pub fn content_type(&self) -> &str {
+        match self {
+            EndpointFormat::Json => "application/json",
+            EndpointFormat::Csv => "text/csv",
+        }
+    }
+}
+
+/// Whether an `If-None-Match` header value matches `etag`.
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|candidate| candidate.trim().trim_start_matches("W/"))
+        .any(|candidate| candidate == "*" || candidate == etag)
+}
+
+/// Respond with `body`, letting clients and proxies cache it for
+/// `max_age_seconds`. The ETag is a hash of the body, so clients that
+/// already have the current result get an empty `304 Not Modified`.
+pub fn cached_response(
+    req: &HttpRequest,
+    format: &EndpointFormat,
+    body: String,
+    max_age_seconds: i32,
+) -> HttpResponse {
+    let etag = format!("\"{}\"", blake3::hash(body.as_bytes()).to_hex());
+    let cache_control = if max_age_seconds > 0 {
+        format!("public, max-age={max_age_seconds}")
+    } else {
+        "no-cache".to_string()
+    };
+    let not_modified = req
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header((header::CACHE_CONTROL, cache_control))
+        .insert_header((header::ETAG, etag));
+    if not_modified {
+        response.finish()
+    } else {
+        response.content_type(format.content_type()).body(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn parses_formats() {
+        assert_eq!(
+            EndpointFormat::from_parameter(None).unwrap(),
+            EndpointFormat::Json
+        );
+        assert_eq!(
+            EndpointFormat::from_parameter(Some("csv")).unwrap(),
+            EndpointFormat::Csv
+        );
+        assert!(EndpointFormat::from_parameter(Some("xml")).is_err());
+    }
+
+    #[test]
+    fn sets_caching_headers_and_honors_etags() {
+        let req = TestRequest::default().to_http_request();
+        let response = cached_response(&req, &EndpointFormat::Json, "[]".to_string(), 30);
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=30"
+        );
+        let etag = response.headers().get(header::ETAG).unwrap().clone();
+
+        let req = TestRequest::default()
+            .insert_header((header::IF_NONE_MATCH, etag.clone()))
+            .to_http_request();
+        let response = cached_response(&req, &EndpointFormat::Json, "[]".to_string(), 0);
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(
+            response.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-cache"
+        );
+
+        let response = cached_response(&req, &EndpointFormat::Json, "[1]".to_string(), 0);
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn matches_etag_lists() {
+        assert!(etag_matches("\"a\", W/\"b\"", "\"b\""));
+        assert!(etag_matches("*", "\"b\""));
+        assert!(!etag_matches("\"a\"", "\"b\""));
+    }
+}
@@ -0,0 +1,250 @@
+use crate::error::AybError;
+use crate::http::structs::{SavedQueryParameter, SavedQueryParameterType};
+use std::collections::{HashMap, HashSet};
+
+/// A `:name` placeholder found at `start..end` of a query.
+struct Placeholder<'a> {
+    start: usize,
+    end: usize,
+    name: &'a str,
+}
+
+/// Find the `:name` placeholders in `query`. Colons inside string
+/// literals, quoted identifiers, and comments don't start placeholders,
+/// and neither do `::` casts.
+fn placeholders(query: &str) -> Vec<Placeholder<'_>> {
+    let bytes = query.as_bytes();
+    let is_name_start = |c: u8| c.is_ascii_alphabetic() || c == b'_';
+    let is_name_char = |c: u8| c.is_ascii_alphanumeric() || c == b'_';
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // A doubled quote inside a literal ends it and immediately
+            // starts another, which skips the same text.
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+            b':' if bytes.get(i + 1).is_some_and(|c| is_name_start(*c)) => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && is_name_char(bytes[i]) {
+                    i += 1;
+                }
+                found.push(Placeholder {
+                    start,
+                    end: i,
+                    name: &query[start + 1..i],
+                });
+            }
+            _ => i += 1,
+        }
+    }
+    found
+}
+
+/// Turn `value` into a SQL literal of `parameter`'s type.
+fn to_literal(parameter: &SavedQueryParameter, value: &str) -> Result<String, AybError> {
+    let invalid = || AybError::Other {
+        message: format!(
+            "Parameter {} must be of type {}, but got {value}",
+            parameter.name,
+            parameter.parameter_type.to_str()
+        ),
+    };
+    match parameter.parameter_type {
+        SavedQueryParameterType::Text => Ok(format!("'{}'", value.replace('\'', "''"))),
+        SavedQueryParameterType::Integer => value
+            .trim()
+            .parse::<i64>()
+            .map(|value| value.to_string())
+            .map_err(|_| invalid()),
+        SavedQueryParameterType::Real => match value.trim().parse::<f64>() {
+            Ok(value) if value.is_finite() => Ok(format!("{value:?}")),
+            _ => Err(invalid()),
+        },
+    }
+}
+
+/// Check that every placeholder in `query` is declared exactly once in
+/// `parameters`, and that defaults match their parameter's type.
+pub fn validate_saved_query(
+    query: &str,
+    parameters: &[SavedQueryParameter],
+) -> Result<(), AybError> {
+    if query.trim().is_empty() {
+        return Err(AybError::Other {
+            message: "A saved query can't be empty".to_string(),
+        });
+    }
+    let mut declared = HashSet::new();
+    for parameter in parameters {
+        if !declared.insert(parameter.name.as_str()) {
+            return Err(AybError::Other {
+                message: format!("Parameter {} is declared more than once", parameter.name),
+            });
+        }
+        if let Some(default) = &parameter.default {
+            to_literal(parameter, default)?;
+        }
+    }
+    for placeholder in placeholders(query) {
+        if !declared.contains(placeholder.name) {
+            return Err(AybError::Other {
+                message: format!(
+                    "Query uses parameter :{} without declaring it",
+                    placeholder.name
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Replace the placeholders in `query` with `values` (or parameters'
+/// defaults), quoted according to each parameter's type. The result is
+/// run like any other query, so it's still limited by the caller's
+/// access to the database.
+pub fn render_saved_query(
+    query: &str,
+    parameters: &[SavedQueryParameter],
+    values: &HashMap<String, String>,
+) -> Result<String, AybError> {
+    if let Some(unknown) = values
+        .keys()
+        .find(|name| !parameters.iter().any(|parameter| &parameter.name == *name))
+    {
+        return Err(AybError::Other {
+            message: format!("Unknown parameter {unknown}"),
+        });
+    }
+
+    let mut rendered = String::with_capacity(query.len());
+    let mut copied_up_to = 0;
+    for placeholder in placeholders(query) {
+        let parameter = parameters
+            .iter()
+            .find(|parameter| parameter.name == placeholder.name)
+            .ok_or_else(|| AybError::Other {
+                message: format!(
+                    "Query uses parameter :{} without declaring it",
+                    placeholder.name
+                ),
+            })?;
+        let value = values
+            .get(&parameter.name)
+            .or(parameter.default.as_ref())
+            .ok_or_else(|| AybError::Other {
+                message: format!("Missing a value for parameter {}", parameter.name),
+            })?;
+        rendered.push_str(&query[copied_up_to..placeholder.start]);
+        rendered.push_str(&to_literal(parameter, value)?);
+        copied_up_to = placeholder.end;
+    }
+    rendered.push_str(&query[copied_up_to..]);
+    Ok(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(
+        name: &str,
+        parameter_type: SavedQueryParameterType,
+        default: Option<&str>,
+    ) -> SavedQueryParameter {
+        SavedQueryParameter {
+            name: name.to_string(),
+            parameter_type,
+            default: default.map(str::to_string),
+        }
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn finds_placeholders_outside_literals_comments_and_casts() {
+        let query = "SELECT ':skipped', \"a:b\", x::INTEGER -- :comment\n\
+                     FROM t /* :block */ WHERE a = :first AND b = :second_2";
+        let names: Vec<&str> = placeholders(query).iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["first", "second_2"]);
+    }
+
+    #[test]
+    fn renders_typed_literals() {
+        let parameters = vec![
+            parameter("name", SavedQueryParameterType::Text, None),
+            parameter("min", SavedQueryParameterType::Integer, None),
+            parameter("ratio", SavedQueryParameterType::Real, Some("0.5")),
+        ];
+        let rendered = render_saved_query(
+            "SELECT * FROM t WHERE name = :name AND n >= :min AND r < :ratio AND n < :min",
+            &parameters,
+            &values(&[("name", "O'Brien"), ("min", " 3 ")]),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered,
+            "SELECT * FROM t WHERE name = 'O''Brien' AND n >= 3 AND r < 0.5 AND n < 3"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        let parameters = vec![
+            parameter("min", SavedQueryParameterType::Integer, None),
+            parameter("ratio", SavedQueryParameterType::Real, None),
+        ];
+        let query = "SELECT :min, :ratio";
+        for provided in [
+            values(&[("min", "1")]),
+            values(&[("min", "1 OR 1=1"), ("ratio", "1")]),
+            values(&[("min", "1"), ("ratio", "NaN")]),
+            values(&[("min", "1"), ("ratio", "1"), ("other", "1")]),
+        ] {
+            assert!(render_saved_query(query, &parameters, &provided).is_err());
+        }
+    }
+
+    #[test]
+    fn validates_declarations() {
+        let text = |name| parameter(name, SavedQueryParameterType::Text, None);
+        assert!(validate_saved_query("SELECT :a", &[text("a")]).is_ok());
+        assert!(validate_saved_query("SELECT 1", &[text("unused")]).is_ok());
+        assert!(validate_saved_query("SELECT :a", &[]).is_err());
+        assert!(validate_saved_query("SELECT :a", &[text("a"), text("a")]).is_err());
+        assert!(validate_saved_query(" ", &[]).is_err());
+        assert!(validate_saved_query(
+            "SELECT :a",
+            &[parameter(
+                "a",
+                SavedQueryParameterType::Integer,
+                Some("many")
+            )]
+        )
+        .is_err());
+    }
+}
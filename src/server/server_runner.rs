@@ -3,10 +3,15 @@ use crate::ayb_db::db_interfaces::AybDb;
 use crate::email::create_email_backends;
 use crate::error::AybError;
 use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::server::audit_log::schedule_audit_log_pruning;
+use crate::server::change_feed::ChangeFeed;
 use crate::server::config::read_config;
 use crate::server::config::AybConfigCors;
+use crate::server::snapshots::cache::{schedule_snapshot_cache_eviction, SnapshotCache};
 use crate::server::snapshots::execution::schedule_periodic_snapshots;
+use crate::server::snapshots::wal::schedule_wal_replication;
 use crate::server::tokens::retrieve_and_validate_api_token;
+use crate::server::webhooks::schedule_webhook_deliveries;
 use crate::server::{api_endpoints, ui_endpoints};
 use actix_cors::Cors;
 use actix_web::body::MessageBody;
@@ -37,6 +42,8 @@ pub fn config(cfg: &mut web::ServiceConfig) {
     //     `database_details`) — anonymous requests pass through, but
     //     invalid / revoked / expired bearer tokens are still rejected (a
     //     bad token is never silently downgraded to anonymous).
+    //   - No `wrap` for `run_published_endpoint`, which serves published
+    //     queries to anyone and never looks at credentials.
     cfg.service(
         web::scope("/v1")
             .service(api_endpoints::create_database_endpoint)
@@ -45,12 +52,50 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             .service(api_endpoints::query_endpoint)
             .service(api_endpoints::entity_details_endpoint)
             .service(api_endpoints::update_profile_endpoint)
+            .service(api_endpoints::list_organization_members_endpoint)
+            .service(api_endpoints::update_organization_member_endpoint)
+            .service(api_endpoints::remove_organization_member_endpoint)
+            .service(api_endpoints::update_member_sharing_level_endpoint)
+            .service(api_endpoints::list_teams_endpoint)
+            .service(api_endpoints::create_team_endpoint)
+            .service(api_endpoints::delete_team_endpoint)
+            .service(api_endpoints::add_team_member_endpoint)
+            .service(api_endpoints::remove_team_member_endpoint)
             .service(api_endpoints::list_snapshots_endpoint)
             .service(api_endpoints::restore_snapshot_endpoint)
+            .service(api_endpoints::diff_endpoint)
+            .service(api_endpoints::create_snapshot_endpoint)
+            .service(api_endpoints::snapshot_retention_endpoint)
+            .service(api_endpoints::update_snapshot_retention_endpoint)
+            .service(api_endpoints::snapshot_schedule_endpoint)
+            .service(api_endpoints::update_snapshot_schedule_endpoint)
             .service(api_endpoints::share_endpoint)
             .service(api_endpoints::list_database_permissions_endpoint)
             .service(api_endpoints::list_tokens_endpoint)
-            .service(api_endpoints::revoke_token_endpoint),
+            .service(api_endpoints::revoke_token_endpoint)
+            .service(api_endpoints::rename_database_endpoint)
+            .service(api_endpoints::transfer_database_endpoint)
+            .service(api_endpoints::cancel_database_transfer_endpoint)
+            .service(api_endpoints::accept_database_transfer_endpoint)
+            .service(api_endpoints::list_database_transfers_endpoint)
+            .service(api_endpoints::list_audit_log_endpoint)
+            .service(api_endpoints::fork_database_endpoint)
+            .service(api_endpoints::list_saved_queries_endpoint)
+            .service(api_endpoints::save_query_endpoint)
+            .service(api_endpoints::run_saved_query_endpoint)
+            .service(api_endpoints::delete_saved_query_endpoint)
+            .service(api_endpoints::list_published_endpoints_endpoint)
+            .service(api_endpoints::publish_endpoint_endpoint)
+            .service(api_endpoints::unpublish_endpoint_endpoint)
+            .service(api_endpoints::run_published_endpoint_endpoint)
+            .service(api_endpoints::create_webhook_endpoint)
+            .service(api_endpoints::list_webhooks_endpoint)
+            .service(api_endpoints::delete_webhook_endpoint)
+            .service(api_endpoints::list_webhook_deliveries_endpoint)
+            .service(api_endpoints::subscribe_to_changes_endpoint)
+            // Registered last: `DELETE /{entity}/{database}` would
+            // otherwise also match `DELETE /tokens/{short_token}`.
+            .service(api_endpoints::delete_database_endpoint),
     );
 
     // CSRF note: the cookie-authenticated UI endpoints below rely on the
@@ -81,13 +126,25 @@ pub fn config(cfg: &mut web::ServiceConfig) {
         .service(ui_endpoints::entity_details_endpoint)
         .service(ui_endpoints::create_database_endpoint)
         .service(ui_endpoints::update_profile_endpoint)
+        .service(ui_endpoints::add_member_endpoint)
+        .service(ui_endpoints::remove_member_endpoint)
+        .service(ui_endpoints::update_member_sharing_level_ui_endpoint)
         .service(ui_endpoints::database_endpoint)
         .service(ui_endpoints::query_endpoint)
+        .service(ui_endpoints::run_saved_query_endpoint)
         .service(ui_endpoints::update_public_sharing_endpoint)
         .service(ui_endpoints::share_with_entity_endpoint)
         .service(ui_endpoints::database_permissions_endpoint)
         .service(ui_endpoints::database_snapshots_endpoint)
-        .service(ui_endpoints::restore_snapshot_endpoint);
+        .service(ui_endpoints::restore_snapshot_endpoint)
+        .service(ui_endpoints::diff_endpoint)
+        .service(ui_endpoints::create_snapshot_endpoint)
+        .service(ui_endpoints::rename_database_endpoint)
+        .service(ui_endpoints::delete_database_endpoint)
+        .service(ui_endpoints::transfer_database_endpoint)
+        .service(ui_endpoints::accept_database_transfer_endpoint)
+        .service(ui_endpoints::cancel_database_transfer_endpoint)
+        .service(ui_endpoints::fork_database_endpoint);
 }
 
 /// Validate `token` against the metadata DB attached to `req`, and on
@@ -168,14 +225,42 @@ pub async fn run_server(config_path: &Path) -> std::io::Result<()> {
         .expect("unable to connect to ayb database");
     let email_backends = create_email_backends(&ayb_conf.email);
 
-    // Create the daemon registry for managing persistent query runner processes
-    let daemon_registry = DaemonRegistry::new();
+    // Create the daemon registry for managing persistent query runner
+    // processes. With point-in-time recovery, WAL replication (rather
+    // than queries) checkpoints SQLite's write-ahead logs.
+    let point_in_time_recovery = ayb_conf
+        .snapshots
+        .as_ref()
+        .is_some_and(|snapshots| snapshots.point_in_time.is_some());
+    let daemon_registry = DaemonRegistry::new().retain_wal(point_in_time_recovery);
     // Clone for cleanup handler before moving into closure
     let cleanup_daemon_registry = daemon_registry.clone();
+    let change_feed = ChangeFeed::new();
+    let snapshot_cache = SnapshotCache::new();
 
     schedule_periodic_snapshots(ayb_conf_for_server.clone(), ayb_db.clone())
         .await
         .expect("unable to start periodic snapshot scheduler");
+    schedule_wal_replication(
+        ayb_conf_for_server.clone(),
+        ayb_db.clone(),
+        daemon_registry.clone(),
+    )
+    .await
+    .expect("unable to start WAL replication scheduler");
+    schedule_snapshot_cache_eviction(
+        ayb_conf_for_server.clone(),
+        snapshot_cache.clone(),
+        daemon_registry.clone(),
+    )
+    .await
+    .expect("unable to start snapshot cache eviction scheduler");
+    schedule_audit_log_pruning(ayb_conf_for_server.clone(), ayb_db.clone())
+        .await
+        .expect("unable to start audit log pruning scheduler");
+    schedule_webhook_deliveries(ayb_conf_for_server.clone(), ayb_db.clone())
+        .await
+        .expect("unable to start webhook delivery scheduler");
 
     println!("Starting server {}:{}...", ayb_conf.host, ayb_conf.port);
     crate::hosted_db::sandbox::print_isolation_status(
@@ -193,6 +278,8 @@ pub async fn run_server(config_path: &Path) -> std::io::Result<()> {
             .app_data(web::Data::new(ayb_conf_for_server.clone()))
             .app_data(web::Data::new(email_backends.clone()))
             .app_data(web::Data::new(daemon_registry.clone()))
+            .app_data(web::Data::new(change_feed.clone()))
+            .app_data(web::Data::new(snapshot_cache.clone()))
             .configure(config)
     })
     .bind((ayb_conf.host, ayb_conf.port))?
@@ -1,4 +1,12 @@
+pub mod cache;
+pub mod chunking;
+pub mod codec;
+pub mod encryption;
 pub mod execution;
 pub mod hashes;
 pub mod models;
+pub mod recovery;
+pub mod retention;
 pub mod storage;
+pub mod store;
+pub mod wal;
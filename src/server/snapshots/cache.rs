@@ -0,0 +1,234 @@
+use crate::ayb_db::models::DBType;
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::paths::{snapshot_cache_parent_path, snapshot_cache_path};
+use crate::hosted_db::{engine_for, QueryOutput};
+use crate::server::config::AybConfig;
+use crate::server::snapshots::execution::parse_snapshot_interval;
+use crate::server::snapshots::storage::SnapshotStorage;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+const DEFAULT_IDLE: &str = "10m";
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A snapshot fetched to be queried.
+struct CachedSnapshot {
+    // Where the snapshot is fetched to, `{directory}/{database_slug}`.
+    directory: PathBuf,
+    database_slug: String,
+    fetched: bool,
+    last_used: Instant,
+}
+
+impl CachedSnapshot {
+    fn db_path(&self) -> PathBuf {
+        self.directory.join(&self.database_slug)
+    }
+}
+
+/// A cached snapshot, which is locked until this is dropped.
+pub struct FetchedSnapshot {
+    snapshot: OwnedMutexGuard<CachedSnapshot>,
+}
+
+impl FetchedSnapshot {
+    pub fn db_path(&self) -> PathBuf {
+        self.snapshot.db_path()
+    }
+}
+
+/// Snapshots that were fetched to be queried, which stay on disk (and
+/// keep their read-only daemon) until they've been idle for a while.
+/// Snapshot ids describe their contents, so a fetched snapshot never
+/// goes stale.
+#[derive(Clone, Default)]
+pub struct SnapshotCache {
+    // Each snapshot is locked while it's fetched or queried, so that it
+    // isn't evicted out from under a query.
+    snapshots: Arc<Mutex<HashMap<PathBuf, Arc<Mutex<CachedSnapshot>>>>>,
+}
+
+impl SnapshotCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `query` read-only against snapshot `snapshot_id` of
+    /// `{entity_slug}/{database_slug}`, fetching the snapshot from
+    /// `snapshot_storage` if it isn't cached.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn query(
+        &self,
+        snapshot_storage: &SnapshotStorage,
+        daemon_registry: &DaemonRegistry,
+        data_path: &str,
+        entity_slug: &str,
+        database_slug: &str,
+        snapshot_id: &str,
+        db_type: &DBType,
+        query: &str,
+    ) -> Result<QueryOutput, AybError> {
+        let snapshot = self
+            .fetch(
+                snapshot_storage,
+                data_path,
+                entity_slug,
+                database_slug,
+                snapshot_id,
+                db_type,
+            )
+            .await?;
+        daemon_registry
+            .execute_read_only_query(&snapshot.db_path(), query, db_type)
+            .await
+    }
+
+    /// Fetch snapshot `snapshot_id` of `{entity_slug}/{database_slug}`
+    /// from `snapshot_storage` if it isn't cached. The snapshot isn't
+    /// evicted until the returned `FetchedSnapshot` is dropped, and
+    /// whoever fetches the same snapshot in the meantime waits for it.
+    pub async fn fetch(
+        &self,
+        snapshot_storage: &SnapshotStorage,
+        data_path: &str,
+        entity_slug: &str,
+        database_slug: &str,
+        snapshot_id: &str,
+        db_type: &DBType,
+    ) -> Result<FetchedSnapshot, AybError> {
+        // The snapshot id ends up in a path, and ids are hex-encoded
+        // hashes.
+        if snapshot_id.is_empty() || !snapshot_id.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AybError::SnapshotDoesNotExistError);
+        }
+        let directory = snapshot_cache_path(entity_slug, database_slug, snapshot_id, data_path);
+        let snapshot = self
+            .snapshots
+            .lock()
+            .await
+            .entry(directory.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(CachedSnapshot {
+                    directory,
+                    database_slug: database_slug.to_string(),
+                    fetched: false,
+                    last_used: Instant::now(),
+                }))
+            })
+            .clone();
+
+        let mut snapshot = snapshot.lock_owned().await;
+        snapshot.last_used = Instant::now();
+        if !snapshot.fetched {
+            // Anything already in the directory was left behind by a
+            // fetch that didn't finish.
+            if snapshot.directory.exists() {
+                fs::remove_dir_all(&snapshot.directory)?;
+            }
+            fs::create_dir_all(&snapshot.directory)?;
+            let fetched = async {
+                snapshot_storage
+                    .retrieve_snapshot(entity_slug, database_slug, snapshot_id, &snapshot.directory)
+                    .await?;
+                engine_for(db_type).prepare_read_only(&snapshot.db_path())
+            }
+            .await;
+            if let Err(err) = fetched {
+                fs::remove_dir_all(&snapshot.directory)?;
+                return Err(err);
+            }
+            snapshot.fetched = true;
+        }
+        Ok(FetchedSnapshot { snapshot })
+    }
+
+    /// Remove snapshots that haven't been queried for `idle`, along
+    /// with their daemons.
+    async fn evict(&self, idle: Duration, daemon_registry: &DaemonRegistry) {
+        let mut snapshots = self.snapshots.lock().await;
+        let mut evicted = Vec::new();
+        for (directory, snapshot) in snapshots.iter() {
+            // Snapshots that are being fetched or queried aren't idle.
+            let Ok(snapshot) = snapshot.try_lock() else {
+                continue;
+            };
+            if snapshot.last_used.elapsed() < idle {
+                continue;
+            }
+            if snapshot.fetched {
+                if let Err(err) = remove_cached_snapshot(&snapshot, daemon_registry).await {
+                    eprintln!(
+                        "Unable to remove cached snapshot {}: {err}",
+                        directory.display()
+                    );
+                    continue;
+                }
+            }
+            evicted.push(directory.clone());
+        }
+        for directory in evicted {
+            snapshots.remove(&directory);
+        }
+    }
+}
+
+async fn remove_cached_snapshot(
+    snapshot: &CachedSnapshot,
+    daemon_registry: &DaemonRegistry,
+) -> Result<(), AybError> {
+    daemon_registry
+        .shut_down_daemon(&snapshot.db_path())
+        .await?;
+    fs::remove_dir_all(&snapshot.directory)?;
+    Ok(())
+}
+
+/// Clears out snapshots cached before the server started, and then
+/// evicts snapshots from `snapshot_cache` once they've been idle for
+/// `snapshots.query_cache_idle`.
+pub async fn schedule_snapshot_cache_eviction(
+    config: AybConfig,
+    snapshot_cache: SnapshotCache,
+    daemon_registry: DaemonRegistry,
+) -> Result<(), AybError> {
+    let Some(ref snapshot_config) = config.snapshots else {
+        return Ok(());
+    };
+    clear_snapshot_cache(&config.data_path)?;
+    let idle = parse_snapshot_interval(
+        snapshot_config
+            .query_cache_idle
+            .as_deref()
+            .unwrap_or(DEFAULT_IDLE),
+    )?;
+    let scheduler = JobScheduler::new().await?;
+    scheduler
+        .add(Job::new_repeated_async(
+            EVICTION_INTERVAL.min(idle),
+            move |_, _| {
+                let snapshot_cache = snapshot_cache.clone();
+                let daemon_registry = daemon_registry.clone();
+                Box::pin(async move {
+                    snapshot_cache.evict(idle, &daemon_registry).await;
+                })
+            },
+        )?)
+        .await?;
+    scheduler.shutdown_on_ctrl_c();
+    scheduler.start().await?;
+    Ok(())
+}
+
+fn clear_snapshot_cache(data_path: &str) -> Result<(), AybError> {
+    let path = snapshot_cache_parent_path(data_path);
+    if path.exists() {
+        fs::remove_dir_all(path)?;
+    }
+    Ok(())
+}
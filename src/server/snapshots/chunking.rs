@@ -0,0 +1,145 @@
+use std::io::{self, Read};
+
+// Snapshots are split into chunks at positions chosen by their content
+// (FastCDC), not at fixed offsets. Rewriting a database, as `VACUUM
+// INTO` and DuckDB's `COPY FROM DATABASE` do, shifts the pages that
+// follow a change, and content-defined boundaries shift with them, so
+// that a write still only changes the chunks around the pages it
+// touched.
+const MIN_CHUNK_SIZE: usize = 16 * 1024;
+const AVG_CHUNK_SIZE: usize = 64 * 1024;
+const MAX_CHUNK_SIZE: usize = 256 * 1024;
+// Normalized chunking: before the average size, a boundary needs more
+// hash bits to be zero than after it, which keeps chunk sizes close to
+// the average. Gear hash bits depend on more of the window the higher
+// they are, so the masks use the top bits.
+const MASK_SMALL: u64 = !(u64::MAX >> 18);
+const MASK_LARGE: u64 = !(u64::MAX >> 14);
+
+// Changing this table changes every chunk boundary, which doesn't
+// break existing snapshots but stops new ones sharing their chunks.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    // splitmix64, seeded with a fixed value.
+    let mut table = [0; 256];
+    let mut state: u64 = 0x6179_6263_6463_6863;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Returns the length of the chunk at the start of `data`, which holds
+/// either at least `MAX_CHUNK_SIZE` bytes or the rest of the input.
+fn cut_point(data: &[u8]) -> usize {
+    if data.len() <= MIN_CHUNK_SIZE {
+        return data.len();
+    }
+    let end = data.len().min(MAX_CHUNK_SIZE);
+    let normal = end.min(AVG_CHUNK_SIZE);
+    let mut hash: u64 = 0;
+    for (i, byte) in data.iter().enumerate().take(end).skip(MIN_CHUNK_SIZE) {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let mask = if i < normal { MASK_SMALL } else { MASK_LARGE };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Splits everything `reader` returns into content-defined chunks.
+pub struct Chunker<R: Read> {
+    reader: R,
+    buffer: Vec<u8>,
+    done: bool,
+}
+
+impl<R: Read> Chunker<R> {
+    pub fn new(reader: R) -> Self {
+        Chunker {
+            reader,
+            buffer: Vec::with_capacity(2 * MAX_CHUNK_SIZE),
+            done: false,
+        }
+    }
+
+    /// Returns the next chunk, or `None` once the input is exhausted.
+    pub fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        while !self.done && self.buffer.len() < MAX_CHUNK_SIZE {
+            let filled = self.buffer.len();
+            self.buffer.resize(filled + MAX_CHUNK_SIZE, 0);
+            let read = self.reader.read(&mut self.buffer[filled..])?;
+            self.buffer.truncate(filled + read);
+            self.done = read == 0;
+        }
+        if self.buffer.is_empty() {
+            return Ok(None);
+        }
+        let length = cut_point(&self.buffer);
+        let rest = self.buffer.split_off(length);
+        Ok(Some(std::mem::replace(&mut self.buffer, rest)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_bytes(length: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..length)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn chunks(data: &[u8]) -> Vec<Vec<u8>> {
+        let mut chunker = Chunker::new(data);
+        let mut chunks = Vec::new();
+        while let Some(chunk) = chunker.next_chunk().unwrap() {
+            chunks.push(chunk);
+        }
+        chunks
+    }
+
+    #[test]
+    fn test_chunks_cover_input() {
+        let data = random_bytes(3 * 1024 * 1024 + 123, 1);
+        let split = chunks(&data);
+        assert_eq!(split.concat(), data);
+        assert!(split
+            .iter()
+            .all(|chunk| chunk.len() <= MAX_CHUNK_SIZE && !chunk.is_empty()));
+        assert!(split[..split.len() - 1]
+            .iter()
+            .all(|chunk| chunk.len() >= MIN_CHUNK_SIZE));
+        assert!(chunks(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_boundaries_follow_inserted_bytes() {
+        let data = random_bytes(3 * 1024 * 1024, 2);
+        let mut shifted = data.clone();
+        shifted.splice(100_000..100_000, random_bytes(4096, 3));
+        let before = chunks(&data);
+        let after = chunks(&shifted);
+        let new_chunks = after.iter().filter(|chunk| !before.contains(chunk)).count();
+        assert!(
+            new_chunks <= 2,
+            "{new_chunks} of {} chunks are new",
+            after.len()
+        );
+    }
+}
@@ -0,0 +1,292 @@
+use crate::server::snapshots::encryption::{
+    is_encrypted, DecryptingWriter, EncryptingWriter, SnapshotKey, SnapshotKeys, MAGIC,
+};
+use std::io::{self, Write};
+use std::mem;
+use zstd::stream::write::Decoder;
+use zstd::stream::Encoder;
+
+/// Compresses what's written to it, and then encrypts it with `key`,
+/// if there is one. The result collects in a buffer that `take`
+/// empties, so that an object can be uploaded as it's encoded rather
+/// than held in memory whole.
+pub struct ObjectEncoder {
+    encoder: Encoder<'static, EncodedOutput>,
+}
+
+enum EncodedOutput {
+    Plain(Vec<u8>),
+    Encrypted(EncryptingWriter<Vec<u8>>),
+}
+
+impl Write for EncodedOutput {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            EncodedOutput::Plain(buffer) => buffer.write(data),
+            EncodedOutput::Encrypted(writer) => writer.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl ObjectEncoder {
+    pub fn new(key: Option<&SnapshotKey>) -> io::Result<ObjectEncoder> {
+        let output = match key {
+            Some(key) => EncodedOutput::Encrypted(EncryptingWriter::new(Vec::new(), key)?),
+            None => EncodedOutput::Plain(Vec::new()),
+        };
+        Ok(ObjectEncoder {
+            encoder: Encoder::new(output, 0)?, // 0 = default compression for zstd
+        })
+    }
+
+    /// Takes what's been encoded so far.
+    pub fn take(&mut self) -> Vec<u8> {
+        match self.encoder.get_mut() {
+            EncodedOutput::Plain(buffer) => mem::take(buffer),
+            EncodedOutput::Encrypted(writer) => mem::take(writer.get_mut()),
+        }
+    }
+
+    /// Finishes encoding, and returns the rest of the object.
+    pub fn finish(self) -> io::Result<Vec<u8>> {
+        match self.encoder.finish()? {
+            EncodedOutput::Plain(buffer) => Ok(buffer),
+            EncodedOutput::Encrypted(writer) => writer.finish(),
+        }
+    }
+}
+
+impl Write for ObjectEncoder {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.encoder.write(data)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder.flush()
+    }
+}
+
+/// Decodes an object as it's written to it, a piece at a time, and
+/// writes the result to `output`: the object is decrypted if it's
+/// encrypted, and then decompressed if it's `compressed`. Once `keys`
+/// are configured, objects that aren't encrypted (like those stored
+/// before encryption was configured) are only read if
+/// `read_unencrypted` allows them.
+pub struct ObjectDecoder<'a, W: Write> {
+    keys: Option<&'a SnapshotKeys>,
+    compressed: bool,
+    // `None` only while moving from one stage to the next.
+    stage: Option<DecoderStage<'a, W>>,
+}
+
+enum DecoderStage<'a, W: Write> {
+    // Until enough of the object is written to tell whether it's
+    // encrypted.
+    Start(Vec<u8>, W),
+    Plain(Decompressor<W>),
+    Encrypted(DecryptingWriter<'a, Decompressor<W>>),
+}
+
+enum Decompressor<W: Write> {
+    Uncompressed(W),
+    Compressed(Decoder<'static, W>),
+}
+
+impl<W: Write> Decompressor<W> {
+    fn new(output: W, compressed: bool) -> io::Result<Decompressor<W>> {
+        Ok(if compressed {
+            Decompressor::Compressed(Decoder::new(output)?)
+        } else {
+            Decompressor::Uncompressed(output)
+        })
+    }
+
+    fn finish(self) -> io::Result<W> {
+        match self {
+            Decompressor::Uncompressed(output) => Ok(output),
+            Decompressor::Compressed(mut decoder) => {
+                decoder.flush()?;
+                Ok(decoder.into_inner())
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for Decompressor<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self {
+            Decompressor::Uncompressed(output) => output.write(data),
+            Decompressor::Compressed(decoder) => decoder.write(data),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Decompressor::Uncompressed(output) => output.flush(),
+            Decompressor::Compressed(decoder) => decoder.flush(),
+        }
+    }
+}
+
+impl<'a, W: Write> ObjectDecoder<'a, W> {
+    pub fn new(output: W, keys: Option<&'a SnapshotKeys>, compressed: bool) -> Self {
+        ObjectDecoder {
+            keys,
+            compressed,
+            stage: Some(DecoderStage::Start(Vec::new(), output)),
+        }
+    }
+
+    /// Moves past the `Start` stage, once it's known whether the
+    /// object is encrypted.
+    fn begin(&mut self) -> io::Result<()> {
+        if !matches!(self.stage, Some(DecoderStage::Start(..))) {
+            return Ok(());
+        }
+        let Some(DecoderStage::Start(start, output)) = self.stage.take() else {
+            unreachable!();
+        };
+        let decompressor = Decompressor::new(output, self.compressed)?;
+        let mut stage = if is_encrypted(&start) {
+            let keys = self.keys.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Snapshot is encrypted, but no snapshot encryption key is configured",
+                )
+            })?;
+            DecoderStage::Encrypted(DecryptingWriter::new(decompressor, keys))
+        } else if self.keys.is_some_and(|keys| !keys.read_unencrypted()) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Snapshot isn't encrypted, but snapshot encryption is configured. To restore snapshots stored before encryption was turned on, set `read_unencrypted = true` in [snapshots.encryption]",
+            ));
+        } else {
+            DecoderStage::Plain(decompressor)
+        };
+        match &mut stage {
+            DecoderStage::Plain(writer) => writer.write_all(&start)?,
+            DecoderStage::Encrypted(writer) => writer.write_all(&start)?,
+            DecoderStage::Start(..) => unreachable!(),
+        }
+        self.stage = Some(stage);
+        Ok(())
+    }
+
+    /// Finishes decoding, and returns `output`.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.begin()?;
+        match self.stage.take() {
+            Some(DecoderStage::Plain(writer)) => writer.finish(),
+            Some(DecoderStage::Encrypted(writer)) => writer.finish()?.finish(),
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl<W: Write> Write for ObjectDecoder<'_, W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        match self.stage.as_mut() {
+            Some(DecoderStage::Start(start, _)) => {
+                start.extend_from_slice(data);
+                if start.len() >= MAGIC.len() {
+                    self.begin()?;
+                }
+            }
+            Some(DecoderStage::Plain(writer)) => writer.write_all(data)?,
+            Some(DecoderStage::Encrypted(writer)) => writer.write_all(data)?,
+            None => unreachable!(),
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::config::AybConfigSnapshotsEncryption;
+
+    fn keys(read_unencrypted: bool) -> SnapshotKeys {
+        SnapshotKeys::from_config(&AybConfigSnapshotsEncryption {
+            key: "7Bfo0GZ2vDmoFq3Xu0nEXnuNzNoSLjjgV/jZOtYUVAY=".to_string(),
+            previous_keys: None,
+            read_unencrypted: Some(read_unencrypted),
+        })
+        .unwrap()
+    }
+
+    /// Encodes `data` as it would be uploaded: a piece at a time,
+    /// taking what's encoded after each piece.
+    fn encode(data: &[u8], key: Option<&SnapshotKey>) -> Vec<u8> {
+        let mut encoder = ObjectEncoder::new(key).unwrap();
+        let mut encoded = Vec::new();
+        for piece in data.chunks(100_000) {
+            encoder.write_all(piece).unwrap();
+            encoded.extend(encoder.take());
+        }
+        encoded.extend(encoder.finish().unwrap());
+        encoded
+    }
+
+    /// Decodes `data` as it would be downloaded: in pieces small
+    /// enough to split the header.
+    fn decode(data: &[u8], keys: Option<&SnapshotKeys>, compressed: bool) -> io::Result<Vec<u8>> {
+        let mut decoder = ObjectDecoder::new(Vec::new(), keys, compressed);
+        for piece in data.chunks(3) {
+            decoder.write_all(piece)?;
+        }
+        decoder.finish()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let keys = keys(true);
+        let data: Vec<u8> = (0..1_000_000).map(|i| (i % 7) as u8).collect();
+        for key in [None, Some(keys.current())] {
+            let encoded = encode(&data, key);
+            assert_eq!(is_encrypted(&encoded), key.is_some());
+            assert_eq!(decode(&encoded, Some(&keys), true).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_uncompressed_objects() {
+        let keys = keys(true);
+        // Objects too short to tell whether they're encrypted are
+        // stored as they are.
+        for data in [&b""[..], b"{}", b"{\"size\": 0}"] {
+            assert_eq!(decode(data, Some(&keys), false).unwrap(), data);
+        }
+        let mut writer = EncryptingWriter::new(Vec::new(), keys.current()).unwrap();
+        writer.write_all(b"{\"size\": 0}").unwrap();
+        let encrypted = writer.finish().unwrap();
+        assert_eq!(
+            decode(&encrypted, Some(&keys), false).unwrap(),
+            b"{\"size\": 0}"
+        );
+        let error = decode(&encrypted, None, false).unwrap_err();
+        assert!(error.to_string().contains("no snapshot encryption key"));
+    }
+
+    #[test]
+    fn test_unencrypted_objects_need_opting_in() {
+        let keys = keys(false);
+        let data = b"{\"size\": 0}";
+        for encoded in [&data[..], &encode(data, None)] {
+            let error = decode(encoded, Some(&keys), false).unwrap_err();
+            assert!(error.to_string().contains("read_unencrypted"));
+        }
+        assert!(decode(b"", Some(&keys), false).is_err());
+        let encrypted = encode(data, Some(keys.current()));
+        assert_eq!(decode(&encrypted, Some(&keys), true).unwrap(), data);
+        // Without keys, unencrypted objects are all there is.
+        assert_eq!(decode(data, None, false).unwrap(), data);
+    }
+}
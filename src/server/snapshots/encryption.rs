@@ -0,0 +1,400 @@
+use crate::error::AybError;
+use crate::server::config::AybConfigSnapshotsEncryption;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key};
+use hkdf::Hkdf;
+use prefixed_api_key::rand::rngs::OsRng;
+use prefixed_api_key::rand::RngCore;
+use sha2::Sha256;
+use std::io::{self, Write};
+
+// Encrypted objects start with a header holding this magic number and
+// format version, the ID of the server key they were encrypted with,
+// a random salt that derives a key for the object alone, and the
+// random prefix of every segment's nonce.
+pub const MAGIC: [u8; 4] = *b"aybe";
+const VERSION: u8 = 1;
+const KEY_ID_SIZE: usize = 8;
+const SALT_SIZE: usize = 32;
+// A ChaCha20-Poly1305 nonce is 12 bytes, of which the STREAM
+// construction uses 5 for the segment counter and last-segment flag.
+const NONCE_PREFIX_SIZE: usize = 7;
+const HEADER_SIZE: usize = MAGIC.len() + 1 + KEY_ID_SIZE + SALT_SIZE + NONCE_PREFIX_SIZE;
+// Objects are encrypted in segments of this many bytes, so that
+// neither encrypting nor decrypting them has to hold the whole object.
+const SEGMENT_SIZE: usize = 64 * 1024;
+const TAG_SIZE: usize = 16;
+
+/// A server key that snapshots are encrypted with.
+pub struct SnapshotKey {
+    id: [u8; KEY_ID_SIZE],
+    key: [u8; 32],
+    chunk_hash_key: [u8; 32],
+}
+
+impl SnapshotKey {
+    /// Decodes a base64-encoded 32-byte key from `ayb.toml`.
+    pub fn from_base64(key: &str) -> Result<SnapshotKey, AybError> {
+        let key: [u8; 32] = STANDARD
+            .decode(key)
+            .ok()
+            .and_then(|key| key.try_into().ok())
+            .ok_or_else(|| AybError::ConfigurationError {
+                message: "Snapshot encryption keys must be 32 bytes, encoded in base64 (e.g., the output of `openssl rand -base64 32`). See snapshot configuration documentation at https://github.com/marcua/ayb#snapshots--backups".to_string(),
+            })?;
+        let mut id = [0; KEY_ID_SIZE];
+        Hkdf::<Sha256>::new(None, &key)
+            .expand(b"ayb snapshot key id", &mut id)
+            .expect("key IDs are a valid HKDF output length");
+        let mut chunk_hash_key = [0; 32];
+        Hkdf::<Sha256>::new(None, &key)
+            .expand(b"ayb snapshot chunk hash", &mut chunk_hash_key)
+            .expect("blake3 keys are a valid HKDF output length");
+        Ok(SnapshotKey {
+            id,
+            key,
+            chunk_hash_key,
+        })
+    }
+
+    /// Identifies the key without revealing it, so that objects can
+    /// record which key encrypted them.
+    pub fn id(&self) -> String {
+        self.id.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    /// Hashes a chunk with a key derived from this one, so that the
+    /// names of encrypted chunks don't reveal what they hold to anyone
+    /// who can guess it.
+    pub fn chunk_hash(&self, chunk: &[u8]) -> String {
+        blake3::keyed_hash(&self.chunk_hash_key, chunk)
+            .to_hex()
+            .to_string()
+    }
+
+    /// The key that encrypts a single object, derived from this key
+    /// and the object's salt.
+    fn cipher(&self, salt: &[u8]) -> ChaCha20Poly1305 {
+        let mut key = Key::default();
+        Hkdf::<Sha256>::new(Some(salt), &self.key)
+            .expand(b"ayb snapshot encryption", &mut key)
+            .expect("ChaCha20-Poly1305 keys are a valid HKDF output length");
+        ChaCha20Poly1305::new(&key)
+    }
+}
+
+/// The keys configured in `[snapshots.encryption]`: the current key,
+/// which encrypts new objects, and keys that encrypted older objects
+/// before the current key replaced them.
+pub struct SnapshotKeys {
+    current: SnapshotKey,
+    previous: Vec<SnapshotKey>,
+    read_unencrypted: bool,
+}
+
+impl SnapshotKeys {
+    pub fn from_config(config: &AybConfigSnapshotsEncryption) -> Result<SnapshotKeys, AybError> {
+        Ok(SnapshotKeys {
+            current: SnapshotKey::from_base64(&config.key)?,
+            previous: config
+                .previous_keys
+                .iter()
+                .flatten()
+                .map(|key| SnapshotKey::from_base64(key))
+                .collect::<Result<_, _>>()?,
+            read_unencrypted: config.read_unencrypted.unwrap_or(false),
+        })
+    }
+
+    pub fn current(&self) -> &SnapshotKey {
+        &self.current
+    }
+
+    /// Whether objects that aren't encrypted can be read.
+    pub fn read_unencrypted(&self) -> bool {
+        self.read_unencrypted
+    }
+
+    fn find(&self, id: &[u8]) -> Option<&SnapshotKey> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| key.id == id)
+    }
+
+    /// The key whose `id()` is `id`.
+    pub fn get(&self, id: &str) -> Option<&SnapshotKey> {
+        std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|key| key.id() == id)
+    }
+}
+
+/// Whether `data` was encrypted by an `EncryptingWriter`.
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+fn encryption_error(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Encrypts everything written to it with ChaCha20-Poly1305, one
+/// segment at a time, and writes the result to `inner`. `finish` has
+/// to be called to write the last segment.
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    header: [u8; HEADER_SIZE],
+    encryptor: EncryptorBE32<ChaCha20Poly1305>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    pub fn new(mut inner: W, key: &SnapshotKey) -> io::Result<EncryptingWriter<W>> {
+        let mut salt = [0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce_prefix = [0; NONCE_PREFIX_SIZE];
+        OsRng.fill_bytes(&mut nonce_prefix);
+
+        let mut header = [0; HEADER_SIZE];
+        let fields: [&[u8]; 5] = [&MAGIC, &[VERSION], &key.id, &salt, &nonce_prefix];
+        let mut offset = 0;
+        for field in fields {
+            header[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+        inner.write_all(&header)?;
+
+        Ok(EncryptingWriter {
+            inner,
+            header,
+            encryptor: EncryptorBE32::from_aead(key.cipher(&salt), (&nonce_prefix).into()),
+            buffer: Vec::with_capacity(SEGMENT_SIZE),
+        })
+    }
+
+    /// The writer that encrypted segments go to, so that they can be
+    /// taken as they're encrypted.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Encrypts the last segment, which marks the end of the object so
+    /// that a truncated object fails to decrypt, and returns `inner`.
+    pub fn finish(mut self) -> io::Result<W> {
+        let segment = self
+            .encryptor
+            .encrypt_last(Payload {
+                msg: &self.buffer,
+                aad: &self.header,
+            })
+            .map_err(|_| encryption_error("Failed to encrypt snapshot"))?;
+        self.inner.write_all(&segment)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(data);
+        // Hold on to a full segment until more data arrives, since the
+        // last segment is encrypted differently.
+        while self.buffer.len() > SEGMENT_SIZE {
+            let segment = self
+                .encryptor
+                .encrypt_next(Payload {
+                    msg: &self.buffer[..SEGMENT_SIZE],
+                    aad: &self.header,
+                })
+                .map_err(|_| encryption_error("Failed to encrypt snapshot"))?;
+            self.inner.write_all(&segment)?;
+            self.buffer.drain(..SEGMENT_SIZE);
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Decrypts an object written by an `EncryptingWriter` as it's
+/// written to it, with whichever of `keys` encrypted it, and writes
+/// the result to `inner`. `finish` has to be called to decrypt the last
+/// segment.
+pub struct DecryptingWriter<'a, W: Write> {
+    inner: W,
+    keys: &'a SnapshotKeys,
+    header: Vec<u8>,
+    // `None` until the whole header is written.
+    decryptor: Option<DecryptorBE32<ChaCha20Poly1305>>,
+    buffer: Vec<u8>,
+}
+
+impl<'a, W: Write> DecryptingWriter<'a, W> {
+    pub fn new(inner: W, keys: &'a SnapshotKeys) -> DecryptingWriter<'a, W> {
+        DecryptingWriter {
+            inner,
+            keys,
+            header: Vec::with_capacity(HEADER_SIZE),
+            decryptor: None,
+            buffer: Vec::with_capacity(SEGMENT_SIZE + TAG_SIZE),
+        }
+    }
+
+    fn decryptor(&self) -> io::Result<DecryptorBE32<ChaCha20Poly1305>> {
+        let (magic, rest) = self.header.split_at(MAGIC.len());
+        let (version, rest) = rest.split_at(1);
+        let (key_id, rest) = rest.split_at(KEY_ID_SIZE);
+        let (salt, nonce_prefix) = rest.split_at(SALT_SIZE);
+        if magic != MAGIC || version[0] != VERSION {
+            return Err(encryption_error("Not an encrypted snapshot"));
+        }
+        let key = self.keys.find(key_id).ok_or_else(|| {
+            let key_id: String = key_id.iter().map(|byte| format!("{byte:02x}")).collect();
+            encryption_error(&format!(
+                "Snapshot was encrypted with key {key_id}, which isn't configured"
+            ))
+        })?;
+        Ok(DecryptorBE32::from_aead(
+            key.cipher(salt),
+            nonce_prefix.into(),
+        ))
+    }
+
+    /// Decrypts the last segment, which fails if the object was
+    /// truncated, and returns `inner`.
+    pub fn finish(mut self) -> io::Result<W> {
+        let Some(decryptor) = self.decryptor else {
+            return Err(encryption_error("Snapshot is truncated"));
+        };
+        let plaintext = decryptor
+            .decrypt_last(Payload {
+                msg: &self.buffer,
+                aad: &self.header,
+            })
+            .map_err(|_| {
+                encryption_error("Snapshot is corrupt or was encrypted with another key")
+            })?;
+        self.inner.write_all(&plaintext)?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for DecryptingWriter<'_, W> {
+    fn write(&mut self, mut data: &[u8]) -> io::Result<usize> {
+        let written = data.len();
+        if self.decryptor.is_none() {
+            let header_bytes = data.len().min(HEADER_SIZE - self.header.len());
+            self.header.extend_from_slice(&data[..header_bytes]);
+            data = &data[header_bytes..];
+            if self.header.len() < HEADER_SIZE {
+                return Ok(written);
+            }
+            self.decryptor = Some(self.decryptor()?);
+        }
+        self.buffer.extend_from_slice(data);
+        // As with `EncryptingWriter`, hold on to a full segment until
+        // more data arrives, since the last segment is decrypted
+        // differently.
+        let segment_size = SEGMENT_SIZE + TAG_SIZE;
+        while self.buffer.len() > segment_size {
+            let decryptor = self.decryptor.as_mut().unwrap();
+            let plaintext = decryptor
+                .decrypt_next(Payload {
+                    msg: &self.buffer[..segment_size],
+                    aad: &self.header,
+                })
+                .map_err(|_| {
+                    encryption_error("Snapshot is corrupt or was encrypted with another key")
+                })?;
+            self.inner.write_all(&plaintext)?;
+            self.buffer.drain(..segment_size);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "7Bfo0GZ2vDmoFq3Xu0nEXnuNzNoSLjjgV/jZOtYUVAY=";
+    const OLD_KEY: &str = "lq2pXzU9yVdPlp4+hpUNYaq6N1f6LZu0mMdnh2wV8qQ=";
+
+    fn keys(key: &str, previous_keys: &[&str]) -> SnapshotKeys {
+        SnapshotKeys::from_config(&AybConfigSnapshotsEncryption {
+            key: key.to_string(),
+            previous_keys: Some(previous_keys.iter().map(|key| key.to_string()).collect()),
+            read_unencrypted: None,
+        })
+        .unwrap()
+    }
+
+    fn encrypt(keys: &SnapshotKeys, data: &[u8]) -> Vec<u8> {
+        let mut writer = EncryptingWriter::new(Vec::new(), keys.current()).unwrap();
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    fn decrypt(keys: &SnapshotKeys, data: &[u8]) -> io::Result<Vec<u8>> {
+        // Write a byte at a time at first, to split the header.
+        let mut writer = DecryptingWriter::new(Vec::new(), keys);
+        for byte in data.iter().take(HEADER_SIZE + 1) {
+            writer.write_all(&[*byte])?;
+        }
+        writer.write_all(data.get(HEADER_SIZE + 1..).unwrap_or_default())?;
+        writer.finish()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let keys = keys(KEY, &[]);
+        // Empty, within a segment, exactly a segment, and spanning
+        // several segments.
+        for size in [0, 10, SEGMENT_SIZE, 3 * SEGMENT_SIZE + 5] {
+            let data: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&keys, &data);
+            assert!(is_encrypted(&encrypted));
+            assert_eq!(decrypt(&keys, &encrypted).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_tampering_and_truncation_fail() {
+        let keys = keys(KEY, &[]);
+        let data = vec![7; 2 * SEGMENT_SIZE];
+        let encrypted = encrypt(&keys, &data);
+
+        let mut tampered = encrypted.clone();
+        tampered[HEADER_SIZE + 100] ^= 1;
+        assert!(decrypt(&keys, &tampered).is_err());
+
+        // Dropping the last segment leaves an object whose segments
+        // are valid, but which doesn't end in a last segment.
+        let truncated = &encrypted[..HEADER_SIZE + SEGMENT_SIZE + TAG_SIZE];
+        assert!(decrypt(&keys, truncated).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let old_keys = keys(OLD_KEY, &[]);
+        let encrypted = encrypt(&old_keys, b"before rotation");
+
+        // Objects encrypted with a previous key can be decrypted, but
+        // not once that key is dropped.
+        let rotated_keys = keys(KEY, &[OLD_KEY]);
+        assert_ne!(rotated_keys.current().id(), old_keys.current().id());
+        assert_eq!(
+            decrypt(&rotated_keys, &encrypted).unwrap(),
+            b"before rotation"
+        );
+        let error = decrypt(&keys(KEY, &[]), &encrypted).unwrap_err();
+        assert!(error.to_string().contains("isn't configured"));
+    }
+}
@@ -1,22 +1,41 @@
 use crate::ayb_db::db_interfaces::AybDb;
-use crate::ayb_db::models::DBType;
+use crate::ayb_db::models::{DBType, InstantiatedDatabase};
 use crate::error::AybError;
 use crate::hosted_db::engine_for;
 use crate::hosted_db::paths::{
     current_database_path, database_parent_path, database_snapshot_path, pathbuf_to_file_name,
     pathbuf_to_parent,
 };
-use crate::server::config::AybConfig;
+use crate::server::config::{AybConfig, AybConfigSnapshots};
 use crate::server::snapshots::hashes::hash_db_directory;
 use crate::server::snapshots::models::{Snapshot, SnapshotType};
+use crate::server::snapshots::recovery::{
+    sqlite_database_path, METADATA_DATABASE, METADATA_ENTITY,
+};
+use crate::server::snapshots::retention::{retention_policy, snapshots_to_prune, RetentionPolicy};
 use crate::server::snapshots::storage::SnapshotStorage;
+use chrono::Utc;
 use go_parse_duration::parse_duration;
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tokio_cron_scheduler::{Job, JobScheduler};
 
+// Databases can be snapshotted more often than the server's interval,
+// so the scheduler checks which databases are due at least this often.
+const MAX_SCHEDULER_TICK: Duration = Duration::from_secs(60);
+
+/// What the snapshot scheduler remembers between runs.
+#[derive(Default)]
+struct SnapshotRuns {
+    is_running: bool,
+    // When each `(entity_slug, database_slug)` was last snapshotted.
+    last_snapshot_at: HashMap<(String, String), Instant>,
+}
+
 pub async fn schedule_periodic_snapshots(
     config: AybConfig,
     ayb_db: Box<dyn AybDb>,
@@ -24,41 +43,49 @@ pub async fn schedule_periodic_snapshots(
     if let Some(ref snapshot_config) = config.snapshots {
         if let Some(ref automation_config) = snapshot_config.automation {
             let scheduler = JobScheduler::new().await?;
-            let duration = Duration::from_nanos(
-                parse_duration(&automation_config.interval)?
-                    .try_into()
-                    .map_err(|err| AybError::SnapshotError {
-                        message: format!(
-                            "Unable to turn snapshot interval into a duration: {err:?}"
-                        ),
-                    })?,
-            );
+            let default_interval = parse_snapshot_interval(&automation_config.interval)?;
+            let tick = default_interval.min(MAX_SCHEDULER_TICK);
+            if sqlite_database_path(&config.database_url).is_none() {
+                println!(
+                    "The metadata database isn't a SQLite file, so it won't be backed up with snapshots"
+                );
+            } else if snapshot_config.encryption.is_none() {
+                eprintln!(
+                    "WARNING: The metadata database holds secrets like webhook signing secrets, so it won't be backed up with snapshots unless [snapshots.encryption] is configured. Without a backup, `ayb server recover` can't rebuild this server."
+                );
+            }
             // Since jobs are scheduled to run on an interval, it's
             // possible that if it takes a while to snapshot
             // databases, two snapshot jobs will run at the same
             // time. To avoid asynchrony-related issues, we skip a
             // snapshot run if a previous one is running.
-            let is_running = Arc::new(Mutex::new(false));
+            let runs = Arc::new(Mutex::new(SnapshotRuns::default()));
             scheduler
-                .add(Job::new_repeated_async(duration, move |_, _| {
-                    let is_running = Arc::clone(&is_running);
+                .add(Job::new_repeated_async(tick, move |_, _| {
+                    let runs = Arc::clone(&runs);
                     let config = config.clone();
                     let ayb_db = ayb_db.clone();
                     Box::pin(async move {
-                        let mut guard = is_running.lock().await;
-                        if *guard {
+                        let mut guard = runs.lock().await;
+                        if guard.is_running {
                             println!("Previous snapshot logic running, will skip this round...");
                             return;
                         }
                         // Mark the job as running
-                        *guard = true;
-                        if let Some(err) = create_snapshots(&config.clone(), &ayb_db.clone())
-                            .await
-                            .err()
+                        guard.is_running = true;
+                        if let Some(err) = create_snapshots(
+                            &config,
+                            &ayb_db,
+                            default_interval,
+                            tick,
+                            &mut guard.last_snapshot_at,
+                        )
+                        .await
+                        .err()
                         {
                             eprintln!("Unable to walk database directory for snapshots: {err}");
                         }
-                        *guard = false;
+                        guard.is_running = false;
                     })
                 })?)
                 .await?;
@@ -70,12 +97,81 @@ pub async fn schedule_periodic_snapshots(
     Ok(())
 }
 
+/// Parses a snapshot interval, which is in Go's time.ParseDuration
+/// format (e.g., "5m" means "every 5 minutes").
+pub fn parse_snapshot_interval(interval: &str) -> Result<Duration, AybError> {
+    let duration = Duration::from_nanos(parse_duration(interval)?.try_into().map_err(|err| {
+        AybError::SnapshotError {
+            message: format!("Unable to turn snapshot interval into a duration: {err:?}"),
+        }
+    })?);
+    if duration.is_zero() {
+        return Err(AybError::SnapshotError {
+            message: format!("Snapshot interval {interval} must be longer than zero"),
+        });
+    }
+    Ok(duration)
+}
+
 // TODO(marcua): Figure how how to avoid this Clippy ignore and the
 // one on snapshot_database. If I remove the Box, I get an
 // unimplemented trait compiler error, but if I keep it, I get a
 // Clippy warning.
 #[allow(clippy::borrowed_box)]
-async fn create_snapshots(config: &AybConfig, ayb_db: &Box<dyn AybDb>) -> Result<(), AybError> {
+async fn create_snapshots(
+    config: &AybConfig,
+    ayb_db: &Box<dyn AybDb>,
+    default_interval: Duration,
+    tick: Duration,
+    last_snapshot_at: &mut HashMap<(String, String), Instant>,
+) -> Result<(), AybError> {
+    // Databases that set their own schedule, by (entity slug,
+    // database slug). The rest are snapshotted every
+    // `default_interval`.
+    let schedules: HashMap<(String, String), (Option<Duration>, bool)> = ayb_db
+        .list_snapshot_schedules()
+        .await?
+        .into_iter()
+        .map(|schedule| {
+            let interval = schedule.snapshot_interval.and_then(|interval| {
+                parse_snapshot_interval(&interval)
+                    .map_err(|err| {
+                        eprintln!(
+                            "Ignoring snapshot interval of {}/{}: {err}",
+                            schedule.entity_slug, schedule.database_slug
+                        )
+                    })
+                    .ok()
+            });
+            (
+                (schedule.entity_slug, schedule.database_slug),
+                (interval, schedule.automatic_snapshots.unwrap_or(true)),
+            )
+        })
+        .collect();
+    let now = Instant::now();
+
+    // The metadata database is backed up alongside databases so that
+    // `ayb server recover` can rebuild the whole server. It holds
+    // secrets that shouldn't leave the machine in plaintext, so it's
+    // only backed up when snapshots are encrypted.
+    let encrypted = config
+        .snapshots
+        .as_ref()
+        .is_some_and(|snapshots| snapshots.encryption.is_some());
+    if let Some(metadata_path) = sqlite_database_path(&config.database_url).filter(|_| encrypted) {
+        let key = (METADATA_ENTITY.to_string(), METADATA_DATABASE.to_string());
+        if is_due(last_snapshot_at.get(&key), now, tick, default_interval) {
+            last_snapshot_at.insert(key, now);
+            if let Some(err) = snapshot_metadata_database(config, &metadata_path)
+                .await
+                .err()
+            {
+                eprintln!("Unable to back up the metadata database: {err}");
+            }
+        }
+    }
+
     // Walk the data path for entity slugs, database slugs
     println!("Creating snapshots...");
     let entity_paths =
@@ -99,6 +195,15 @@ async fn create_snapshots(config: &AybConfig, ayb_db: &Box<dyn AybDb>) -> Result
             let entry_path = entry?.path();
             let database = pathbuf_to_file_name(&entry_path)?;
             if entry_path.is_dir() {
+                let key = (entity.clone(), database.clone());
+                let (interval, enabled) = schedules
+                    .get(&key)
+                    .map(|(interval, enabled)| (interval.unwrap_or(default_interval), *enabled))
+                    .unwrap_or((default_interval, true));
+                if !enabled || !is_due(last_snapshot_at.get(&key), now, tick, interval) {
+                    continue;
+                }
+                last_snapshot_at.insert(key, now);
                 if let Some(err) = snapshot_database(config, ayb_db, &entity, &database)
                     .await
                     .err()
@@ -119,6 +224,66 @@ async fn create_snapshots(config: &AybConfig, ayb_db: &Box<dyn AybDb>) -> Result
     Ok(())
 }
 
+/// Whether a database last snapshotted at `last` is due for a snapshot
+/// every `interval`. Runs happen every `tick`, give or take, so a
+/// database is due if it will be overdue before the next one.
+fn is_due(last: Option<&Instant>, now: Instant, tick: Duration, interval: Duration) -> bool {
+    last.is_none_or(|last| now.duration_since(*last) + tick / 2 >= interval)
+}
+
+// Snapshots of a database share its chunks, so snapshots are taken
+// one at a time: otherwise pruning could delete the chunks of a
+// snapshot that's still being uploaded.
+static SNAPSHOT_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Copy the current version of `{entity_slug}/{database_slug}` into a
+/// new snapshot directory, returning the path of the copy and an
+/// automatic snapshot of it, or `None` if the database has never been
+/// written to.
+fn copy_database(
+    config: &AybConfig,
+    entity_slug: &str,
+    database_slug: &str,
+    db_type: &DBType,
+) -> Result<Option<(PathBuf, Snapshot)>, AybError> {
+    let db_path = current_database_path(entity_slug, database_slug, &config.data_path)?;
+    // A database file is only materialized on the first write query
+    // (the engine creates it, not `create_database`), so there is
+    // nothing to back up until then, and the engine can't open a file
+    // that doesn't exist.
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let source_database_id = pathbuf_to_file_name(&pathbuf_to_parent(&db_path)?)?;
+    let mut snapshot_path = database_snapshot_path(entity_slug, database_slug, &config.data_path)?;
+    let snapshot_directory = snapshot_path.clone();
+    snapshot_path.push(database_slug);
+    // Try to remove the file if it already exists, but don't fail if it doesn't.
+    fs::remove_file(&snapshot_path).ok();
+
+    let engine = engine_for(db_type);
+    let hash = engine
+        .create_snapshot(&db_path, &snapshot_path)
+        .and_then(|_| hash_db_directory(&snapshot_directory));
+    match hash {
+        Ok(hash) => Ok(Some((
+            snapshot_path,
+            Snapshot {
+                snapshot_id: hash,
+                snapshot_type: SnapshotType::Automatic as i16,
+                label: None,
+                db_type: *db_type as i16,
+                triggered_by: None,
+                source_database_id,
+            },
+        ))),
+        Err(err) => {
+            fs::remove_dir_all(&snapshot_directory).ok();
+            Err(err)
+        }
+    }
+}
+
 #[allow(clippy::borrowed_box)]
 pub async fn snapshot_database(
     config: &AybConfig,
@@ -137,83 +302,28 @@ pub async fn snapshot_database(
     match ayb_db.get_database(entity_slug, database_slug).await {
         Ok(db) => {
             let db_type = DBType::try_from(db.db_type)?;
-            let db_path = current_database_path(entity_slug, database_slug, &config.data_path)?;
-            // A database file is only materialized on the first write query
-            // (the engine creates it, not `create_database`). Skip snapshots
-            // for a database that has been created but never written -- there
-            // is nothing to back up yet, and trying would error every
-            // interval (the engine can't open a file that doesn't exist).
-            if !db_path.exists() {
+            let retention = retention_policy(snapshot_config, ayb_db, &db)
+                .await?
+                .map(|(policy, _)| policy);
+            let _snapshot_lock = SNAPSHOT_LOCK.lock().await;
+            let Some((snapshot_path, snapshot)) =
+                copy_database(config, entity_slug, database_slug, &db_type)?
+            else {
                 return Ok(());
-            }
-            let mut snapshot_path =
-                database_snapshot_path(entity_slug, database_slug, &config.data_path)?;
-            let snapshot_directory = snapshot_path.clone();
-            snapshot_path.push(database_slug);
-            // Try to remove the file if it already exists, but don't fail if it doesn't.
-            fs::remove_file(&snapshot_path).ok();
-
-            let engine = engine_for(&db_type);
-            engine.create_snapshot(&db_path, &snapshot_path)?;
-
-            let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
-            let existing_snapshots = snapshot_storage
-                .list_snapshots(entity_slug, database_slug)
-                .await?;
-            let num_existing_snapshots = existing_snapshots.len();
-            let snapshot_hash = hash_db_directory(&snapshot_directory)?;
-            let mut should_upload_snapshot = true;
-            for snapshot in &existing_snapshots {
-                if snapshot.snapshot_id == snapshot_hash {
-                    println!(
-                        "Snapshot with hash {snapshot_hash} already exists, not uploading again."
-                    );
-                    should_upload_snapshot = false;
-                    break;
-                }
-            }
-            if should_upload_snapshot {
-                println!("Uploading new snapshot with hash {snapshot_hash}.");
-                snapshot_storage
-                    .put(
-                        entity_slug,
-                        database_slug,
-                        &Snapshot {
-                            snapshot_id: snapshot_hash,
-                            snapshot_type: SnapshotType::Automatic as i16,
-                        },
-                        &snapshot_path,
-                    )
-                    .await?;
-
-                // If adding this snapshot resulted in more than the
-                // maximum snapshots we are allowed, prune old ones.
-                let max_snapshots: usize = snapshot_config
-                    .automation
-                    .as_ref()
-                    .unwrap()
-                    .max_snapshots
-                    .into();
-                let prune_snapshots = (num_existing_snapshots + 1).checked_sub(max_snapshots);
-                if let Some(prune_snapshots) = prune_snapshots {
-                    println!("Pruning {prune_snapshots} oldest snapshots");
-                    let mut ids_to_prune: Vec<String> = vec![];
-                    for snapshot_index in 0..prune_snapshots {
-                        ids_to_prune.push(
-                            existing_snapshots[existing_snapshots.len() - snapshot_index - 1]
-                                .snapshot_id
-                                .clone(),
-                        )
-                    }
-
-                    snapshot_storage
-                        .delete_snapshots(entity_slug, database_slug, &ids_to_prune)
-                        .await?;
-                }
-            }
+            };
+            let result = upload_automatic_snapshot(
+                snapshot_config,
+                entity_slug,
+                database_slug,
+                &snapshot,
+                &snapshot_path,
+                retention.as_ref(),
+            )
+            .await;
 
             // Clean up after uploading snapshot.
             fs::remove_dir_all(pathbuf_to_parent(&snapshot_path)?)?;
+            result?;
         }
         Err(err) => match err {
             AybError::RecordNotFound { record_type, .. } if record_type == "database" => {
@@ -226,3 +336,146 @@ pub async fn snapshot_database(
     }
     Ok(())
 }
+
+/// Back up the metadata database at `metadata_path` as an automatic
+/// snapshot, pruned like any database that follows the server's
+/// retention policy.
+async fn snapshot_metadata_database(
+    config: &AybConfig,
+    metadata_path: &Path,
+) -> Result<(), AybError> {
+    let Some(snapshot_config) = &config.snapshots else {
+        return Err(AybError::SnapshotError {
+            message: "No snapshot config found".to_string(),
+        });
+    };
+    let retention = snapshot_config
+        .automation
+        .as_ref()
+        .and_then(|automation| automation.retention.as_ref())
+        .map(RetentionPolicy::from);
+    let _snapshot_lock = SNAPSHOT_LOCK.lock().await;
+    let snapshot_directory =
+        database_snapshot_path(METADATA_ENTITY, METADATA_DATABASE, &config.data_path)?;
+    let snapshot_path = snapshot_directory.join(METADATA_DATABASE);
+    let result = async {
+        engine_for(&DBType::Sqlite).create_snapshot(metadata_path, &snapshot_path)?;
+        let snapshot = Snapshot {
+            snapshot_id: hash_db_directory(&snapshot_directory)?,
+            snapshot_type: SnapshotType::Automatic as i16,
+            label: None,
+            db_type: DBType::Sqlite as i16,
+            triggered_by: None,
+            source_database_id: String::new(),
+        };
+        upload_automatic_snapshot(
+            snapshot_config,
+            METADATA_ENTITY,
+            METADATA_DATABASE,
+            &snapshot,
+            &snapshot_path,
+            retention.as_ref(),
+        )
+        .await
+    }
+    .await;
+
+    // Clean up after uploading snapshot.
+    fs::remove_dir_all(&snapshot_directory)?;
+    result
+}
+
+async fn upload_automatic_snapshot(
+    snapshot_config: &AybConfigSnapshots,
+    entity_slug: &str,
+    database_slug: &str,
+    snapshot: &Snapshot,
+    snapshot_path: &PathBuf,
+    retention: Option<&RetentionPolicy>,
+) -> Result<(), AybError> {
+    let snapshot_hash = &snapshot.snapshot_id;
+    let snapshot_storage = SnapshotStorage::new(snapshot_config).await?;
+    let existing_snapshots = snapshot_storage
+        .list_snapshots(entity_slug, database_slug)
+        .await?;
+    if existing_snapshots
+        .iter()
+        .any(|snapshot| &snapshot.snapshot_id == snapshot_hash)
+    {
+        println!("Snapshot with hash {snapshot_hash} already exists, not uploading again.");
+        return Ok(());
+    }
+
+    println!("Uploading new snapshot with hash {snapshot_hash}.");
+    snapshot_storage
+        .put(entity_slug, database_slug, snapshot, snapshot_path)
+        .await?;
+
+    // Prune the automatic snapshots that neither the most recent
+    // `max_snapshots` nor the retention policy keep. Manual snapshots
+    // are kept until someone deletes them.
+    let max_snapshots: usize = snapshot_config
+        .automation
+        .as_ref()
+        .unwrap()
+        .max_snapshots
+        .into();
+    let snapshots = snapshot_storage
+        .list_snapshots(entity_slug, database_slug)
+        .await?;
+    let ids_to_prune: Vec<String> =
+        snapshots_to_prune(&snapshots, max_snapshots, retention, Utc::now())
+            .into_iter()
+            .map(|snapshot| snapshot.snapshot_id.clone())
+            .collect();
+    if !ids_to_prune.is_empty() {
+        println!("Pruning {} old snapshots", ids_to_prune.len());
+        snapshot_storage
+            .delete_snapshots(entity_slug, database_slug, &ids_to_prune)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Take a snapshot of `database` right away, returning its ID. Manual
+/// snapshots are never pruned automatically. If the database hasn't
+/// changed since an existing snapshot, that snapshot becomes a manual
+/// one with `label`, taken by `triggered_by`.
+pub async fn create_manual_snapshot(
+    config: &AybConfig,
+    database: &InstantiatedDatabase,
+    entity_slug: &str,
+    label: Option<String>,
+    triggered_by: &str,
+) -> Result<String, AybError> {
+    let Some(snapshot_config) = &config.snapshots else {
+        return Err(AybError::SnapshotError {
+            message: "Snapshots aren't configured on this server".to_string(),
+        });
+    };
+    let database_slug = &database.slug;
+    let db_type = DBType::try_from(database.db_type)?;
+    let _snapshot_lock = SNAPSHOT_LOCK.lock().await;
+    let Some((snapshot_path, mut snapshot)) =
+        copy_database(config, entity_slug, database_slug, &db_type)?
+    else {
+        return Err(AybError::SnapshotError {
+            message: format!("Database {entity_slug}/{database_slug} has no data to snapshot yet"),
+        });
+    };
+    snapshot.snapshot_type = SnapshotType::Manual as i16;
+    snapshot.label = label;
+    snapshot.triggered_by = Some(triggered_by.to_string());
+    let result = async {
+        SnapshotStorage::new(snapshot_config)
+            .await?
+            .put(entity_slug, database_slug, &snapshot, &snapshot_path)
+            .await
+    }
+    .await;
+
+    // Clean up after uploading snapshot.
+    fs::remove_dir_all(pathbuf_to_parent(&snapshot_path)?)?;
+    result?;
+    Ok(snapshot.snapshot_id)
+}
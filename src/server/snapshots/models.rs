@@ -3,7 +3,6 @@ use crate::{from_str, try_from_i16};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use std::collections::HashMap;
 use std::str::FromStr;
 
 #[derive(Serialize_repr, Deserialize_repr, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -46,23 +45,87 @@ pub struct Snapshot {
     // A blake3 hash of the snapshot directory before compressing.
     pub snapshot_id: String,
     pub snapshot_type: i16,
+    // A human-readable description of a manual snapshot.
+    pub label: Option<String>,
+    pub db_type: i16,
+    // The slug of the entity that took a manual snapshot. Automatic
+    // snapshots are taken by the server, and have none.
+    pub triggered_by: Option<String>,
+    // The id of the database's `current` directory when the snapshot
+    // was taken, which changes every time the database is restored.
+    pub source_database_id: String,
 }
 
-impl Snapshot {
-    pub fn to_header_map(&self) -> Result<HashMap<String, String>, AybError> {
-        let mut headers = HashMap::new();
-        headers.insert(
-            "snapshot_type".to_string(),
-            SnapshotType::try_from(self.snapshot_type)?
-                .to_str()
-                .to_string(),
-        );
-        Ok(headers)
-    }
-}
-
+/// A snapshot along with what's known about it. Snapshots stored
+/// before ayb recorded a field don't have it.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ListSnapshotResult {
     pub last_modified_at: DateTime<Utc>,
     pub snapshot_id: String,
+    pub snapshot_type: String,
+    pub label: Option<String>,
+    pub db_type: Option<String>,
+    pub uncompressed_size: Option<u64>,
+    pub compressed_size: Option<u64>,
+    pub triggered_by: Option<String>,
+    pub ayb_version: Option<String>,
+    pub source_database_id: Option<String>,
+}
+
+/// One run of write-ahead log replication for a database: a copy of
+/// the database taken when the run started, and the log segments
+/// shipped after it.
+#[derive(Debug)]
+pub struct WalGeneration {
+    pub started_at: DateTime<Utc>,
+    pub segments: Vec<WalSegment>,
+}
+
+#[derive(Debug)]
+pub struct WalSegment {
+    // Segments are numbered from 0 within their generation, in the
+    // order they have to be applied.
+    pub sequence: u64,
+    // Every transaction in the segment committed by this time.
+    pub shipped_at: DateTime<Utc>,
+}
+
+/// What's stored for a snapshot: the database file split into chunks,
+/// in order. Chunks are stored once per database, named by their
+/// hash, so snapshots that share most of their data share most of
+/// their chunks.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    // The size of the database file, in bytes.
+    pub size: u64,
+    // Names of the file's chunks, in the order they appear: their
+    // blake3 hash, followed by `.{key id}` if they're encrypted (in
+    // which case the hash is keyed, see `SnapshotKey::chunk_hash`).
+    pub chunks: Vec<String>,
+    // The snapshot's `SnapshotType` and label, which are stored with
+    // it rather than as object metadata so that every `SnapshotStore`
+    // can keep them.
+    #[serde(default)]
+    pub snapshot_type: i16,
+    #[serde(default)]
+    pub label: Option<String>,
+    // Metadata about the snapshot, which snapshots stored by older
+    // versions of ayb don't have.
+    #[serde(default)]
+    pub db_type: Option<i16>,
+    // The total size of the snapshot's compressed chunks, in bytes,
+    // including chunks shared with other snapshots.
+    #[serde(default)]
+    pub compressed_size: Option<u64>,
+    #[serde(default)]
+    pub triggered_by: Option<String>,
+    #[serde(default)]
+    pub ayb_version: Option<String>,
+    #[serde(default)]
+    pub source_database_id: Option<String>,
+    // When the snapshot was taken. Copying a snapshot to another
+    // database resets its object's last-modified time, so this is
+    // what listing and retention go by when it's there.
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
 }
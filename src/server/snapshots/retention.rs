@@ -0,0 +1,217 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{InstantiatedDatabase, SnapshotPolicy};
+use crate::error::AybError;
+use crate::server::config::{AybConfigSnapshots, AybConfigSnapshotsRetention};
+use crate::server::snapshots::models::{ListSnapshotResult, SnapshotType};
+use chrono::{DateTime, Datelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A grandfather-father-son retention policy: beyond the most recent
+/// `max_snapshots`, keep the newest snapshot of each of the last
+/// `hourly` hours, `daily` days, and `weekly` weeks.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    pub hourly: u32,
+    pub daily: u32,
+    pub weekly: u32,
+}
+
+impl From<&AybConfigSnapshotsRetention> for RetentionPolicy {
+    fn from(retention: &AybConfigSnapshotsRetention) -> Self {
+        RetentionPolicy {
+            hourly: retention.hourly.unwrap_or(0),
+            daily: retention.daily.unwrap_or(0),
+            weekly: retention.weekly.unwrap_or(0),
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// The policy a database set for itself, if it set one.
+    pub fn from_snapshot_policy(policy: &SnapshotPolicy) -> Option<Self> {
+        let settings = [
+            policy.retain_hourly,
+            policy.retain_daily,
+            policy.retain_weekly,
+        ];
+        if settings.iter().all(Option::is_none) {
+            return None;
+        }
+        let [hourly, daily, weekly] = settings.map(|setting| setting.unwrap_or(0).max(0) as u32);
+        Some(RetentionPolicy {
+            hourly,
+            daily,
+            weekly,
+        })
+    }
+}
+
+/// Where the retention policy of a database comes from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetentionSource {
+    Database,
+    Server,
+}
+
+impl RetentionSource {
+    // Suppress clippy here, as this exact behavior (&self) -> &str is
+    // allowed in `ayb_db/models.rs`.
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_str(&self) -> &str {
+        match self {
+            RetentionSource::Database => "database",
+            RetentionSource::Server => "server",
+        }
+    }
+}
+
+/// The retention policy that applies to `database`: its own, if it set
+/// one, or else the server's.
+#[allow(clippy::borrowed_box)]
+pub async fn retention_policy(
+    snapshot_config: &AybConfigSnapshots,
+    ayb_db: &Box<dyn AybDb>,
+    database: &InstantiatedDatabase,
+) -> Result<Option<(RetentionPolicy, RetentionSource)>, AybError> {
+    if let Some(policy) = ayb_db
+        .get_snapshot_policy(database.id)
+        .await?
+        .as_ref()
+        .and_then(RetentionPolicy::from_snapshot_policy)
+    {
+        return Ok(Some((policy, RetentionSource::Database)));
+    }
+    Ok(snapshot_config
+        .automation
+        .as_ref()
+        .and_then(|automation| automation.retention.as_ref())
+        .map(|retention| (RetentionPolicy::from(retention), RetentionSource::Server)))
+}
+
+/// Numbers the hour, day, or week that a time falls in.
+type Period = fn(&DateTime<Utc>) -> i64;
+
+/// Of `snapshots`, newest first, the automatic ones that neither the
+/// most recent `max_snapshots` nor `policy` keep as of `now`. Manual
+/// snapshots are never pruned.
+pub fn snapshots_to_prune<'a>(
+    snapshots: &'a [ListSnapshotResult],
+    max_snapshots: usize,
+    policy: Option<&RetentionPolicy>,
+    now: DateTime<Utc>,
+) -> Vec<&'a ListSnapshotResult> {
+    let automatic: Vec<&ListSnapshotResult> = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.snapshot_type == SnapshotType::Automatic.to_str())
+        .collect();
+    let mut keep: HashSet<&str> = automatic
+        .iter()
+        .take(max_snapshots)
+        .map(|snapshot| snapshot.snapshot_id.as_str())
+        .collect();
+    if let Some(policy) = policy {
+        // Periods are numbered so that consecutive periods have
+        // consecutive numbers. Weeks start on Monday, as 0001-01-01 was
+        // one.
+        let tiers: [(u32, Period); 3] = [
+            (policy.hourly, |time| time.timestamp().div_euclid(60 * 60)),
+            (policy.daily, |time| {
+                time.date_naive().num_days_from_ce().into()
+            }),
+            (policy.weekly, |time| {
+                i64::from(time.date_naive().num_days_from_ce() - 1).div_euclid(7)
+            }),
+        ];
+        for (count, period) in tiers {
+            let current_period = period(&now);
+            let mut seen_periods = HashSet::new();
+            // Snapshots are newest first, so the first one seen in each
+            // period is the one to keep.
+            for snapshot in &automatic {
+                let snapshot_period = period(&snapshot.last_modified_at);
+                if current_period - snapshot_period < i64::from(count)
+                    && seen_periods.insert(snapshot_period)
+                {
+                    keep.insert(&snapshot.snapshot_id);
+                }
+            }
+        }
+    }
+    automatic
+        .into_iter()
+        .filter(|snapshot| !keep.contains(snapshot.snapshot_id.as_str()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn snapshot(id: &str, last_modified_at: DateTime<Utc>) -> ListSnapshotResult {
+        ListSnapshotResult {
+            last_modified_at,
+            snapshot_id: id.to_string(),
+            snapshot_type: SnapshotType::Automatic.to_str().to_string(),
+            label: None,
+            db_type: None,
+            uncompressed_size: None,
+            compressed_size: None,
+            triggered_by: None,
+            ayb_version: None,
+            source_database_id: None,
+        }
+    }
+
+    fn pruned_ids(pruned: Vec<&ListSnapshotResult>) -> Vec<&str> {
+        pruned
+            .iter()
+            .map(|snapshot| snapshot.snapshot_id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_max_snapshots_without_policy() {
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 0, 0).unwrap();
+        let mut snapshots: Vec<ListSnapshotResult> = (0..4)
+            .map(|minutes| snapshot(&format!("s{minutes}"), now - Duration::minutes(minutes)))
+            .collect();
+        snapshots[2].snapshot_type = SnapshotType::Manual.to_str().to_string();
+
+        // The manual snapshot is neither pruned nor counted.
+        assert_eq!(
+            pruned_ids(snapshots_to_prune(&snapshots, 2, None, now)),
+            vec!["s3"]
+        );
+    }
+
+    #[test]
+    fn test_grandfather_father_son_policy() {
+        // Wednesday.
+        let now = Utc.with_ymd_and_hms(2026, 10, 21, 12, 30, 0).unwrap();
+        let snapshots = vec![
+            snapshot("now", now),
+            // Two snapshots in the previous hour: only the newer is kept.
+            snapshot("hour-1-newer", now - Duration::minutes(40)),
+            snapshot("hour-1-older", now - Duration::minutes(80)),
+            // Yesterday, beyond the hourly tier but within the daily one.
+            snapshot("day-1", now - Duration::days(1)),
+            // Earlier yesterday, so not the newest of its day.
+            snapshot("day-1-older", now - Duration::days(1) - Duration::hours(2)),
+            // Monday of the previous week: the newest of that week.
+            snapshot("week-1", now - Duration::days(9)),
+            // Three weeks ago, beyond every tier.
+            snapshot("week-3", now - Duration::days(21)),
+        ];
+        let policy = RetentionPolicy {
+            hourly: 2,
+            daily: 2,
+            weekly: 2,
+        };
+        assert_eq!(
+            pruned_ids(snapshots_to_prune(&snapshots, 1, Some(&policy), now)),
+            vec!["hour-1-older", "day-1-older", "week-3"]
+        );
+    }
+}
@@ -1,61 +1,49 @@
+use crate::ayb_db::models::DBType;
 use crate::error::AybError;
 use crate::server::config::AybConfigSnapshots;
-use crate::server::snapshots::models::{ListSnapshotResult, Snapshot};
+use crate::server::snapshots::chunking::Chunker;
+use crate::server::snapshots::codec::{ObjectDecoder, ObjectEncoder};
+use crate::server::snapshots::encryption::{EncryptingWriter, SnapshotKey, SnapshotKeys};
+use crate::server::snapshots::models::{
+    ListSnapshotResult, Snapshot, SnapshotManifest, SnapshotType, WalGeneration, WalSegment,
+};
+use crate::server::snapshots::store::{
+    create_snapshot_store, ObjectStream, ObjectUpload, SnapshotStore,
+};
+use chrono::{DateTime, Utc};
 use futures_util::future::join_all;
-use s3::creds::Credentials;
-use s3::error::S3Error;
-use s3::{Bucket, Region};
+use futures_util::TryStreamExt;
+use std::collections::{BTreeMap, HashSet};
 use std::fs::File;
-use std::io::{self, Cursor, Write};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
-use zstd::stream::{Decoder, Encoder};
 
+// Snapshots uploaded before they were split into chunks are a single
+// zstd-compressed copy of the database, rather than a manifest.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+// Objects too large to hold in memory, like the base of a write-ahead
+// log generation, are read and encoded in pieces of this size.
+const STREAM_PIECE_SIZE: usize = 1024 * 1024;
+
+/// Snapshots of every database, and their replicated write-ahead
+/// logs, laid out in a `SnapshotStore`. When `[snapshots.encryption]`
+/// is configured, everything is encrypted before it's stored.
 pub struct SnapshotStorage {
-    bucket: Bucket,
+    store: Box<dyn SnapshotStore>,
     path_prefix: String,
+    keys: Option<SnapshotKeys>,
 }
 
 impl SnapshotStorage {
     pub async fn new(config: &AybConfigSnapshots) -> Result<SnapshotStorage, AybError> {
-        let credentials = Credentials::new(
-            Some(&config.access_key_id),
-            Some(&config.secret_access_key),
-            None,
-            None,
-            None,
-        )
-        .map_err(|err| AybError::S3ExecutionError {
-            message: format!("Failed to create S3 credentials: {err:?}"),
-        })?;
-
-        let region_str = config.region.clone().unwrap_or("us-east-1".to_string());
-        let region = if let Some(endpoint_url) = &config.endpoint_url {
-            Region::Custom {
-                region: region_str,
-                endpoint: endpoint_url.to_string(),
-            }
-        } else {
-            region_str
-                .parse()
-                .map_err(|err| AybError::S3ExecutionError {
-                    message: format!("Failed to parse region: {region_str}, {err:?}"),
-                })?
-        };
-        let mut bucket = Bucket::new(&config.bucket, region, credentials).map_err(|err| {
-            AybError::S3ExecutionError {
-                message: format!("Failed to load bucket: {err:?}"),
-            }
-        })?;
-        let force_path_style = config.force_path_style.unwrap_or(false);
-        let mut path_prefix = config.path_prefix.clone();
-        if force_path_style {
-            bucket = bucket.with_path_style();
-            path_prefix = format!("{}/{}", config.bucket, path_prefix);
-        }
-
         Ok(SnapshotStorage {
-            bucket: *bucket,
-            path_prefix,
+            store: create_snapshot_store(config)?,
+            path_prefix: config.path_prefix.clone(),
+            keys: config
+                .encryption
+                .as_ref()
+                .map(SnapshotKeys::from_config)
+                .transpose()?,
         })
     }
 
@@ -66,6 +54,163 @@ impl SnapshotStorage {
         )
     }
 
+    /// Write-ahead log replication stores each generation under
+    /// `wal/{started_at_millis}/` next to the database's snapshots: a
+    /// `base` copy of the database, and segments named
+    /// `{sequence}-{shipped_at_millis}`.
+    fn wal_path(&self, entity_slug: &str, database_slug: &str, key: &str) -> String {
+        self.db_path(entity_slug, database_slug, &format!("wal/{key}"))
+    }
+
+    /// Chunks are stored under `chunks/{hash}` next to the database's
+    /// snapshots, and shared by every snapshot of the database.
+    fn chunk_path(&self, entity_slug: &str, database_slug: &str, hash: &str) -> String {
+        self.db_path(entity_slug, database_slug, &format!("chunks/{hash}"))
+    }
+
+    /// Keys of every object stored under `prefix`.
+    async fn list_keys(&self, prefix: String) -> Result<Vec<String>, AybError> {
+        Ok(self
+            .store
+            .list(&prefix)
+            .await?
+            .into_iter()
+            .map(|object| object.key)
+            .collect())
+    }
+
+    async fn delete_keys(&self, keys: &[String]) -> Result<(), AybError> {
+        let results = join_all(keys.iter().map(|key| self.store.delete(key))).await;
+        for result in results {
+            result?;
+        }
+        Ok(())
+    }
+
+    fn current_key(&self) -> Option<&SnapshotKey> {
+        self.keys.as_ref().map(SnapshotKeys::current)
+    }
+
+    /// Encrypts `data` with the current key, if encryption is
+    /// configured.
+    fn seal(&self, data: &[u8]) -> Result<Vec<u8>, AybError> {
+        let Some(key) = self.current_key() else {
+            return Ok(data.to_vec());
+        };
+        let mut writer = EncryptingWriter::new(Vec::new(), key)?;
+        writer.write_all(data)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Compresses `data`, and then encrypts it if encryption is
+    /// configured.
+    fn encode(&self, mut data: impl io::Read) -> Result<Vec<u8>, AybError> {
+        let mut encoder = ObjectEncoder::new(self.current_key())?;
+        io::copy(&mut data, &mut encoder)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Decodes `data`, the object stored at `key`: decrypts it if it
+    /// was encrypted, and then decompresses it if it's `compressed`.
+    fn decode(&self, key: &str, data: &[u8], compressed: bool) -> Result<Vec<u8>, AybError> {
+        let mut decoder = ObjectDecoder::new(Vec::new(), self.keys.as_ref(), compressed);
+        decoder
+            .write_all(data)
+            .and_then(|_| decoder.finish())
+            .map_err(|error| read_error(key, error))
+    }
+
+    async fn get_decoded(&self, key: &str) -> Result<Vec<u8>, AybError> {
+        self.decode(key, &self.store.get(key).await?, true)
+    }
+
+    async fn put_encoded(&self, key: &str, data: impl io::Read) -> Result<(), AybError> {
+        self.store.put(key, &self.encode(data)?).await
+    }
+
+    /// Decodes the object stored at `key` to a file at `path` as it's
+    /// downloaded, so that it's never held in memory whole. `start` is
+    /// the part of the object already read from `stream`.
+    async fn decode_to_file(
+        &self,
+        key: &str,
+        start: &[u8],
+        mut stream: ObjectStream,
+        path: &Path,
+    ) -> Result<(), AybError> {
+        let mut decoder = ObjectDecoder::new(File::create(path)?, self.keys.as_ref(), true);
+        decoder
+            .write_all(start)
+            .map_err(|error| read_error(key, error))?;
+        while let Some(piece) = stream.try_next().await? {
+            decoder
+                .write_all(&piece)
+                .map_err(|error| read_error(key, error))?;
+        }
+        decoder.finish().map_err(|error| read_error(key, error))?;
+        Ok(())
+    }
+
+    /// Encodes the file at `path` and uploads it to `key` as it's
+    /// encoded, so that it's never held in memory whole.
+    async fn put_encoded_file(&self, key: &str, path: &Path) -> Result<(), AybError> {
+        let mut upload = self.store.start_upload(key).await?;
+        match self.encode_to_upload(path, upload.as_mut()).await {
+            Ok(()) => upload.complete().await,
+            Err(err) => {
+                if let Err(abort_err) = upload.abort().await {
+                    eprintln!("Unable to abort upload of {key}: {abort_err}");
+                }
+                Err(err)
+            }
+        }
+    }
+
+    async fn encode_to_upload(
+        &self,
+        path: &Path,
+        upload: &mut dyn ObjectUpload,
+    ) -> Result<(), AybError> {
+        let mut file = File::open(path)?;
+        let mut encoder = ObjectEncoder::new(self.current_key())?;
+        let mut piece = vec![0; STREAM_PIECE_SIZE];
+        loop {
+            let read = file.read(&mut piece)?;
+            if read == 0 {
+                break;
+            }
+            encoder.write_all(&piece[..read])?;
+            upload.write(&encoder.take()).await?;
+        }
+        upload.write(&encoder.finish()?).await
+    }
+
+    /// Chunks are named by their hash, followed by the ID of the key
+    /// that encrypted them, if any. Encrypted chunks are hashed with a
+    /// key derived from that key (see `SnapshotKey::chunk_hash`), so
+    /// that their names don't give away their contents. Snapshots
+    /// taken after a key is rotated store their chunks again under the
+    /// new key, so that the old key is no longer needed once older
+    /// snapshots are pruned.
+    fn chunk_name(&self, chunk: &[u8]) -> String {
+        match self.current_key() {
+            Some(key) => format!("{}.{}", key.chunk_hash(chunk), key.id()),
+            None => blake3::hash(chunk).to_hex().to_string(),
+        }
+    }
+
+    /// Whether `chunk` is the chunk that `chunk_name` named `name`.
+    fn chunk_matches(&self, name: &str, chunk: &[u8]) -> bool {
+        match name.split_once('.') {
+            Some((hash, key_id)) => self
+                .keys
+                .as_ref()
+                .and_then(|keys| keys.get(key_id))
+                .is_some_and(|key| key.chunk_hash(chunk) == hash),
+            None => blake3::hash(chunk).to_hex().as_str() == name,
+        }
+    }
+
     #[allow(clippy::ptr_arg)]
     pub async fn delete_snapshots(
         &self,
@@ -73,31 +218,132 @@ impl SnapshotStorage {
         database_slug: &str,
         snapshot_ids: &Vec<String>,
     ) -> Result<(), AybError> {
-        let delete_futures: Vec<_> = snapshot_ids
+        let keys: Vec<String> = snapshot_ids
             .iter()
-            .map(|snapshot_id| {
-                let key = self
-                    .db_path(entity_slug, database_slug, snapshot_id)
-                    .clone();
-
-                async move {
-                    self.bucket.delete_object(&key).await.map_err(|err| {
-                        AybError::S3ExecutionError {
-                            message: format!("Failed to delete snapshot {key}: {err:?}"),
-                        }
-                    })
-                }
+            .map(|snapshot_id| self.db_path(entity_slug, database_slug, snapshot_id))
+            .collect();
+        self.delete_keys(&keys).await?;
+
+        self.delete_unreferenced_chunks(entity_slug, database_slug)
+            .await
+    }
+
+    /// Deletes the chunks of `{entity_slug}/{database_slug}` that no
+    /// remaining snapshot's manifest refers to.
+    async fn delete_unreferenced_chunks(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+    ) -> Result<(), AybError> {
+        let mut referenced_chunks = HashSet::new();
+        for snapshot in self.list_snapshots(entity_slug, database_slug).await? {
+            if let Some(manifest) = self
+                .get_manifest(entity_slug, database_slug, &snapshot.snapshot_id)
+                .await?
+            {
+                referenced_chunks.extend(manifest.chunks);
+            }
+        }
+        let chunk_prefix = self.chunk_path(entity_slug, database_slug, "");
+        let unreferenced_keys: Vec<String> = self
+            .list_keys(chunk_prefix.clone())
+            .await?
+            .into_iter()
+            .filter(|key| {
+                key.strip_prefix(&chunk_prefix)
+                    .is_some_and(|hash| !referenced_chunks.contains(hash))
             })
             .collect();
+        self.delete_keys(&unreferenced_keys).await
+    }
 
-        // Await all delete operations
-        let results = join_all(delete_futures).await;
+    /// The manifest of a snapshot, or `None` for snapshots stored
+    /// before they were split into chunks.
+    async fn get_manifest(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        snapshot_id: &str,
+    ) -> Result<Option<SnapshotManifest>, AybError> {
+        let key = self.db_path(entity_slug, database_slug, snapshot_id);
+        self.parse_manifest(&key, &self.store.get(&key).await?)
+    }
 
-        // Handle errors
-        for result in results {
-            result?; // Return the first error, if any
+    /// The manifest stored at `key`, or `None` for snapshots stored
+    /// before they were split into chunks.
+    fn parse_manifest(&self, key: &str, data: &[u8]) -> Result<Option<SnapshotManifest>, AybError> {
+        if data.starts_with(&ZSTD_MAGIC) {
+            return Ok(None);
         }
+        self.decode_manifest(key, data).map(Some)
+    }
+
+    fn decode_manifest(&self, key: &str, data: &[u8]) -> Result<SnapshotManifest, AybError> {
+        Ok(serde_json::from_slice(&self.decode(key, data, false)?)?)
+    }
+
+    /// Deletes every snapshot of `{entity_slug}/{database_slug}`,
+    /// along with its replicated write-ahead log.
+    pub async fn delete_all_snapshots(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+    ) -> Result<(), AybError> {
+        let keys = self
+            .list_keys(self.db_path(entity_slug, database_slug, ""))
+            .await?;
+        self.delete_keys(&keys).await
+    }
 
+    /// Moves every snapshot of `{from_entity_slug}/{from_database_slug}`
+    /// to `{to_entity_slug}/{to_database_slug}`. Snapshots are copied
+    /// oldest-first so that the relative order of those stored before
+    /// manifests recorded `created_at` (which S3 tracks via
+    /// last-modified times) survives the move, and the originals are
+    /// only deleted once everything has been copied.
+    pub async fn move_snapshots(
+        &self,
+        from_entity_slug: &str,
+        from_database_slug: &str,
+        to_entity_slug: &str,
+        to_database_slug: &str,
+    ) -> Result<(), AybError> {
+        let snapshots = self
+            .list_snapshots(from_entity_slug, from_database_slug)
+            .await?;
+        for snapshot in snapshots.iter().rev() {
+            let from = self.db_path(from_entity_slug, from_database_slug, &snapshot.snapshot_id);
+            let to = self.db_path(to_entity_slug, to_database_slug, &snapshot.snapshot_id);
+            self.store.copy(&from, &to).await?;
+        }
+
+        // Chunks and the replicated write-ahead log don't depend on
+        // last-modified times, so they can be moved in any order.
+        let from_prefix = self.db_path(from_entity_slug, from_database_slug, "");
+        let to_prefix = self.db_path(to_entity_slug, to_database_slug, "");
+        let nested_keys: Vec<String> = self
+            .list_keys(from_prefix.clone())
+            .await?
+            .into_iter()
+            .filter(|key| {
+                key.strip_prefix(&from_prefix)
+                    .is_some_and(|name| name.contains('/'))
+            })
+            .collect();
+        for from in &nested_keys {
+            let to = from.replacen(&from_prefix, &to_prefix, 1);
+            self.store.copy(from, &to).await?;
+        }
+        // Once everything is copied the move has succeeded, so failing
+        // to delete the originals only leaves them behind.
+        if let Err(err) = self
+            .delete_all_snapshots(from_entity_slug, from_database_slug)
+            .await
+        {
+            eprintln!(
+                "Unable to delete moved snapshots of {from_entity_slug}/{from_database_slug}: {err}"
+            );
+        }
         Ok(())
     }
 
@@ -108,34 +354,49 @@ impl SnapshotStorage {
         snapshot_id: &str,
         destination_path: &Path,
     ) -> Result<(), AybError> {
-        let s3_path = self.db_path(entity_slug, database_slug, snapshot_id);
+        let snapshot_key = self.db_path(entity_slug, database_slug, snapshot_id);
         let mut snapshot_path = destination_path.to_path_buf();
         snapshot_path.push(database_slug);
 
-        let response = self
-            .bucket
-            .get_object(&s3_path)
-            .await
-            .map_err(|err| match err {
-                S3Error::HttpFailWithBody(status_code, ref body) => {
-                    if status_code == 404 && body.contains("<Code>NoSuchKey</Code>") {
-                        return AybError::SnapshotDoesNotExistError;
-                    }
-                    AybError::S3ExecutionError {
-                        message: format!("Failed to retrieve snapshot {s3_path}: {err:?}"),
-                    }
-                }
-                _ => AybError::S3ExecutionError {
-                    message: format!("Failed to retrieve snapshot {s3_path}: {err:?}"),
-                },
-            })?;
+        // Snapshots stored before they were split into chunks are a
+        // whole database, so they're decoded to disk as they download
+        // rather than read into memory like a manifest.
+        let mut stream = self.store.get_stream(&snapshot_key).await?;
+        let mut data = Vec::new();
+        while data.len() < ZSTD_MAGIC.len() {
+            let Some(piece) = stream.try_next().await? else {
+                break;
+            };
+            data.extend(piece);
+        }
+        if data.starts_with(&ZSTD_MAGIC) {
+            return self
+                .decode_to_file(&snapshot_key, &data, stream, &snapshot_path)
+                .await;
+        }
+        while let Some(piece) = stream.try_next().await? {
+            data.extend(piece);
+        }
+        let manifest = self.decode_manifest(&snapshot_key, &data)?;
 
-        let body = Cursor::new(response.bytes());
-        let mut decoder = Decoder::new(body)?;
-        let mut decompressed_data = Vec::new();
-        io::copy(&mut decoder, &mut decompressed_data)?;
+        // Reassemble the database from its chunks, one at a time.
         let mut file = File::create(snapshot_path)?;
-        file.write_all(&decompressed_data)?;
+        for name in &manifest.chunks {
+            let chunk = self
+                .get_decoded(&self.chunk_path(entity_slug, database_slug, name))
+                .await?;
+            if !self.chunk_matches(name, &chunk) {
+                return Err(AybError::SnapshotError {
+                    message: format!("Chunk {name} of snapshot {snapshot_key} is corrupt"),
+                });
+            }
+            file.write_all(&chunk)?;
+        }
+        if file.metadata()?.len() != manifest.size {
+            return Err(AybError::SnapshotError {
+                message: format!("Snapshot {snapshot_key} doesn't match the size in its manifest"),
+            });
+        }
 
         Ok(())
     }
@@ -146,32 +407,54 @@ impl SnapshotStorage {
         database_slug: &str,
     ) -> Result<Vec<ListSnapshotResult>, AybError> {
         let path = self.db_path(entity_slug, database_slug, "");
-        let results =
-            self.bucket
-                .list(path, None)
-                .await
-                .map_err(|err| AybError::S3ExecutionError {
-                    message: format!("Failed to list snapshots: {err:?}"),
-                })?;
-
         let mut snapshots = Vec::new();
-
-        for result in results {
-            for object in result.contents {
-                let key = object.key;
-                if let Some(snapshot_id) = key.rsplit('/').next() {
-                    snapshots.push(ListSnapshotResult {
-                        last_modified_at: object.last_modified.parse().map_err(|err| {
-                            AybError::S3ExecutionError {
-                                message: format!(
-                                    "Failed to read last modified datetime from object {key}: {err:?}"
-                                ),
-                            }
-                        })?,
-                        snapshot_id: snapshot_id.to_string(),
-                    });
-                }
+        for object in self.store.list(&path).await? {
+            // Skip chunks and the replicated write-ahead log, which
+            // are nested under `chunks/` and `wal/`.
+            let Some(snapshot_id) = object.key.strip_prefix(&path) else {
+                continue;
+            };
+            if snapshot_id.contains('/') {
+                continue;
             }
+            // Snapshots stored before manifests existed were all
+            // automatic, and have no other metadata.
+            let snapshot = match self
+                .get_manifest(entity_slug, database_slug, snapshot_id)
+                .await?
+            {
+                Some(manifest) => ListSnapshotResult {
+                    last_modified_at: manifest.created_at.unwrap_or(object.last_modified_at),
+                    snapshot_id: snapshot_id.to_string(),
+                    snapshot_type: SnapshotType::try_from(manifest.snapshot_type)?
+                        .to_str()
+                        .to_string(),
+                    label: manifest.label,
+                    db_type: manifest
+                        .db_type
+                        .map(DBType::try_from)
+                        .transpose()?
+                        .map(|db_type| db_type.to_str().to_string()),
+                    uncompressed_size: Some(manifest.size),
+                    compressed_size: manifest.compressed_size,
+                    triggered_by: manifest.triggered_by,
+                    ayb_version: manifest.ayb_version,
+                    source_database_id: manifest.source_database_id,
+                },
+                None => ListSnapshotResult {
+                    last_modified_at: object.last_modified_at,
+                    snapshot_id: snapshot_id.to_string(),
+                    snapshot_type: SnapshotType::Automatic.to_str().to_string(),
+                    label: None,
+                    db_type: None,
+                    uncompressed_size: None,
+                    compressed_size: None,
+                    triggered_by: None,
+                    ayb_version: None,
+                    source_database_id: None,
+                },
+            };
+            snapshots.push(snapshot);
         }
 
         // Return results in descending order.
@@ -187,18 +470,528 @@ impl SnapshotStorage {
         snapshot_path: &PathBuf,
     ) -> Result<(), AybError> {
         let path = self.db_path(entity_slug, database_slug, &snapshot.snapshot_id);
-        let mut input_file = File::open(snapshot_path)?;
-        let mut encoder = Encoder::new(Vec::new(), 0)?; // 0 = default compression for zstd
-        io::copy(&mut input_file, &mut encoder)?;
-        let compressed_data = encoder.finish()?;
+        let chunk_prefix = self.chunk_path(entity_slug, database_slug, "");
+        let mut stored_chunks: HashSet<String> = self
+            .list_keys(chunk_prefix.clone())
+            .await?
+            .into_iter()
+            .filter_map(|key| key.strip_prefix(&chunk_prefix).map(str::to_string))
+            .collect();
+
+        // Only upload the chunks that no earlier snapshot stored.
+        let mut chunker = Chunker::new(File::open(snapshot_path)?);
+        let mut manifest = SnapshotManifest {
+            size: 0,
+            chunks: Vec::new(),
+            snapshot_type: snapshot.snapshot_type,
+            label: snapshot.label.clone(),
+            db_type: Some(snapshot.db_type),
+            compressed_size: Some(0),
+            triggered_by: snapshot.triggered_by.clone(),
+            ayb_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            source_database_id: Some(snapshot.source_database_id.clone()),
+            created_at: Some(Utc::now()),
+        };
+        let mut compressed_size = 0;
+        while let Some(chunk) = chunker.next_chunk()? {
+            let name = self.chunk_name(&chunk);
+            // Chunks are compressed even when they're already stored,
+            // so that the snapshot's compressed size counts them too.
+            let compressed_chunk = self.encode(chunk.as_slice())?;
+            if stored_chunks.insert(name.clone()) {
+                self.store
+                    .put(
+                        &self.chunk_path(entity_slug, database_slug, &name),
+                        &compressed_chunk,
+                    )
+                    .await?;
+            }
+            compressed_size += compressed_chunk.len() as u64;
+            manifest.size += chunk.len() as u64;
+            manifest.chunks.push(name);
+        }
+        manifest.compressed_size = Some(compressed_size);
+
+        // Upload the manifest last, so that it only ever refers to
+        // chunks that are already stored.
+        self.store
+            .put(&path, &self.seal(&serde_json::to_vec(&manifest)?)?)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Uploads `base_path`, a copy of the database, as the base of a
+    /// new write-ahead log generation that starts at `started_at`.
+    pub async fn put_wal_base(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        started_at: DateTime<Utc>,
+        base_path: &Path,
+    ) -> Result<(), AybError> {
+        let key = self.wal_path(
+            entity_slug,
+            database_slug,
+            &format!("{}/base", started_at.timestamp_millis()),
+        );
+        self.put_encoded_file(&key, base_path).await
+    }
+
+    /// Uploads `wal`, a write-ahead log holding committed frames, as
+    /// `segment` of the generation that started at `started_at`.
+    pub async fn put_wal_segment(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        started_at: DateTime<Utc>,
+        segment: &WalSegment,
+        wal: &[u8],
+    ) -> Result<(), AybError> {
+        let key = self.wal_path(
+            entity_slug,
+            database_slug,
+            &wal_segment_name(started_at, segment),
+        );
+        self.put_encoded(&key, wal).await
+    }
+
+    /// Lists the write-ahead log generations of
+    /// `{entity_slug}/{database_slug}` that have a base, oldest first,
+    /// with their segments in order.
+    pub async fn list_wal_generations(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+    ) -> Result<Vec<WalGeneration>, AybError> {
+        let prefix = self.wal_path(entity_slug, database_slug, "");
+        let mut generations: BTreeMap<i64, (bool, Vec<WalSegment>)> = BTreeMap::new();
+        for key in self.list_keys(prefix.clone()).await? {
+            let Some((started_at, name)) = key
+                .strip_prefix(&prefix)
+                .and_then(|key| key.split_once('/'))
+            else {
+                continue;
+            };
+            let Ok(started_at) = started_at.parse() else {
+                continue;
+            };
+            let (has_base, segments) = generations.entry(started_at).or_default();
+            if name == "base" {
+                *has_base = true;
+            } else if let Some((sequence, shipped_at)) = name.split_once('-') {
+                if let (Ok(sequence), Some(shipped_at)) = (
+                    sequence.parse(),
+                    shipped_at
+                        .parse()
+                        .ok()
+                        .and_then(DateTime::from_timestamp_millis),
+                ) {
+                    segments.push(WalSegment {
+                        sequence,
+                        shipped_at,
+                    });
+                }
+            }
+        }
+        Ok(generations
+            .into_iter()
+            .filter_map(|(started_at, (has_base, mut segments))| {
+                segments.sort_by_key(|segment| segment.sequence);
+                Some(WalGeneration {
+                    started_at: DateTime::from_timestamp_millis(started_at).filter(|_| has_base)?,
+                    segments,
+                })
+            })
+            .collect())
+    }
 
-        self.bucket
-            .put_object(&path, &compressed_data)
+    /// Downloads the base of `generation` to
+    /// `{destination_path}/{database_slug}`.
+    pub async fn retrieve_wal_base(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        generation: &WalGeneration,
+        destination_path: &Path,
+    ) -> Result<(), AybError> {
+        let key = self.wal_path(
+            entity_slug,
+            database_slug,
+            &format!("{}/base", generation.started_at.timestamp_millis()),
+        );
+        let stream = self.store.get_stream(&key).await?;
+        self.decode_to_file(&key, &[], stream, &destination_path.join(database_slug))
             .await
-            .map_err(|err| AybError::S3ExecutionError {
-                message: format!("Failed to upload snapshot {path}: {err:?}"),
-            })?;
+    }
+
+    /// Downloads `segment` of `generation`.
+    pub async fn retrieve_wal_segment(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        generation: &WalGeneration,
+        segment: &WalSegment,
+    ) -> Result<Vec<u8>, AybError> {
+        let key = self.wal_path(
+            entity_slug,
+            database_slug,
+            &wal_segment_name(generation.started_at, segment),
+        );
+        self.get_decoded(&key).await
+    }
 
+    /// Deletes the base and segments of each of `generations`.
+    pub async fn delete_wal_generations(
+        &self,
+        entity_slug: &str,
+        database_slug: &str,
+        generations: &[WalGeneration],
+    ) -> Result<(), AybError> {
+        for generation in generations {
+            let keys = self
+                .list_keys(self.wal_path(
+                    entity_slug,
+                    database_slug,
+                    &format!("{}/", generation.started_at.timestamp_millis()),
+                ))
+                .await?;
+            self.delete_keys(&keys).await?;
+        }
         Ok(())
     }
 }
+
+fn read_error(key: &str, error: io::Error) -> AybError {
+    AybError::SnapshotError {
+        message: format!("Unable to read {key}: {error}"),
+    }
+}
+
+fn wal_segment_name(started_at: DateTime<Utc>, segment: &WalSegment) -> String {
+    format!(
+        "{}/{}-{}",
+        started_at.timestamp_millis(),
+        segment.sequence,
+        segment.shipped_at.timestamp_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hosted_db::engine_for;
+    use crate::server::config::{AybConfigSnapshotsEncryption, AybConfigSnapshotsLocal};
+    use crate::server::snapshots::models::SnapshotType;
+    use crate::server::snapshots::retention::{snapshots_to_prune, RetentionPolicy};
+    use chrono::TimeZone;
+
+    async fn storage(path: &Path, encrypted: bool) -> SnapshotStorage {
+        SnapshotStorage::new(&AybConfigSnapshots {
+            access_key_id: None,
+            secret_access_key: None,
+            bucket: None,
+            path_prefix: "prefix".to_string(),
+            endpoint_url: None,
+            region: None,
+            force_path_style: None,
+            local: Some(AybConfigSnapshotsLocal {
+                path: path.to_string_lossy().to_string(),
+            }),
+            automation: None,
+            point_in_time: None,
+            encryption: encrypted.then(|| AybConfigSnapshotsEncryption {
+                key: "7Bfo0GZ2vDmoFq3Xu0nEXnuNzNoSLjjgV/jZOtYUVAY=".to_string(),
+                previous_keys: None,
+                read_unencrypted: None,
+            }),
+            query_cache_idle: None,
+        })
+        .await
+        .unwrap()
+    }
+
+    fn snapshot(snapshot_id: &str) -> Snapshot {
+        Snapshot {
+            snapshot_id: snapshot_id.to_string(),
+            snapshot_type: SnapshotType::Automatic as i16,
+            label: None,
+            db_type: DBType::Sqlite as i16,
+            triggered_by: None,
+            source_database_id: "source".to_string(),
+        }
+    }
+
+    /// About a megabyte that doesn't repeat itself.
+    fn database() -> Vec<u8> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        (0..1024 * 1024)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    async fn chunk_keys(storage: &SnapshotStorage) -> HashSet<String> {
+        storage
+            .list_keys(storage.chunk_path("e", "db.sqlite", ""))
+            .await
+            .unwrap()
+            .into_iter()
+            .collect()
+    }
+
+    async fn put(storage: &SnapshotStorage, dir: &Path, snapshot_id: &str, data: &[u8]) {
+        let path = dir.join(snapshot_id);
+        std::fs::write(&path, data).unwrap();
+        storage
+            .put("e", "db.sqlite", &snapshot(snapshot_id), &path)
+            .await
+            .unwrap();
+    }
+
+    async fn retrieve(storage: &SnapshotStorage, dir: &Path, snapshot_id: &str) -> Vec<u8> {
+        let destination = dir.join(format!("{snapshot_id}-restored"));
+        std::fs::create_dir(&destination).unwrap();
+        storage
+            .retrieve_snapshot("e", "db.sqlite", snapshot_id, &destination)
+            .await
+            .unwrap();
+        std::fs::read(destination.join("db.sqlite")).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_changed_page_uploads_few_chunks() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(store_dir.path(), false).await;
+        let mut data = database();
+        put(&storage, dir.path(), "one", &data).await;
+        let first_chunks = chunk_keys(&storage).await;
+        assert!(first_chunks.len() > 4);
+
+        // Changing a page only stores the chunks around it.
+        data[512 * 1024] ^= 0xff;
+        put(&storage, dir.path(), "two", &data).await;
+        let second_chunks = chunk_keys(&storage).await;
+        let new_chunks = second_chunks.difference(&first_chunks).count();
+        assert!((1..=2).contains(&new_chunks));
+        assert!(first_chunks.is_subset(&second_chunks));
+    }
+
+    #[tokio::test]
+    async fn test_real_snapshot_of_changed_table_uploads_few_chunks() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(store_dir.path(), false).await;
+        let db_path = dir.path().join("db.sqlite");
+        let connection = rusqlite::Connection::open(&db_path).unwrap();
+        for table in ["first", "second", "third"] {
+            connection
+                .execute_batch(&format!(
+                    "CREATE TABLE {table}(id INTEGER PRIMARY KEY, value TEXT);
+                     WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 4000)
+                     INSERT INTO {table}(value) SELECT hex(randomblob(100)) FROM n;"
+                ))
+                .unwrap();
+        }
+
+        // Snapshots rewrite the whole file, so the pages after the
+        // inserted row (which needs pages of its own) move, but their
+        // chunks are still shared.
+        let engine = engine_for(&DBType::Sqlite);
+        let snapshot_path = dir.path().join("one");
+        engine.create_snapshot(&db_path, &snapshot_path).unwrap();
+        storage
+            .put("e", "db.sqlite", &snapshot("one"), &snapshot_path)
+            .await
+            .unwrap();
+        let first_chunks = chunk_keys(&storage).await;
+        connection
+            .execute(
+                "INSERT INTO first(value) VALUES (hex(randomblob(5000)))",
+                [],
+            )
+            .unwrap();
+        let snapshot_path = dir.path().join("two");
+        engine.create_snapshot(&db_path, &snapshot_path).unwrap();
+        storage
+            .put("e", "db.sqlite", &snapshot("two"), &snapshot_path)
+            .await
+            .unwrap();
+
+        let new_chunks = chunk_keys(&storage).await.difference(&first_chunks).count();
+        assert!(first_chunks.len() >= 20);
+        assert!(
+            new_chunks <= 5,
+            "{new_chunks} of {} chunks are new",
+            first_chunks.len()
+        );
+        assert_eq!(
+            retrieve(&storage, dir.path(), "two").await,
+            std::fs::read(&snapshot_path).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deleting_snapshots_removes_unreferenced_chunks() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(store_dir.path(), false).await;
+        let mut data = database();
+        put(&storage, dir.path(), "one", &data).await;
+        let first_chunks = chunk_keys(&storage).await;
+        data[0] ^= 0xff;
+        put(&storage, dir.path(), "two", &data).await;
+        assert_eq!(chunk_keys(&storage).await.len(), first_chunks.len() + 1);
+
+        // Only the first chunk of the deleted snapshot is unreferenced.
+        storage
+            .delete_snapshots("e", "db.sqlite", &vec!["one".to_string()])
+            .await
+            .unwrap();
+        let manifest = storage
+            .get_manifest("e", "db.sqlite", "two")
+            .await
+            .unwrap()
+            .unwrap();
+        let referenced_chunks: HashSet<String> = manifest
+            .chunks
+            .iter()
+            .map(|name| storage.chunk_path("e", "db.sqlite", name))
+            .collect();
+        let remaining_chunks = chunk_keys(&storage).await;
+        assert_eq!(remaining_chunks, referenced_chunks);
+        assert_eq!(first_chunks.difference(&remaining_chunks).count(), 1);
+        assert_eq!(retrieve(&storage, dir.path(), "two").await, data);
+
+        storage
+            .delete_snapshots("e", "db.sqlite", &vec!["two".to_string()])
+            .await
+            .unwrap();
+        assert!(chunk_keys(&storage).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_moved_snapshots_keep_their_creation_times() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let storage = storage(store_dir.path(), false).await;
+        let now = Utc.with_ymd_and_hms(2026, 10, 18, 12, 30, 0).unwrap();
+        let data = database();
+        for (snapshot_id, minutes_ago) in [("a", 10), ("b", 50), ("c", 80), ("d", 210)] {
+            put(&storage, dir.path(), snapshot_id, &data).await;
+            // Backdate the snapshot, as if it were taken `minutes_ago`.
+            let mut manifest = storage
+                .get_manifest("e", "db.sqlite", snapshot_id)
+                .await
+                .unwrap()
+                .unwrap();
+            manifest.created_at = Some(now - chrono::Duration::minutes(minutes_ago));
+            storage
+                .store
+                .put(
+                    &storage.db_path("e", "db.sqlite", snapshot_id),
+                    &storage
+                        .seal(&serde_json::to_vec(&manifest).unwrap())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        storage
+            .move_snapshots("e", "db.sqlite", "other", "moved.sqlite")
+            .await
+            .unwrap();
+
+        // Moving rewrote every object, but the newest snapshot and the
+        // newest of the previous hour are still the ones kept.
+        let snapshots = storage
+            .list_snapshots("other", "moved.sqlite")
+            .await
+            .unwrap();
+        let ids: Vec<&str> = snapshots.iter().map(|s| s.snapshot_id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b", "c", "d"]);
+        assert_eq!(
+            snapshots[3].last_modified_at,
+            now - chrono::Duration::minutes(210)
+        );
+        let policy = RetentionPolicy {
+            hourly: 2,
+            daily: 0,
+            weekly: 0,
+        };
+        let pruned: Vec<&str> = snapshots_to_prune(&snapshots, 1, Some(&policy), now)
+            .iter()
+            .map(|s| s.snapshot_id.as_str())
+            .collect();
+        assert_eq!(pruned, vec!["c", "d"]);
+    }
+
+    #[tokio::test]
+    async fn test_encrypted_chunks_hide_their_contents() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let data = database();
+        let plain_storage = storage(store_dir.path(), false).await;
+        put(&plain_storage, dir.path(), "plain", &data).await;
+        let mut storage = storage(store_dir.path(), true).await;
+        put(&storage, dir.path(), "encrypted", &data).await;
+
+        // Encrypted chunks aren't named after the hash of what they
+        // hold, so they share nothing with the unencrypted snapshot.
+        let plain_manifest = plain_storage
+            .get_manifest("e", "db.sqlite", "plain")
+            .await
+            .unwrap()
+            .unwrap();
+        let manifest = storage
+            .get_manifest("e", "db.sqlite", "encrypted")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(manifest.chunks.len(), plain_manifest.chunks.len());
+        for (name, plain_name) in manifest.chunks.iter().zip(&plain_manifest.chunks) {
+            assert!(!name.starts_with(plain_name.as_str()));
+        }
+        assert_eq!(retrieve(&storage, dir.path(), "encrypted").await, data);
+
+        // The unencrypted snapshot can only be restored once that's
+        // allowed.
+        let destination = dir.path().join("plain-rejected");
+        std::fs::create_dir(&destination).unwrap();
+        let error = storage
+            .retrieve_snapshot("e", "db.sqlite", "plain", &destination)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("read_unencrypted"));
+        storage.keys = Some(
+            SnapshotKeys::from_config(&AybConfigSnapshotsEncryption {
+                key: "7Bfo0GZ2vDmoFq3Xu0nEXnuNzNoSLjjgV/jZOtYUVAY=".to_string(),
+                previous_keys: None,
+                read_unencrypted: Some(true),
+            })
+            .unwrap(),
+        );
+        assert_eq!(retrieve(&storage, dir.path(), "plain").await, data);
+    }
+
+    #[tokio::test]
+    async fn test_restore_is_byte_identical() {
+        for encrypted in [false, true] {
+            let store_dir = tempfile::tempdir().unwrap();
+            let dir = tempfile::tempdir().unwrap();
+            let storage = storage(store_dir.path(), encrypted).await;
+            let data = database();
+            put(&storage, dir.path(), "one", &data).await;
+            // An empty database has no chunks at all.
+            put(&storage, dir.path(), "empty", &[]).await;
+
+            assert_eq!(retrieve(&storage, dir.path(), "one").await, data);
+            assert!(retrieve(&storage, dir.path(), "empty").await.is_empty());
+        }
+    }
+}
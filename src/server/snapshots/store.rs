@@ -0,0 +1,608 @@
+use crate::error::AybError;
+use crate::server::config::{AybConfigSnapshots, AybConfigSnapshotsLocal};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use s3::creds::Credentials;
+use s3::error::S3Error;
+use s3::serde_types::Part;
+use s3::{Bucket, Region};
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+// Objects uploaded a part at a time are sent to S3 in parts of this
+// size. S3 requires every part but the last to be at least 5 MiB.
+const UPLOAD_PART_SIZE: usize = 8 * 1024 * 1024;
+// Local objects are read in pieces of this size.
+const LOCAL_READ_SIZE: usize = 256 * 1024;
+
+/// An object in snapshot storage.
+#[derive(Debug)]
+pub struct StoredObject {
+    pub key: String,
+    pub last_modified_at: DateTime<Utc>,
+}
+
+/// Where snapshots are kept. Objects are named by `/`-separated keys,
+/// and `SnapshotStorage` lays out snapshots, their chunks, and the
+/// replicated write-ahead log on top of these operations.
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), AybError>;
+    /// Starts uploading an object too large to hold in memory, a part
+    /// at a time.
+    async fn start_upload(&self, key: &str) -> Result<Box<dyn ObjectUpload>, AybError>;
+    /// Fails with `SnapshotDoesNotExistError` if there's no object at
+    /// `key`.
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AybError>;
+    /// Like `get`, but returns the object in pieces as it's
+    /// downloaded, for objects too large to hold in memory.
+    async fn get_stream(&self, key: &str) -> Result<ObjectStream, AybError>;
+    /// Every object whose key starts with `prefix`.
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AybError>;
+    async fn delete(&self, key: &str) -> Result<(), AybError>;
+    /// Copies the object at `from` to `to`, which is then the most
+    /// recently modified of the two.
+    async fn copy(&self, from: &str, to: &str) -> Result<(), AybError>;
+}
+
+/// An object being uploaded a part at a time. Nothing is stored at
+/// its key until the upload is completed, and an upload that fails
+/// partway should be aborted to clean up the parts it stored.
+#[async_trait]
+pub trait ObjectUpload: Send {
+    async fn write(&mut self, data: &[u8]) -> Result<(), AybError>;
+    async fn complete(self: Box<Self>) -> Result<(), AybError>;
+    async fn abort(self: Box<Self>) -> Result<(), AybError>;
+}
+
+/// The pieces of an object, in order, as they're downloaded.
+pub type ObjectStream = BoxStream<'static, Result<Vec<u8>, AybError>>;
+
+/// Creates the store that `config` selects: a local directory if
+/// `local` is set, and an S3-compatible bucket otherwise.
+pub fn create_snapshot_store(
+    config: &AybConfigSnapshots,
+) -> Result<Box<dyn SnapshotStore>, AybError> {
+    config.validate()?;
+    match &config.local {
+        Some(local_config) => Ok(Box::new(LocalSnapshotStore::new(local_config))),
+        None => Ok(Box::new(S3SnapshotStore::new(config)?)),
+    }
+}
+
+pub struct S3SnapshotStore {
+    bucket: Bucket,
+    // Prepended to every key. When `force_path_style` is set, keys
+    // have always included the bucket name.
+    key_prefix: String,
+}
+
+impl S3SnapshotStore {
+    pub fn new(config: &AybConfigSnapshots) -> Result<S3SnapshotStore, AybError> {
+        // `validate` ensures that these are set.
+        let required = |value: &Option<String>| value.clone().unwrap_or_default();
+        let credentials = Credentials::new(
+            Some(&required(&config.access_key_id)),
+            Some(&required(&config.secret_access_key)),
+            None,
+            None,
+            None,
+        )
+        .map_err(|err| AybError::S3ExecutionError {
+            message: format!("Failed to create S3 credentials: {err:?}"),
+        })?;
+
+        let region_str = config.region.clone().unwrap_or("us-east-1".to_string());
+        let region = if let Some(endpoint_url) = &config.endpoint_url {
+            Region::Custom {
+                region: region_str,
+                endpoint: endpoint_url.to_string(),
+            }
+        } else {
+            region_str
+                .parse()
+                .map_err(|err| AybError::S3ExecutionError {
+                    message: format!("Failed to parse region: {region_str}, {err:?}"),
+                })?
+        };
+        let bucket_name = required(&config.bucket);
+        let mut bucket = Bucket::new(&bucket_name, region, credentials).map_err(|err| {
+            AybError::S3ExecutionError {
+                message: format!("Failed to load bucket: {err:?}"),
+            }
+        })?;
+        let force_path_style = config.force_path_style.unwrap_or(false);
+        let mut key_prefix = String::new();
+        if force_path_style {
+            bucket = bucket.with_path_style();
+            key_prefix = format!("{bucket_name}/");
+        }
+
+        Ok(S3SnapshotStore {
+            bucket: *bucket,
+            key_prefix,
+        })
+    }
+
+    fn key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for S3SnapshotStore {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), AybError> {
+        let key = self.key(key);
+        self.bucket
+            .put_object(&key, data)
+            .await
+            .map_err(|err| AybError::S3ExecutionError {
+                message: format!("Failed to upload snapshot {key}: {err:?}"),
+            })?;
+        Ok(())
+    }
+
+    async fn start_upload(&self, key: &str) -> Result<Box<dyn ObjectUpload>, AybError> {
+        Ok(Box::new(S3ObjectUpload {
+            bucket: self.bucket.clone(),
+            key: self.key(key),
+            upload_id: None,
+            buffer: Vec::new(),
+            parts: Vec::new(),
+        }))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AybError> {
+        let key = self.key(key);
+        let response = self
+            .bucket
+            .get_object(&key)
+            .await
+            .map_err(|err| retrieve_error(&key, err))?;
+        Ok(response.bytes().to_vec())
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<ObjectStream, AybError> {
+        let key = self.key(key);
+        let response = self
+            .bucket
+            .get_object_stream(&key)
+            .await
+            .map_err(|err| retrieve_error(&key, err))?;
+        Ok(response
+            .bytes
+            .map_ok(|bytes| bytes.to_vec())
+            .map_err(move |err| retrieve_error(&key, err))
+            .boxed())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AybError> {
+        let results = self
+            .bucket
+            .list(self.key(prefix), None)
+            .await
+            .map_err(|err| AybError::S3ExecutionError {
+                message: format!("Failed to list snapshots: {err:?}"),
+            })?;
+        let mut objects = Vec::new();
+        for object in results.into_iter().flat_map(|result| result.contents) {
+            let key = object.key;
+            let Some(stripped_key) = key.strip_prefix(&self.key_prefix) else {
+                continue;
+            };
+            objects.push(StoredObject {
+                key: stripped_key.to_string(),
+                last_modified_at: object.last_modified.parse().map_err(|err| {
+                    AybError::S3ExecutionError {
+                        message: format!(
+                            "Failed to read last modified datetime from object {key}: {err:?}"
+                        ),
+                    }
+                })?,
+            });
+        }
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AybError> {
+        let key = self.key(key);
+        self.bucket
+            .delete_object(&key)
+            .await
+            .map_err(|err| AybError::S3ExecutionError {
+                message: format!("Failed to delete snapshot {key}: {err:?}"),
+            })?;
+        Ok(())
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), AybError> {
+        let (from, to) = (self.key(from), self.key(to));
+        self.bucket
+            .copy_object_internal(&from, &to)
+            .await
+            .map_err(|err| AybError::S3ExecutionError {
+                message: format!("Failed to copy snapshot {from} to {to}: {err:?}"),
+            })?;
+        Ok(())
+    }
+}
+
+fn retrieve_error(key: &str, err: S3Error) -> AybError {
+    match err {
+        S3Error::HttpFailWithBody(404, ref body) if body.contains("<Code>NoSuchKey</Code>") => {
+            AybError::SnapshotDoesNotExistError
+        }
+        _ => AybError::S3ExecutionError {
+            message: format!("Failed to retrieve snapshot {key}: {err:?}"),
+        },
+    }
+}
+
+/// Uploads an object to S3 in `UPLOAD_PART_SIZE` parts. Objects smaller
+/// than a part are uploaded whole when the upload is completed, and
+/// the multipart upload is only started once a part is full.
+struct S3ObjectUpload {
+    bucket: Bucket,
+    key: String,
+    upload_id: Option<String>,
+    buffer: Vec<u8>,
+    parts: Vec<Part>,
+}
+
+impl S3ObjectUpload {
+    fn upload_error(&self, err: S3Error) -> AybError {
+        AybError::S3ExecutionError {
+            message: format!("Failed to upload snapshot {}: {err:?}", self.key),
+        }
+    }
+
+    async fn upload_part(&mut self, part: Vec<u8>) -> Result<(), AybError> {
+        let upload_id = match &self.upload_id {
+            Some(upload_id) => upload_id.clone(),
+            None => {
+                let upload = self
+                    .bucket
+                    .initiate_multipart_upload(&self.key, "application/octet-stream")
+                    .await
+                    .map_err(|err| self.upload_error(err))?;
+                self.upload_id = Some(upload.upload_id.clone());
+                upload.upload_id
+            }
+        };
+        let part = self
+            .bucket
+            .put_multipart_chunk(
+                part,
+                &self.key,
+                self.parts.len() as u32 + 1,
+                &upload_id,
+                "application/octet-stream",
+            )
+            .await
+            .map_err(|err| self.upload_error(err))?;
+        self.parts.push(part);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ObjectUpload for S3ObjectUpload {
+    async fn write(&mut self, data: &[u8]) -> Result<(), AybError> {
+        self.buffer.extend_from_slice(data);
+        while self.buffer.len() >= UPLOAD_PART_SIZE {
+            let part = self.buffer.drain(..UPLOAD_PART_SIZE).collect();
+            self.upload_part(part).await?;
+        }
+        Ok(())
+    }
+
+    async fn complete(mut self: Box<Self>) -> Result<(), AybError> {
+        if self.upload_id.is_none() {
+            self.bucket
+                .put_object(&self.key, &self.buffer)
+                .await
+                .map_err(|err| self.upload_error(err))?;
+            return Ok(());
+        }
+        if !self.buffer.is_empty() {
+            let part = std::mem::take(&mut self.buffer);
+            self.upload_part(part).await?;
+        }
+        let upload_id = self.upload_id.clone().unwrap_or_default();
+        self.bucket
+            .complete_multipart_upload(&self.key, &upload_id, std::mem::take(&mut self.parts))
+            .await
+            .map_err(|err| self.upload_error(err))?;
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) -> Result<(), AybError> {
+        if let Some(upload_id) = &self.upload_id {
+            self.bucket
+                .abort_upload(&self.key, upload_id)
+                .await
+                .map_err(|err| self.upload_error(err))?;
+        }
+        Ok(())
+    }
+}
+
+/// Keeps each object in a file under `path`, at the path its key
+/// names. Useful for development, tests, and single-machine
+/// deployments that back up the directory some other way.
+pub struct LocalSnapshotStore {
+    path: PathBuf,
+}
+
+impl LocalSnapshotStore {
+    pub fn new(config: &AybConfigSnapshotsLocal) -> LocalSnapshotStore {
+        LocalSnapshotStore {
+            path: PathBuf::from(&config.path),
+        }
+    }
+
+    fn file_path(&self, key: &str) -> PathBuf {
+        // With a blank `path_prefix`, keys start with `/`.
+        self.path.join(key.trim_start_matches('/'))
+    }
+
+    fn list_files(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        let entries = match fs::read_dir(directory) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                Self::list_files(&path, files)?;
+            } else if !is_partial_file(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Objects are written to a hidden file next to their destination,
+/// then renamed into place, so that partial writes are never listed.
+fn is_partial_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.'))
+}
+
+/// The hidden file that an object at `path` is written to first,
+/// after creating the directory that holds it.
+fn partial_path(path: &Path) -> Result<PathBuf, AybError> {
+    let parent = path.parent().ok_or_else(|| AybError::SnapshotError {
+        message: format!("Invalid snapshot path {}", path.display()),
+    })?;
+    fs::create_dir_all(parent)?;
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+    Ok(parent.join(format!(".{file_name}.partial")))
+}
+
+fn write_atomically(path: &Path, data: &[u8]) -> Result<(), AybError> {
+    let partial_path = partial_path(path)?;
+    fs::write(&partial_path, data)?;
+    fs::rename(&partial_path, path)?;
+    Ok(())
+}
+
+fn open_object(path: &Path) -> Result<File, AybError> {
+    match File::open(path) {
+        Ok(file) => Ok(file),
+        Err(err) if err.kind() == ErrorKind::NotFound => Err(AybError::SnapshotDoesNotExistError),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Writes an object to its partial file as it's uploaded.
+struct LocalObjectUpload {
+    path: PathBuf,
+    partial_path: PathBuf,
+    file: File,
+}
+
+#[async_trait]
+impl ObjectUpload for LocalObjectUpload {
+    async fn write(&mut self, data: &[u8]) -> Result<(), AybError> {
+        Ok(self.file.write_all(data)?)
+    }
+
+    async fn complete(self: Box<Self>) -> Result<(), AybError> {
+        self.file.sync_all()?;
+        fs::rename(&self.partial_path, &self.path)?;
+        Ok(())
+    }
+
+    async fn abort(self: Box<Self>) -> Result<(), AybError> {
+        match fs::remove_file(&self.partial_path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotStore for LocalSnapshotStore {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), AybError> {
+        write_atomically(&self.file_path(key), data)
+    }
+
+    async fn start_upload(&self, key: &str) -> Result<Box<dyn ObjectUpload>, AybError> {
+        let path = self.file_path(key);
+        let partial_path = partial_path(&path)?;
+        Ok(Box::new(LocalObjectUpload {
+            file: File::create(&partial_path)?,
+            path,
+            partial_path,
+        }))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AybError> {
+        let mut data = Vec::new();
+        open_object(&self.file_path(key))?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    async fn get_stream(&self, key: &str) -> Result<ObjectStream, AybError> {
+        let file = open_object(&self.file_path(key))?;
+        Ok(stream::try_unfold(file, |mut file| async move {
+            let mut piece = vec![0; LOCAL_READ_SIZE];
+            let read = file.read(&mut piece)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            piece.truncate(read);
+            Ok(Some((piece, file)))
+        })
+        .boxed())
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<StoredObject>, AybError> {
+        // Only walk the directory that holds every key with `prefix`.
+        let directory = match prefix.rfind('/') {
+            Some(index) => self.file_path(&prefix[..index]),
+            None => self.path.clone(),
+        };
+        let mut files = Vec::new();
+        Self::list_files(&directory, &mut files)?;
+        let leading_slash = if prefix.starts_with('/') { "/" } else { "" };
+        let mut objects = Vec::new();
+        for file in files {
+            let Ok(relative_path) = file.strip_prefix(&self.path) else {
+                continue;
+            };
+            let components: Vec<String> = relative_path
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect();
+            let key = format!("{leading_slash}{}", components.join("/"));
+            if key.starts_with(prefix) {
+                objects.push(StoredObject {
+                    key,
+                    last_modified_at: fs::metadata(&file)?.modified()?.into(),
+                });
+            }
+        }
+        Ok(objects)
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AybError> {
+        match fs::remove_file(self.file_path(key)) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn copy(&self, from: &str, to: &str) -> Result<(), AybError> {
+        let mut from_file = open_object(&self.file_path(from))?;
+        let to_path = self.file_path(to);
+        let partial_path = partial_path(&to_path)?;
+        io::copy(&mut from_file, &mut File::create(&partial_path)?)?;
+        fs::rename(&partial_path, &to_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(path: &Path) -> LocalSnapshotStore {
+        LocalSnapshotStore::new(&AybConfigSnapshotsLocal {
+            path: path.to_string_lossy().to_string(),
+        })
+    }
+
+    fn keys(mut objects: Vec<StoredObject>) -> Vec<String> {
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        objects.into_iter().map(|object| object.key).collect()
+    }
+
+    #[tokio::test]
+    async fn test_local_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.put("prefix/e/db/one", b"first").await.unwrap();
+        store.put("prefix/e/db/chunks/abc", b"chunk").await.unwrap();
+        store.put("prefix/e/db2/one", b"other").await.unwrap();
+
+        assert_eq!(store.get("prefix/e/db/one").await.unwrap(), b"first");
+        assert!(matches!(
+            store.get("prefix/e/db/missing").await,
+            Err(AybError::SnapshotDoesNotExistError)
+        ));
+        assert_eq!(
+            keys(store.list("prefix/e/db/").await.unwrap()),
+            vec!["prefix/e/db/chunks/abc", "prefix/e/db/one"]
+        );
+        assert!(store.list("prefix/nobody/").await.unwrap().is_empty());
+
+        store
+            .copy("prefix/e/db/one", "prefix/e/db3/one")
+            .await
+            .unwrap();
+        assert_eq!(store.get("prefix/e/db3/one").await.unwrap(), b"first");
+        store.delete("prefix/e/db/one").await.unwrap();
+        // Deleting a missing object isn't an error, as with S3.
+        store.delete("prefix/e/db/one").await.unwrap();
+        assert_eq!(
+            keys(store.list("prefix/e/db/").await.unwrap()),
+            vec!["prefix/e/db/chunks/abc"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_store_uploads_and_streams() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        let data: Vec<u8> = (0..3 * LOCAL_READ_SIZE + 5).map(|i| i as u8).collect();
+
+        // Nothing is listed until the upload completes.
+        let mut upload = store.start_upload("prefix/e/db/big").await.unwrap();
+        for part in data.chunks(100_000) {
+            upload.write(part).await.unwrap();
+        }
+        assert!(store.list("prefix/e/db/").await.unwrap().is_empty());
+        upload.complete().await.unwrap();
+        let pieces: Vec<Vec<u8>> = store
+            .get_stream("prefix/e/db/big")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(pieces.len(), 4);
+        assert_eq!(pieces.concat(), data);
+
+        // Aborted uploads leave nothing behind.
+        let mut upload = store.start_upload("prefix/e/db/aborted").await.unwrap();
+        upload.write(b"partial").await.unwrap();
+        upload.abort().await.unwrap();
+        assert!(matches!(
+            store.get_stream("prefix/e/db/aborted").await,
+            Err(AybError::SnapshotDoesNotExistError)
+        ));
+        assert_eq!(
+            keys(store.list("prefix/e/db/").await.unwrap()),
+            vec!["prefix/e/db/big"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_local_store_keys_without_prefix() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.put("/e/db/one", b"first").await.unwrap();
+        assert_eq!(store.get("/e/db/one").await.unwrap(), b"first");
+        assert_eq!(keys(store.list("/e/db/").await.unwrap()), vec!["/e/db/one"]);
+    }
+}
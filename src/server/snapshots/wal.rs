@@ -0,0 +1,564 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::DBType;
+use crate::error::AybError;
+use crate::hosted_db::daemon_registry::DaemonRegistry;
+use crate::hosted_db::paths::{
+    current_database_path, database_parent_path, database_snapshot_path, pathbuf_to_file_name,
+};
+use crate::hosted_db::sqlite::checkpoint_wal;
+use crate::server::config::{AybConfig, AybConfigSnapshotsPointInTime};
+use crate::server::snapshots::models::WalSegment;
+use crate::server::snapshots::storage::SnapshotStorage;
+use chrono::{DateTime, Utc};
+use go_parse_duration::parse_duration;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::Mutex;
+use tokio_cron_scheduler::{Job, JobScheduler};
+
+// See https://www.sqlite.org/fileformat.html#the_write_ahead_log for
+// the layout of the log.
+const WAL_HEADER_SIZE: usize = 32;
+const WAL_FRAME_HEADER_SIZE: usize = 24;
+const WAL_MAGIC_LITTLE_ENDIAN: u32 = 0x377f0682;
+const WAL_MAGIC_BIG_ENDIAN: u32 = 0x377f0683;
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// SQLite's write-ahead log checksum of `data`, continued from
+/// `checksum`.
+fn wal_checksum(big_endian: bool, data: &[u8], checksum: (u32, u32)) -> (u32, u32) {
+    let (mut s0, mut s1) = checksum;
+    for words in data.chunks_exact(8) {
+        let (x0, x1) = if big_endian {
+            (read_u32(words, 0), read_u32(words, 4))
+        } else {
+            (
+                u32::from_le_bytes(words[..4].try_into().unwrap()),
+                u32::from_le_bytes(words[4..].try_into().unwrap()),
+            )
+        };
+        s0 = s0.wrapping_add(x0).wrapping_add(s1);
+        s1 = s1.wrapping_add(x1).wrapping_add(s0);
+    }
+    (s0, s1)
+}
+
+/// A page image in the write-ahead log.
+#[derive(Debug)]
+struct WalFrame<'a> {
+    page_number: u32,
+    // The size of the database in pages after the transaction, for
+    // the frame that commits it, and 0 otherwise.
+    committed_size: u32,
+    page: &'a [u8],
+}
+
+/// The page size and committed frames of `wal`, which SQLite wrote
+/// as a write-ahead log. Frames after the last commit are left out, as
+/// are stale frames from before the log was last restarted, which
+/// have the wrong salt or checksum.
+fn committed_frames(wal: &[u8]) -> (usize, Vec<WalFrame<'_>>) {
+    if wal.len() < WAL_HEADER_SIZE {
+        return (0, Vec::new());
+    }
+    let big_endian = match read_u32(wal, 0) {
+        WAL_MAGIC_LITTLE_ENDIAN => false,
+        WAL_MAGIC_BIG_ENDIAN => true,
+        _ => return (0, Vec::new()),
+    };
+    let page_size = match read_u32(wal, 8) {
+        // 65536 doesn't fit in the header's 16 bits.
+        1 => 65536,
+        page_size => page_size as usize,
+    };
+    let mut checksum = wal_checksum(big_endian, &wal[..24], (0, 0));
+    if page_size == 0 || checksum != (read_u32(wal, 24), read_u32(wal, 28)) {
+        return (0, Vec::new());
+    }
+    let salt = &wal[16..24];
+
+    let mut frames = Vec::new();
+    let mut committed = 0;
+    let mut offset = WAL_HEADER_SIZE;
+    while offset + WAL_FRAME_HEADER_SIZE + page_size <= wal.len() {
+        let header = &wal[offset..offset + WAL_FRAME_HEADER_SIZE];
+        let page = &wal[offset + WAL_FRAME_HEADER_SIZE..offset + WAL_FRAME_HEADER_SIZE + page_size];
+        checksum = wal_checksum(big_endian, &header[..8], checksum);
+        checksum = wal_checksum(big_endian, page, checksum);
+        if &header[8..16] != salt || checksum != (read_u32(header, 16), read_u32(header, 20)) {
+            break;
+        }
+        frames.push(WalFrame {
+            page_number: read_u32(header, 0),
+            committed_size: read_u32(header, 4),
+            page,
+        });
+        if read_u32(header, 4) != 0 {
+            committed = frames.len();
+        }
+        offset += WAL_FRAME_HEADER_SIZE + page_size;
+    }
+    frames.truncate(committed);
+    (page_size, frames)
+}
+
+/// The length of the part of `wal` that holds its header and
+/// committed frames, which is itself a valid write-ahead log.
+fn committed_wal_length(wal: &[u8]) -> usize {
+    let (page_size, frames) = committed_frames(wal);
+    if frames.is_empty() {
+        0
+    } else {
+        WAL_HEADER_SIZE + frames.len() * (WAL_FRAME_HEADER_SIZE + page_size)
+    }
+}
+
+/// Write the transactions committed in `wal` to the SQLite database
+/// file at `database_path`, as a checkpoint would.
+pub fn apply_wal(wal: &[u8], database_path: &Path) -> Result<(), AybError> {
+    let (page_size, frames) = committed_frames(wal);
+    let mut database = OpenOptions::new().write(true).open(database_path)?;
+    for frame in frames {
+        let page_index =
+            frame
+                .page_number
+                .checked_sub(1)
+                .ok_or_else(|| AybError::SnapshotError {
+                    message: "Write-ahead log frame has no page number".to_string(),
+                })?;
+        database.seek(SeekFrom::Start(u64::from(page_index) * page_size as u64))?;
+        database.write_all(frame.page)?;
+        if frame.committed_size != 0 {
+            database.set_len(u64::from(frame.committed_size) * page_size as u64)?;
+        }
+    }
+    database.sync_all()?;
+    Ok(())
+}
+
+fn wal_path(db_path: &Path) -> PathBuf {
+    let mut path = OsString::from(db_path);
+    path.push("-wal");
+    PathBuf::from(path)
+}
+
+/// A database file's size and modification time. If either changes
+/// between replication runs, something wrote to the file without going
+/// through the write-ahead log, and the log alone can't replay it.
+fn file_signature(path: &Path) -> Result<(u64, SystemTime), AybError> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.len(), metadata.modified()?))
+}
+
+/// How far replication of a database's current generation has gotten.
+struct ReplicationState {
+    started_at: DateTime<Utc>,
+    next_sequence: u64,
+    base_bytes: u64,
+    shipped_bytes: u64,
+    database_signature: (u64, SystemTime),
+}
+
+/// Replication state, by the path of the database version it's for.
+/// Restoring a database creates a new version, which starts a new
+/// generation.
+type ReplicationStates = HashMap<PathBuf, ReplicationState>;
+
+/// Continuously replicate the write-ahead log of each SQLite database
+/// to snapshot storage, so that databases can be restored to any point
+/// in time within the retention period. Queries leave the log in place
+/// (see `DaemonRegistry::retain_wal`), and each run ships the
+/// transactions committed since the last one before checkpointing the
+/// log itself.
+pub async fn schedule_wal_replication(
+    config: AybConfig,
+    ayb_db: Box<dyn AybDb>,
+    daemon_registry: DaemonRegistry,
+) -> Result<(), AybError> {
+    let Some(point_in_time_config) = config
+        .snapshots
+        .as_ref()
+        .and_then(|snapshots| snapshots.point_in_time.clone())
+    else {
+        return Ok(());
+    };
+    let interval = parse_point_in_time_duration(&point_in_time_config.interval)?;
+    // Runs that take longer than the interval make the next run skip.
+    let states = Arc::new(Mutex::new(ReplicationStates::new()));
+    let scheduler = JobScheduler::new().await?;
+    scheduler
+        .add(Job::new_repeated_async(interval, move |_, _| {
+            let config = config.clone();
+            let point_in_time_config = point_in_time_config.clone();
+            let ayb_db = ayb_db.clone();
+            let daemon_registry = daemon_registry.clone();
+            let states = states.clone();
+            Box::pin(async move {
+                let Ok(mut states) = states.try_lock() else {
+                    return;
+                };
+                if let Err(err) = replicate_databases(
+                    &config,
+                    &point_in_time_config,
+                    &ayb_db,
+                    &daemon_registry,
+                    &mut states,
+                )
+                .await
+                {
+                    eprintln!("Unable to walk database directory for WAL replication: {err}");
+                }
+            })
+        })?)
+        .await?;
+    scheduler.shutdown_on_ctrl_c();
+    scheduler.start().await?;
+    Ok(())
+}
+
+pub fn parse_point_in_time_duration(duration: &str) -> Result<Duration, AybError> {
+    Ok(Duration::from_nanos(
+        parse_duration(duration)?
+            .try_into()
+            .map_err(|err| AybError::SnapshotError {
+                message: format!("Unable to turn {duration} into a duration: {err:?}"),
+            })?,
+    ))
+}
+
+#[allow(clippy::borrowed_box)]
+async fn replicate_databases(
+    config: &AybConfig,
+    point_in_time_config: &AybConfigSnapshotsPointInTime,
+    ayb_db: &Box<dyn AybDb>,
+    daemon_registry: &DaemonRegistry,
+    states: &mut ReplicationStates,
+) -> Result<(), AybError> {
+    let snapshot_storage = SnapshotStorage::new(config.snapshots.as_ref().unwrap()).await?;
+    let mut replicated_states = ReplicationStates::new();
+    for entity_entry in fs::read_dir(database_parent_path(&config.data_path, true)?)? {
+        let entity_path = entity_entry?.path();
+        if !entity_path.is_dir() {
+            continue;
+        }
+        let entity = pathbuf_to_file_name(&entity_path)?;
+        for database_entry in fs::read_dir(&entity_path)? {
+            let database = pathbuf_to_file_name(&database_entry?.path())?;
+            let db = match ayb_db.get_database(&entity, &database).await {
+                Ok(db) => db,
+                Err(AybError::RecordNotFound { record_type, .. }) if record_type == "database" => {
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+            // DuckDB's log isn't made of page images, so it can't be
+            // replayed onto a copy of the database.
+            if DBType::try_from(db.db_type)? != DBType::Sqlite {
+                continue;
+            }
+            let db_path = current_database_path(&entity, &database, &config.data_path)?;
+            if !db_path.exists() {
+                continue;
+            }
+            // A failed run drops the database's state, so that the
+            // next run starts a new generation rather than leaving a
+            // gap in this one.
+            let state = states.remove(&db_path);
+            match replicate_database(
+                config,
+                point_in_time_config,
+                &snapshot_storage,
+                daemon_registry,
+                &entity,
+                &database,
+                &db_path,
+                state,
+            )
+            .await
+            {
+                Ok(state) => {
+                    replicated_states.insert(db_path, state);
+                }
+                Err(err) => {
+                    eprintln!("Unable to replicate WAL of {entity}/{database}: {err}");
+                }
+            }
+        }
+    }
+    *states = replicated_states;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn replicate_database(
+    config: &AybConfig,
+    point_in_time_config: &AybConfigSnapshotsPointInTime,
+    snapshot_storage: &SnapshotStorage,
+    daemon_registry: &DaemonRegistry,
+    entity_slug: &str,
+    database_slug: &str,
+    db_path: &Path,
+    state: Option<ReplicationState>,
+) -> Result<ReplicationState, AybError> {
+    let wal_path = wal_path(db_path);
+    let wal_is_empty = fs::metadata(&wal_path).map_or(true, |metadata| metadata.len() == 0);
+    let current_signature = file_signature(db_path)?;
+    // Generations are rolled over once they've shipped more log than
+    // the size of the database, so that restores never have to replay
+    // much more than a copy of the database's worth of log.
+    let continue_generation = state.as_ref().is_some_and(|state| {
+        state.database_signature == current_signature && state.shipped_bytes <= state.base_bytes
+    });
+    if continue_generation && wal_is_empty {
+        return Ok(state.unwrap());
+    }
+
+    let base_directory = if continue_generation {
+        None
+    } else {
+        Some(database_snapshot_path(
+            entity_slug,
+            database_slug,
+            &config.data_path,
+        )?)
+    };
+    // Ship the log, checkpoint it, and copy the database while no query
+    // can add to the log. Uploads happen after, without blocking
+    // queries.
+    let (wal, shipped_at, database_signature) = daemon_registry
+        .with_exclusive_access(db_path, || -> Result<_, AybError> {
+            let mut wal = match fs::read(&wal_path) {
+                Ok(wal) => wal,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Vec::new(),
+                Err(err) => return Err(err.into()),
+            };
+            wal.truncate(committed_wal_length(&wal));
+            checkpoint_wal(db_path)?;
+            if let Some(base_directory) = &base_directory {
+                fs::copy(db_path, base_directory.join(database_slug))?;
+            }
+            Ok((wal, Utc::now(), file_signature(db_path)?))
+        })
+        .await??;
+
+    if let Some(mut state) = state {
+        // The log is still worth shipping to a generation that's being
+        // rolled over, but not one that missed writes.
+        if state.database_signature == current_signature && !wal.is_empty() {
+            snapshot_storage
+                .put_wal_segment(
+                    entity_slug,
+                    database_slug,
+                    state.started_at,
+                    &WalSegment {
+                        sequence: state.next_sequence,
+                        shipped_at,
+                    },
+                    &wal,
+                )
+                .await?;
+            state.next_sequence += 1;
+            state.shipped_bytes += wal.len() as u64;
+        }
+        if continue_generation {
+            state.database_signature = database_signature;
+            return Ok(state);
+        }
+    }
+
+    let base_directory = base_directory.unwrap();
+    let base_path = base_directory.join(database_slug);
+    println!("Starting WAL replication generation for {entity_slug}/{database_slug}");
+    let uploaded = snapshot_storage
+        .put_wal_base(entity_slug, database_slug, shipped_at, &base_path)
+        .await;
+    let base_bytes = fs::metadata(&base_path)?.len();
+    fs::remove_dir_all(&base_directory)?;
+    uploaded?;
+    prune_wal_generations(
+        point_in_time_config,
+        snapshot_storage,
+        entity_slug,
+        database_slug,
+    )
+    .await?;
+    Ok(ReplicationState {
+        started_at: shipped_at,
+        next_sequence: 0,
+        base_bytes,
+        shipped_bytes: 0,
+        database_signature,
+    })
+}
+
+/// Delete generations that no restore within the retention period
+/// needs: those followed by a generation that started before the
+/// period did.
+async fn prune_wal_generations(
+    point_in_time_config: &AybConfigSnapshotsPointInTime,
+    snapshot_storage: &SnapshotStorage,
+    entity_slug: &str,
+    database_slug: &str,
+) -> Result<(), AybError> {
+    let retention = parse_point_in_time_duration(&point_in_time_config.retention)?;
+    let cutoff = Utc::now()
+        - chrono::Duration::from_std(retention).map_err(|err| AybError::SnapshotError {
+            message: format!("Unable to turn retention into a duration: {err:?}"),
+        })?;
+    let mut generations = snapshot_storage
+        .list_wal_generations(entity_slug, database_slug)
+        .await?;
+    let expired = generations
+        .iter()
+        .skip(1)
+        .take_while(|generation| generation.started_at <= cutoff)
+        .count();
+    generations.truncate(expired);
+    snapshot_storage
+        .delete_wal_generations(entity_slug, database_slug, &generations)
+        .await
+}
+
+/// Rebuild `{entity_slug}/{database_slug}` as it was at `target` in
+/// `{destination_path}/{database_slug}`, from the latest generation
+/// that started by then. Transactions are replayed a segment at a
+/// time, so the result includes every transaction shipped by `target`.
+pub async fn restore_wal(
+    snapshot_storage: &SnapshotStorage,
+    entity_slug: &str,
+    database_slug: &str,
+    target: DateTime<Utc>,
+    destination_path: &Path,
+) -> Result<(), AybError> {
+    let generation = snapshot_storage
+        .list_wal_generations(entity_slug, database_slug)
+        .await?
+        .into_iter()
+        .rev()
+        .find(|generation| generation.started_at <= target)
+        .ok_or_else(|| AybError::Other {
+            message: format!(
+                "No point-in-time backup of {entity_slug}/{database_slug} goes back to {target}"
+            ),
+        })?;
+    snapshot_storage
+        .retrieve_wal_base(entity_slug, database_slug, &generation, destination_path)
+        .await?;
+    let database_path = destination_path.join(database_slug);
+    for (expected_sequence, segment) in (0..).zip(&generation.segments) {
+        // A missing segment means that the ones after it can't be
+        // replayed.
+        if segment.sequence != expected_sequence || segment.shipped_at > target {
+            break;
+        }
+        let wal = snapshot_storage
+            .retrieve_wal_segment(entity_slug, database_slug, &generation, segment)
+            .await?;
+        apply_wal(&wal, &database_path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::config::DbConfig;
+
+    fn open(path: &Path) -> rusqlite::Connection {
+        let conn = rusqlite::Connection::open(path).unwrap();
+        let _mode: String = conn
+            .query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))
+            .unwrap();
+        conn.pragma_update(None, "wal_autocheckpoint", 0).unwrap();
+        conn.set_db_config(DbConfig::SQLITE_DBCONFIG_NO_CKPT_ON_CLOSE, true)
+            .unwrap();
+        conn
+    }
+
+    fn values(path: &Path) -> Vec<i64> {
+        let conn = rusqlite::Connection::open(path).unwrap();
+        let mut statement = conn.prepare("SELECT x FROM test ORDER BY x").unwrap();
+        let values = statement
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        values
+    }
+
+    #[test]
+    fn test_apply_wal_replays_committed_transactions() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let base_path = dir.path().join("base.sqlite");
+        let conn = open(&db_path);
+        conn.execute_batch("CREATE TABLE test(x INTEGER);").unwrap();
+        drop(conn);
+        checkpoint_wal(&db_path).unwrap();
+        fs::copy(&db_path, &base_path).unwrap();
+
+        let conn = open(&db_path);
+        conn.execute_batch(
+            "INSERT INTO test VALUES (1), (2);
+             INSERT INTO test SELECT x + 2 FROM test;
+             DELETE FROM test WHERE x = 1;",
+        )
+        .unwrap();
+        drop(conn);
+        let mut wal = fs::read(wal_path(&db_path)).unwrap();
+        assert_eq!(committed_wal_length(&wal), wal.len());
+
+        // A transaction that hasn't committed yet is left out, even
+        // though its frame is valid.
+        let committed_length = wal.len();
+        let frame_size = WAL_FRAME_HEADER_SIZE + committed_frames(&wal).0;
+        let mut frame = wal[WAL_HEADER_SIZE..WAL_HEADER_SIZE + frame_size].to_vec();
+        frame[4..8].copy_from_slice(&[0, 0, 0, 0]);
+        let big_endian = read_u32(&wal, 0) == WAL_MAGIC_BIG_ENDIAN;
+        let last_frame = committed_length - frame_size;
+        let checksum = (
+            read_u32(&wal, last_frame + 16),
+            read_u32(&wal, last_frame + 20),
+        );
+        let checksum = wal_checksum(big_endian, &frame[..8], checksum);
+        let checksum = wal_checksum(big_endian, &frame[WAL_FRAME_HEADER_SIZE..], checksum);
+        frame[16..20].copy_from_slice(&checksum.0.to_be_bytes());
+        frame[20..24].copy_from_slice(&checksum.1.to_be_bytes());
+        wal.extend_from_slice(&frame);
+        assert_eq!(committed_wal_length(&wal), committed_length);
+
+        apply_wal(&wal, &base_path).unwrap();
+        assert_eq!(values(&base_path), vec![2, 3, 4]);
+        assert_eq!(values(&base_path), values(&db_path));
+    }
+
+    #[test]
+    fn test_committed_wal_length_rejects_corrupt_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("test.sqlite");
+        let conn = open(&db_path);
+        conn.execute_batch("CREATE TABLE test(x INTEGER); INSERT INTO test VALUES (1);")
+            .unwrap();
+        drop(conn);
+        let wal = fs::read(wal_path(&db_path)).unwrap();
+
+        assert_eq!(committed_wal_length(&[]), 0);
+        assert_eq!(committed_wal_length(&wal[..WAL_HEADER_SIZE]), 0);
+        let mut corrupt_header = wal.clone();
+        corrupt_header[12] ^= 1;
+        assert_eq!(committed_wal_length(&corrupt_header), 0);
+        // Frames after a corrupt one are stale, even if they'd
+        // otherwise check out.
+        let mut corrupt_frame = wal.clone();
+        corrupt_frame[WAL_HEADER_SIZE + WAL_FRAME_HEADER_SIZE] ^= 1;
+        assert_eq!(committed_wal_length(&corrupt_frame), 0);
+    }
+}
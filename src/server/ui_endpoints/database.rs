@@ -28,10 +28,41 @@ pub async fn database(
         "can_manage_database",
         &database_response.can_manage_database,
     );
+    context.insert(
+        "can_administer_database",
+        &database_response.can_administer_database,
+    );
+    // Surface an outgoing transfer so the owner can see or cancel it.
+    let pending_transfer = if database_response.can_administer_database {
+        client
+            .list_database_transfers()
+            .await
+            .ok()
+            .and_then(|response| {
+                response
+                    .transfers
+                    .into_iter()
+                    .find(|transfer| transfer.database == format!("{entity_slug}/{database_slug}"))
+            })
+    } else {
+        None
+    };
+    context.insert("pending_transfer", &pending_transfer);
     context.insert(
         "highest_query_access_level",
         &database_response.highest_query_access_level,
     );
+    let saved_queries = if database_response.highest_query_access_level.is_some() {
+        client
+            .list_saved_queries(entity_slug, database_slug)
+            .await
+            .map(|response| response.saved_queries)
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    context.insert("saved_queries", &saved_queries);
+    context.insert("forked_from", &database_response.forked_from);
     context.insert(
         "public_sharing_level",
         &database_response.public_sharing_level,
@@ -1,3 +1,4 @@
+use crate::ayb_db::models::EntityType;
 use crate::http::structs::EntityPath;
 use crate::server::config::AybConfig;
 use crate::server::ui_endpoints::auth::{authentication_details, init_ayb_client};
@@ -43,10 +44,39 @@ pub async fn entity_details(
     );
     context.insert("databases", &entity_response.databases);
 
+    let logged_in_entity = authentication_details(&req).map(|details| details.entity);
+    // Only the recipient sees databases that are waiting to be accepted.
+    let incoming_transfers = if logged_in_entity.as_deref() == Some(entity_slug) {
+        client
+            .list_database_transfers()
+            .await
+            .map(|response| {
+                response
+                    .transfers
+                    .into_iter()
+                    .filter(|transfer| &transfer.to_entity == entity_slug)
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    context.insert("incoming_transfers", &incoming_transfers);
+    context.insert("entity_type", &entity_response.entity_type);
     context.insert(
-        "logged_in_entity",
-        &authentication_details(&req).map(|details| details.entity),
+        "can_manage_organization",
+        &entity_response.permissions.can_manage_organization,
     );
+    // Only members can see an organization's members.
+    let members = if entity_response.entity_type == EntityType::Organization.to_str()
+        && logged_in_entity.is_some()
+    {
+        client.list_organization_members(entity_slug).await.ok()
+    } else {
+        None
+    };
+    context.insert("members", &members);
+    context.insert("logged_in_entity", &logged_in_entity);
 
     ok_response("entity_details.html", &context)
 }
@@ -0,0 +1,105 @@
+use crate::ayb_db::models::{EntityDatabaseSharingLevel, OrganizationRole};
+use crate::http::structs::EntityPath;
+use crate::server::config::AybConfig;
+use crate::server::ui_endpoints::auth::init_ayb_client;
+use crate::server::ui_endpoints::templates::error_snippet;
+use actix_web::{post, web, HttpRequest, HttpResponse, Result};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct AddMemberRequest {
+    member: String,
+    role: String,
+}
+
+#[post("/{entity}/add_member")]
+pub async fn add_member(
+    req: HttpRequest,
+    path: web::Path<EntityPath>,
+    form: web::Form<AddMemberRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let organization_slug = &path.entity.to_lowercase();
+    let member = &form.member.trim().to_lowercase();
+
+    if member.is_empty() {
+        return error_snippet("Missing member", "Please provide a username.");
+    }
+
+    let role = match OrganizationRole::from_str(&form.role) {
+        Ok(role) => role,
+        Err(_) => return error_snippet("Invalid role", "Please pick a valid role."),
+    };
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .update_organization_member(organization_slug, member, &role)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok()
+            .append_header(("HX-Redirect", format!("/{organization_slug}")))
+            .finish()),
+        Err(err) => error_snippet("Error updating member", &format!("{err}")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RemoveMemberRequest {
+    member: String,
+}
+
+#[post("/{entity}/remove_member")]
+pub async fn remove_member(
+    req: HttpRequest,
+    path: web::Path<EntityPath>,
+    form: web::Form<RemoveMemberRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let organization_slug = &path.entity.to_lowercase();
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .remove_organization_member(organization_slug, &form.member)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok()
+            .append_header(("HX-Redirect", format!("/{organization_slug}")))
+            .finish()),
+        Err(err) => error_snippet("Error removing member", &format!("{err}")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMemberSharingLevelRequest {
+    sharing_level: String,
+}
+
+#[post("/{entity}/update_member_sharing_level")]
+pub async fn update_member_sharing_level(
+    req: HttpRequest,
+    path: web::Path<EntityPath>,
+    form: web::Form<UpdateMemberSharingLevelRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let organization_slug = &path.entity.to_lowercase();
+
+    let sharing_level = match EntityDatabaseSharingLevel::from_str(&form.sharing_level) {
+        Ok(level) => level,
+        Err(_) => return error_snippet("Invalid access level", "Please pick a valid level."),
+    };
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .update_member_sharing_level(organization_slug, &sharing_level)
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok()
+            .append_header(("HX-Redirect", format!("/{organization_slug}")))
+            .finish()),
+        Err(err) => error_snippet("Error updating member access", &format!("{err}")),
+    }
+}
@@ -5,9 +5,12 @@ mod database;
 mod entity_details;
 mod log_in;
 mod log_out;
+mod members;
 mod oauth;
 mod query;
 mod register;
+mod saved_queries;
+mod settings;
 mod sharing;
 mod snapshots;
 mod templates;
@@ -21,19 +24,30 @@ pub use entity_details::{
 };
 pub use log_in::{log_in as log_in_endpoint, log_in_submit as log_in_submit_endpoint};
 pub use log_out::log_out as log_out_endpoint;
+pub use members::{
+    add_member as add_member_endpoint, remove_member as remove_member_endpoint,
+    update_member_sharing_level as update_member_sharing_level_ui_endpoint,
+};
 pub use oauth::{
     oauth_authorize as oauth_authorize_endpoint,
     oauth_authorize_submit as oauth_authorize_submit_endpoint,
 };
 pub use query::query as query_endpoint;
 pub use register::{register as register_endpoint, register_submit as register_submit_endpoint};
+pub use saved_queries::run_saved_query as run_saved_query_endpoint;
+pub use settings::{
+    accept_database_transfer as accept_database_transfer_endpoint,
+    cancel_database_transfer as cancel_database_transfer_endpoint,
+    delete_database as delete_database_endpoint, fork_database as fork_database_endpoint,
+    rename_database as rename_database_endpoint, transfer_database as transfer_database_endpoint,
+};
 pub use sharing::{
     database_permissions as database_permissions_endpoint,
     share_with_entity as share_with_entity_endpoint,
     update_public_sharing as update_public_sharing_endpoint,
 };
 pub use snapshots::{
-    database_snapshots as database_snapshots_endpoint,
-    restore_snapshot as restore_snapshot_endpoint,
+    create_snapshot as create_snapshot_endpoint, database_snapshots as database_snapshots_endpoint,
+    diff as diff_endpoint, restore_snapshot as restore_snapshot_endpoint,
 };
 pub use tokens::{entity_tokens as entity_tokens_endpoint, revoke_token as revoke_token_endpoint};
@@ -64,7 +64,7 @@ pub async fn oauth_authorize(
     if QueryMode::from_str(&query.scope).is_err() {
         return Ok(oauth_error_page(
             "invalid_scope",
-            "scope must be 'read-only' or 'read-write'",
+            "scope must be 'read-only', 'read-write-data', or 'read-write'",
         ));
     }
 
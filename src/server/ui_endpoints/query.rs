@@ -1,3 +1,4 @@
+use crate::hosted_db::QueryResult;
 use crate::http::structs::EntityDatabasePath;
 use crate::server::config::AybConfig;
 use crate::server::ui_endpoints::auth::init_ayb_client;
@@ -94,26 +95,35 @@ pub async fn query(
                 ))
                 .body(csv_content))
         }
-        _ => {
-            // Get total number of rows and limit display to 500
-            let total_rows = query_result.rows.len();
-            let display_limit = 500;
-            let display_rows = if total_rows > display_limit {
-                &query_result.rows[0..display_limit]
-            } else {
-                &query_result.rows
-            };
-
-            let mut context = tera::Context::new();
-            context.insert("entity", entity_slug);
-            context.insert("database", database_slug);
-            context.insert("query", query_text);
-            context.insert("fields", &query_result.fields);
-            context.insert("rows", &query_result.rows);
-            context.insert("display_rows", &display_rows);
-            context.insert("total_rows", &total_rows);
-            context.insert("display_limit", &display_limit);
-            ok_response("query_results.html", &context)
-        }
+        _ => render_query_results(entity_slug, database_slug, Some(query_text), query_result),
     }
 }
+
+/// Render `query_result` as an HTML table. Without the `query_text` that
+/// produced it, the results can't be downloaded in other formats.
+pub(super) fn render_query_results(
+    entity_slug: &str,
+    database_slug: &str,
+    query_text: Option<&str>,
+    query_result: QueryResult,
+) -> Result<HttpResponse> {
+    // Get total number of rows and limit display to 500
+    let total_rows = query_result.rows.len();
+    let display_limit = 500;
+    let display_rows = if total_rows > display_limit {
+        &query_result.rows[0..display_limit]
+    } else {
+        &query_result.rows
+    };
+
+    let mut context = tera::Context::new();
+    context.insert("entity", entity_slug);
+    context.insert("database", database_slug);
+    context.insert("query", &query_text);
+    context.insert("fields", &query_result.fields);
+    context.insert("rows", &query_result.rows);
+    context.insert("display_rows", &display_rows);
+    context.insert("total_rows", &total_rows);
+    context.insert("display_limit", &display_limit);
+    ok_response("query_results.html", &context)
+}
@@ -0,0 +1,35 @@
+use crate::http::structs::EntityDatabaseSavedQueryPath;
+use crate::server::config::AybConfig;
+use crate::server::ui_endpoints::auth::init_ayb_client;
+use crate::server::ui_endpoints::query::render_query_results;
+use crate::server::ui_endpoints::templates::error_snippet;
+use actix_web::{post, web, HttpRequest, HttpResponse, Result};
+use std::collections::HashMap;
+
+#[post("/{entity}/{database}/saved_queries/{saved_query}/run")]
+pub async fn run_saved_query(
+    req: HttpRequest,
+    path: web::Path<EntityDatabaseSavedQueryPath>,
+    form: web::Form<HashMap<String, String>>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    let name = &path.saved_query;
+    // Blank fields fall back to the parameters' defaults.
+    let values: HashMap<String, String> = form
+        .into_inner()
+        .into_iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .collect();
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .run_saved_query(entity_slug, database_slug, name, &values)
+        .await
+    {
+        Ok(query_result) => render_query_results(entity_slug, database_slug, None, query_result),
+        Err(err) => error_snippet(&format!("Error running {name}"), &format!("{err}")),
+    }
+}
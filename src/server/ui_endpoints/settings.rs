@@ -0,0 +1,211 @@
+use crate::ayb_db::models::EntityDatabaseSharingLevel;
+use crate::http::structs::EntityDatabasePath;
+use crate::server::config::AybConfig;
+use crate::server::ui_endpoints::auth::{authentication_details, init_ayb_client};
+use crate::server::ui_endpoints::templates::error_snippet;
+use actix_web::{post, web, HttpRequest, HttpResponse, Result};
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Deserialize)]
+pub struct RenameDatabaseRequest {
+    new_database_slug: String,
+}
+
+#[post("/{entity}/{database}/rename")]
+pub async fn rename_database(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    form: web::Form<RenameDatabaseRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    let new_database_slug = &form.new_database_slug.trim().to_lowercase();
+
+    if new_database_slug.is_empty() {
+        return error_snippet(
+            "Missing name",
+            "Please provide a new name for the database.",
+        );
+    }
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .rename_database(entity_slug, database_slug, new_database_slug)
+        .await
+    {
+        Ok(database) => {
+            let redirect_url = format!("/{}/{}#settings", database.entity, database.database);
+            Ok(HttpResponse::Ok()
+                .append_header(("HX-Redirect", redirect_url))
+                .finish())
+        }
+        Err(err) => error_snippet("Error renaming database", &format!("{err}")),
+    }
+}
+
+#[post("/{entity}/{database}/delete")]
+pub async fn delete_database(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client.delete_database(entity_slug, database_slug).await {
+        Ok(_) => Ok(HttpResponse::Ok()
+            .append_header(("HX-Redirect", format!("/{entity_slug}")))
+            .finish()),
+        Err(err) => error_snippet("Error deleting database", &format!("{err}")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TransferDatabaseRequest {
+    entity_for_transfer: String,
+    former_owner_sharing_level: String,
+}
+
+#[post("/{entity}/{database}/transfer")]
+pub async fn transfer_database(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    form: web::Form<TransferDatabaseRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    let entity_for_transfer = &form.entity_for_transfer.trim().to_lowercase();
+
+    if entity_for_transfer.is_empty() {
+        return error_snippet(
+            "Missing recipient",
+            "Please provide the username of the new owner.",
+        );
+    }
+
+    let former_owner_sharing_level =
+        match EntityDatabaseSharingLevel::from_str(&form.former_owner_sharing_level) {
+            Ok(level) => level,
+            Err(_) => return error_snippet("Invalid access level", "Please pick a valid level."),
+        };
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .transfer_database(
+            entity_slug,
+            database_slug,
+            entity_for_transfer,
+            &former_owner_sharing_level,
+        )
+        .await
+    {
+        Ok(_) => Ok(HttpResponse::Ok()
+            .append_header((
+                "HX-Redirect",
+                format!("/{entity_slug}/{database_slug}#settings"),
+            ))
+            .finish()),
+        Err(err) => error_snippet("Error transferring database", &format!("{err}")),
+    }
+}
+
+#[post("/{entity}/{database}/accept_transfer")]
+pub async fn accept_database_transfer(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .accept_database_transfer(entity_slug, database_slug)
+        .await
+    {
+        Ok(database) => Ok(HttpResponse::Ok()
+            .append_header((
+                "HX-Redirect",
+                format!("/{}/{}", database.entity, database.database),
+            ))
+            .finish()),
+        Err(err) => error_snippet("Error accepting transfer", &format!("{err}")),
+    }
+}
+
+#[post("/{entity}/{database}/cancel_transfer")]
+pub async fn cancel_database_transfer(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .cancel_database_transfer(entity_slug, database_slug)
+        .await
+    {
+        Ok(_) => {
+            // Owners cancel from the database's settings, while recipients
+            // decline from their own profile page.
+            let logged_in_entity = authentication_details(&req).map(|details| details.entity);
+            let redirect_url = match logged_in_entity {
+                Some(logged_in_entity) if &logged_in_entity != entity_slug => {
+                    format!("/{logged_in_entity}")
+                }
+                _ => format!("/{entity_slug}/{database_slug}#settings"),
+            };
+            Ok(HttpResponse::Ok()
+                .append_header(("HX-Redirect", redirect_url))
+                .finish())
+        }
+        Err(err) => error_snippet("Error canceling transfer", &format!("{err}")),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ForkDatabaseRequest {
+    database_for_fork: String,
+}
+
+#[post("/{entity}/{database}/fork")]
+pub async fn fork_database(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    form: web::Form<ForkDatabaseRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    let database_for_fork = &form.database_for_fork.trim().to_lowercase();
+
+    if database_for_fork.is_empty() {
+        return error_snippet("Missing name", "Please provide a name for the fork.");
+    }
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .fork_database(entity_slug, database_slug, None, Some(database_for_fork))
+        .await
+    {
+        Ok(database) => Ok(HttpResponse::Ok()
+            .append_header((
+                "HX-Redirect",
+                format!("/{}/{}", database.entity, database.database),
+            ))
+            .finish()),
+        Err(err) => error_snippet("Error forking database", &format!("{err}")),
+    }
+}
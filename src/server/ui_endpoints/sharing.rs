@@ -16,6 +16,10 @@ pub struct UpdatePublicSharingRequest {
 pub struct ShareWithEntityRequest {
     entity: String,
     sharing_level: String,
+    #[serde(default)]
+    object: Option<String>,
+    #[serde(default)]
+    columns: Option<String>,
 }
 
 #[post("/{entity}/{database}/update_public_sharing")]
@@ -70,6 +74,9 @@ pub async fn database_permissions(
         Ok(permissions) => {
             let mut context = tera::Context::new();
             context.insert("permissions", &permissions.permissions);
+            context.insert("object_grants", &permissions.object_grants);
+            context.insert("entity", entity_slug);
+            context.insert("database", database_slug);
 
             let html = render("database_permissions.html", &context);
             Ok(HttpResponse::Ok().content_type("text/html").body(html))
@@ -105,10 +112,30 @@ pub async fn share_with_entity(
 
     let client = init_ayb_client(&ayb_config, &req);
 
-    match client
-        .share(entity_slug, database_slug, target_entity, &sharing_level)
-        .await
-    {
+    let object = form.object.as_deref().map(str::trim).unwrap_or_default();
+    let columns = form
+        .columns
+        .as_deref()
+        .map(str::trim)
+        .filter(|columns| !columns.is_empty());
+    let result = if object.is_empty() {
+        client
+            .share(entity_slug, database_slug, target_entity, &sharing_level)
+            .await
+    } else {
+        client
+            .share_object(
+                entity_slug,
+                database_slug,
+                target_entity,
+                &sharing_level,
+                object,
+                columns,
+            )
+            .await
+    };
+
+    match result {
         Ok(_) => success_snippet(&format!(
             "Database access updated for user '{target_entity}'."
         )),
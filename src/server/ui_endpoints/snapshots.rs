@@ -1,4 +1,4 @@
-use crate::http::structs::EntityDatabasePath;
+use crate::http::structs::{DiffQuery, EntityDatabasePath};
 use crate::server::config::AybConfig;
 use crate::server::ui_endpoints::auth::init_ayb_client;
 use crate::server::ui_endpoints::templates::{error_snippet, render, success_snippet};
@@ -8,6 +8,12 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 pub struct RestoreSnapshotRequest {
     snapshot_id: String,
+    new_database_slug: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateSnapshotRequest {
+    label: Option<String>,
 }
 
 #[get("/{entity}/{database}/snapshots")]
@@ -33,6 +39,38 @@ pub async fn database_snapshots(
     }
 }
 
+#[get("/{entity}/{database}/diff")]
+pub async fn diff(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    query: web::Query<DiffQuery>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    // The form sends an empty `to` to compare with the current version.
+    let to = query.to.as_deref().filter(|to| !to.is_empty());
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .diff(entity_slug, database_slug, &query.from, to)
+        .await
+    {
+        Ok(diff) => {
+            let mut context = tera::Context::new();
+            context.insert("from", &query.from);
+            context.insert("to", &to);
+            context.insert("schema_changes", &diff.schema_changes);
+            context.insert("tables", &diff.tables);
+
+            let html = render("snapshot_diff.html", &context);
+            Ok(HttpResponse::Ok().content_type("text/html").body(html))
+        }
+        Err(err) => error_snippet("Error comparing snapshots", &format!("{err}")),
+    }
+}
+
 #[post("/{entity}/{database}/restore_snapshot")]
 pub async fn restore_snapshot(
     req: HttpRequest,
@@ -50,6 +88,31 @@ pub async fn restore_snapshot(
 
     let client = init_ayb_client(&ayb_config, &req);
 
+    let new_database_slug = form
+        .new_database_slug
+        .as_deref()
+        .map(str::trim)
+        .filter(|slug| !slug.is_empty());
+    if let Some(new_database_slug) = new_database_slug {
+        return match client
+            .restore_snapshot_into(
+                entity_slug,
+                database_slug,
+                Some(snapshot_id),
+                None,
+                None,
+                Some(new_database_slug),
+            )
+            .await
+        {
+            Ok(database) => success_snippet(&format!(
+                "Snapshot '{snapshot_id}' successfully restored into {}/{}.",
+                database.entity, database.database
+            )),
+            Err(err) => error_snippet("Error restoring snapshot", &format!("{err}")),
+        };
+    }
+
     match client
         .restore_snapshot(entity_slug, database_slug, snapshot_id)
         .await
@@ -60,3 +123,29 @@ pub async fn restore_snapshot(
         Err(err) => error_snippet("Error restoring snapshot", &format!("{err}")),
     }
 }
+
+#[post("/{entity}/{database}/create_snapshot")]
+pub async fn create_snapshot(
+    req: HttpRequest,
+    path: web::Path<EntityDatabasePath>,
+    form: web::Form<CreateSnapshotRequest>,
+    ayb_config: web::Data<AybConfig>,
+) -> Result<HttpResponse> {
+    let entity_slug = &path.entity.to_lowercase();
+    let database_slug = &path.database.to_lowercase();
+    let label = form
+        .label
+        .as_deref()
+        .map(str::trim)
+        .filter(|label| !label.is_empty());
+
+    let client = init_ayb_client(&ayb_config, &req);
+
+    match client
+        .create_snapshot(entity_slug, database_slug, label)
+        .await
+    {
+        Ok(snapshot) => success_snippet(&format!("Created snapshot '{}'.", snapshot.snapshot_id)),
+        Err(err) => error_snippet("Error creating snapshot", &format!("{err}")),
+    }
+}
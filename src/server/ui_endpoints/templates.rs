@@ -84,6 +84,11 @@ fn templates() -> &'static Tera {
             include_str!("templates/database_snapshots.html"),
         )
         .unwrap();
+        tera.add_raw_template(
+            "snapshot_diff.html",
+            include_str!("templates/snapshot_diff.html"),
+        )
+        .unwrap();
         tera.add_raw_template(
             "error_snippet.html",
             include_str!("templates/error_snippet.html"),
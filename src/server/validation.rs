@@ -120,6 +120,22 @@ pub fn validate_database_slug(slug: &str) -> Result<(), AybError> {
     validate_slug_syntax("database", slug)
 }
 
+/// Validate a team slug. Like database slugs, teams are namespaced
+/// under their organization.
+pub fn validate_team_slug(slug: &str) -> Result<(), AybError> {
+    validate_slug_syntax("team", slug)
+}
+
+/// Validate the name of a saved query, which appears in its URL.
+pub fn validate_saved_query_name(name: &str) -> Result<(), AybError> {
+    validate_slug_syntax("saved query", name)
+}
+
+/// Validate the name a saved query is published under.
+pub fn validate_endpoint_name(name: &str) -> Result<(), AybError> {
+    validate_slug_syntax("endpoint", name)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,543 @@
+use crate::ayb_db::db_interfaces::AybDb;
+use crate::ayb_db::models::{
+    InstantiatedDatabase, InstantiatedWebhook, InstantiatedWebhookDelivery, WebhookDelivery,
+    WebhookDeliveryStatus,
+};
+use crate::error::AybError;
+use crate::hosted_db::{ChangeOperation, TableChange};
+use crate::server::config::AybConfig;
+use actix_web::web;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, KeyInit, Mac};
+use prefixed_api_key::rand::rngs::OsRng;
+use prefixed_api_key::rand::RngCore;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::lookup_host;
+use tokio::sync::Mutex;
+use tokio_cron_scheduler::{Job, JobScheduler};
+use url::{Host, Url};
+
+/// Headers sent with each delivery. Receivers verify a delivery by
+/// recomputing its signature (see `sign_webhook_payload`).
+pub const SIGNATURE_HEADER: &str = "X-Ayb-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Ayb-Timestamp";
+pub const DELIVERY_HEADER: &str = "X-Ayb-Delivery";
+
+/// How often pending deliveries are sent, and how long a receiver has
+/// to respond to each one.
+const DELIVERY_INTERVAL: Duration = Duration::from_secs(5);
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const DELIVERY_BATCH_SIZE: i64 = 100;
+
+/// Failed deliveries are retried after 5 seconds, then twice as long
+/// after each attempt, until `MAX_DELIVERY_ATTEMPTS` (about an hour and
+/// a half in all).
+const FIRST_RETRY_DELAY_SECONDS: i64 = 5;
+const MAX_DELIVERY_ATTEMPTS: i32 = 10;
+
+/// The body of a delivery: the changes one committed query made that
+/// the webhook is subscribed to.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub webhook_id: i32,
+    pub entity: String,
+    pub database: String,
+    pub changes: Vec<TableChange>,
+    pub committed_at: String,
+}
+
+/// Whether deliveries can be sent to `ip`. Loopback, private,
+/// link-local (which includes cloud metadata services at
+/// 169.254.169.254), and other addresses that aren't publicly routable
+/// are refused, so that webhooks can't reach into the server's network.
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // 0.0.0.0/8 and carrier-grade NAT's 100.64.0.0/10.
+                || first == 0
+                || (first == 100 && (64..128).contains(&second)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Whether `host` is one of `[webhooks] allowed_hosts`, which
+/// deliveries can reach whatever address it has.
+fn is_allowed_host(host: &str, allowed_hosts: &[String]) -> bool {
+    allowed_hosts
+        .iter()
+        .any(|allowed_host| allowed_host.eq_ignore_ascii_case(host))
+}
+
+/// The hosts in `[webhooks] allowed_hosts`.
+pub fn allowed_webhook_hosts(ayb_config: &AybConfig) -> Vec<String> {
+    ayb_config
+        .webhooks
+        .as_ref()
+        .and_then(|webhooks| webhooks.allowed_hosts.clone())
+        .unwrap_or_default()
+}
+
+/// Check that `url` is an http or https URL whose host only resolves
+/// to public addresses (see `is_public_address`), unless the host is
+/// in `allowed_hosts`.
+pub async fn validate_webhook_url(url: &str, allowed_hosts: &[String]) -> Result<(), AybError> {
+    let invalid = |reason: &str| AybError::Other {
+        message: format!("Invalid webhook URL {url}: {reason}"),
+    };
+    let parsed = match Url::parse(url) {
+        Ok(parsed) if ["http", "https"].contains(&parsed.scheme()) => parsed,
+        _ => return Err(invalid("must be an http or https URL")),
+    };
+    let Some(host) = parsed.host() else {
+        return Err(invalid("must be an http or https URL"));
+    };
+    if is_allowed_host(&host.to_string(), allowed_hosts) {
+        return Ok(());
+    }
+    let addresses: Vec<IpAddr> = match host {
+        Host::Ipv4(ip) => vec![IpAddr::V4(ip)],
+        Host::Ipv6(ip) => vec![IpAddr::V6(ip)],
+        Host::Domain(domain) => lookup_host((domain, parsed.port_or_known_default().unwrap_or(80)))
+            .await
+            .map_err(|_| invalid(&format!("unable to resolve {domain}")))?
+            .map(|address| address.ip())
+            .collect(),
+    };
+    if addresses.is_empty() || !addresses.into_iter().all(is_public_address) {
+        return Err(invalid(&format!(
+            "{host} isn't a public address, and isn't in [webhooks] allowed_hosts"
+        )));
+    }
+    Ok(())
+}
+
+/// Resolves the hosts deliveries are sent to, dropping addresses that
+/// aren't public (unless the host is in `allowed_hosts`). URLs are
+/// validated before each delivery, but resolving them again here means
+/// that a host can't pass validation and then resolve somewhere else
+/// when the delivery connects.
+struct PublicAddressResolver {
+    allowed_hosts: Vec<String>,
+}
+
+impl Resolve for PublicAddressResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let allowed = is_allowed_host(name.as_str(), &self.allowed_hosts);
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|address| allowed || is_public_address(address.ip()))
+                .collect();
+            if addresses.is_empty() {
+                return Err(format!("{} has no public addresses", name.as_str()).into());
+            }
+            let addresses: Addrs = Box::new(addresses.into_iter());
+            Ok(addresses)
+        })
+    }
+}
+
+pub fn generate_webhook_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    format!("whsec_{}", URL_SAFE_NO_PAD.encode(bytes))
+}
+
+/// The signature of a delivery of `body` sent at `timestamp` (seconds
+/// since the Unix epoch): `sha256=` followed by the hex-encoded
+/// HMAC-SHA256 of `{timestamp}.{body}`, keyed with the webhook's secret.
+/// Signing the timestamp lets receivers reject replayed deliveries.
+pub fn sign_webhook_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let signature = payload_mac(secret, timestamp, body).finalize().into_bytes();
+    format!(
+        "sha256={}",
+        signature
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>()
+    )
+}
+
+/// Whether `signature` is the signature of a delivery of `body` sent at
+/// `timestamp` (see `sign_webhook_payload`). The comparison takes the
+/// same time wherever the signatures differ, so it doesn't reveal how
+/// much of a forged signature was right.
+pub fn verify_webhook_signature(secret: &str, timestamp: i64, body: &str, signature: &str) -> bool {
+    let Some(hex) = signature.strip_prefix("sha256=") else {
+        return false;
+    };
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return false;
+    }
+    let Ok(signature) = (0..hex.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&hex[index..index + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+    else {
+        return false;
+    };
+    payload_mac(secret, timestamp, body)
+        .verify_slice(&signature)
+        .is_ok()
+}
+
+/// HMAC-SHA256 of `{timestamp}.{body}`, keyed with `secret`.
+fn payload_mac(secret: &str, timestamp: i64, body: &str) -> Hmac<Sha256> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    mac
+}
+
+/// The `changes` `webhook` is subscribed to.
+fn matching_changes(
+    webhook: &InstantiatedWebhook,
+    changes: &[TableChange],
+) -> Result<Vec<TableChange>, AybError> {
+    let tables: Vec<String> = serde_json::from_str(&webhook.tables)?;
+    let events: Vec<ChangeOperation> = serde_json::from_str(&webhook.events)?;
+    Ok(changes
+        .iter()
+        .filter(|change| {
+            // Table names are case-insensitive in both SQLite and DuckDB.
+            (tables.is_empty()
+                || tables
+                    .iter()
+                    .any(|table| table.eq_ignore_ascii_case(&change.table)))
+                && (events.is_empty() || events.contains(&change.operation))
+        })
+        .cloned()
+        .collect())
+}
+
+/// Queue a delivery to each of `webhooks` that's subscribed to any of
+/// `changes`, which a query committed to `database`. Deliveries are
+/// sent by the job `schedule_webhook_deliveries` starts.
+pub async fn enqueue_webhook_deliveries(
+    ayb_db: &web::Data<Box<dyn AybDb>>,
+    entity_slug: &str,
+    database: &InstantiatedDatabase,
+    webhooks: &[InstantiatedWebhook],
+    changes: &[TableChange],
+) -> Result<(), AybError> {
+    let now = chrono::Utc::now().naive_utc();
+    for webhook in webhooks {
+        let changes = matching_changes(webhook, changes)?;
+        if changes.is_empty() {
+            continue;
+        }
+        let payload = WebhookPayload {
+            webhook_id: webhook.id,
+            entity: entity_slug.to_string(),
+            database: database.slug.clone(),
+            changes,
+            committed_at: now.to_string(),
+        };
+        ayb_db
+            .create_webhook_delivery(&WebhookDelivery {
+                webhook_id: webhook.id,
+                payload: serde_json::to_string(&payload)?,
+                next_attempt_at: now,
+            })
+            .await?;
+    }
+    Ok(())
+}
+
+fn retry_delay(attempts: i32) -> chrono::Duration {
+    chrono::Duration::seconds(FIRST_RETRY_DELAY_SECONDS << (attempts - 1).clamp(0, 16))
+}
+
+/// Send `delivery` and record the outcome, scheduling a retry if it
+/// failed and has attempts left.
+async fn attempt_delivery(
+    ayb_db: &dyn AybDb,
+    client: &reqwest::Client,
+    allowed_hosts: &[String],
+    mut delivery: InstantiatedWebhookDelivery,
+) -> Result<(), AybError> {
+    let webhook = ayb_db.get_webhook(delivery.webhook_id).await?;
+    let timestamp = chrono::Utc::now().timestamp();
+    // A host's addresses (and the allowed hosts) can change after a
+    // webhook is created, so its URL is checked again before each
+    // delivery.
+    let response = match validate_webhook_url(&webhook.url, allowed_hosts).await {
+        Ok(()) => client
+            .post(&webhook.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(
+                SIGNATURE_HEADER,
+                sign_webhook_payload(&webhook.secret, timestamp, &delivery.payload),
+            )
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map_err(|err| err.to_string()),
+        Err(err) => Err(err.to_string()),
+    };
+
+    let now = chrono::Utc::now().naive_utc();
+    delivery.attempts += 1;
+    match response {
+        Ok(response) if response.status().is_success() => {
+            delivery.status = WebhookDeliveryStatus::Delivered as i16;
+            delivery.response_status = Some(response.status().as_u16().into());
+            delivery.error = None;
+            delivery.delivered_at = Some(now);
+        }
+        Ok(response) => {
+            delivery.response_status = Some(response.status().as_u16().into());
+            delivery.error = Some(format!("Receiver responded with {}", response.status()));
+        }
+        Err(err) => {
+            delivery.response_status = None;
+            delivery.error = Some(err);
+        }
+    }
+    if delivery.status == WebhookDeliveryStatus::Pending as i16 {
+        if delivery.attempts >= MAX_DELIVERY_ATTEMPTS {
+            delivery.status = WebhookDeliveryStatus::Failed as i16;
+        } else {
+            delivery.next_attempt_at = now + retry_delay(delivery.attempts);
+        }
+    }
+    ayb_db.update_webhook_delivery(&delivery).await
+}
+
+async fn deliver_due_webhooks(
+    ayb_db: &dyn AybDb,
+    client: &reqwest::Client,
+    allowed_hosts: &[String],
+) -> Result<(), AybError> {
+    let due = ayb_db
+        .list_due_webhook_deliveries(chrono::Utc::now().naive_utc(), DELIVERY_BATCH_SIZE)
+        .await?;
+    for delivery in due {
+        let id = delivery.id;
+        if let Err(err) = attempt_delivery(ayb_db, client, allowed_hosts, delivery).await {
+            eprintln!("Unable to attempt webhook delivery {id}: {err}");
+        }
+    }
+    Ok(())
+}
+
+/// Send pending webhook deliveries from the server process. Query
+/// daemons report changes but are sandboxed away from the network, so
+/// they can't deliver webhooks themselves.
+pub async fn schedule_webhook_deliveries(
+    config: AybConfig,
+    ayb_db: Box<dyn AybDb>,
+) -> Result<(), AybError> {
+    let allowed_hosts = Arc::new(allowed_webhook_hosts(&config));
+    let client = reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .no_proxy()
+        .dns_resolver(PublicAddressResolver {
+            allowed_hosts: allowed_hosts.to_vec(),
+        })
+        .build()?;
+    // A run that's still waiting on slow receivers makes the next run
+    // skip, rather than send the same deliveries twice.
+    let running = Arc::new(Mutex::new(()));
+    let scheduler = JobScheduler::new().await?;
+    scheduler
+        .add(Job::new_repeated_async(DELIVERY_INTERVAL, move |_, _| {
+            let ayb_db = ayb_db.clone();
+            let client = client.clone();
+            let allowed_hosts = allowed_hosts.clone();
+            let running = running.clone();
+            Box::pin(async move {
+                let Ok(_running) = running.try_lock() else {
+                    return;
+                };
+                if let Err(err) = deliver_due_webhooks(&*ayb_db, &client, &allowed_hosts).await {
+                    eprintln!("Unable to deliver webhooks: {err}");
+                }
+            })
+        })?)
+        .await?;
+    scheduler.shutdown_on_ctrl_c();
+    scheduler.start().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_webhook_signature() {
+        let signature = sign_webhook_payload("whsec_secret", 1700000000, "{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert!(verify_webhook_signature(
+            "whsec_secret",
+            1700000000,
+            "{}",
+            &signature
+        ));
+        // A different secret, timestamp, or body doesn't verify.
+        assert!(!verify_webhook_signature(
+            "whsec_other",
+            1700000000,
+            "{}",
+            &signature
+        ));
+        assert!(!verify_webhook_signature(
+            "whsec_secret",
+            1700000001,
+            "{}",
+            &signature
+        ));
+        assert!(!verify_webhook_signature(
+            "whsec_secret",
+            1700000000,
+            "[]",
+            &signature
+        ));
+        // Nor do malformed or truncated signatures.
+        assert!(!verify_webhook_signature(
+            "whsec_secret",
+            1700000000,
+            "{}",
+            &signature["sha256=".len()..]
+        ));
+        assert!(!verify_webhook_signature(
+            "whsec_secret",
+            1700000000,
+            "{}",
+            &signature[..signature.len() - 2]
+        ));
+        assert!(!verify_webhook_signature(
+            "whsec_secret",
+            1700000000,
+            "{}",
+            "sha256=zz"
+        ));
+    }
+
+    #[test]
+    fn test_matching_changes() {
+        let webhook = |tables: &str, events: &str| InstantiatedWebhook {
+            id: 1,
+            database_id: 1,
+            url: "http://localhost/".to_string(),
+            tables: tables.to_string(),
+            events: events.to_string(),
+            secret: "secret".to_string(),
+            creator_id: 1,
+            created_at: chrono::Utc::now().naive_utc(),
+        };
+        let change = |table: &str, operation| TableChange {
+            table: table.to_string(),
+            operation,
+            row_count: 1,
+            rowids: vec![1],
+        };
+        let changes = vec![
+            change("scores", ChangeOperation::Insert),
+            change("scores", ChangeOperation::Delete),
+            change("players", ChangeOperation::Update),
+        ];
+
+        assert_eq!(
+            matching_changes(&webhook("[]", "[]"), &changes).unwrap(),
+            changes
+        );
+        assert_eq!(
+            matching_changes(&webhook(r#"["Scores"]"#, "[]"), &changes).unwrap(),
+            changes[..2]
+        );
+        assert_eq!(
+            matching_changes(&webhook("[]", r#"["update","delete"]"#), &changes).unwrap(),
+            changes[1..]
+        );
+        assert_eq!(
+            matching_changes(&webhook(r#"["players"]"#, r#"["insert"]"#), &changes).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_is_public_address() {
+        for address in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(
+                !is_public_address(address.parse().unwrap()),
+                "{address} shouldn't be public"
+            );
+        }
+        for address in ["93.184.215.14", "100.128.0.1", "2606:4700::1111"] {
+            assert!(
+                is_public_address(address.parse().unwrap()),
+                "{address} should be public"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validate_webhook_url_allowed_hosts() {
+        let allowed_hosts = vec!["127.0.0.1".to_string()];
+        assert!(
+            validate_webhook_url("http://127.0.0.1:8080/hooks", &allowed_hosts)
+                .await
+                .is_ok()
+        );
+        assert!(
+            validate_webhook_url("http://127.0.0.2/hooks", &allowed_hosts)
+                .await
+                .is_err()
+        );
+        assert!(validate_webhook_url("http://127.0.0.1/hooks", &[])
+            .await
+            .is_err());
+        assert!(validate_webhook_url("https://93.184.215.14/hooks", &[])
+            .await
+            .is_ok());
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(1), chrono::Duration::seconds(5));
+        assert_eq!(retry_delay(2), chrono::Duration::seconds(10));
+        assert_eq!(retry_delay(4), chrono::Duration::seconds(40));
+    }
+}
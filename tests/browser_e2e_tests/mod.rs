@@ -10,7 +10,10 @@ mod token_management;
 pub use create_and_query_database::test_create_and_query_database_flow;
 pub use duckdb::test_duckdb_flow;
 pub use entity_profile::test_entity_profile_flow;
-pub use oauth_flow::{test_oauth_deny_flow, test_oauth_flow};
+pub use oauth_flow::{
+    test_oauth_deny_flow, test_oauth_flow, test_oauth_token_cannot_administer,
+    test_oauth_token_snapshot_scope,
+};
 pub use permissions::test_permissions_flow;
 pub use registration_tests::test_registration_flow;
 pub use snapshots::test_snapshots_flow;
@@ -423,3 +423,119 @@ pub async fn test_oauth_deny_flow(
 
     Ok(())
 }
+
+/// Test that a scoped token can't query or diff the snapshots of a
+/// database other than the one it's scoped to, even though its owner
+/// manages that database.
+pub async fn test_oauth_token_snapshot_scope(
+    username: &str,
+    base_url: &str,
+    scoped_token: &str,
+) -> Result<(), Box<dyn Error>> {
+    let other_database_path = format!("{}/test.duckdb", username);
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!(
+            "{}/v1/{}/query?snapshot=any-snapshot",
+            base_url, other_database_path
+        ))
+        .header("Authorization", format!("Bearer {}", scoped_token))
+        .body("SELECT 1")
+        .send()
+        .await?;
+
+    assert_ne!(
+        response.status(),
+        200,
+        "Scoped token should not be able to query another database's snapshots"
+    );
+    let error_body = response.text().await?;
+    let expected = format!(
+        "Authenticated entity {} can't query database {}",
+        username, other_database_path
+    );
+    assert!(
+        error_body.contains(&expected),
+        "Error message should be '{}', got: {}",
+        expected,
+        error_body
+    );
+
+    let response = client
+        .get(format!(
+            "{}/v1/{}/diff?from=any-snapshot",
+            base_url, other_database_path
+        ))
+        .header("Authorization", format!("Bearer {}", scoped_token))
+        .send()
+        .await?;
+
+    assert_ne!(
+        response.status(),
+        200,
+        "Scoped token should not be able to diff another database's snapshots"
+    );
+    let error_body = response.text().await?;
+    assert!(
+        error_body.contains(&expected),
+        "Error message should be '{}', got: {}",
+        expected,
+        error_body
+    );
+
+    println!("Confirmed: scoped token cannot query or diff another database's snapshots");
+
+    Ok(())
+}
+
+/// Test that a scoped token can't delete or rename even the database
+/// it's scoped to, since it was only granted to query it.
+pub async fn test_oauth_token_cannot_administer(
+    username: &str,
+    base_url: &str,
+    scoped_token: &str,
+) -> Result<(), Box<dyn Error>> {
+    let database_path = format!("{}/test.sqlite", username);
+    let client = reqwest::Client::new();
+
+    let requests = [
+        (
+            "delete",
+            client.delete(format!("{}/v1/{}", base_url, database_path)),
+        ),
+        (
+            "rename",
+            client
+                .post(format!("{}/v1/{}/rename", base_url, database_path))
+                .header("new-database-slug", "renamed.sqlite"),
+        ),
+    ];
+    for (action, request) in requests {
+        let response = request
+            .header("Authorization", format!("Bearer {}", scoped_token))
+            .send()
+            .await?;
+        assert_ne!(
+            response.status(),
+            200,
+            "Scoped token should not be able to {} its database",
+            action
+        );
+        let error_body = response.text().await?;
+        let expected = format!(
+            "Authenticated entity {} can't {} database {}",
+            username, action, database_path
+        );
+        assert!(
+            error_body.contains(&expected),
+            "Error message should be '{}', got: {}",
+            expected,
+            error_body
+        );
+    }
+
+    println!("Confirmed: scoped token cannot delete or rename its database");
+
+    Ok(())
+}
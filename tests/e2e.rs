@@ -6,14 +6,19 @@ mod utils;
 
 use crate::browser_e2e_tests::{
     test_create_and_query_database_flow, test_duckdb_flow, test_entity_profile_flow,
-    test_oauth_deny_flow, test_oauth_flow, test_permissions_flow, test_registration_flow,
+    test_oauth_deny_flow, test_oauth_flow, test_oauth_token_cannot_administer,
+    test_oauth_token_snapshot_scope, test_permissions_flow, test_registration_flow,
     test_snapshots_flow, test_token_management_flow,
 };
 use crate::e2e_tests::{
-    test_anonymous_access, test_create_and_query_db, test_create_and_query_duckdb,
-    test_entity_details_and_profile, test_health_check, test_oauth_token_exchange_errors,
-    test_permissions, test_registration, test_snapshots, test_snapshots_duckdb,
-    test_token_management,
+    test_anonymous_access, test_audit_log, test_change_subscriptions, test_create_and_query_db,
+    test_create_and_query_duckdb, test_encrypted_snapshots, test_entity_details_and_profile,
+    test_health_check, test_manual_snapshots, test_oauth_token_exchange_errors,
+    test_object_permissions, test_organization_members, test_organization_teams, test_permissions,
+    test_point_in_time_recovery, test_published_endpoints, test_read_write_data_permissions,
+    test_registration, test_rename_and_delete_database, test_saved_queries, test_server_recovery,
+    test_snapshot_retention, test_snapshot_schedule, test_snapshots, test_snapshots_duckdb,
+    test_streamed_snapshots, test_token_management, test_transfer_database, test_webhooks,
 };
 use crate::utils::browser::BrowserHelpers;
 use crate::utils::email::clear_email_data;
@@ -94,9 +99,23 @@ async fn client_server_integration(
     test_create_and_query_db(&config_path, &api_keys, server_url, &mut expected_config)?;
     test_entity_details_and_profile(&config_path, &api_keys)?;
     test_snapshots(test_type, &config_path, &api_keys).await?;
+    test_manual_snapshots(&config_path, &api_keys)?;
+    test_snapshot_retention(&config_path, &api_keys)?;
+    test_snapshot_schedule(&config_path, &api_keys)?;
     test_permissions(&config_path, &api_keys).await?;
     test_anonymous_access(&config_path, &api_keys, server_url).await?;
     test_token_management(&config_path, &api_keys)?;
+    test_rename_and_delete_database(&config_path, &api_keys)?;
+    test_transfer_database(&config_path, &api_keys)?;
+    test_organization_members(&config_path, &api_keys)?;
+    test_organization_teams(&config_path, &api_keys)?;
+    test_object_permissions(&config_path, &api_keys)?;
+    test_read_write_data_permissions(&config_path, &api_keys)?;
+    test_audit_log(&config_path, &api_keys)?;
+    test_saved_queries(&config_path, &api_keys)?;
+    test_published_endpoints(&config_path, &api_keys, server_url).await?;
+    test_webhooks(&config_path, &api_keys)?;
+    test_change_subscriptions(&config_path, &api_keys, server_url).await?;
     test_oauth_token_exchange_errors(server_url).await?;
     test_create_and_query_duckdb(&config_path, &api_keys)?;
     test_snapshots_duckdb(test_type, &config_path, &api_keys).await?;
@@ -104,6 +123,53 @@ async fn client_server_integration(
     Ok(())
 }
 
+#[tokio::test]
+async fn snapshot_integration_local_storage() -> Result<(), Box<dyn std::error::Error>> {
+    snapshot_integration("sqlite_local_snapshots", "http://127.0.0.1:5436").await
+}
+
+#[tokio::test]
+async fn snapshot_integration_encrypted() -> Result<(), Box<dyn std::error::Error>> {
+    snapshot_integration("sqlite_encrypted_snapshots", "http://127.0.0.1:5437").await
+}
+
+#[tokio::test]
+async fn snapshot_integration_point_in_time() -> Result<(), Box<dyn std::error::Error>> {
+    snapshot_integration("sqlite_point_in_time", "http://127.0.0.1:5438").await
+}
+
+/// Runs the snapshot tests against a server that stores snapshots
+/// differently than the client/server integration runs do: in a local
+/// directory, encrypted, or with a replicated write-ahead log.
+async fn snapshot_integration(
+    test_type: &str,
+    server_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = format!("tests/ayb_data_{test_type}/ayb.json");
+    let mut expected_config = ClientConfig::new();
+    let _cleanup = Cleanup;
+
+    ensure_minio_running()?;
+    reset_test_environment(test_type)?;
+    let ayb_server = AybServer::run(test_type).expect("failed to start the ayb server");
+    thread::sleep(time::Duration::from_secs(10));
+
+    let api_keys = test_registration(test_type, &config_path, server_url, &mut expected_config)?;
+    test_create_and_query_db(&config_path, &api_keys, server_url, &mut expected_config)?;
+    test_snapshots(test_type, &config_path, &api_keys).await?;
+    test_streamed_snapshots(test_type).await?;
+    match test_type {
+        "sqlite_encrypted_snapshots" => {
+            test_encrypted_snapshots(test_type, &config_path, &api_keys).await?;
+            test_server_recovery(test_type, &config_path, &api_keys, ayb_server)?;
+        }
+        "sqlite_point_in_time" => test_point_in_time_recovery(&config_path, &api_keys)?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn browser_e2e() -> Result<(), Box<dyn std::error::Error>> {
     let _cleanup = Cleanup;
@@ -147,7 +213,7 @@ async fn browser_e2e() -> Result<(), Box<dyn std::error::Error>> {
     test_snapshots_flow(page, &username, &base_url).await?;
 
     // Test OAuth flow (creates scoped tokens for read-only and read-write access)
-    let (readonly_token, _readwrite_token) = test_oauth_flow(page, &username, &base_url).await?;
+    let (readonly_token, readwrite_token) = test_oauth_flow(page, &username, &base_url).await?;
 
     // Test OAuth deny flow
     test_oauth_deny_flow(page, &username, &base_url).await?;
@@ -158,5 +224,10 @@ async fn browser_e2e() -> Result<(), Box<dyn std::error::Error>> {
     // Test DuckDB flows
     test_duckdb_flow(page, &username, &base_url).await?;
 
+    // The read-write token is scoped to test.sqlite, so it can't query
+    // test.duckdb's snapshots.
+    test_oauth_token_snapshot_scope(&username, &base_url, &readwrite_token).await?;
+    test_oauth_token_cannot_administer(&username, &base_url, &readwrite_token).await?;
+
     Ok(())
 }
@@ -0,0 +1,139 @@
+use crate::e2e_tests::{FIRST_ENTITY_SLUG, ORG_ENTITY_SLUG, SECOND_ENTITY_SLUG};
+use crate::utils::ayb::{audit_log, create_database, delete_database, rename_database, share};
+use std::collections::HashMap;
+
+const AUDITED_DB: &str = "e2e-first/audited.sqlite";
+const AUDITED_RENAMED_DB: &str = "e2e-first/audited-renamed.sqlite";
+
+pub fn test_audit_log(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let entry = r"\d+,\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}(?:\.\d+)?";
+
+    // Organization admins see who changed access to its databases,
+    // newest first.
+    audit_log(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        Some("test.sqlite"),
+        "2",
+        &format!(
+            "Id,Time,Actor,Token,Database,Action,Details\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,test.sqlite,share,Set {SECOND_ENTITY_SLUG}'s access to no-access\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,test.sqlite,share,Set {SECOND_ENTITY_SLUG}'s access to read-write-data"
+        ),
+    )?;
+
+    // Entries about other databases are filtered out.
+    audit_log(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        Some("nonexistent.sqlite"),
+        "2",
+        "No audit log entries",
+    )?;
+
+    // Plain members of the organization can't see its audit log, but
+    // every entity can see its own.
+    audit_log(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        None,
+        "2",
+        "Error: Authenticated entity e2e-second can't view the audit log of e2e-org",
+    )?;
+    audit_log(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        Some("test.sqlite"),
+        "2",
+        "Error: Authenticated entity e2e-second can't view the audit log of e2e-org/test.sqlite",
+    )?;
+    audit_log(
+        config_path,
+        first_api_key,
+        FIRST_ENTITY_SLUG,
+        None,
+        "1",
+        &format!("Id,Time,Actor,Token,Database,Action,Details\n{entry},{FIRST_ENTITY_SLUG},.*"),
+    )?;
+
+    // A database that reuses a deleted one's slug starts with an empty
+    // history, and its history follows it when it's renamed.
+    create_database(
+        config_path,
+        first_api_key,
+        AUDITED_DB,
+        "sqlite",
+        "Successfully created e2e-first/audited.sqlite",
+    )?;
+    share(
+        config_path,
+        first_api_key,
+        AUDITED_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "Permissions for e2e-second on e2e-first/audited.sqlite updated successfully",
+    )?;
+    delete_database(
+        config_path,
+        first_api_key,
+        AUDITED_DB,
+        "Deleted e2e-first/audited.sqlite",
+    )?;
+    create_database(
+        config_path,
+        first_api_key,
+        AUDITED_DB,
+        "sqlite",
+        "Successfully created e2e-first/audited.sqlite",
+    )?;
+    rename_database(
+        config_path,
+        first_api_key,
+        AUDITED_DB,
+        "audited-renamed.sqlite",
+        "Renamed e2e-first/audited.sqlite to e2e-first/audited-renamed.sqlite",
+    )?;
+    audit_log(
+        config_path,
+        first_api_key,
+        FIRST_ENTITY_SLUG,
+        Some("audited-renamed.sqlite"),
+        "5",
+        &format!(
+            "Id,Time,Actor,Token,Database,Action,Details\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,audited.sqlite,create-database,.*"
+        ),
+    )?;
+
+    // The deleted database's history can still be listed by its slug.
+    audit_log(
+        config_path,
+        first_api_key,
+        FIRST_ENTITY_SLUG,
+        Some("audited.sqlite"),
+        "5",
+        &format!(
+            "Id,Time,Actor,Token,Database,Action,Details\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,audited.sqlite,delete-database,Deleted database\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,audited.sqlite,share,Set {SECOND_ENTITY_SLUG}'s access to read-only\n\
+             {entry},{FIRST_ENTITY_SLUG},\\w+,audited.sqlite,create-database,.*"
+        ),
+    )?;
+    delete_database(
+        config_path,
+        first_api_key,
+        AUDITED_RENAMED_DB,
+        "Deleted e2e-first/audited-renamed.sqlite",
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use crate::utils::ayb::{create_database, query, share, share_object};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const CHANGES_DB: &str = "e2e-first/changes.sqlite";
+
+/// Read the next Server-Sent Event (or comment) from `response`,
+/// keeping any partial event that follows it in `buffer`.
+async fn next_message(
+    response: &mut reqwest::Response,
+    buffer: &mut String,
+) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    loop {
+        if let Some(boundary) = buffer.find("\n\n") {
+            let message = buffer[..boundary].to_string();
+            buffer.replace_range(..boundary + 2, "");
+            return Ok(Some(message));
+        }
+        match tokio::time::timeout(Duration::from_secs(10), response.chunk()).await?? {
+            Some(chunk) => buffer.push_str(std::str::from_utf8(&chunk)?),
+            None => return Ok(None),
+        }
+    }
+}
+
+/// The changes in a `change` event.
+fn changes(message: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+    let data = message
+        .strip_prefix("event: change\ndata: ")
+        .ok_or_else(|| format!("Not a change event: {message}"))?;
+    let event: serde_json::Value = serde_json::from_str(data)?;
+    assert_eq!(event["entity"], "e2e-first");
+    assert_eq!(event["database"], "changes.sqlite");
+    Ok(event["changes"].clone())
+}
+
+pub async fn test_change_subscriptions(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+    server_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let third_api_key = &api_keys.get("third").unwrap()[0];
+    let changes_url = format!("{server_url}/v1/{CHANGES_DB}/changes");
+    let client = reqwest::Client::new();
+
+    create_database(
+        config_path,
+        first_api_key,
+        CHANGES_DB,
+        "sqlite",
+        "Successfully created e2e-first/changes.sqlite",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE scores (name varchar, score integer);",
+        CHANGES_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE secrets (value varchar);",
+        CHANGES_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    // The second entity can only read `scores`.
+    share_object(
+        config_path,
+        first_api_key,
+        CHANGES_DB,
+        "e2e-second",
+        "read-only",
+        "scores",
+        None,
+        "Permissions for e2e-second on e2e-first/changes.sqlite updated successfully",
+    )?;
+
+    // Subscribers need to be able to query the database.
+    let response = client
+        .get(&changes_url)
+        .bearer_auth(third_api_key)
+        .send()
+        .await?;
+    assert_eq!(response.status(), 500);
+    assert!(response.text().await?.contains(
+        "Authenticated entity e2e-third can't subscribe to changes in database e2e-first/changes.sqlite"
+    ));
+
+    let mut first_buffer = String::new();
+    let mut first_stream = client
+        .get(&changes_url)
+        .bearer_auth(first_api_key)
+        .send()
+        .await?;
+    assert_eq!(first_stream.status(), 200);
+    assert_eq!(
+        first_stream.headers()["content-type"].to_str()?,
+        "text/event-stream"
+    );
+    assert_eq!(
+        next_message(&mut first_stream, &mut first_buffer).await?,
+        Some(": subscribed".to_string())
+    );
+    let mut second_buffer = String::new();
+    let mut second_stream = client
+        .get(&changes_url)
+        .bearer_auth(second_api_key)
+        .send()
+        .await?;
+    assert_eq!(
+        next_message(&mut second_stream, &mut second_buffer).await?,
+        Some(": subscribed".to_string())
+    );
+    let mut filtered_buffer = String::new();
+    let mut filtered_stream = client
+        .get(format!("{changes_url}?tables=secrets"))
+        .bearer_auth(first_api_key)
+        .send()
+        .await?;
+    assert_eq!(
+        next_message(&mut filtered_stream, &mut filtered_buffer).await?,
+        Some(": subscribed".to_string())
+    );
+
+    query(
+        config_path,
+        first_api_key,
+        "INSERT INTO secrets VALUES ('hidden');",
+        CHANGES_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "INSERT INTO scores VALUES ('low', 1), ('high', 10);",
+        CHANGES_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    let secrets_insert = serde_json::json!([
+        {"table": "secrets", "operation": "insert", "row_count": 1, "rowids": [1]}
+    ]);
+    let scores_insert = serde_json::json!([
+        {"table": "scores", "operation": "insert", "row_count": 2, "rowids": [1, 2]}
+    ]);
+
+    // Managers see every change, in order.
+    let message = next_message(&mut first_stream, &mut first_buffer).await?;
+    assert_eq!(changes(&message.unwrap())?, secrets_insert);
+    let message = next_message(&mut first_stream, &mut first_buffer).await?;
+    assert_eq!(changes(&message.unwrap())?, scores_insert);
+    // Others only see changes to tables they can read.
+    let message = next_message(&mut second_stream, &mut second_buffer).await?;
+    assert_eq!(changes(&message.unwrap())?, scores_insert);
+    // Subscriptions can be limited to some tables.
+    let message = next_message(&mut filtered_stream, &mut filtered_buffer).await?;
+    assert_eq!(changes(&message.unwrap())?, secrets_insert);
+
+    // Losing access to the database ends the subscription.
+    share(
+        config_path,
+        first_api_key,
+        CHANGES_DB,
+        "e2e-second",
+        "no-access",
+        "Permissions for e2e-second on e2e-first/changes.sqlite updated successfully",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "DELETE FROM scores WHERE name = 'low';",
+        CHANGES_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    assert_eq!(
+        next_message(&mut second_stream, &mut second_buffer).await?,
+        None
+    );
+    let message = next_message(&mut first_stream, &mut first_buffer).await?;
+    assert_eq!(
+        changes(&message.unwrap())?,
+        serde_json::json!([
+            {"table": "scores", "operation": "delete", "row_count": 1, "rowids": [1]}
+        ])
+    );
+
+    Ok(())
+}
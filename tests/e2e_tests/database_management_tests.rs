@@ -0,0 +1,339 @@
+use crate::e2e_tests::{SECOND_ENTITY_SLUG, THIRD_ENTITY_SLUG};
+use crate::utils::ayb::{
+    accept_transfer, cancel_transfer, create_database, delete_database, list_database_permissions,
+    list_transfers, query, rename_database, share, share_object, transfer_database,
+};
+use std::collections::HashMap;
+
+const ORIGINAL_DB: &str = "e2e-first/to-rename.sqlite";
+const RENAMED_DB: &str = "e2e-first/renamed.sqlite";
+const TRANSFERRED_DB: &str = "e2e-first/to-transfer.sqlite";
+const RECEIVED_DB: &str = "e2e-second/to-transfer.sqlite";
+
+pub fn test_rename_and_delete_database(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_key = &api_keys.get("first").unwrap()[0];
+    let second_key = &api_keys.get("second").unwrap()[0];
+
+    create_database(
+        config_path,
+        first_key,
+        ORIGINAL_DB,
+        "sqlite",
+        "Successfully created e2e-first/to-rename.sqlite",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "CREATE TABLE renamed_table(name varchar); INSERT INTO renamed_table (name) VALUES ('kept');",
+        ORIGINAL_DB,
+        "table",
+        "Rows: 0",
+    )?;
+    share(
+        config_path,
+        first_key,
+        ORIGINAL_DB,
+        SECOND_ENTITY_SLUG,
+        "manager",
+        "Permissions for e2e-second on e2e-first/to-rename.sqlite updated successfully",
+    )?;
+
+    // Even a manager can't rename or delete a database they don't own.
+    rename_database(
+        config_path,
+        second_key,
+        ORIGINAL_DB,
+        "renamed.sqlite",
+        "Error: Authenticated entity e2e-second can't rename database e2e-first/to-rename.sqlite",
+    )?;
+    delete_database(
+        config_path,
+        second_key,
+        ORIGINAL_DB,
+        "Error: Authenticated entity e2e-second can't delete database e2e-first/to-rename.sqlite",
+    )?;
+
+    // Can't rename a database to a slug that's already taken or invalid.
+    rename_database(
+        config_path,
+        first_key,
+        ORIGINAL_DB,
+        "test.sqlite",
+        "Error: Database test.sqlite already exists",
+    )?;
+    rename_database(
+        config_path,
+        first_key,
+        ORIGINAL_DB,
+        "../escape.sqlite",
+        "Error: Invalid database slug: ../escape.sqlite. Only letters, numbers, underscores, hyphens, and periods are allowed",
+    )?;
+
+    // The owner can rename the database. Its data and permissions
+    // follow it, and the old name no longer resolves.
+    rename_database(
+        config_path,
+        first_key,
+        ORIGINAL_DB,
+        "renamed.sqlite",
+        "Renamed e2e-first/to-rename.sqlite to e2e-first/renamed.sqlite",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM renamed_table;",
+        RENAMED_DB,
+        "csv",
+        "name\nkept",
+    )?;
+    query(
+        config_path,
+        second_key,
+        "SELECT name FROM renamed_table;",
+        RENAMED_DB,
+        "csv",
+        "name\nkept",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_key,
+        RENAMED_DB,
+        "csv",
+        "Entity,Sharing level,Source\ne2e-second,manager,direct",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM renamed_table;",
+        ORIGINAL_DB,
+        "csv",
+        "Error: RecordNotFound",
+    )?;
+
+    // Deleting the database removes it entirely, so the slug can be
+    // reused for a fresh, empty database.
+    delete_database(
+        config_path,
+        first_key,
+        RENAMED_DB,
+        "Deleted e2e-first/renamed.sqlite",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM renamed_table;",
+        RENAMED_DB,
+        "csv",
+        "Error: RecordNotFound",
+    )?;
+    create_database(
+        config_path,
+        first_key,
+        RENAMED_DB,
+        "sqlite",
+        "Successfully created e2e-first/renamed.sqlite",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM renamed_table;",
+        RENAMED_DB,
+        "csv",
+        "no such table: renamed_table",
+    )?;
+    delete_database(
+        config_path,
+        first_key,
+        RENAMED_DB,
+        "Deleted e2e-first/renamed.sqlite",
+    )?;
+
+    Ok(())
+}
+
+pub fn test_transfer_database(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_key = &api_keys.get("first").unwrap()[0];
+    let second_key = &api_keys.get("second").unwrap()[0];
+    let third_key = &api_keys.get("third").unwrap()[0];
+    let org_key = &api_keys.get("org").unwrap()[0];
+
+    create_database(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        "sqlite",
+        "Successfully created e2e-first/to-transfer.sqlite",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "CREATE TABLE transferred_table(name varchar); INSERT INTO transferred_table (name) VALUES ('moved'); CREATE TABLE secret_table(secret varchar);",
+        TRANSFERRED_DB,
+        "table",
+        "Rows: 0",
+    )?;
+
+    // Only the owner can propose a transfer, and not to themselves.
+    transfer_database(
+        config_path,
+        second_key,
+        TRANSFERRED_DB,
+        SECOND_ENTITY_SLUG,
+        "manager",
+        "Error: Authenticated entity e2e-second can't transfer database e2e-first/to-transfer.sqlite",
+    )?;
+    transfer_database(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        "e2e-first",
+        "manager",
+        "Error: e2e-first already owns e2e-first/to-transfer.sqlite",
+    )?;
+
+    // A proposed transfer can only be accepted by its recipient, and
+    // the recipient can decline it.
+    transfer_database(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "Proposed transferring e2e-first/to-transfer.sqlite to e2e-second",
+    )?;
+    list_transfers(
+        config_path,
+        second_key,
+        "csv",
+        "e2e-first/to-transfer.sqlite,e2e-second,read-only,",
+    )?;
+    accept_transfer(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        "Error: Authenticated entity e2e-first can't accept the transfer of database e2e-first/to-transfer.sqlite",
+    )?;
+    cancel_transfer(
+        config_path,
+        second_key,
+        TRANSFERRED_DB,
+        "Canceled transfer of e2e-first/to-transfer.sqlite",
+    )?;
+    accept_transfer(
+        config_path,
+        second_key,
+        TRANSFERRED_DB,
+        "Error: No pending transfer for database e2e-first/to-transfer.sqlite",
+    )?;
+
+    // A collaborator and public access are both limited to one table.
+    share_object(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        THIRD_ENTITY_SLUG,
+        "read-only",
+        "transferred_table",
+        None,
+        "Permissions for e2e-third on e2e-first/to-transfer.sqlite updated successfully",
+    )?;
+    share_object(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        "public",
+        "read-only",
+        "transferred_table",
+        None,
+        "Permissions for public on e2e-first/to-transfer.sqlite updated successfully",
+    )?;
+
+    // Once accepted, the database moves under the recipient with its
+    // data intact, and the former owner keeps the access they asked for.
+    transfer_database(
+        config_path,
+        first_key,
+        TRANSFERRED_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "Proposed transferring e2e-first/to-transfer.sqlite to e2e-second",
+    )?;
+    accept_transfer(
+        config_path,
+        second_key,
+        TRANSFERRED_DB,
+        "Transferred e2e-first/to-transfer.sqlite to e2e-second/to-transfer.sqlite",
+    )?;
+    query(
+        config_path,
+        second_key,
+        "SELECT name FROM transferred_table;",
+        RECEIVED_DB,
+        "csv",
+        "name\nmoved",
+    )?;
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM transferred_table;",
+        RECEIVED_DB,
+        "csv",
+        "name\nmoved",
+    )?;
+    list_database_permissions(
+        config_path,
+        second_key,
+        RECEIVED_DB,
+        "csv",
+        "Entity,Sharing level,Source\ne2e-first,read-only,direct\ne2e-third,read-only,direct\nLimited to objects:\nEntity,Object,Columns\npublic,transferred_table,*\ne2e-third,transferred_table,*",
+    )?;
+
+    // The collaborator and public access are still limited to the
+    // table they were granted.
+    for key in [third_key, org_key] {
+        query(
+            config_path,
+            key,
+            "SELECT name FROM transferred_table;",
+            RECEIVED_DB,
+            "csv",
+            "name\nmoved",
+        )?;
+        query(
+            config_path,
+            key,
+            "SELECT secret FROM secret_table;",
+            RECEIVED_DB,
+            "csv",
+            "Error: Not permitted to read secret_table",
+        )?;
+    }
+    query(
+        config_path,
+        first_key,
+        "SELECT name FROM transferred_table;",
+        TRANSFERRED_DB,
+        "csv",
+        "Error: RecordNotFound",
+    )?;
+    delete_database(
+        config_path,
+        first_key,
+        RECEIVED_DB,
+        "Error: Authenticated entity e2e-first can't delete database e2e-second/to-transfer.sqlite",
+    )?;
+    delete_database(
+        config_path,
+        second_key,
+        RECEIVED_DB,
+        "Deleted e2e-second/to-transfer.sqlite",
+    )?;
+
+    Ok(())
+}
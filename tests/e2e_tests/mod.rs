@@ -1,23 +1,43 @@
 mod anonymous_access_tests;
+mod audit_log_tests;
+mod change_subscription_tests;
 mod create_and_query_db_tests;
+mod database_management_tests;
 mod entity_details_and_profile_tests;
 mod health_check_tests;
 mod oauth_tests;
+mod organization_tests;
 mod permissions_tests;
+mod published_endpoint_tests;
 mod registration_tests;
+mod saved_query_tests;
 mod snapshot_tests;
 mod token_management_tests;
+mod webhook_tests;
 
 pub use anonymous_access_tests::test_anonymous_access;
+pub use audit_log_tests::test_audit_log;
+pub use change_subscription_tests::test_change_subscriptions;
 pub use create_and_query_db_tests::test_create_and_query_db;
 pub use create_and_query_db_tests::test_create_and_query_duckdb;
+pub use database_management_tests::{test_rename_and_delete_database, test_transfer_database};
 pub use entity_details_and_profile_tests::test_entity_details_and_profile;
 pub use health_check_tests::test_health_check;
 pub use oauth_tests::test_oauth_token_exchange_errors;
-pub use permissions_tests::test_permissions;
+pub use organization_tests::{test_organization_members, test_organization_teams};
+pub use permissions_tests::{
+    test_object_permissions, test_permissions, test_read_write_data_permissions,
+};
+pub use published_endpoint_tests::test_published_endpoints;
 pub use registration_tests::test_registration;
-pub use snapshot_tests::{test_snapshots, test_snapshots_duckdb};
+pub use saved_query_tests::test_saved_queries;
+pub use snapshot_tests::{
+    test_encrypted_snapshots, test_manual_snapshots, test_point_in_time_recovery,
+    test_server_recovery, test_snapshot_retention, test_snapshot_schedule, test_snapshots,
+    test_snapshots_duckdb, test_streamed_snapshots,
+};
 pub use token_management_tests::test_token_management;
+pub use webhook_tests::test_webhooks;
 
 const FIRST_ENTITY_DB: &str = "e2e-first/test.sqlite";
 const FIRST_ENTITY_DB_CASED: &str = "E2E-FiRST/test.sqlite";
@@ -29,3 +49,5 @@ const FIRST_ENTITY_SLUG: &str = "e2e-first";
 const FIRST_ENTITY_SLUG_CASED: &str = "E2E-FiRsT";
 const SECOND_ENTITY_SLUG: &str = "e2e-second";
 const THIRD_ENTITY_SLUG: &str = "e2e-third";
+const ORG_ENTITY_SLUG: &str = "e2e-org";
+const ORG_ENTITY_DB: &str = "e2e-org/test.sqlite";
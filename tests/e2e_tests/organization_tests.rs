@@ -0,0 +1,349 @@
+use crate::e2e_tests::{ORG_ENTITY_DB, ORG_ENTITY_SLUG, SECOND_ENTITY_SLUG, THIRD_ENTITY_SLUG};
+use crate::utils::ayb::{
+    add_member, add_team_member, create_database, create_team, delete_team,
+    list_database_permissions, list_members, list_teams, query, remove_member, remove_team_member,
+    set_member_sharing_level, share, share_with_team,
+};
+use std::collections::HashMap;
+
+pub fn test_organization_members(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let org_api_key = &api_keys.get("org").unwrap()[0];
+
+    // Non-members can't see or change an organization's members, or
+    // create databases for it.
+    list_members(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "csv",
+        "Error: Authenticated entity e2e-second can't list members of e2e-org",
+    )?;
+    add_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "e2e-first",
+        "admin",
+        "Error: Authenticated entity e2e-first can't grant or revoke the admin role in e2e-org",
+    )?;
+    create_database(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "sqlite",
+        "Error: Authenticated entity e2e-first can't create a database for entity e2e-org",
+    )?;
+
+    // The organization itself can add members, but only users can be
+    // members.
+    add_member(
+        config_path,
+        org_api_key,
+        ORG_ENTITY_SLUG,
+        "e2e-first",
+        "admin",
+        "e2e-first now has the admin role in e2e-org",
+    )?;
+    add_member(
+        config_path,
+        org_api_key,
+        ORG_ENTITY_SLUG,
+        ORG_ENTITY_SLUG,
+        "member",
+        "Error: e2e-org is an organization, and only users can be members of an organization",
+    )?;
+
+    // Admins can create and manage the organization's databases.
+    create_database(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "sqlite",
+        "Successfully created e2e-org/test.sqlite",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE test_table(fname varchar, lname varchar);",
+        ORG_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+
+    // Admins can add members, but not owners. Members can't manage
+    // anyone.
+    add_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        SECOND_ENTITY_SLUG,
+        "member",
+        "e2e-second now has the member role in e2e-org",
+    )?;
+    add_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        THIRD_ENTITY_SLUG,
+        "owner",
+        "Error: Authenticated entity e2e-first can't grant or revoke the owner role in e2e-org",
+    )?;
+    add_member(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        THIRD_ENTITY_SLUG,
+        "member",
+        "Error: Authenticated entity e2e-second can't grant or revoke the member role in e2e-org",
+    )?;
+    set_member_sharing_level(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "manager",
+        "Error: Authenticated entity e2e-second can't manage organization e2e-org",
+    )?;
+
+    // Members start with no access to the organization's databases,
+    // and inherit whatever level the organization grants them.
+    query(
+        config_path,
+        second_api_key,
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        ORG_ENTITY_DB,
+        "table",
+        "Error: Authenticated entity e2e-second can't query database e2e-org/test.sqlite",
+    )?;
+    set_member_sharing_level(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "read-only",
+        "Members of e2e-org now have read-only access to its databases",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        ORG_ENTITY_DB,
+        "table",
+        " the_count \n-----------\n 0 \n\nRows: 1",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "INSERT INTO test_table (fname, lname) VALUES ('a member', 'a member');",
+        ORG_ENTITY_DB,
+        "table",
+        "Error: Attempted to write to database while in read-only mode",
+    )?;
+    list_members(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "csv",
+        "Members have read-only access to e2e-org's databases\nEntity,Role\ne2e-first,admin\ne2e-second,member",
+    )?;
+
+    // Members can leave an organization, but can't remove others.
+    remove_member(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "e2e-first",
+        "Error: Authenticated entity e2e-second can't remove e2e-first from e2e-org",
+    )?;
+    remove_member(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        SECOND_ENTITY_SLUG,
+        "Removed e2e-second from e2e-org",
+    )?;
+    remove_member(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        SECOND_ENTITY_SLUG,
+        "Error: e2e-second is not a member of e2e-org",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        ORG_ENTITY_DB,
+        "table",
+        "Error: Authenticated entity e2e-second can't query database e2e-org/test.sqlite",
+    )?;
+
+    Ok(())
+}
+
+pub fn test_organization_teams(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let org_api_key = &api_keys.get("org").unwrap()[0];
+
+    // Admins manage teams, which can only contain the organization's
+    // members.
+    create_team(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        "Created team analysts in e2e-org",
+    )?;
+    create_team(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        "Error: Team analysts already exists",
+    )?;
+    add_team_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        SECOND_ENTITY_SLUG,
+        "Error: e2e-second is not a member of e2e-org",
+    )?;
+    add_member(
+        config_path,
+        org_api_key,
+        ORG_ENTITY_SLUG,
+        SECOND_ENTITY_SLUG,
+        "member",
+        "e2e-second now has the member role in e2e-org",
+    )?;
+    set_member_sharing_level(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "no-access",
+        "Members of e2e-org now have no-access access to its databases",
+    )?;
+    add_team_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        SECOND_ENTITY_SLUG,
+        "Added e2e-second to team analysts in e2e-org",
+    )?;
+    create_team(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "others",
+        "Error: Authenticated entity e2e-second can't manage teams of e2e-org",
+    )?;
+    list_teams(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "csv",
+        "Team,Members\nanalysts,e2e-second",
+    )?;
+
+    // Team members get the team's access, and the highest of their
+    // direct and team grants wins.
+    query(
+        config_path,
+        second_api_key,
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        ORG_ENTITY_DB,
+        "table",
+        "Error: Authenticated entity e2e-second can't query database e2e-org/test.sqlite",
+    )?;
+    share_with_team(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "analysts",
+        "read-write",
+        "Permissions for team analysts on e2e-org/test.sqlite updated successfully",
+    )?;
+    share(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "Permissions for e2e-second on e2e-org/test.sqlite updated successfully",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "INSERT INTO test_table (fname, lname) VALUES ('an analyst', 'an analyst');",
+        ORG_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "csv",
+        "Entity,Sharing level,Source\ne2e-second,read-only,direct\ne2e-second,read-write,team:analysts",
+    )?;
+
+    // Leaving the team drops back to the direct grant.
+    remove_team_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        SECOND_ENTITY_SLUG,
+        "Removed e2e-second from team analysts in e2e-org",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "INSERT INTO test_table (fname, lname) VALUES ('an analyst', 'an analyst');",
+        ORG_ENTITY_DB,
+        "table",
+        "Error: Attempted to write to database while in read-only mode",
+    )?;
+
+    // Deleting a team revokes its grants.
+    add_team_member(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        SECOND_ENTITY_SLUG,
+        "Added e2e-second to team analysts in e2e-org",
+    )?;
+    delete_team(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_SLUG,
+        "analysts",
+        "Deleted team analysts from e2e-org",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "csv",
+        "Entity,Sharing level,Source\ne2e-second,read-only,direct",
+    )?;
+    list_teams(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_SLUG,
+        "csv",
+        "No teams in e2e-org",
+    )?;
+
+    Ok(())
+}
@@ -1,9 +1,10 @@
 use crate::e2e_tests::{
-    FIRST_ENTITY_DB, FIRST_ENTITY_DB2, FIRST_ENTITY_SLUG, SECOND_ENTITY_SLUG, THIRD_ENTITY_SLUG,
+    FIRST_ENTITY_DB, FIRST_ENTITY_DB2, FIRST_ENTITY_SLUG, ORG_ENTITY_DB, SECOND_ENTITY_SLUG,
+    THIRD_ENTITY_SLUG,
 };
 use crate::utils::ayb::{
-    database_details, list_database_permissions, list_databases, list_snapshots,
-    list_snapshots_match_output, query, share, update_database,
+    database_details, delete_database, fork_database, list_database_permissions, list_databases,
+    list_snapshots, list_snapshots_match_output, query, share, share_object, update_database,
 };
 use std::collections::HashMap;
 
@@ -65,6 +66,14 @@ pub async fn test_permissions(
         "Error: Authenticated entity e2e-second can't access database e2e-first/test.sqlite",
     )?;
 
+    fork_database(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "e2e-second/forked.sqlite",
+        "Error: Authenticated entity e2e-second can't fork database e2e-first/test.sqlite",
+    )?;
+
     // Second entity can't update database, but first can.
     update_database(
         config_path,
@@ -104,8 +113,59 @@ pub async fn test_permissions(
         "Database: e2e-first/test.sqlite\nType: sqlite\nAccess level: No query access",
     )?;
 
-    // TODO(marcua): When we implement forking, test that second
-    // entity can fork now, but not before the permission was granted.
+    // With fork-level access, the second entity can fork the database
+    // into its own account, but not into someone else's. The fork has
+    // the upstream's data, and changes to it stay in the fork.
+    fork_database(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "e2e-third/forked.sqlite",
+        "Error: Authenticated entity e2e-second can't create a database for entity e2e-third",
+    )?;
+    fork_database(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "e2e-second/forked.sqlite",
+        "Forked e2e-first/test.sqlite to e2e-second/forked.sqlite",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        "INSERT INTO test_table (fname, lname) VALUES ('the forker', 'the forker');",
+        "e2e-second/forked.sqlite",
+        "table",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        "e2e-second/forked.sqlite",
+        "table",
+        " the_count \n-----------\n 5 \n\nRows: 1",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        "SELECT COUNT(*) AS the_count FROM test_table;",
+        FIRST_ENTITY_DB,
+        "table",
+        " the_count \n-----------\n 4 \n\nRows: 1",
+    )?;
+    database_details(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        "e2e-second/forked.sqlite",
+        "Forked from: e2e-first/test.sqlite",
+    )?;
+    delete_database(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        "e2e-second/forked.sqlite",
+        "Deleted e2e-second/forked.sqlite",
+    )?;
 
     // With public read-only permissions, the second entity can issue
     // read-only (SELECT) queries, but not modify the database (e.g.,
@@ -222,7 +282,7 @@ pub async fn test_permissions(
         &api_keys.get("first").unwrap()[0],
         FIRST_ENTITY_DB,
         "csv",
-        "Entity,Sharing level\ne2e-second,read-only",
+        "Entity,Sharing level,Source\ne2e-second,read-only,direct",
     )?;
     // Second entity has read-only access.
     query(
@@ -479,7 +539,7 @@ pub async fn test_permissions(
         &api_keys.get("second").unwrap()[0],
         FIRST_ENTITY_DB,
         "csv",
-        "Entity,Sharing level\ne2e-second,manager",
+        "Entity,Sharing level,Source\ne2e-second,manager,direct",
     )?;
 
     // Access to e2e-first/test.sqlite doesn't grant access to
@@ -592,7 +652,7 @@ pub async fn test_permissions(
         &api_keys.get("first").unwrap()[0],
         FIRST_ENTITY_DB,
         "csv",
-        "Entity,Sharing level\ne2e-second,manager\ne2e-third,read-only",
+        "Entity,Sharing level,Source\ne2e-second,manager,direct\ne2e-third,read-only,direct",
     )?;
     // Third entity can query database.
     query(
@@ -728,3 +788,241 @@ pub async fn test_permissions(
 
     Ok(())
 }
+
+pub fn test_object_permissions(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let third_api_key = &api_keys.get("third").unwrap()[0];
+
+    // The second entity has read-only access to the organization's
+    // database, which an object grant limits to a single column.
+    share_object(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "test_table",
+        Some("fname"),
+        "Permissions for e2e-second on e2e-org/test.sqlite updated successfully",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT fname FROM test_table;",
+        ORG_ENTITY_DB,
+        "csv",
+        "fname\nan analyst\n\nRows: 1",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT * FROM test_table;",
+        ORG_ENTITY_DB,
+        "csv",
+        "Error: Not permitted to read test_table.lname in this database",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT fname FROM test_table WHERE lname = 'an analyst';",
+        ORG_ENTITY_DB,
+        "csv",
+        "Error: Not permitted to read test_table.lname in this database",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "csv",
+        "Entity,Sharing level,Source\ne2e-second,read-only,direct\nLimited to objects:\nEntity,Object,Columns\ne2e-second,test_table,fname",
+    )?;
+
+    // Forking would copy every table and column, so it's refused.
+    fork_database(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_DB,
+        "e2e-second/restricted_fork.sqlite",
+        "Error: Authenticated entity e2e-second can only access some tables of e2e-org/test.sqlite, so it can't fork it",
+    )?;
+
+    // Public access can be limited the same way, which also makes the
+    // database publicly readable.
+    query(
+        config_path,
+        third_api_key,
+        "SELECT fname FROM test_table;",
+        ORG_ENTITY_DB,
+        "csv",
+        "Error: Authenticated entity e2e-third can't query database e2e-org/test.sqlite",
+    )?;
+    share_object(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "public",
+        "read-write",
+        "test_table",
+        None,
+        "Error: Public access can only be limited to objects at the read-only level",
+    )?;
+    share_object(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "public",
+        "read-only",
+        "test_table",
+        None,
+        "Permissions for public on e2e-org/test.sqlite updated successfully",
+    )?;
+    query(
+        config_path,
+        third_api_key,
+        "SELECT fname, lname FROM test_table;",
+        ORG_ENTITY_DB,
+        "csv",
+        "fname,lname\nan analyst,an analyst\n\nRows: 1",
+    )?;
+    fork_database(
+        config_path,
+        third_api_key,
+        ORG_ENTITY_DB,
+        "e2e-third/restricted_fork.sqlite",
+        "Error: Authenticated entity e2e-third can only access some tables of e2e-org/test.sqlite, so it can't fork it",
+    )?;
+    query(
+        config_path,
+        third_api_key,
+        "PRAGMA table_info(test_table);",
+        ORG_ENTITY_DB,
+        "csv",
+        "Error: Not permitted to run this statement in this database",
+    )?;
+
+    // Revoking an entity's last object revokes its own access, leaving
+    // it with public access.
+    share_object(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        SECOND_ENTITY_SLUG,
+        "no-access",
+        "test_table",
+        None,
+        "Permissions for e2e-second on e2e-org/test.sqlite updated successfully",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "csv",
+        "Limited to objects:\nEntity,Object,Columns\npublic,test_table,*",
+    )?;
+    share_object(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "public",
+        "no-access",
+        "test_table",
+        None,
+        "Permissions for public on e2e-org/test.sqlite updated successfully",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "SELECT fname FROM test_table;",
+        ORG_ENTITY_DB,
+        "csv",
+        "Error: Authenticated entity e2e-second can't query database e2e-org/test.sqlite",
+    )?;
+    list_database_permissions(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        "csv",
+        "No shared permissions for e2e-org/test.sqlite",
+    )?;
+
+    Ok(())
+}
+
+pub fn test_read_write_data_permissions(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+
+    // The second entity can change the organization database's data,
+    // but not its schema.
+    share(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        SECOND_ENTITY_SLUG,
+        "read-write-data",
+        "Permissions for e2e-second on e2e-org/test.sqlite updated successfully",
+    )?;
+    database_details(
+        config_path,
+        second_api_key,
+        ORG_ENTITY_DB,
+        "Database: e2e-org/test.sqlite\nType: sqlite\nAccess level: ReadWriteData",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "INSERT INTO test_table (fname, lname) VALUES ('a writer', 'a writer');",
+        ORG_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        second_api_key,
+        "UPDATE test_table SET lname = 'the writer' WHERE fname = 'a writer';",
+        ORG_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    for schema_change in [
+        "CREATE TABLE another_table (x INTEGER);",
+        "ALTER TABLE test_table ADD COLUMN email TEXT;",
+        "CREATE INDEX test_index ON test_table (fname);",
+        "DROP TABLE test_table;",
+        "PRAGMA user_version = 2;",
+    ] {
+        query(
+            config_path,
+            second_api_key,
+            schema_change,
+            ORG_ENTITY_DB,
+            "csv",
+            "Error: Not permitted to change the schema in this database",
+        )?;
+    }
+    query(
+        config_path,
+        second_api_key,
+        "DELETE FROM test_table WHERE fname = 'a writer';",
+        ORG_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    share(
+        config_path,
+        first_api_key,
+        ORG_ENTITY_DB,
+        SECOND_ENTITY_SLUG,
+        "no-access",
+        "Permissions for e2e-second on e2e-org/test.sqlite updated successfully",
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use crate::e2e_tests::FIRST_ENTITY_SLUG;
+use crate::utils::ayb::{
+    list_published_endpoints, publish_endpoint, save_query, unpublish_endpoint,
+};
+use std::collections::HashMap;
+
+// Reuses the database and `by_score` saved query from the saved query
+// tests, which leave e2e-second with read-only access.
+const SAVED_QUERY_DB: &str = "e2e-first/saved.sqlite";
+
+pub async fn test_published_endpoints(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+    server_url: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let endpoint_url = format!("{server_url}/v1/{SAVED_QUERY_DB}/endpoints/leaderboard");
+    let client = reqwest::Client::new();
+
+    // Only the database's managers can publish endpoints.
+    publish_endpoint(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "leaderboard",
+        "by_score",
+        None,
+        "Error: Authenticated entity e2e-second can't publish endpoints for e2e-first/saved.sqlite",
+    )?;
+    publish_endpoint(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "leaderboard",
+        "by_score",
+        Some("120"),
+        "Published by_score at /v1/e2e-first/saved.sqlite/endpoints/leaderboard",
+    )?;
+    list_published_endpoints(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        &format!(
+            "Name,Path,Saved query,Parameters,Max age,Publisher,Published at\n\
+             leaderboard,/v1/e2e-first/saved.sqlite/endpoints/leaderboard,by_score,\
+             min:integer label:text=fallback,120,{FIRST_ENTITY_SLUG},[^,]+"
+        ),
+    )?;
+
+    // Anyone can call the endpoint without a token.
+    let response = client
+        .get(format!("{endpoint_url}?min=5&label=public"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["cache-control"].to_str()?,
+        "public, max-age=120"
+    );
+    let etag = response.headers()["etag"].to_str()?.to_string();
+    let body: serde_json::Value = response.json().await?;
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "fields": ["name", "score", "label"],
+            "rows": [["mid", "5", "public"], ["high", "10", "public"]],
+        })
+    );
+
+    // Unchanged results aren't sent again.
+    let response = client
+        .get(format!("{endpoint_url}?min=5&label=public"))
+        .header("If-None-Match", &etag)
+        .send()
+        .await?;
+    assert_eq!(response.status(), 304);
+
+    let response = client
+        .get(format!("{endpoint_url}?min=10&format=csv"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"].to_str()?, "text/csv");
+    assert_eq!(
+        response.text().await?,
+        "name,score,label\nhigh,10,fallback\n"
+    );
+
+    // The endpoint only accepts the saved query's declared parameters.
+    let response = client
+        .get(format!("{endpoint_url}?min=5&limit=1"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 500);
+    assert!(response.text().await?.contains("Unknown parameter limit"));
+    let response = client
+        .get(format!("{endpoint_url}?min=5%20OR%201=1"))
+        .send()
+        .await?;
+    assert_eq!(response.status(), 500);
+
+    // Endpoints keep serving the query they were published with.
+    save_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        "SELECT name FROM scores WHERE score >= :min;",
+        &["min:integer"],
+        "Saved query by_score on e2e-first/saved.sqlite",
+    )?;
+    let response = client
+        .get(format!("{endpoint_url}?min=10&format=csv"))
+        .send()
+        .await?;
+    assert_eq!(
+        response.text().await?,
+        "name,score,label\nhigh,10,fallback\n"
+    );
+
+    unpublish_endpoint(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "leaderboard",
+        "Error: Authenticated entity e2e-second can't unpublish endpoints for e2e-first/saved.sqlite",
+    )?;
+    unpublish_endpoint(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "leaderboard",
+        "Unpublished endpoint leaderboard from e2e-first/saved.sqlite",
+    )?;
+    let response = client.get(format!("{endpoint_url}?min=5")).send().await?;
+    assert_eq!(response.status(), 500);
+    list_published_endpoints(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "No published endpoints for e2e-first/saved.sqlite",
+    )?;
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::ayb_assert_cmd;
-use crate::e2e_tests::{FIRST_ENTITY_SLUG, SECOND_ENTITY_SLUG, THIRD_ENTITY_SLUG};
+use crate::e2e_tests::{FIRST_ENTITY_SLUG, ORG_ENTITY_SLUG, SECOND_ENTITY_SLUG, THIRD_ENTITY_SLUG};
 use crate::utils::ayb::register;
 use crate::utils::email::{clear_email_data, extract_token_from_emails, get_emails_for_recipient};
 use ayb::client::config::ClientConfig;
@@ -268,11 +268,29 @@ pub fn test_registration(
     });
     let third_api_key0 = extract_api_key(cmd.get_output())?;
 
+    // Register an organization (e2e-org), whose members get tested later.
+    let cmd = ayb_assert_cmd!("client", "register", ORG_ENTITY_SLUG, "e2e-org@example.org", "organization"; {
+        "AYB_CLIENT_CONFIG_FILE" => config_path,
+        "AYB_SERVER_URL" => server_url,
+    });
+    cmd.stdout("Check your email to finish registering e2e-org\n");
+
+    let org_emails = get_emails_for_recipient(test_type, "e2e-org@example.org")?;
+    assert_eq!(org_emails.len(), 1);
+    let org_token0 = extract_token_from_emails(&[org_emails[0].clone()]).unwrap();
+
+    let cmd = ayb_assert_cmd!("client", "confirm", &org_token0; {
+        "AYB_CLIENT_CONFIG_FILE" => format!("{}-throwaway", config_path),
+        "AYB_SERVER_URL" => server_url,
+    });
+    let org_api_key0 = extract_api_key(cmd.get_output())?;
+
     // To summarize where we are at this point
     // * User e2e-first has three API tokens (first_api_key[0...2]). We'll use these
     //   interchangeably in subsequent tests.
     // * User e2e-second has one API token (second_api_key0)
     // * User e2e-third has one API token (third_api_key0)
+    // * Organization e2e-org has one API token (org_api_key0)
     let mut api_keys: HashMap<String, Vec<String>> = HashMap::new();
     api_keys.insert(
         "first".to_string(),
@@ -280,6 +298,7 @@ pub fn test_registration(
     );
     api_keys.insert("second".to_string(), vec![second_api_key0]);
     api_keys.insert("third".to_string(), vec![third_api_key0]);
+    api_keys.insert("org".to_string(), vec![org_api_key0]);
     test_banned_username_registration(config_path, server_url)?;
     Ok(api_keys)
 }
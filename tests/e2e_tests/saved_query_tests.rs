@@ -0,0 +1,170 @@
+use crate::e2e_tests::{FIRST_ENTITY_SLUG, SECOND_ENTITY_SLUG};
+use crate::utils::ayb::{
+    create_database, delete_saved_query, list_saved_queries, query, run_saved_query, save_query,
+    share,
+};
+use std::collections::HashMap;
+
+const SAVED_QUERY_DB: &str = "e2e-first/saved.sqlite";
+const BY_SCORE: &str =
+    "SELECT name, score, :label AS label FROM scores WHERE score >= :min ORDER BY score;";
+
+pub fn test_saved_queries(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+
+    create_database(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "sqlite",
+        "Successfully created e2e-first/saved.sqlite",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE scores (name varchar, score integer); INSERT INTO scores VALUES ('low', 1), ('mid', 5), ('high', 10);",
+        SAVED_QUERY_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+
+    // Saved queries declare the parameters they use.
+    save_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        BY_SCORE,
+        &["min:integer"],
+        "Error: Query uses parameter :label without declaring it",
+    )?;
+    save_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        BY_SCORE,
+        &["min:integer", "label:text=fallback"],
+        "Saved query by_score on e2e-first/saved.sqlite",
+    )?;
+    list_saved_queries(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        &format!(
+            "Name,Description,Parameters,Author,Updated at,Query\n\
+             by_score,,min:integer label:text=fallback,{FIRST_ENTITY_SLUG},[^,]+,{}",
+            regex::escape(&format!("\"{BY_SCORE}\""))
+        ),
+    )?;
+
+    // Values are quoted according to their parameter's type, and
+    // parameters without a value fall back to their default.
+    run_saved_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &["min=5"],
+        "name,score,label\nmid,5,fallback\nhigh,10,fallback\n\nRows: 2",
+    )?;
+    run_saved_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &["min=10", "label=it's"],
+        "name,score,label\nhigh,10,it's\n\nRows: 1",
+    )?;
+    run_saved_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &[],
+        "Error: Missing a value for parameter min",
+    )?;
+    run_saved_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &["min=0 OR 1=1"],
+        "Error: Parameter min must be of type integer, but got 0 OR 1=1",
+    )?;
+
+    // Saved queries are only available to entities that can query the
+    // database, and run with their access level.
+    run_saved_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &["min=5"],
+        "Error: Authenticated entity e2e-second can't query database e2e-first/saved.sqlite",
+    )?;
+    share(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        SECOND_ENTITY_SLUG,
+        "read-only",
+        "Permissions for e2e-second on e2e-first/saved.sqlite updated successfully",
+    )?;
+    run_saved_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        &["min=10"],
+        "name,score,label\nhigh,10,fallback\n\nRows: 1",
+    )?;
+    save_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        "SELECT 1;",
+        &[],
+        "Error: Authenticated entity e2e-second can't save query by_score on e2e-first/saved.sqlite",
+    )?;
+    save_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "add_score",
+        "INSERT INTO scores VALUES (:name, 0);",
+        &["name:text"],
+        "Saved query add_score on e2e-first/saved.sqlite",
+    )?;
+    run_saved_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "add_score",
+        &["name=zero"],
+        "Error: Attempted to write to database while in read-only mode",
+    )?;
+
+    // The database's owner can delete anyone's saved queries.
+    delete_saved_query(
+        config_path,
+        first_api_key,
+        SAVED_QUERY_DB,
+        "add_score",
+        "Deleted saved query add_score from e2e-first/saved.sqlite",
+    )?;
+    delete_saved_query(
+        config_path,
+        second_api_key,
+        SAVED_QUERY_DB,
+        "by_score",
+        "Error: Authenticated entity e2e-second can't delete saved query by_score on e2e-first/saved.sqlite",
+    )?;
+
+    Ok(())
+}
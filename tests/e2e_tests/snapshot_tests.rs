@@ -2,12 +2,33 @@ use crate::e2e_tests::{
     FIRST_ENTITY_DB, FIRST_ENTITY_DB_SLUG, FIRST_ENTITY_DUCKDB, FIRST_ENTITY_DUCKDB_SLUG,
     FIRST_ENTITY_SLUG,
 };
-use crate::utils::ayb::{list_snapshots, list_snapshots_match_output, query, restore_snapshot};
-use crate::utils::testing::snapshot_storage;
+use crate::utils::ayb::{
+    create_database, create_snapshot, delete_database, diff, list_snapshots,
+    list_snapshots_match_output, query, query_snapshot, restore_point_in_time, restore_snapshot,
+    restore_snapshot_into, snapshot_retention, snapshot_schedule, update_snapshot_retention,
+    update_snapshot_schedule,
+};
+use crate::utils::testing::{
+    snapshot_config, snapshot_storage, wipe_and_recover_server, AybServer,
+};
+use ayb::ayb_db::models::DBType;
+use ayb::server::snapshots::encryption::is_encrypted;
+use ayb::server::snapshots::models::{Snapshot, SnapshotType};
+use ayb::server::snapshots::storage::SnapshotStorage;
+use ayb::server::snapshots::store::create_snapshot_store;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
+use std::fs;
 use std::thread;
 use std::time;
 
+const POINT_IN_TIME_DB: &str = "e2e-first/point_in_time.sqlite";
+const SCRATCH_DB: &str = "e2e-first/scratch.sqlite";
+const RESTORED_DB: &str = "e2e-first/restored.sqlite";
+const ENCRYPTED_DB: &str = "e2e-first/encrypted.sqlite";
+const ENCRYPTED_DB_SLUG: &str = "encrypted.sqlite";
+const STREAMED_DB_SLUG: &str = "streamed.sqlite";
+
 /// Poll until the snapshot list reaches `expected` entries. When
 /// `changed_since` is `Some(id)`, also require the newest snapshot's
 /// ID to differ from `id` — this handles the pruning case where the
@@ -94,6 +115,11 @@ pub async fn test_snapshots(
         None,
     );
 
+    // The daemon's snapshots are automatic, and nobody triggered them.
+    assert_eq!(snapshots[0].snapshot_type, "automatic");
+    assert_eq!(snapshots[0].triggered_by, None);
+    assert_eq!(snapshots[0].db_type.as_deref(), Some("sqlite"));
+
     // No change to database, so same number of snapshots after sleep.
     let last_modified_at = snapshots[0].last_modified_at;
     thread::sleep(time::Duration::from_secs(4));
@@ -312,6 +338,381 @@ pub async fn test_snapshots(
 /// A simpler snapshot/restore cycle for a DuckDB database, paralleling
 /// the SQLite test above. Assumes `test_create_and_query_duckdb` has
 /// already created `e2e-first/test.duckdb` with two rows.
+pub fn test_manual_snapshots(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Can't take a snapshot from an account without access.
+    create_snapshot(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        None,
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+
+    // Snapshot a new state of the database by hand. Whether or not
+    // the daemon gets to it first, the snapshot ends up manual.
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "INSERT INTO test_table (fname, lname) VALUES ('manual first', 'manual last');",
+        FIRST_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    create_snapshot(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        Some("before-cleanup"),
+        "Created snapshot [a-f0-9]{64} of e2e-first/test.sqlite",
+    )?;
+    let snapshots = list_snapshots(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "csv",
+    )?;
+    let manual_snapshots: Vec<_> = snapshots
+        .iter()
+        .filter(|snapshot| snapshot.snapshot_type == "manual")
+        .collect();
+    assert_eq!(
+        manual_snapshots.len(),
+        1,
+        "there should be one manual snapshot"
+    );
+    assert_eq!(
+        manual_snapshots[0].label.as_deref(),
+        Some("before-cleanup"),
+        "the manual snapshot should keep its label"
+    );
+    assert_eq!(
+        manual_snapshots[0].triggered_by.as_deref(),
+        Some(FIRST_ENTITY_SLUG),
+        "the manual snapshot should record who took it"
+    );
+    assert_eq!(manual_snapshots[0].db_type.as_deref(), Some("sqlite"));
+    assert!(manual_snapshots[0].uncompressed_size.unwrap() > 0);
+    assert!(manual_snapshots[0].compressed_size.unwrap() > 0);
+    assert_eq!(
+        manual_snapshots[0].ayb_version.as_deref(),
+        Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert!(manual_snapshots[0].source_database_id.is_some());
+    let manual_snapshot_id = manual_snapshots[0].snapshot_id.clone();
+
+    // Restoring the manual snapshot brings back the row.
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "DELETE FROM test_table WHERE fname = 'manual first';",
+        FIRST_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    restore_snapshot(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        &format!("Restored e2e-first/test.sqlite to snapshot {manual_snapshot_id}"),
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "SELECT COUNT(*) AS the_count FROM test_table WHERE fname = 'manual first';",
+        FIRST_ENTITY_DB,
+        "table",
+        " the_count \n-----------\n 1 \n\nRows: 1",
+    )?;
+
+    // Snapshots can be queried without restoring them, but only
+    // read from.
+    query_snapshot(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        "SELECT COUNT(*) AS the_count FROM test_table WHERE fname = 'manual first';",
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "INSERT INTO test_table (fname, lname) VALUES ('after snapshot', 'after snapshot');",
+        FIRST_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    for _ in 0..2 {
+        // The second query is served from the cached snapshot.
+        query_snapshot(
+            config_path,
+            &api_keys.get("first").unwrap()[0],
+            "SELECT COUNT(*) AS the_count FROM test_table WHERE fname IN ('manual first', 'after snapshot');",
+            FIRST_ENTITY_DB,
+            &manual_snapshot_id,
+            " the_count \n-----------\n 1 \n\nRows: 1",
+        )?;
+    }
+    // Diffing the snapshot against the current database shows the row
+    // inserted since. `test_table` has no primary key, so its rows are
+    // compared whole.
+    diff(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        None,
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+    diff(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        None,
+        " test_table |             | 1        | 0       | 0       |",
+    )?;
+    diff(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        Some(&manual_snapshot_id),
+        "No changes",
+    )?;
+    query_snapshot(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        "DELETE FROM test_table;",
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        "Error: Attempted to write to database while in read-only mode",
+    )?;
+    query_snapshot(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        "SELECT 1;",
+        FIRST_ENTITY_DB,
+        "../../../ayb.sqlite",
+        "Error: SnapshotDoesNotExistError",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "DELETE FROM test_table WHERE fname = 'after snapshot';",
+        FIRST_ENTITY_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+
+    // The snapshot can also be restored into a new database, next to
+    // the original.
+    restore_snapshot_into(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        RESTORED_DB,
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+    restore_snapshot_into(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        RESTORED_DB,
+        "Restored e2e-first/test.sqlite into e2e-first/restored.sqlite",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        "SELECT COUNT(*) AS the_count FROM test_table WHERE fname = 'manual first';",
+        RESTORED_DB,
+        "table",
+        " the_count \n-----------\n 1 \n\nRows: 1",
+    )?;
+    restore_snapshot_into(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        &manual_snapshot_id,
+        RESTORED_DB,
+        "Error: Database already exists",
+    )?;
+    delete_database(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        RESTORED_DB,
+        "Deleted e2e-first/restored.sqlite",
+    )?;
+
+    Ok(())
+}
+
+pub fn test_snapshot_retention(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Only entities that manage a database can change its retention.
+    update_snapshot_retention(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "--daily=7",
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+
+    // The test server keeps the most recent snapshots and nothing
+    // else. Pruning runs after every automatic snapshot, so there's
+    // nothing left for it to prune.
+    snapshot_retention(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "Max snapshots: 6\nRetention policy: none\nNo snapshots would be pruned",
+    )?;
+
+    // A database can keep more snapshots than the server does.
+    update_snapshot_retention(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "--daily=7",
+        "Updated snapshot retention of e2e-first/test.sqlite",
+    )?;
+    snapshot_retention(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "Max snapshots: 6\nRetention policy: hourly 0, daily 7, weekly 0 (set on the database)\nNo snapshots would be pruned",
+    )?;
+
+    update_snapshot_retention(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "--reset",
+        "Reset snapshot retention of e2e-first/test.sqlite to the server's",
+    )?;
+    snapshot_retention(
+        config_path,
+        &api_keys.get("first").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "Max snapshots: 6\nRetention policy: none\nNo snapshots would be pruned",
+    )?;
+
+    Ok(())
+}
+
+pub fn test_snapshot_schedule(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = &api_keys.get("first").unwrap()[0];
+
+    // Only entities that manage a database can change its schedule.
+    update_snapshot_schedule(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        FIRST_ENTITY_DB,
+        "--disable",
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/test.sqlite",
+    )?;
+    snapshot_schedule(
+        config_path,
+        api_key,
+        FIRST_ENTITY_DB,
+        "Automatic snapshots: every 2s (set on the server)",
+    )?;
+
+    // A scratch database that opted out of automatic snapshots isn't
+    // snapshotted, even after it's written to.
+    create_database(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "sqlite",
+        "Successfully created e2e-first/scratch.sqlite",
+    )?;
+    update_snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "--disable",
+        "Updated snapshot schedule of e2e-first/scratch.sqlite",
+    )?;
+    snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "Automatic snapshots: off (set on the database)",
+    )?;
+    query(
+        config_path,
+        &api_keys.get("first").unwrap()[1],
+        "CREATE TABLE scratch(value int);",
+        SCRATCH_DB,
+        "table",
+        "\nRows: 0",
+    )?;
+    thread::sleep(time::Duration::from_secs(5));
+    assert!(
+        list_snapshots(config_path, api_key, SCRATCH_DB, "csv")?.is_empty(),
+        "a database without automatic snapshots shouldn't be snapshotted"
+    );
+
+    // Turning automatic snapshots back on snapshots it.
+    update_snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "--enable",
+        "Updated snapshot schedule of e2e-first/scratch.sqlite",
+    )?;
+    wait_for_snapshot_count(config_path, api_key, SCRATCH_DB, 1, None);
+
+    // Databases can be snapshotted on their own interval.
+    update_snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "--interval=1h",
+        "Updated snapshot schedule of e2e-first/scratch.sqlite",
+    )?;
+    snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "Automatic snapshots: every 1h (set on the database)",
+    )?;
+    update_snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "--reset",
+        "Reset snapshot schedule of e2e-first/scratch.sqlite to the server's",
+    )?;
+    snapshot_schedule(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "Automatic snapshots: every 2s (set on the server)",
+    )?;
+
+    delete_database(
+        config_path,
+        api_key,
+        SCRATCH_DB,
+        "Deleted e2e-first/scratch.sqlite",
+    )?;
+
+    Ok(())
+}
+
 pub async fn test_snapshots_duckdb(
     db_type: &str,
     config_path: &str,
@@ -393,3 +794,340 @@ pub async fn test_snapshots_duckdb(
 
     Ok(())
 }
+
+pub fn test_point_in_time_recovery(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = &api_keys.get("first").unwrap()[0];
+    let count_rows = |expected: &str| {
+        query(
+            config_path,
+            api_key,
+            "SELECT COUNT(*) AS the_count FROM scores;",
+            POINT_IN_TIME_DB,
+            "table",
+            &format!(" the_count \n-----------\n {expected} \n\nRows: 1"),
+        )
+    };
+    // WAL replication ships committed transactions every second, so
+    // wait a few seconds to be sure they've been shipped.
+    let wait_for_replication = || thread::sleep(time::Duration::from_secs(4));
+
+    create_database(
+        config_path,
+        api_key,
+        POINT_IN_TIME_DB,
+        "sqlite",
+        "Successfully created e2e-first/point_in_time.sqlite",
+    )?;
+    query(
+        config_path,
+        api_key,
+        "CREATE TABLE scores (name varchar, score integer);",
+        POINT_IN_TIME_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    wait_for_replication();
+    query(
+        config_path,
+        api_key,
+        "INSERT INTO scores VALUES ('first', 1);",
+        POINT_IN_TIME_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    wait_for_replication();
+    let one_row_at = Utc::now().to_rfc3339();
+    query(
+        config_path,
+        api_key,
+        "INSERT INTO scores VALUES ('second', 2);",
+        POINT_IN_TIME_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    wait_for_replication();
+    let two_rows_at = Utc::now().to_rfc3339();
+    query(
+        config_path,
+        api_key,
+        "DELETE FROM scores;",
+        POINT_IN_TIME_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    count_rows("0")?;
+
+    // Restores can go to any point covered by the replicated log, not
+    // just to snapshots, including points before an earlier restore.
+    restore_point_in_time(
+        config_path,
+        api_key,
+        POINT_IN_TIME_DB,
+        &one_row_at,
+        &format!(
+            "Restored e2e-first/point_in_time.sqlite to {}",
+            one_row_at.parse::<DateTime<Utc>>()?.to_rfc3339()
+        ),
+    )?;
+    count_rows("1")?;
+    restore_point_in_time(
+        config_path,
+        api_key,
+        POINT_IN_TIME_DB,
+        &two_rows_at,
+        &format!(
+            "Restored e2e-first/point_in_time.sqlite to {}",
+            two_rows_at.parse::<DateTime<Utc>>()?.to_rfc3339()
+        ),
+    )?;
+    count_rows("2")?;
+
+    // Nothing was replicated before the database existed.
+    restore_point_in_time(
+        config_path,
+        api_key,
+        POINT_IN_TIME_DB,
+        "2000-01-01T00:00:00Z",
+        "Error: No point-in-time backup of e2e-first/point_in_time.sqlite goes back to 2000-01-01 00:00:00 UTC",
+    )?;
+    // Only the database's managers can restore it.
+    restore_point_in_time(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        POINT_IN_TIME_DB,
+        &two_rows_at,
+        "Error: Authenticated entity e2e-second can't manage snapshots on database e2e-first/point_in_time.sqlite",
+    )?;
+    count_rows("2")?;
+
+    Ok(())
+}
+
+/// Snapshots are encrypted before they're stored, and snapshots
+/// encrypted with a key that's since been replaced can still be
+/// restored. Assumes `[snapshots.encryption]` is configured with a
+/// previous key.
+pub async fn test_encrypted_snapshots(
+    test_type: &str,
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = &api_keys.get("first").unwrap()[0];
+    let count_rows = |expected: &str| {
+        query(
+            config_path,
+            api_key,
+            "SELECT COUNT(*) AS the_count FROM scores;",
+            ENCRYPTED_DB,
+            "table",
+            &format!(" the_count \n-----------\n {expected} \n\nRows: 1"),
+        )
+    };
+
+    create_database(
+        config_path,
+        api_key,
+        ENCRYPTED_DB,
+        "sqlite",
+        "Successfully created e2e-first/encrypted.sqlite",
+    )?;
+    query(
+        config_path,
+        api_key,
+        "CREATE TABLE scores (name varchar, score integer);",
+        ENCRYPTED_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        api_key,
+        "INSERT INTO scores VALUES ('first', 1);",
+        ENCRYPTED_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    let snapshots = wait_for_snapshot_count(config_path, api_key, ENCRYPTED_DB, 1, None);
+
+    // Every object stored for the database, from its manifests to its
+    // chunks, is encrypted.
+    let config = snapshot_config(test_type)?;
+    let store = create_snapshot_store(&config)?;
+    let objects = store
+        .list(&format!(
+            "{}/{FIRST_ENTITY_SLUG}/{ENCRYPTED_DB_SLUG}/",
+            config.path_prefix
+        ))
+        .await?;
+    assert!(objects.len() > 1);
+    for object in objects {
+        assert!(
+            is_encrypted(&store.get(&object.key).await?),
+            "{} isn't encrypted",
+            object.key
+        );
+    }
+
+    // Store the snapshot again with the previous key, as if it had been
+    // taken before the key was replaced.
+    let snapshot_dir = tempfile::tempdir()?;
+    snapshot_storage(test_type)
+        .await?
+        .retrieve_snapshot(
+            FIRST_ENTITY_SLUG,
+            ENCRYPTED_DB_SLUG,
+            &snapshots[0].snapshot_id,
+            snapshot_dir.path(),
+        )
+        .await?;
+    let mut previous_key_config = config.clone();
+    let encryption = previous_key_config.encryption.as_mut().unwrap();
+    encryption.key = encryption.previous_keys.take().unwrap().remove(0);
+    SnapshotStorage::new(&previous_key_config)
+        .await?
+        .put(
+            FIRST_ENTITY_SLUG,
+            ENCRYPTED_DB_SLUG,
+            &Snapshot {
+                snapshot_id: snapshots[0].snapshot_id.clone(),
+                snapshot_type: SnapshotType::Automatic as i16,
+                label: None,
+                db_type: DBType::Sqlite as i16,
+                triggered_by: None,
+                source_database_id: snapshots[0].source_database_id.clone().unwrap_or_default(),
+            },
+            &snapshot_dir.path().join(ENCRYPTED_DB_SLUG),
+        )
+        .await?;
+
+    query(
+        config_path,
+        api_key,
+        "INSERT INTO scores VALUES ('second', 2);",
+        ENCRYPTED_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    count_rows("2")?;
+    restore_snapshot(
+        config_path,
+        api_key,
+        ENCRYPTED_DB,
+        &snapshots[0].snapshot_id,
+        &format!(
+            "Restored e2e-first/encrypted.sqlite to snapshot {}",
+            snapshots[0].snapshot_id
+        ),
+    )?;
+    count_rows("1")?;
+
+    Ok(())
+}
+
+/// Objects too large to hold in memory, like the base of a replicated
+/// write-ahead log, are streamed to and from storage. Against S3, this
+/// one is uploaded in several multipart upload parts.
+pub async fn test_streamed_snapshots(test_type: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // Three S3 parts of pseudo-random bytes, which don't compress.
+    let mut state: u64 = 0x9e3779b97f4a7c15;
+    let data: Vec<u8> = (0..20 * 1024 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let base_dir = tempfile::tempdir()?;
+    let base_path = base_dir.path().join(STREAMED_DB_SLUG);
+    fs::write(&base_path, &data)?;
+
+    let storage = snapshot_storage(test_type).await?;
+    storage
+        .put_wal_base(FIRST_ENTITY_SLUG, STREAMED_DB_SLUG, Utc::now(), &base_path)
+        .await?;
+    let generations = storage
+        .list_wal_generations(FIRST_ENTITY_SLUG, STREAMED_DB_SLUG)
+        .await?;
+    assert_eq!(generations.len(), 1);
+    let restore_dir = tempfile::tempdir()?;
+    storage
+        .retrieve_wal_base(
+            FIRST_ENTITY_SLUG,
+            STREAMED_DB_SLUG,
+            &generations[0],
+            restore_dir.path(),
+        )
+        .await?;
+    assert!(fs::read(restore_dir.path().join(STREAMED_DB_SLUG))? == data);
+
+    storage
+        .delete_wal_generations(FIRST_ENTITY_SLUG, STREAMED_DB_SLUG, &generations)
+        .await?;
+    assert!(storage
+        .list_wal_generations(FIRST_ENTITY_SLUG, STREAMED_DB_SLUG)
+        .await?
+        .is_empty());
+
+    Ok(())
+}
+
+/// Rebuilds the server from its snapshot storage with `ayb server
+/// recover`, as if its machine had been lost, and checks that its
+/// entities, tokens, and databases are back. Stops `ayb_server` and runs
+/// its replacement until the checks are done. Assumes
+/// `test_encrypted_snapshots` has run, since the metadata database is
+/// only backed up when snapshots are encrypted.
+pub fn test_server_recovery(
+    test_type: &str,
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+    ayb_server: AybServer,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let api_key = &api_keys.get("first").unwrap()[0];
+    let names = "SELECT name FROM scores ORDER BY score;";
+
+    // Write a row no earlier snapshot has, and wait for the snapshot of
+    // it. The metadata database is backed up in the same run, before
+    // the databases are.
+    let snapshot_count = list_snapshots(config_path, api_key, ENCRYPTED_DB, "csv")?.len();
+    query(
+        config_path,
+        api_key,
+        "INSERT INTO scores VALUES ('recovered', 3);",
+        ENCRYPTED_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    wait_for_snapshot_count(config_path, api_key, ENCRYPTED_DB, snapshot_count + 1, None);
+
+    drop(ayb_server);
+    wipe_and_recover_server(test_type)?;
+    let _ayb_server = AybServer::run(test_type).expect("failed to start the ayb server");
+    thread::sleep(time::Duration::from_secs(10));
+
+    // The token still works, so the metadata database was restored,
+    // and so was the database it lists.
+    query(
+        config_path,
+        api_key,
+        names,
+        ENCRYPTED_DB,
+        "csv",
+        "name\nfirst\nrecovered\n\nRows: 2",
+    )?;
+    // So were permissions.
+    query(
+        config_path,
+        &api_keys.get("second").unwrap()[0],
+        names,
+        ENCRYPTED_DB,
+        "csv",
+        "Error: Authenticated entity e2e-second can't query database e2e-first/encrypted.sqlite",
+    )?;
+
+    Ok(())
+}
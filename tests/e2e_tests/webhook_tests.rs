@@ -0,0 +1,274 @@
+use crate::utils::ayb::{
+    create_database, create_webhook, delete_webhook, list_webhook_deliveries, list_webhooks, query,
+};
+use ayb::server::webhooks::verify_webhook_signature;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+const WEBHOOK_DB: &str = "e2e-first/webhooks.sqlite";
+
+/// A request the test receiver got: its lowercased headers and body.
+struct ReceivedRequest {
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// Start an HTTP server that reports each request it receives. It
+/// fails the first request with a 500 so that the test sees a retry,
+/// and accepts the rest.
+fn start_receiver() -> Result<(String, mpsc::Receiver<ReceivedRequest>), Box<dyn std::error::Error>>
+{
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let url = format!("http://{}/hooks", listener.local_addr()?);
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for (request_number, stream) in listener.incoming().enumerate() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let mut reader = BufReader::new(&stream);
+            let mut headers = HashMap::new();
+            let mut line = String::new();
+            // Skip the request line, then read headers up to the blank line.
+            let _ = reader.read_line(&mut line);
+            loop {
+                line.clear();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 || line.trim().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+            let content_length = headers
+                .get("content-length")
+                .and_then(|length| length.parse().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; content_length];
+            let _ = reader.read_exact(&mut body);
+            let status = if request_number == 0 {
+                "500 Internal Server Error"
+            } else {
+                "200 OK"
+            };
+            let _ = stream.write_all(
+                format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .as_bytes(),
+            );
+            let _ = sender.send(ReceivedRequest {
+                headers,
+                body: String::from_utf8_lossy(&body).to_string(),
+            });
+        }
+    });
+    Ok((url, receiver))
+}
+
+pub fn test_webhooks(
+    config_path: &str,
+    api_keys: &HashMap<String, Vec<String>>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let first_api_key = &api_keys.get("first").unwrap()[0];
+    let second_api_key = &api_keys.get("second").unwrap()[0];
+    let (url, receiver) = start_receiver()?;
+
+    create_database(
+        config_path,
+        first_api_key,
+        WEBHOOK_DB,
+        "sqlite",
+        "Successfully created e2e-first/webhooks.sqlite",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE scores (name varchar, score integer);",
+        WEBHOOK_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "CREATE TABLE players (name varchar);",
+        WEBHOOK_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+
+    // Only the database's managers can create webhooks, and only for
+    // http(s) URLs.
+    assert_eq!(
+        create_webhook(config_path, second_api_key, WEBHOOK_DB, &url, &[], &[])?,
+        "Error: Authenticated entity e2e-second can't create webhooks for e2e-first/webhooks.sqlite\n"
+    );
+    assert_eq!(
+        create_webhook(
+            config_path,
+            first_api_key,
+            WEBHOOK_DB,
+            "ftp://localhost/hooks",
+            &[],
+            &[]
+        )?,
+        "Error: Invalid webhook URL ftp://localhost/hooks: must be an http or https URL\n"
+    );
+
+    // Webhooks can't reach into the server's network, except on the
+    // hosts the server allows (the test receiver's 127.0.0.1).
+    for (url, host) in [
+        (
+            "http://169.254.169.254/latest/meta-data/",
+            "169.254.169.254",
+        ),
+        ("http://10.0.0.1/hooks", "10.0.0.1"),
+        ("http://[::1]/hooks", "[::1]"),
+        ("http://localhost/hooks", "localhost"),
+    ] {
+        assert_eq!(
+            create_webhook(config_path, first_api_key, WEBHOOK_DB, url, &[], &[])?,
+            format!(
+                "Error: Invalid webhook URL {url}: {host} isn't a public address, and isn't in [webhooks] allowed_hosts\n"
+            )
+        );
+    }
+
+    let output = create_webhook(
+        config_path,
+        first_api_key,
+        WEBHOOK_DB,
+        &url,
+        &["scores"],
+        &["insert"],
+    )?;
+    let mut lines = output.lines();
+    let webhook_id = lines
+        .next()
+        .and_then(|line| line.strip_prefix("Created webhook "))
+        .and_then(|line| line.strip_suffix(" for e2e-first/webhooks.sqlite"))
+        .expect("webhook ID in output")
+        .to_string();
+    let secret = lines
+        .next()
+        .and_then(|line| line.rsplit_once(": "))
+        .map(|(_, secret)| secret.to_string())
+        .expect("webhook secret in output");
+    assert!(secret.starts_with("whsec_"));
+    list_webhooks(
+        config_path,
+        first_api_key,
+        WEBHOOK_DB,
+        &format!("Id,URL,Tables,Events,Created at\n{webhook_id},{url},scores,insert,[^,]+"),
+    )?;
+
+    // Changes the webhook isn't subscribed to aren't delivered, so the
+    // first delivery is for the insert into `scores`.
+    query(
+        config_path,
+        first_api_key,
+        "INSERT INTO players VALUES ('ignored');",
+        WEBHOOK_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "INSERT INTO scores VALUES ('low', 1), ('high', 10);",
+        WEBHOOK_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+    query(
+        config_path,
+        first_api_key,
+        "UPDATE scores SET score = 2 WHERE name = 'low';",
+        WEBHOOK_DB,
+        "csv",
+        "\nRows: 0",
+    )?;
+
+    // The receiver fails the first attempt, and the delivery is retried.
+    let first_attempt = receiver.recv_timeout(Duration::from_secs(30))?;
+    let second_attempt = receiver.recv_timeout(Duration::from_secs(30))?;
+    assert_eq!(first_attempt.body, second_attempt.body);
+    assert_eq!(
+        first_attempt.headers["x-ayb-delivery"],
+        second_attempt.headers["x-ayb-delivery"]
+    );
+    for attempt in [&first_attempt, &second_attempt] {
+        let timestamp: i64 = attempt.headers["x-ayb-timestamp"].parse()?;
+        assert!(verify_webhook_signature(
+            &secret,
+            timestamp,
+            &attempt.body,
+            &attempt.headers["x-ayb-signature"]
+        ));
+    }
+    let payload: serde_json::Value = serde_json::from_str(&second_attempt.body)?;
+    assert_eq!(payload["webhook_id"].to_string(), webhook_id);
+    assert_eq!(payload["entity"], "e2e-first");
+    assert_eq!(payload["database"], "webhooks.sqlite");
+    assert_eq!(
+        payload["changes"],
+        serde_json::json!([
+            {"table": "scores", "operation": "insert", "row_count": 2, "rowids": [1, 2]}
+        ])
+    );
+    assert!(receiver.recv_timeout(Duration::from_secs(6)).is_err());
+
+    // The delivery log records both attempts. The server records the
+    // outcome after the receiver responds, so give it a moment.
+    let delivery_id = &second_attempt.headers["x-ayb-delivery"];
+    let mut deliveries = String::new();
+    for _ in 0..10 {
+        deliveries = list_webhook_deliveries(config_path, first_api_key, WEBHOOK_DB, &webhook_id)?;
+        if deliveries.contains(&format!("\n{delivery_id},delivered,2,200,,")) {
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    assert!(
+        deliveries.starts_with(
+            "Id,Status,Attempts,Response,Error,Created at,Next attempt at,Delivered at,Payload\n"
+        ),
+        "{deliveries}"
+    );
+    assert!(
+        deliveries.contains(&format!("\n{delivery_id},delivered,2,200,,")),
+        "{deliveries}"
+    );
+    assert_eq!(deliveries.lines().count(), 2, "{deliveries}");
+
+    // Only the database's managers can see and delete webhooks.
+    assert_eq!(
+        list_webhook_deliveries(config_path, second_api_key, WEBHOOK_DB, &webhook_id)?,
+        "Error: Authenticated entity e2e-second can't list webhook deliveries of e2e-first/webhooks.sqlite\n"
+    );
+    delete_webhook(
+        config_path,
+        second_api_key,
+        WEBHOOK_DB,
+        &webhook_id,
+        "Error: Authenticated entity e2e-second can't delete webhooks of e2e-first/webhooks.sqlite",
+    )?;
+    delete_webhook(
+        config_path,
+        first_api_key,
+        WEBHOOK_DB,
+        &webhook_id,
+        &format!("Deleted webhook {webhook_id} from e2e-first/webhooks.sqlite"),
+    )?;
+    list_webhooks(
+        config_path,
+        first_api_key,
+        WEBHOOK_DB,
+        "No webhooks for e2e-first/webhooks.sqlite",
+    )?;
+
+    Ok(())
+}
@@ -53,6 +53,43 @@ pub fn query(
     Ok(())
 }
 
+pub fn query_snapshot(
+    config: &str,
+    api_key: &str,
+    query: &str,
+    database: &str,
+    snapshot_id: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "query", database, "--snapshot", snapshot_id, "--format", "table", query; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(predicate::str::contains(result));
+    Ok(())
+}
+
+pub fn diff(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    from: &str,
+    to: Option<&str>,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = match to {
+        Some(to) => ayb_assert_cmd!("client", "--config", config, "diff", database, from, to; {
+            "AYB_API_TOKEN" => api_key,
+        }),
+        None => ayb_assert_cmd!("client", "--config", config, "diff", database, from; {
+            "AYB_API_TOKEN" => api_key,
+        }),
+    };
+
+    cmd.stdout(predicate::str::contains(result));
+    Ok(())
+}
+
 pub fn query_no_api_token(
     config: &str,
     query: &str,
@@ -128,6 +165,28 @@ pub fn list_databases_no_auth(
     Ok(())
 }
 
+pub fn create_snapshot(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    label: Option<&str>,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = match label {
+        Some(label) => {
+            ayb_assert_cmd!("client", "--config", config, "create_snapshot", database, "--label", label; {
+                "AYB_API_TOKEN" => api_key,
+            })
+        }
+        None => ayb_assert_cmd!("client", "--config", config, "create_snapshot", database; {
+            "AYB_API_TOKEN" => api_key,
+        }),
+    };
+
+    cmd.stdout(predicate::str::is_match(format!("{result}\n")).unwrap());
+    Ok(())
+}
+
 pub fn list_snapshots(
     config: &str,
     api_key: &str,
@@ -149,11 +208,20 @@ pub fn list_snapshots(
     }
 
     assert_eq!(
-        output_lines[0], "Name,Last modified",
+        output_lines[0],
+        "Name,Last modified,Type,Label,Database type,Size,Compressed size,Triggered by,ayb version,Source database",
         "first result line should be a header row"
     );
-    let re = Regex::new(r"([a-f0-9]{64}),(\d{4,5}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?\+00:00)")
-        .unwrap();
+    let re = Regex::new(
+        r"^([a-f0-9]{64}),(\d{4,5}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(?:\.\d+)?\+00:00),(automatic|manual),([^,]*),(sqlite|duckdb)?,(\d*),(\d*),([^,]*),([^,]*),([^,]*)$",
+    )
+    .unwrap();
+    let optional = |capture: &regex::Captures, index| {
+        capture
+            .get(index)
+            .map(|value: regex::Match| value.as_str().to_string())
+            .filter(|value| !value.is_empty())
+    };
     let mut snapshots = Vec::new();
     for line in &mut output_lines[1..] {
         let capture = re
@@ -173,6 +241,18 @@ pub fn list_snapshots(
             )
             .expect("datetime should be in ISO format")
             .into(),
+            snapshot_type: capture
+                .get(3)
+                .expect("snapshot line should have a type")
+                .as_str()
+                .to_string(),
+            label: optional(&capture, 4),
+            db_type: optional(&capture, 5),
+            uncompressed_size: optional(&capture, 6).map(|size| size.parse().unwrap()),
+            compressed_size: optional(&capture, 7).map(|size| size.parse().unwrap()),
+            triggered_by: optional(&capture, 8),
+            ayb_version: optional(&capture, 9),
+            source_database_id: optional(&capture, 10),
         })
     }
 
@@ -194,6 +274,64 @@ pub fn list_snapshots_match_output(
     Ok(())
 }
 
+pub fn snapshot_retention(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "snapshot_retention", database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn update_snapshot_retention(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    retention: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "update_snapshot_retention", database, retention; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn snapshot_schedule(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "snapshot_schedule", database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn update_snapshot_schedule(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    schedule: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "update_snapshot_schedule", database, schedule; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
 pub fn restore_snapshot(
     config: &str,
     api_key: &str,
@@ -209,6 +347,37 @@ pub fn restore_snapshot(
     Ok(())
 }
 
+pub fn restore_snapshot_into(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    snapshot_id: &str,
+    into: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "restore_snapshot", database, snapshot_id, "--into", into; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn restore_point_in_time(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    at: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "restore_snapshot", database, "--at", at; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
 pub fn profile(
     config: &str,
     api_key: &str,
@@ -301,6 +470,33 @@ pub fn share(
     Ok(())
 }
 
+pub fn share_object(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    entity: &str,
+    sharing_level: &str,
+    object: &str,
+    columns: Option<&str>,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = match columns {
+        Some(columns) => {
+            ayb_assert_cmd!("client", "--config", config, "share", database, entity, sharing_level, "--object", object, "--columns", columns; {
+                "AYB_API_TOKEN" => api_key,
+            })
+        }
+        None => {
+            ayb_assert_cmd!("client", "--config", config, "share", database, entity, sharing_level, "--object", object; {
+                "AYB_API_TOKEN" => api_key,
+            })
+        }
+    };
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
 pub fn database_details(
     config: &str,
     api_key: &str,
@@ -388,3 +584,520 @@ pub fn revoke_token(
     cmd.stdout(format!("{result}\n"));
     Ok(())
 }
+
+pub fn rename_database(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    new_database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "rename_database", database, new_database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn delete_database(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "delete_database", database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn fork_database(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    fork: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "fork_database", database, fork; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn transfer_database(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    entity: &str,
+    former_owner_sharing_level: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "transfer_database", database, entity, "--former_owner_sharing_level", former_owner_sharing_level; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn accept_transfer(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "accept_transfer", database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn cancel_transfer(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "cancel_transfer", database; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn list_transfers(
+    config: &str,
+    api_key: &str,
+    format: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "list_transfers", "--format", format; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    // Transfers include a creation timestamp, so only match a prefix.
+    cmd.stdout(predicate::str::contains(result));
+    Ok(())
+}
+
+pub fn list_members(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    format: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "list_members", organization, "--format", format; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn add_member(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    entity: &str,
+    role: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "add_member", organization, entity, role; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn remove_member(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    entity: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "remove_member", organization, entity; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn set_member_sharing_level(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    sharing_level: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "set_member_sharing_level", organization, sharing_level; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn share_with_team(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    team: &str,
+    sharing_level: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "share_with_team", database, team, sharing_level; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn list_teams(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    format: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "list_teams", organization, "--format", format; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn create_team(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    team: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "create_team", organization, team; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn delete_team(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    team: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "delete_team", organization, team; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn add_team_member(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    team: &str,
+    entity: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "add_team_member", organization, team, entity; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn remove_team_member(
+    config: &str,
+    api_key: &str,
+    organization: &str,
+    team: &str,
+    entity: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "remove_team_member", organization, team, entity; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn audit_log(
+    config: &str,
+    api_key: &str,
+    entity: &str,
+    database: Option<&str>,
+    limit: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "client",
+        "--config",
+        config,
+        "audit_log",
+        entity,
+        "--limit",
+        limit,
+        "--format",
+        "csv",
+    ];
+    if let Some(database) = database {
+        args.extend(["--database", database]);
+    }
+    let cmd = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args(args)
+        .env("AYB_API_TOKEN", api_key)
+        .output()?
+        .assert()
+        .success();
+
+    cmd.stdout(predicate::str::is_match(format!("^{result}\n$")).unwrap());
+    Ok(())
+}
+
+pub fn save_query(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    name: &str,
+    query: &str,
+    parameters: &[&str],
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "client", "--config", config, "saved", "save", database, name, query,
+    ];
+    if !parameters.is_empty() {
+        args.push("--parameters");
+        args.extend(parameters);
+    }
+    let cmd = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args(args)
+        .env("AYB_API_TOKEN", api_key)
+        .output()?
+        .assert()
+        .success();
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn list_saved_queries(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "saved", "list", database, "--format", "csv"; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(predicate::str::is_match(format!("^{result}\n$")).unwrap());
+    Ok(())
+}
+
+pub fn run_saved_query(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    name: &str,
+    values: &[&str],
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "client", "--config", config, "saved", "run", database, name, "--format", "csv",
+    ];
+    if !values.is_empty() {
+        args.push("--values");
+        args.extend(values);
+    }
+    let cmd = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args(args)
+        .env("AYB_API_TOKEN", api_key)
+        .output()?
+        .assert()
+        .success();
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn delete_saved_query(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    name: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "saved", "delete", database, name; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn publish_endpoint(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    name: &str,
+    saved_query: &str,
+    max_age: Option<&str>,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "client",
+        "--config",
+        config,
+        "endpoints",
+        "publish",
+        database,
+        name,
+        saved_query,
+    ];
+    if let Some(max_age) = max_age {
+        args.extend(["--max_age", max_age]);
+    }
+    let cmd = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args(args)
+        .env("AYB_API_TOKEN", api_key)
+        .output()?
+        .assert()
+        .success();
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+pub fn list_published_endpoints(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "endpoints", "list", database, "--format", "csv"; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(predicate::str::is_match(format!("^{result}\n$")).unwrap());
+    Ok(())
+}
+
+pub fn unpublish_endpoint(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    name: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "endpoints", "unpublish", database, name; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+/// Create a webhook and return the CLI's output, which includes the
+/// webhook's ID and signing secret.
+pub fn create_webhook(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    url: &str,
+    tables: &[&str],
+    events: &[&str],
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut args = vec![
+        "client", "--config", config, "webhooks", "create", database, url,
+    ];
+    if !tables.is_empty() {
+        args.push("--tables");
+        args.extend(tables);
+    }
+    if !events.is_empty() {
+        args.push("--events");
+        args.extend(events);
+    }
+    let output = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args(args)
+        .env("AYB_API_TOKEN", api_key)
+        .output()?;
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+pub fn list_webhooks(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "webhooks", "list", database, "--format", "csv"; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(predicate::str::is_match(format!("^{result}\n$")).unwrap());
+    Ok(())
+}
+
+pub fn delete_webhook(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    webhook_id: &str,
+    result: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cmd = ayb_assert_cmd!("client", "--config", config, "webhooks", "delete", database, webhook_id; {
+        "AYB_API_TOKEN" => api_key,
+    });
+
+    cmd.stdout(format!("{result}\n"));
+    Ok(())
+}
+
+/// List a webhook's deliveries as CSV and return the CLI's output.
+pub fn list_webhook_deliveries(
+    config: &str,
+    api_key: &str,
+    database: &str,
+    webhook_id: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let output = Command::new(env!("CARGO_BIN_EXE_ayb"))
+        .args([
+            "client",
+            "--config",
+            config,
+            "webhooks",
+            "deliveries",
+            database,
+            webhook_id,
+            "--format",
+            "csv",
+        ])
+        .env("AYB_API_TOKEN", api_key)
+        .output()?;
+
+    Ok(String::from_utf8(output.stdout)?)
+}
@@ -32,9 +32,12 @@ pub fn extract_token_from_emails(emails: &[EmailEntry]) -> Option<String> {
 
 pub fn get_email_file_for_test_type(test_type: &str) -> Result<String, Box<dyn std::error::Error>> {
     match test_type {
-        "postgres" | "sqlite" | "browser_sqlite" => {
-            Ok(format!("tests/ayb_data_{}/emails.jsonl", test_type))
-        }
+        "postgres"
+        | "sqlite"
+        | "browser_sqlite"
+        | "sqlite_local_snapshots"
+        | "sqlite_encrypted_snapshots"
+        | "sqlite_point_in_time" => Ok(format!("tests/ayb_data_{}/emails.jsonl", test_type)),
         _ => Err(format!("Unknown test type: {}", test_type).into()),
     }
 }
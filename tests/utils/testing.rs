@@ -1,5 +1,5 @@
 use ayb::error::AybError;
-use ayb::server::config::read_config;
+use ayb::server::config::{read_config, AybConfigSnapshots};
 use ayb::server::snapshots::storage::SnapshotStorage;
 use std::fs;
 use std::io::Write;
@@ -33,6 +33,9 @@ pub fn get_test_port(test_type: &str) -> Result<u16, Box<dyn std::error::Error>>
         "postgres" => Ok(5433),
         "sqlite" => Ok(5434),
         "browser_sqlite" => Ok(5435),
+        "sqlite_local_snapshots" => Ok(5436),
+        "sqlite_encrypted_snapshots" => Ok(5437),
+        "sqlite_point_in_time" => Ok(5438),
         _ => Err(format!("Unknown test_type: {}", test_type).into()),
     }
 }
@@ -49,6 +52,38 @@ pub fn generate_test_config(test_type: &str) -> Result<String, Box<dyn std::erro
         format!("sqlite://tests/ayb_data_{test_type}/ayb.sqlite")
     };
     let path_prefix = test_type;
+    // The main runs store snapshots unencrypted in MinIO. The snapshot
+    // runs each cover another way of storing them.
+    let snapshot_storage = if test_type == "sqlite_local_snapshots" {
+        format!(
+            r#"[snapshots.local]
+path = "./tests/ayb_data_{test_type}/snapshot_storage""#
+        )
+    } else {
+        r#"access_key_id = "minioadmin"
+secret_access_key = "minioadmin"
+bucket = "bucket"
+endpoint_url = "http://localhost:9000"
+force_path_style = true"#
+            .to_string()
+    };
+    let snapshot_options = match test_type {
+        "sqlite_encrypted_snapshots" => {
+            r#"
+[snapshots.encryption]
+key = "Z3q0vUQ3tBXqCPFh2c6l0m0VxS8vN1YH6Kc8S2k3Z0Q="
+previous_keys = ["wL4co0/y3WqcQRvYauIwFy5goEeazsHfRmUueCDxJRc="]
+"#
+        }
+        "sqlite_point_in_time" => {
+            r#"
+[snapshots.point_in_time]
+interval = "1s"
+retention = "1h"
+"#
+        }
+        _ => "",
+    };
 
     let config_content = format!(
         r#"host = "0.0.0.0"
@@ -66,22 +101,23 @@ token_expiration_seconds = 3600
 [cors]
 origin = "*"
 
+[webhooks]
+allowed_hosts = ["127.0.0.1"]
+
 [snapshots]
-access_key_id = "minioadmin"
-secret_access_key = "minioadmin"
-bucket = "bucket"
 path_prefix = "{path_prefix}"
-endpoint_url = "http://localhost:9000"
-force_path_style = true
+{snapshot_storage}
 
 [snapshots.automation]
 interval = "2s"
 max_snapshots = 6
-"#,
+{snapshot_options}"#,
         port = port,
         database_url = database_url,
         test_type = test_type,
-        path_prefix = path_prefix
+        path_prefix = path_prefix,
+        snapshot_storage = snapshot_storage,
+        snapshot_options = snapshot_options
     );
 
     // Write the configuration to file
@@ -128,7 +164,11 @@ pub fn reset_test_environment(test_type: &str) -> Result<(), Box<dyn std::error:
                 .into());
             }
         }
-        "sqlite" | "browser_sqlite" => {
+        "sqlite"
+        | "browser_sqlite"
+        | "sqlite_local_snapshots"
+        | "sqlite_encrypted_snapshots"
+        | "sqlite_point_in_time" => {
             // No additional setup needed beyond data directory removal
         }
         _ => return Err(format!("Unknown test_type: {}", test_type).into()),
@@ -185,10 +225,39 @@ impl Drop for AybServer {
     }
 }
 
-pub async fn snapshot_storage(test_type: &str) -> Result<SnapshotStorage, AybError> {
+/// Wipes the data directory of the `test_type` server, which must be
+/// stopped, along with the SQLite metadata database in it, and rebuilds
+/// them from snapshot storage with `ayb server recover`. The client
+/// configuration in the directory is kept.
+pub fn wipe_and_recover_server(test_type: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let data_dir = format!("./tests/ayb_data_{test_type}");
+    let client_config_path = format!("{data_dir}/ayb.json");
+    let client_config = fs::read(&client_config_path)?;
+    fs::remove_dir_all(&data_dir)?;
+    fs::create_dir_all(&data_dir)?;
+    fs::write(&client_config_path, client_config)?;
+
+    let config_path = generate_test_config(test_type)?;
+    let output = ayb_cmd!("server", "recover", "--config", &config_path; {}).output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "recovery failed: {stdout}{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("Restored the metadata database from its backup"));
+    assert!(stdout.ends_with("Recovery complete\n"));
+    Ok(())
+}
+
+pub fn snapshot_config(test_type: &str) -> Result<AybConfigSnapshots, AybError> {
     let config_path = generate_test_config(test_type).map_err(|e| AybError::Other {
         message: e.to_string(),
     })?;
     let config = read_config(&PathBuf::from(config_path))?;
-    SnapshotStorage::new(&config.snapshots.unwrap()).await
+    Ok(config.snapshots.unwrap())
+}
+
+pub async fn snapshot_storage(test_type: &str) -> Result<SnapshotStorage, AybError> {
+    SnapshotStorage::new(&snapshot_config(test_type)?).await
 }